@@ -1,13 +1,22 @@
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::ffi::OsString;
+use std::io::{BufRead, BufReader, Write};
 use colored::*;
-use anyhow::Result;
+use crate::cli::ExportFormat;
+use crate::error::{AppError, Result};
+use crate::utils;
 use std::fs;
 use indicatif::{ProgressBar, ProgressStyle};
 use walkdir::WalkDir;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
+use tempfile::NamedTempFile;
 
 /// Represents a virus scan result
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct ScanResult {
     pub path: PathBuf,
     pub status: ScanStatus,
@@ -15,7 +24,8 @@ pub struct ScanResult {
 }
 
 /// Status of a virus scan
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ScanStatus {
     Clean,
     Infected,
@@ -23,6 +33,198 @@ pub enum ScanStatus {
     Skipped,
 }
 
+/// Which ClamAV frontend performs the scan
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanEngine {
+    /// Standalone `clamscan`, which loads the signature database on every run
+    Clamscan,
+    /// `clamdscan`, which hands files to the running `clamd` daemon
+    Clamd,
+}
+
+impl ScanEngine {
+    fn command(self) -> Command {
+        match self {
+            ScanEngine::Clamscan => Command::new("clamscan"),
+            ScanEngine::Clamd => {
+                let mut cmd = Command::new("clamdscan");
+                // Pass open file descriptors so clamd can read files owned by the user
+                #[cfg(unix)]
+                cmd.arg("--fdpass");
+                cmd
+            }
+        }
+    }
+}
+
+/// Options controlling how a virus scan runs
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    pub engine: ScanEngine,
+    pub exclude: Vec<String>, // Glob patterns; without a '/' they match the file or directory name
+    pub max_file_size: Option<u64>, // Skip files larger than this many bytes
+    pub since: Option<SystemTime>, // Only scan files modified at or after this time
+    pub changed_only: bool, // Only scan files changed since this directory's last scan (kept in the scan state file)
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self { engine: ScanEngine::Clamscan, exclude: Vec::new(), max_file_size: None, since: None, changed_only: false }
+    }
+}
+
+impl ScanOptions {
+    /// Whether a path is excluded by one of the `exclude` globs
+    fn is_excluded(&self, path: &Path) -> bool {
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        self.exclude.iter().any(|pattern| match glob::Pattern::new(pattern) {
+            Ok(glob) if pattern.contains('/') => glob.matches_path(path),
+            Ok(glob) => glob.matches(&name),
+            Err(_) => false,
+        })
+    }
+
+    /// Whether a file is larger than `max_file_size`
+    fn is_too_large(&self, path: &Path) -> bool {
+        match self.max_file_size {
+            Some(max) => fs::metadata(path).map(|m| m.len() > max).unwrap_or(false),
+            None => false,
+        }
+    }
+}
+
+/// Translate a shell glob into the regex syntax clamscan's `--exclude` expects,
+/// anchored the same way [`ScanOptions::is_excluded`] matches it.
+fn glob_to_regex(pattern: &str) -> String {
+    let mut regex = String::from(if pattern.contains('/') { "^" } else { "(^|/)" });
+    for c in pattern.chars() {
+        match c {
+            '*' => regex.push_str(if pattern.contains('/') { ".*" } else { "[^/]*" }),
+            '?' => regex.push_str("[^/]"),
+            '.' | '+' | '(' | ')' | '|' | '^' | '$' | '{' | '}' | '\\' => {
+                regex.push('\\');
+                regex.push(c);
+            }
+            _ => regex.push(c),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+/// Parse a `--since` time: RFC 3339, or local `YYYY-MM-DD[ HH:MM[:SS]]`
+pub fn parse_timestamp(value: &str) -> std::result::Result<SystemTime, String> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.into());
+    }
+    let naive = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok().and_then(|date| date.and_hms_opt(0, 0, 0)))
+        .ok_or_else(|| format!("Invalid time '{}' (expected e.g. 2024-05-01 or 2024-05-01 18:30)", value))?;
+    Local.from_local_datetime(&naive)
+        .earliest()
+        .map(SystemTime::from)
+        .ok_or_else(|| format!("'{}' does not exist in the local time zone", value))
+}
+
+/// When a directory was last scanned with `--changed-only`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LastScan {
+    /// When that scan started (RFC 3339)
+    scanned_at: String,
+    /// Whether it covered subdirectories
+    recursive: bool,
+    /// `signature_version` at the time
+    signatures: Option<String>,
+}
+
+/// Last `--changed-only` scan of each directory, keyed by canonical path
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ScanState {
+    directories: BTreeMap<PathBuf, LastScan>,
+}
+
+fn scan_state_file() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("terminal-pc-matrix").join("scan_state.json"))
+}
+
+// A missing or unreadable state file just means every directory gets a full scan
+fn load_scan_state(path: &Path) -> ScanState {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_scan_state(path: &Path, state: &ScanState) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(state)?)?;
+    Ok(())
+}
+
+/// Start of the window a `--changed-only` scan covers, or None when everything must be scanned:
+/// on the first run, when the last scan didn't descend as deep, and after a signature update,
+/// since new signatures can match files that haven't changed
+fn changed_since(last: Option<&LastScan>, recursive: bool, signatures: Option<&str>) -> Option<SystemTime> {
+    let last = last?;
+    if recursive && !last.recursive {
+        return None;
+    }
+    if let (Some(before), Some(now)) = (last.signatures.as_deref(), signatures) {
+        if before != now {
+            return None;
+        }
+    }
+    DateTime::parse_from_rfc3339(&last.scanned_at).ok().map(SystemTime::from)
+}
+
+/// Latest of the file's modification and status-change times. Copying or extracting a file
+/// usually preserves its mtime, but the ctime still records when it appeared here.
+#[cfg(unix)]
+fn last_changed(metadata: &fs::Metadata) -> Option<SystemTime> {
+    use std::os::unix::fs::MetadataExt;
+    let ctime = u64::try_from(metadata.ctime()).ok()
+        .map(|secs| SystemTime::UNIX_EPOCH + Duration::new(secs, metadata.ctime_nsec() as u32));
+    metadata.modified().ok().max(ctime)
+}
+
+/// Latest of the file's modification and creation times. A copied file keeps its mtime
+/// but gets a fresh creation time.
+#[cfg(not(unix))]
+fn last_changed(metadata: &fs::Metadata) -> Option<SystemTime> {
+    metadata.modified().ok().max(metadata.created().ok())
+}
+
+/// Check if the `clamd` daemon is running and reachable through `clamdscan`
+pub fn check_clamd_available() -> bool {
+    Command::new("clamdscan")
+        .args(["--ping", "1"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Pick the scan engine: `Some(true)` prefers the daemon, `Some(false)` forces
+/// clamscan and `None` uses the daemon whenever it is reachable.
+pub fn choose_engine(daemon: Option<bool>) -> ScanEngine {
+    match daemon {
+        Some(false) => ScanEngine::Clamscan,
+        _ if check_clamd_available() => ScanEngine::Clamd,
+        Some(true) => {
+            println!("{}", "clamd is not reachable, falling back to clamscan.".yellow());
+            ScanEngine::Clamscan
+        }
+        None => ScanEngine::Clamscan,
+    }
+}
+
 /// Check if ClamAV is installed on the system
 pub fn check_clamav_installed() -> bool {
     match Command::new("clamscan")
@@ -43,10 +245,18 @@ pub fn get_clamav_info() -> Result<String> {
         let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
         Ok(version)
     } else {
-        Err(anyhow::anyhow!("Failed to get ClamAV version"))
+        Err(AppError::Other("Failed to get ClamAV version".into()))
     }
 }
 
+/// Version line of the engine, e.g. "ClamAV 1.3.1/27280/Mon May  6 08:25:47 2024", which names
+/// the signature database in use (for clamd, the one the daemon has loaded)
+pub fn signature_version(engine: ScanEngine) -> Option<String> {
+    let output = engine.command().arg("--version").output().ok()?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !version.is_empty()).then_some(version)
+}
+
 /// Update ClamAV virus definitions
 pub fn update_virus_definitions() -> Result<String> {
     println!("{}", "Updating virus definitions...".cyan());
@@ -60,12 +270,12 @@ pub fn update_virus_definitions() -> Result<String> {
         Ok(update_info)
     } else {
         let error = String::from_utf8_lossy(&output.stderr).to_string();
-        Err(anyhow::anyhow!("Failed to update virus definitions: {}", error))
+        Err(AppError::Network(format!("Failed to update virus definitions: {}", error)))
     }
 }
 
 /// Scan a single file for viruses
-pub fn scan_file(file_path: &Path) -> Result<ScanResult> {
+pub fn scan_file(file_path: &Path, options: &ScanOptions) -> Result<ScanResult> {
     if !file_path.exists() {
         return Ok(ScanResult {
             path: file_path.to_path_buf(),
@@ -82,9 +292,17 @@ pub fn scan_file(file_path: &Path) -> Result<ScanResult> {
         });
     }
     
+    if options.is_too_large(file_path) {
+        return Ok(ScanResult {
+            path: file_path.to_path_buf(),
+            status: ScanStatus::Skipped,
+            threat_name: Some("Larger than the size limit".to_string()),
+        });
+    }
+    
     println!("{} {}", "Scanning file:".cyan(), file_path.display());
     
-    let output = Command::new("clamscan")
+    let output = options.engine.command()
         .arg("--no-summary")
         .arg(file_path)
         .output()?;
@@ -116,104 +334,224 @@ pub fn scan_file(file_path: &Path) -> Result<ScanResult> {
     }
 }
 
-/// Parse the ClamAV output
-fn process_clamscan_output(stdout: &str, _stderr: &str) -> Vec<ScanResult> {
-    let mut results: Vec<ScanResult> = Vec::new();
+/// Split a `<path>: <Signature> FOUND` line into its path and signature.
+///
+/// The line is anchored on the trailing ` FOUND` marker and the signature is whatever
+/// sits between the last `: ` and that marker, so drive letters, colons in the path and
+/// spaces in either part are all handled.
+fn parse_found_line(line: &str) -> Option<(&str, &str)> {
+    let rest = line.trim_end().strip_suffix(" FOUND")?;
+    let split = rest.rfind(": ")?;
+    let signature = rest[split + 2..].trim();
+    if signature.is_empty() {
+        return None;
+    }
+    Some((&rest[..split], signature))
+}
 
-    for line in stdout.lines() {
-        if line.contains(": ") {
-            let parts: Vec<&str> = line.splitn(2, ": ").collect();
-            if parts.len() == 2 {
-                let file_path_str = parts[0];
-                let status_str = parts[1];
-                
-                let file_path = PathBuf::from(file_path_str);
-                
-                if status_str == "OK" {
-                    results.push(ScanResult {
-                        path: file_path,
-                        status: ScanStatus::Clean,
-                        threat_name: None,
-                    });
-                } else {
-                    results.push(ScanResult {
-                        path: file_path,
-                        status: ScanStatus::Infected,
-                        threat_name: Some(status_str.to_string()),
-                    });
-                }
-            }
-        }
+/// Parse one line of ClamAV output (`<path>: OK`, `<path>: <Signature> FOUND` or
+/// `<path>: <reason> ERROR`)
+fn parse_clamscan_line(line: &str) -> Option<ScanResult> {
+    let line = line.trim_end();
+    if let Some((path, signature)) = parse_found_line(line) {
+        return Some(ScanResult {
+            path: PathBuf::from(path),
+            status: ScanStatus::Infected,
+            threat_name: Some(signature.to_string()),
+        });
     }
-    results
+    if let Some(path) = line.strip_suffix(": OK") {
+        return Some(ScanResult {
+            path: PathBuf::from(path),
+            status: ScanStatus::Clean,
+            threat_name: None,
+        });
+    }
+    let rest = line.strip_suffix(" ERROR")?;
+    let split = rest.rfind(": ")?;
+    Some(ScanResult {
+        path: PathBuf::from(&rest[..split]),
+        status: ScanStatus::Error,
+        threat_name: Some(rest[split + 2..].to_string()),
+    })
 }
 
-/// Scan a directory for viruses
-pub fn scan_directory(dir_path: &Path, recursive: bool) -> Result<Vec<ScanResult>> {
+/// Scan a directory for viruses.
+///
+/// clamscan's output is read line by line while it runs, so the progress bar advances
+/// per file and infections are reported as soon as they are found.
+pub fn scan_directory(dir_path: &Path, recursive: bool, options: &ScanOptions) -> Result<Vec<ScanResult>> {
     if !dir_path.exists() || !dir_path.is_dir() {
-        return Err(anyhow::anyhow!("Invalid directory path"));
+        return Err(AppError::NotFound(format!("Not a directory: {}", dir_path.display())));
     }
     
     println!("{} {}", "Scanning directory:".cyan(), dir_path.display());
     
-    let mut results: Vec<ScanResult> = Vec::new();
-    let mut file_count = 0;
-    
-    // Count files first for progress bar
-    for entry in WalkDir::new(dir_path).follow_links(true).into_iter().filter_map(|e| e.ok()) {
-        if entry.file_type().is_file() {
-            file_count += 1;
+    // With --changed-only, pick up where this directory's last scan left off
+    let scan_started = SystemTime::now();
+    let state_file = scan_state_file().filter(|_| options.changed_only);
+    let state_key = fs::canonicalize(dir_path)?;
+    let signatures = if options.changed_only { signature_version(options.engine) } else { None };
+    let mut state = state_file.as_deref().map(load_scan_state).unwrap_or_default();
+    let since = match &state_file {
+        Some(_) => {
+            let since = changed_since(state.directories.get(&state_key), recursive, signatures.as_deref());
+            match since {
+                Some(time) => println!("{} {}", "Only scanning files changed since".cyan(), DateTime::<Local>::from(time).format("%Y-%m-%d %H:%M:%S")),
+                None => println!("{}", "No usable earlier scan (or new signatures); scanning everything.".cyan()),
+            }
+            // An explicit --since can only narrow the window further
+            since.max(options.since)
         }
+        None => options.since,
+    };
+    
+    // Count files first for progress bar, skipping what clamscan will skip
+    let mut walker = WalkDir::new(dir_path).follow_links(true);
+    if !recursive {
+        walker = walker.max_depth(1);
+    }
+    let files: Vec<PathBuf> = walker.into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || !options.is_excluded(entry.path()))
+        .filter_map(|e| e.ok())
+        .filter(|entry| entry.file_type().is_file() && !options.is_too_large(entry.path()))
+        .filter(|entry| {
+            // Files without a readable change time are scanned rather than skipped
+            let changed = entry.metadata().ok().and_then(|m| last_changed(&m));
+            !matches!((since, changed), (Some(since), Some(changed)) if changed < since)
+        })
+        .map(|entry| entry.into_path())
+        .collect();
+    let file_count = files.len();
+    if since.is_some() {
+        println!("{} {} changed file(s)", "Found".cyan(), file_count);
     }
     
     // Set up progress bar
-    let pb = ProgressBar::new(file_count);
+    let pb = ProgressBar::new(file_count as u64);
     pb.set_style(ProgressStyle::default_bar()
         .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} files ({eta})")
         .unwrap()
         .progress_chars("#>-"));
     
     // Build the command with the right arguments
-    let mut cmd = Command::new("clamscan");
+    let mut cmd = options.engine.command();
     cmd.arg("--no-summary");
     
-    if recursive {
-        cmd.arg("-r");
+    // Walked file lists go through --file-list; thousands of paths would overflow the command line
+    let mut file_list = None;
+    match options.engine {
+        // Only the changed files were kept, so hand exactly those over
+        _ if since.is_some() => {
+            if files.is_empty() {
+                pb.finish_with_message("Nothing changed".green().to_string());
+                record_scan(state_file.as_deref(), &mut state, state_key, scan_started, recursive, signatures)?;
+                return Ok(Vec::new());
+            }
+            cmd.arg(file_list_arg(file_list.insert(write_file_list(&files)?)));
+        }
+        ScanEngine::Clamscan => {
+            if recursive {
+                cmd.arg("-r");
+            }
+            for pattern in &options.exclude {
+                let regex = glob_to_regex(pattern);
+                cmd.arg(format!("--exclude={}", regex));
+                cmd.arg(format!("--exclude-dir={}", regex));
+            }
+            if let Some(max) = options.max_file_size {
+                cmd.arg(format!("--max-filesize={}", max));
+            }
+            cmd.arg(dir_path);
+        }
+        // clamdscan always descends into directories and takes its limits from clamd.conf,
+        // so hand it the already-filtered files directly
+        ScanEngine::Clamd => {
+            if files.is_empty() {
+                pb.finish_with_message("Scan complete".green().to_string());
+                return Ok(Vec::new());
+            }
+            cmd.arg(file_list_arg(file_list.insert(write_file_list(&files)?)));
+        }
     }
     
-    cmd.arg(dir_path);
+    cmd.stdout(Stdio::piped())
+        .stderr(Stdio::null());
     
-    let output = cmd.output()?;
+    let mut child = cmd.spawn()?;
+    let stdout = child.stdout.take()
+        .ok_or_else(|| AppError::Other("Failed to capture clamscan output".into()))?;
+    
+    let mut results: Vec<ScanResult> = Vec::new();
+    for line in BufReader::new(stdout).lines().map_while(|line| line.ok()) {
+        if let Some(result) = parse_clamscan_line(&line) {
+            if result.status == ScanStatus::Infected {
+                pb.println(format!("{} {} - {}",
+                    "Infected:".red(),
+                    result.path.display(),
+                    result.threat_name.as_deref().unwrap_or("Unknown threat")));
+            }
+            pb.inc(1);
+            results.push(result);
+        }
+    }
+    
+    let status = child.wait()?;
+    // clamscan/clamdscan exit with 1 when they found something and 2 on errors
+    if status.code() == Some(2) && results.is_empty() {
+        pb.abandon();
+        return Err(AppError::Other(format!("ClamAV failed to scan {}", dir_path.display())));
+    }
     
-    // Process the output
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-
-    results = process_clamscan_output(&stdout, &stderr);
-
-    // Update progress bar based on parsed results (or file count if parsing fails)
-    let file_count = results.len();
-    let pb = ProgressBar::new(file_count as u64); 
-    pb.set_style(ProgressStyle::default_bar()
-        .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} files ({eta})")
-        .unwrap()
-        .progress_chars("#>-="));
     pb.finish_with_message("Scan complete".green().to_string());
+    // Infected or unscannable files must come up again next time, so the window only
+    // moves forward after a scan that found nothing wrong
+    let all_clear = status.code() != Some(2)
+        && !results.iter().any(|r| matches!(r.status, ScanStatus::Infected | ScanStatus::Error));
+    if all_clear {
+        record_scan(state_file.as_deref(), &mut state, state_key, scan_started, recursive, signatures)?;
+    } else if state_file.is_some() {
+        println!("{}", "Scan state not updated; infected or failed files will be scanned again next time.".yellow());
+    }
     
     Ok(results)
 }
 
+// One path per line, as clamscan/clamdscan read with --file-list
+fn write_file_list(files: &[PathBuf]) -> Result<NamedTempFile> {
+    let mut list = NamedTempFile::new()?;
+    for file in files {
+        writeln!(list, "{}", file.display())?;
+    }
+    list.flush()?;
+    Ok(list)
+}
+
+fn file_list_arg(list: &NamedTempFile) -> OsString {
+    let mut arg = OsString::from("--file-list=");
+    arg.push(list.path());
+    arg
+}
+
+// Remember a finished `--changed-only` scan; the start time is kept so files changed during it are rescanned
+fn record_scan(state_file: Option<&Path>, state: &mut ScanState, dir: PathBuf, started: SystemTime, recursive: bool, signatures: Option<String>) -> Result<()> {
+    let Some(path) = state_file else {
+        return Ok(());
+    };
+    let scanned_at = DateTime::<Local>::from(started).to_rfc3339();
+    state.directories.insert(dir, LastScan { scanned_at, recursive, signatures });
+    save_scan_state(path, state)
+}
+
 /// Extract threat name from ClamAV output
 fn extract_threat_name(output: &str, file_path: &Path) -> Option<String> {
-    for line in output.lines() {
-        if line.contains(file_path.to_string_lossy().as_ref()) && line.contains("FOUND") {
-            let parts: Vec<&str> = line.split(' ').collect();
-            if parts.len() >= 2 {
-                return Some(parts[parts.len() - 2].to_string());
-            }
-        }
-    }
-    Some("Unknown threat".to_string())
+    let file_path = file_path.to_string_lossy();
+    output.lines()
+        .filter_map(parse_found_line)
+        .find(|(path, _)| *path == file_path)
+        .or_else(|| output.lines().find_map(parse_found_line))
+        .map(|(_, signature)| signature.to_string())
+        .or_else(|| Some("Unknown threat".to_string()))
 }
 
 /// Format scan results for display
@@ -243,23 +581,322 @@ pub fn format_scan_results(results: &[ScanResult]) -> String {
     output
 }
 
-/// Quarantine an infected file
-pub fn quarantine_file(file_path: &Path, quarantine_dir: &Path) -> Result<PathBuf> {
+/// A finished scan with what an audit trail needs: when it ran and with which signatures
+#[derive(Debug, Serialize)]
+pub struct ScanReport<'a> {
+    pub target: &'a Path,
+    /// Scan start and end (RFC 3339)
+    pub started_at: String,
+    pub finished_at: String,
+    pub engine: ScanEngine,
+    /// See [`signature_version`]
+    pub signatures: Option<String>,
+    pub results: &'a [ScanResult],
+}
+
+/// The report as CSV, one row per file; the scan details are repeated on every row
+/// so each line stands on its own once imported elsewhere
+pub fn scan_report_to_csv(report: &ScanReport) -> String {
+    let mut out = String::from("path,status,threat_name,started_at,finished_at,engine,signatures\n");
+    for result in report.results {
+        let status = match result.status {
+            ScanStatus::Clean => "clean",
+            ScanStatus::Infected => "infected",
+            ScanStatus::Error => "error",
+            ScanStatus::Skipped => "skipped",
+        };
+        let engine = match report.engine {
+            ScanEngine::Clamscan => "clamscan",
+            ScanEngine::Clamd => "clamd",
+        };
+        let row = [
+            result.path.display().to_string(),
+            status.to_string(),
+            result.threat_name.clone().unwrap_or_default(),
+            report.started_at.clone(),
+            report.finished_at.clone(),
+            engine.to_string(),
+            report.signatures.clone().unwrap_or_default(),
+        ];
+        out.push_str(&row.iter().map(|field| utils::csv_field(field)).collect::<Vec<_>>().join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Write a scan report to `path` as CSV or pretty-printed JSON
+pub fn export_scan_report(report: &ScanReport, path: &Path, format: ExportFormat) -> Result<()> {
+    let contents = match format {
+        ExportFormat::Csv => scan_report_to_csv(report),
+        ExportFormat::Json => serde_json::to_string_pretty(report)?,
+    };
+    fs::write(path, contents)?;
+    println!("{} {} result(s) to {}", "Exported".green(), report.results.len(), path.display());
+    Ok(())
+}
+
+/// Sidecar record kept next to every quarantined file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineRecord {
+    /// Identifier used to restore the item
+    pub id: String,
+    /// Where the file lived before it was quarantined
+    pub original_path: PathBuf,
+    /// Threat reported by ClamAV, if any
+    pub threat_name: Option<String>,
+    /// When the file was quarantined (RFC 3339)
+    pub quarantined_at: String,
+    /// Unix permission bits of the original file
+    pub mode: Option<u32>,
+}
+
+/// Default quarantine location (`~/.quarantine`)
+pub fn default_quarantine_dir() -> PathBuf {
+    dirs::home_dir().unwrap_or_else(|| PathBuf::from(".")).join(".quarantine")
+}
+
+/// Whether `id` has the shape [`quarantine_file`] generates (`YYYYMMDD-HHMMSS` with an
+/// optional `-N` suffix), so a user-supplied id can't point outside the quarantine
+fn is_quarantine_id(id: &str) -> bool {
+    let digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    let timestamp = |date: &str, time: &str| digits(date) && date.len() == 8 && digits(time) && time.len() == 6;
+    match id.split('-').collect::<Vec<_>>().as_slice() {
+        [date, time] => timestamp(date, time),
+        [date, time, suffix] => timestamp(date, time) && digits(suffix),
+        _ => false,
+    }
+}
+
+fn record_path(quarantine_dir: &Path, id: &str) -> PathBuf {
+    quarantine_dir.join(format!("{}.json", id))
+}
+
+fn payload_path(quarantine_dir: &Path, id: &str) -> PathBuf {
+    quarantine_dir.join(format!("{}.quarantined", id))
+}
+
+/// Move a file, falling back to copy + delete when it crosses filesystems
+fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if fs::rename(from, to).is_err() {
+        fs::copy(from, to)?;
+        fs::remove_file(from)?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn file_mode(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).ok().map(|m| m.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn file_mode(_path: &Path) -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn set_file_mode(path: &Path, mode: u32) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_file_mode(_path: &Path, _mode: u32) -> Result<()> {
+    Ok(())
+}
+
+/// Quarantine an infected file.
+///
+/// The file is moved into `quarantine_dir` with its permissions stripped, and a JSON
+/// sidecar records where it came from so [`restore_file`] can put it back.
+///
+/// With `dry_run` the planned move is printed and the record is returned without touching any file.
+pub fn quarantine_file(file_path: &Path, quarantine_dir: &Path, threat_name: Option<&str>, dry_run: bool) -> Result<QuarantineRecord> {
+    if !file_path.is_file() {
+        return Err(AppError::NotFound(format!("Not a file: {}", file_path.display())));
+    }
+
     // Create quarantine directory if it doesn't exist
-    if !quarantine_dir.exists() {
+    if !quarantine_dir.exists() && !dry_run {
         fs::create_dir_all(quarantine_dir)?;
     }
-    
-    // Generate quarantine file path
-    let file_name = file_path.file_name()
-        .ok_or_else(|| anyhow::anyhow!("Invalid file path"))?;
-    
-    let quarantine_file = quarantine_dir.join(file_name);
-    
-    // Move the file to quarantine
-    fs::rename(file_path, &quarantine_file)?;
-    
-    Ok(quarantine_file)
+
+    // Timestamp-based id, bumped until it doesn't collide with an existing item
+    let now = Local::now();
+    let base_id = now.format("%Y%m%d-%H%M%S").to_string();
+    let mut id = base_id.clone();
+    let mut suffix = 1;
+    while record_path(quarantine_dir, &id).exists() || payload_path(quarantine_dir, &id).exists() {
+        id = format!("{}-{}", base_id, suffix);
+        suffix += 1;
+    }
+
+    let record = QuarantineRecord {
+        id: id.clone(),
+        original_path: fs::canonicalize(file_path)?,
+        threat_name: threat_name.map(str::to_string),
+        quarantined_at: now.to_rfc3339(),
+        mode: file_mode(file_path),
+    };
+
+    let payload = payload_path(quarantine_dir, &id);
+    if dry_run {
+        println!("{} {} -> {}", "Would quarantine".yellow(), record.original_path.display(), payload.display());
+        return Ok(record);
+    }
+    move_file(file_path, &payload)?;
+    // Nobody should be able to run or modify the file while it sits in quarantine
+    set_file_mode(&payload, 0o400)?;
+    fs::write(record_path(quarantine_dir, &id), serde_json::to_string_pretty(&record)?)?;
+
+    Ok(record)
+}
+
+/// List every item in the quarantine, oldest first
+pub fn list_quarantine(quarantine_dir: &Path) -> Result<Vec<QuarantineRecord>> {
+    if !quarantine_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut records = Vec::new();
+    for entry in fs::read_dir(quarantine_dir)? {
+        let path = entry?.path();
+        if path.extension().map_or(false, |ext| ext == "json") {
+            let record: QuarantineRecord = serde_json::from_str(&fs::read_to_string(&path)?)?;
+            records.push(record);
+        }
+    }
+    records.sort_by(|a, b| a.quarantined_at.cmp(&b.quarantined_at));
+    Ok(records)
+}
+
+/// Put a quarantined file back at its original path with its original permissions.
+/// With `dry_run` the checks still run but only the planned move is printed.
+pub fn restore_file(id: &str, quarantine_dir: &Path, dry_run: bool) -> Result<PathBuf> {
+    if !is_quarantine_id(id) {
+        return Err(AppError::InvalidInput(format!("Invalid quarantine id '{}'", id)));
+    }
+    let record_file = record_path(quarantine_dir, id);
+    if !record_file.exists() {
+        return Err(AppError::NotFound(format!("No quarantined item with id '{}'", id)));
+    }
+    let record: QuarantineRecord = serde_json::from_str(&fs::read_to_string(&record_file)?)?;
+
+    if record.original_path.exists() {
+        return Err(AppError::InvalidInput(format!("Refusing to overwrite existing file: {}", record.original_path.display())));
+    }
+    if dry_run {
+        println!("{} {} -> {}", "Would restore".yellow(), payload_path(quarantine_dir, id).display(), record.original_path.display());
+        return Ok(record.original_path);
+    }
+    if let Some(parent) = record.original_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    move_file(&payload_path(quarantine_dir, id), &record.original_path)?;
+    if let Some(mode) = record.mode {
+        set_file_mode(&record.original_path, mode)?;
+    }
+    fs::remove_file(&record_file)?;
+
+    Ok(record.original_path)
+}
+
+/// Parse a retention window such as `30d`, `12h`, `2w` or `90m`; a bare number means days
+pub fn parse_retention(value: &str) -> std::result::Result<chrono::Duration, String> {
+    let value = value.trim().to_ascii_lowercase();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(split) => value.split_at(split),
+        None => (value.as_str(), "d"),
+    };
+    let count: i64 = number.parse().map_err(|_| format!("Invalid age '{}' (expected e.g. 30d, 12h or 2w)", value))?;
+    let age = match unit {
+        "m" => chrono::Duration::try_minutes(count),
+        "h" => chrono::Duration::try_hours(count),
+        "d" => chrono::Duration::try_days(count),
+        "w" => chrono::Duration::try_weeks(count),
+        _ => return Err(format!("Unknown unit '{}' in '{}' (use m, h, d or w)", unit, value)),
+    };
+    age.ok_or_else(|| format!("Age '{}' is too large", value))
+}
+
+/// Overwrite a file with zeros and flush it to disk before unlinking it, so its contents
+/// aren't left in the freed blocks. Best effort: copy-on-write and journaling filesystems
+/// and SSD wear levelling may still hold older copies.
+fn secure_remove(path: &Path) -> Result<()> {
+    // Quarantined payloads are read-only; make this one writable again for the overwrite
+    set_file_mode(path, 0o600)?;
+    let len = fs::metadata(path)?.len();
+    let mut file = fs::OpenOptions::new().write(true).open(path)?;
+    let zeros = [0u8; 64 * 1024];
+    let mut remaining = len;
+    while remaining > 0 {
+        let chunk = remaining.min(zeros.len() as u64) as usize;
+        file.write_all(&zeros[..chunk])?;
+        remaining -= chunk as u64;
+    }
+    file.sync_all()?;
+    drop(file);
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+/// Permanently delete quarantined items, returning the ones removed.
+///
+/// With `older_than` only items quarantined longer ago than that are removed (items whose
+/// date can't be read are kept); without it the whole quarantine is emptied. `secure`
+/// overwrites each file before unlinking it, and `dry_run` only prints what would go.
+pub fn purge_quarantine(quarantine_dir: &Path, older_than: Option<chrono::Duration>, secure: bool, dry_run: bool) -> Result<Vec<QuarantineRecord>> {
+    let cutoff = match older_than {
+        Some(age) => Some(Local::now().checked_sub_signed(age)
+            .ok_or_else(|| AppError::InvalidInput(format!("Age of {} days is too large", age.num_days())))?),
+        None => None,
+    };
+    let expired: Vec<QuarantineRecord> = list_quarantine(quarantine_dir)?
+        .into_iter()
+        .filter(|record| match cutoff {
+            Some(cutoff) => DateTime::parse_from_rfc3339(&record.quarantined_at).is_ok_and(|at| at < cutoff),
+            None => true,
+        })
+        .collect();
+
+    for record in &expired {
+        let payload = payload_path(quarantine_dir, &record.id);
+        if dry_run {
+            println!("{} {} ({})", "Would delete".yellow(), payload.display(), record.original_path.display());
+            continue;
+        }
+        // The record goes even when its payload is already missing
+        if payload.exists() {
+            if secure {
+                secure_remove(&payload)?;
+            } else {
+                fs::remove_file(&payload)?;
+            }
+        }
+        fs::remove_file(record_path(quarantine_dir, &record.id))?;
+    }
+
+    Ok(expired)
+}
+
+/// Format the quarantine listing for display
+pub fn format_quarantine_list(records: &[QuarantineRecord]) -> String {
+    if records.is_empty() {
+        return "Quarantine is empty.".yellow().to_string();
+    }
+
+    let mut output = format!("{} {} item(s)\n", "Quarantine:".green().bold(), records.len());
+    for record in records {
+        output.push_str(&format!("  {}  {}  {} - {}\n",
+            record.id.cyan(),
+            record.quarantined_at.dimmed(),
+            record.original_path.display(),
+            record.threat_name.as_deref().unwrap_or("Unknown threat").red()));
+    }
+    output
 }
 
 fn run_clamscan_command(args: &[&str]) -> Result<String> {
@@ -272,6 +909,231 @@ fn run_clamscan_command(args: &[&str]) -> Result<String> {
         Ok(result)
     } else {
         let error = String::from_utf8_lossy(&output.stderr).to_string();
-        Err(anyhow::anyhow!("clamscan error: {}", error))
+        Err(AppError::Other(format!("clamscan error: {}", error)))
+    }
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_found_line() {
+        assert_eq!(
+            parse_found_line("/tmp/eicar.com: Win.Test.EICAR_HDB-1 FOUND"),
+            Some(("/tmp/eicar.com", "Win.Test.EICAR_HDB-1"))
+        );
+        // Drive letter and a colon inside the file name
+        assert_eq!(
+            parse_found_line(r"C:\Users\me\odd: name.exe: Win.Trojan.Agent-123 FOUND"),
+            Some((r"C:\Users\me\odd: name.exe", "Win.Trojan.Agent-123"))
+        );
+        // Spaces in both the path and the signature
+        assert_eq!(
+            parse_found_line("/home/me/My Files/a b.zip: Heuristics.Encrypted Zip FOUND"),
+            Some(("/home/me/My Files/a b.zip", "Heuristics.Encrypted Zip"))
+        );
+        assert_eq!(parse_found_line("/tmp/clean.txt: OK"), None);
+    }
+
+    #[test]
+    fn test_parse_clamscan_line() {
+        let clean = parse_clamscan_line(r"D:\data\report.pdf: OK").unwrap();
+        assert_eq!(clean.status, ScanStatus::Clean);
+        assert_eq!(clean.path, PathBuf::from(r"D:\data\report.pdf"));
+
+        let infected = parse_clamscan_line(r"D:\data\setup.exe: Win.Malware.Generic FOUND").unwrap();
+        assert_eq!(infected.status, ScanStatus::Infected);
+        assert_eq!(infected.path, PathBuf::from(r"D:\data\setup.exe"));
+        assert_eq!(infected.threat_name.as_deref(), Some("Win.Malware.Generic"));
+
+        let error = parse_clamscan_line("/root/secret: Access denied. ERROR").unwrap();
+        assert_eq!(error.status, ScanStatus::Error);
+
+        assert!(parse_clamscan_line("LibClamAV Warning: something").is_none());
+    }
+
+    #[test]
+    fn test_exclude_globs() {
+        let options = ScanOptions {
+            exclude: vec!["*.iso".to_string(), "/home/*/VMs/*".to_string()],
+            ..ScanOptions::default()
+        };
+        assert!(options.is_excluded(Path::new("/data/ubuntu.iso")));
+        assert!(options.is_excluded(Path::new("/home/me/VMs/win11.vdi")));
+        assert!(!options.is_excluded(Path::new("/data/notes.txt")));
+
+        assert_eq!(glob_to_regex("*.iso"), r"(^|/)[^/]*\.iso$");
+        assert_eq!(glob_to_regex("/home/*/VMs/*"), r"^/home/.*/VMs/.*$");
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        let date = parse_timestamp("2024-05-01").unwrap();
+        let minute = parse_timestamp("2024-05-01 18:30").unwrap();
+        assert_eq!(minute.duration_since(date).unwrap().as_secs(), 18 * 3600 + 30 * 60);
+        assert_eq!(
+            parse_timestamp("2024-05-01T00:00:00Z").unwrap(),
+            SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_714_521_600)
+        );
+        assert!(parse_timestamp("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_changed_since() {
+        let last = LastScan {
+            scanned_at: "2024-05-01T00:00:00Z".to_string(),
+            recursive: true,
+            signatures: Some("ClamAV 1.3.1/27280".to_string()),
+        };
+        let at = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_714_521_600);
+        assert_eq!(changed_since(None, true, None), None);
+        assert_eq!(changed_since(Some(&last), true, Some("ClamAV 1.3.1/27280")), Some(at));
+        assert_eq!(changed_since(Some(&last), false, None), Some(at));
+        assert_eq!(changed_since(Some(&last), true, Some("ClamAV 1.3.1/27281")), None);
+
+        let shallow = LastScan { recursive: false, ..last };
+        assert_eq!(changed_since(Some(&shallow), true, None), None);
+        assert!(changed_since(Some(&shallow), false, None).is_some());
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_last_changed_ignores_preserved_mtime() {
+        let work = tempfile::tempdir().unwrap();
+        let path = work.path().join("copied.bin");
+        let file = fs::File::create(&path).unwrap();
+        let old = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000_000);
+        file.set_modified(old).unwrap();
+        let changed = last_changed(&fs::metadata(&path).unwrap()).unwrap();
+        // Like `cp -p`: the mtime is years old, but the file only just arrived
+        assert!(changed > old + std::time::Duration::from_secs(365 * 24 * 3600));
+    }
+
+    #[test]
+    fn test_file_list() {
+        let files = vec![PathBuf::from("/tmp/a b.txt"), PathBuf::from("/tmp/c.txt")];
+        let list = write_file_list(&files).unwrap();
+        assert_eq!(fs::read_to_string(list.path()).unwrap(), "/tmp/a b.txt\n/tmp/c.txt\n");
+        assert_eq!(file_list_arg(&list), OsString::from(format!("--file-list={}", list.path().display())));
+    }
+
+    #[test]
+    fn test_scan_report() {
+        let results = [
+            ScanResult { path: PathBuf::from("/tmp/a, b.txt"), status: ScanStatus::Clean, threat_name: None },
+            ScanResult { path: PathBuf::from("/tmp/eicar.com"), status: ScanStatus::Infected, threat_name: Some("Eicar-Test-Signature".to_string()) },
+        ];
+        let report = ScanReport {
+            target: Path::new("/tmp"),
+            started_at: "2024-05-01T10:00:00+00:00".to_string(),
+            finished_at: "2024-05-01T10:05:00+00:00".to_string(),
+            engine: ScanEngine::Clamd,
+            signatures: Some("ClamAV 1.3.1/27280".to_string()),
+            results: &results,
+        };
+
+        let csv = scan_report_to_csv(&report);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "\"/tmp/a, b.txt\",clean,,2024-05-01T10:00:00+00:00,2024-05-01T10:05:00+00:00,clamd,ClamAV 1.3.1/27280");
+        assert!(lines[2].starts_with("/tmp/eicar.com,infected,Eicar-Test-Signature,"));
+
+        let json: serde_json::Value = serde_json::from_str(&serde_json::to_string(&report).unwrap()).unwrap();
+        assert_eq!(json["engine"], "clamd");
+        assert_eq!(json["results"][1]["status"], "infected");
+        assert_eq!(json["results"][1]["threat_name"], "Eicar-Test-Signature");
+    }
+
+    #[test]
+    fn test_quarantine_and_restore_roundtrip() {
+        let work = tempfile::tempdir().unwrap();
+        let quarantine_dir = work.path().join("quarantine");
+        let file = work.path().join("eicar.com");
+        fs::write(&file, b"not really a virus").unwrap();
+        set_file_mode(&file, 0o750).unwrap();
+
+        let planned = quarantine_file(&file, &quarantine_dir, None, true).unwrap();
+        assert!(file.exists());
+        assert!(!quarantine_dir.exists());
+        assert_eq!(planned.original_path, fs::canonicalize(&file).unwrap());
+
+        let record = quarantine_file(&file, &quarantine_dir, Some("Eicar-Test-Signature"), false).unwrap();
+        assert!(!file.exists());
+
+        let listed = list_quarantine(&quarantine_dir).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].threat_name.as_deref(), Some("Eicar-Test-Signature"));
+
+        assert!(!restore_file(&record.id, &quarantine_dir, true).unwrap().exists());
+        let restored = restore_file(&record.id, &quarantine_dir, false).unwrap();
+        assert_eq!(fs::read(&restored).unwrap(), b"not really a virus");
+        #[cfg(unix)]
+        assert_eq!(file_mode(&restored), Some(0o750));
+        assert!(list_quarantine(&quarantine_dir).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_restore_rejects_foreign_ids() {
+        assert!(is_quarantine_id("20240501-120000"));
+        assert!(is_quarantine_id("20240501-120000-3"));
+        assert!(!is_quarantine_id("20240501-1200"));
+        assert!(!is_quarantine_id("20240501-120000-"));
+        assert!(!is_quarantine_id("20240501-120000-1-2"));
+
+        let work = tempfile::tempdir().unwrap();
+        let quarantine_dir = work.path().join("quarantine");
+        fs::create_dir_all(&quarantine_dir).unwrap();
+        // A record outside the quarantine must not be reachable through a crafted id
+        fs::write(work.path().join("outside.json"), "{}").unwrap();
+        for id in ["../outside", "/etc/passwd", "..", "a/b", ""] {
+            assert!(matches!(restore_file(id, &quarantine_dir, true), Err(AppError::InvalidInput(_))), "{}", id);
+        }
+    }
+
+    #[test]
+    fn test_parse_retention() {
+        assert_eq!(parse_retention("30d"), Ok(chrono::Duration::days(30)));
+        assert_eq!(parse_retention("7"), Ok(chrono::Duration::days(7)));
+        assert_eq!(parse_retention("12H"), Ok(chrono::Duration::hours(12)));
+        assert_eq!(parse_retention("2w"), Ok(chrono::Duration::weeks(2)));
+        assert!(parse_retention("d").is_err());
+        assert!(parse_retention("3y").is_err());
+        assert!(parse_retention("99999999999w").unwrap_err().contains("too large"));
+        assert!(parse_retention("99999999999999999999d").is_err());
+    }
+
+    #[test]
+    fn test_purge_quarantine() {
+        let work = tempfile::tempdir().unwrap();
+        // Parses, but reaches back past the earliest date chrono can represent
+        let too_old = parse_retention("999999999999m").unwrap();
+        assert!(matches!(purge_quarantine(work.path(), Some(too_old), false, true), Err(AppError::InvalidInput(_))));
+
+        let quarantine_dir = work.path().join("quarantine");
+        for name in ["old.exe", "new.exe"] {
+            let file = work.path().join(name);
+            fs::write(&file, b"payload").unwrap();
+            quarantine_file(&file, &quarantine_dir, None, false).unwrap();
+        }
+
+        // Backdate the first item by 40 days
+        let mut records = list_quarantine(&quarantine_dir).unwrap();
+        records.sort_by_key(|record| record.original_path.ends_with("new.exe"));
+        let old = &mut records[0];
+        old.quarantined_at = (Local::now() - chrono::Duration::days(40)).to_rfc3339();
+        fs::write(record_path(&quarantine_dir, &old.id), serde_json::to_string(&old).unwrap()).unwrap();
+
+        let retention = Some(chrono::Duration::days(30));
+        assert_eq!(purge_quarantine(&quarantine_dir, retention, false, true).unwrap().len(), 1);
+        assert_eq!(list_quarantine(&quarantine_dir).unwrap().len(), 2);
+
+        let purged = purge_quarantine(&quarantine_dir, retention, true, false).unwrap();
+        assert_eq!(purged.len(), 1);
+        assert!(purged[0].original_path.ends_with("old.exe"));
+        assert!(!payload_path(&quarantine_dir, &purged[0].id).exists());
+        assert_eq!(list_quarantine(&quarantine_dir).unwrap().len(), 1);
+
+        assert_eq!(purge_quarantine(&quarantine_dir, None, false, false).unwrap().len(), 1);
+        assert!(list_quarantine(&quarantine_dir).unwrap().is_empty());
+    }
+}
@@ -1,11 +1,21 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use colored::*;
+use futures::stream::StreamExt;
 use indicatif::{ProgressBar, ProgressStyle};
-// use simple_transcribe_rs::{model_handler::ModelHandler, transcriber::Transcriber};
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::time::Duration;
-use tempfile::Builder;
+use tempfile::{Builder, TempPath};
+use crate::error::AppError;
+use crate::utils;
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+// Whisper expects 16kHz mono PCM as 32-bit floats
+const WHISPER_SAMPLE_RATE: u32 = 16_000;
+const MODELS_DIR: &str = "models";
+const MODEL_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
 
 // Available whisper models from smallest to largest
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -38,6 +48,17 @@ impl ModelSize {
             _ => None,
         }
     }
+
+    /// File name of the ggml model as published in the whisper.cpp model repository
+    pub fn model_file_name(&self) -> &'static str {
+        match self {
+            ModelSize::Tiny => "ggml-tiny.bin",
+            ModelSize::Base => "ggml-base.bin",
+            ModelSize::Small => "ggml-small.bin",
+            ModelSize::Medium => "ggml-medium.bin",
+            ModelSize::Large => "ggml-large-v3.bin",
+        }
+    }
 }
 
 pub struct TranscriptionOptions {
@@ -46,6 +67,8 @@ pub struct TranscriptionOptions {
     pub save_timestamps: bool,
     pub output_srt: bool,
     pub output_txt: bool,
+    pub language: Option<String>, // ISO 639-1 code, None = auto-detect
+    pub translate: bool,          // Translate the speech to English
 }
 
 impl Default for TranscriptionOptions {
@@ -56,125 +79,405 @@ impl Default for TranscriptionOptions {
             save_timestamps: true,
             output_srt: true,
             output_txt: true,
+            language: None,
+            translate: false,
         }
     }
 }
 
+/// A single transcribed segment with its position in the audio
+#[derive(Debug, Clone)]
+pub struct TranscriptSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+}
+
+/// Full transcription result
+#[derive(Debug, Clone, Default)]
+pub struct Transcript {
+    pub segments: Vec<TranscriptSegment>,
+    pub language: Option<String>, // Language whisper detected or was told to use
+}
+
+impl Transcript {
+    /// The transcript as plain text, one segment per line
+    pub fn text(&self) -> String {
+        self.segments
+            .iter()
+            .map(|s| s.text.trim())
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
 /// Transcribes audio from a file to text using whisper-rs
 pub async fn transcribe_audio(
-    _audio_file: &Path,
-    _options: TranscriptionOptions,
+    audio_file: &Path,
+    options: TranscriptionOptions,
 ) -> Result<String> {
+    let model_path = ensure_model(options.model_size).await?;
+
     // Show progress because model loading might take time
-    // let pb = ProgressBar::new_spinner();
-    // pb.set_style(
-    //     ProgressStyle::default_spinner()
-    //         .template("{spinner:.green} {msg}")
-    //         .unwrap(),
-    // );
-    // pb.set_message("Loading transcription model...");
-    // pb.enable_steady_tick(Duration::from_millis(100));
-
-    // // Create models directory if it doesn't exist
-    // let models_dir = Path::new("models");
-    // if !models_dir.exists() {
-    //     fs::create_dir_all(models_dir)?;
-    // }
-
-    // // Initialize the model handler (will download the model if not present)
-    // let model_handler = ModelHandler::new(options.model_size.as_str(), "models/").await;
-    
-    // pb.set_message("Transcribing audio...");
-
-    // // Create the transcriber
-    // let transcriber = Transcriber::new(model_handler);
-    
-    // // Transcribe the audio
-    // let audio_path_str = audio_file.to_string_lossy().to_string();
-    // let result = transcriber.transcribe(&audio_path_str, None)
-    //     .map_err(|e| anyhow!("Transcription failed: {}", e))?;
-    
-    // let transcript = result.get_text();
-    // pb.finish_with_message(format!("{}", "Transcription completed!".green()));
-
-    // if options.output_txt || options.output_srt {
-    //     save_transcription_outputs(&result, audio_file, &options)?;
-    // }
-
-    // Ok(transcript.to_string())
-    Err(anyhow!("Audio transcription temporarily disabled."))
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(
+        ProgressStyle::default_spinner()
+            .template("{spinner:.green} {msg}")
+            .unwrap(),
+    );
+    pb.set_message("Decoding audio...");
+    pb.enable_steady_tick(Duration::from_millis(100));
+
+    let samples = load_audio_samples(audio_file)?;
+
+    pb.set_message("Transcribing audio...");
+
+    // Inference is CPU bound, keep it off the async worker threads
+    let language = options.language.clone();
+    let translate = options.translate;
+    let transcript = tokio::task::spawn_blocking(move || {
+        run_whisper(&model_path, &samples, language.as_deref(), translate)
+    })
+    .await
+    .map_err(|e| anyhow!("Transcription task failed: {}", e))??;
+
+    pb.finish_with_message(format!("{}", "Transcription completed!".green()));
+
+    if options.language.is_none() {
+        if let Some(lang) = &transcript.language {
+            println!("{} {}", "Detected language:".cyan(), lang);
+        }
+    }
+
+    if options.output_txt || options.output_srt {
+        save_transcription_outputs(&transcript, audio_file, &options)?;
+    }
+
+    Ok(transcript.text())
+}
+
+/// Runs the whisper model over 16kHz mono samples
+fn run_whisper(model_path: &Path, samples: &[f32], language: Option<&str>, translate: bool) -> Result<Transcript> {
+    let model_str = model_path.to_string_lossy();
+    let ctx = WhisperContext::new_with_params(&model_str, WhisperContextParameters::default())
+        .map_err(|e| anyhow!("Failed to load model '{}': {:?}", model_path.display(), e))?;
+    let mut state = ctx
+        .create_state()
+        .map_err(|e| anyhow!("Failed to create whisper state: {:?}", e))?;
+
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+    params.set_n_threads(threads as i32);
+    params.set_print_special(false);
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_timestamps(false);
+    // whisper.cpp runs language detection when asked for "auto"
+    params.set_language(Some(language.unwrap_or("auto")));
+    params.set_translate(translate);
+
+    state
+        .full(params, samples)
+        .map_err(|e| anyhow!("Transcription failed: {:?}", e))?;
+
+    let n_segments = state
+        .full_n_segments()
+        .map_err(|e| anyhow!("Failed to read segments: {:?}", e))?;
+
+    let mut transcript = Transcript::default();
+    transcript.language = match language {
+        Some(lang) => Some(lang.to_string()),
+        None => state
+            .full_lang_id_from_state()
+            .ok()
+            .and_then(whisper_rs::get_lang_str)
+            .map(|lang| lang.to_string()),
+    };
+
+    for i in 0..n_segments {
+        let text = state
+            .full_get_segment_text(i)
+            .map_err(|e| anyhow!("Failed to read segment {}: {:?}", i, e))?;
+        // whisper reports timestamps in centiseconds
+        let t0 = state.full_get_segment_t0(i).map_err(|e| anyhow!("{:?}", e))?;
+        let t1 = state.full_get_segment_t1(i).map_err(|e| anyhow!("{:?}", e))?;
+        transcript.segments.push(TranscriptSegment {
+            start_ms: (t0.max(0) as u64) * 10,
+            end_ms: (t1.max(0) as u64) * 10,
+            text: text.trim().to_string(),
+        });
+    }
+
+    Ok(transcript)
+}
+
+/// Returns the local path of the requested model, downloading it on first use
+async fn ensure_model(model_size: ModelSize) -> Result<PathBuf> {
+    let models_dir = Path::new(MODELS_DIR);
+    if !models_dir.exists() {
+        fs::create_dir_all(models_dir)?;
+    }
+
+    let model_path = models_dir.join(model_size.model_file_name());
+    if model_path.exists() {
+        return Ok(model_path);
+    }
+
+    let url = format!("{}/{}", MODEL_BASE_URL, model_size.model_file_name());
+    println!("{} {} model from {}", "Downloading".cyan(), model_size.as_str(), url);
+
+    let resp = utils::http_client().get(&url).send().await?;
+    if !resp.status().is_success() {
+        return Err(anyhow!("Failed to download model: HTTP status {}", resp.status()));
+    }
+
+    let pb = ProgressBar::new(resp.content_length().unwrap_or(0));
+    pb.set_style(ProgressStyle::default_bar()
+        .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
+        .unwrap()
+        .progress_chars("#>-"));
+
+    // Write to a partial file so an interrupted download is never mistaken for a model
+    let partial_path = model_path.with_extension("bin.part");
+    let mut file = fs::File::create(&partial_path)?;
+    let mut stream = resp.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        file.write_all(&chunk)?;
+        pb.inc(chunk.len() as u64);
+    }
+    file.flush()?;
+    fs::rename(&partial_path, &model_path)?;
+    pb.finish_with_message("Model downloaded");
+
+    Ok(model_path)
+}
+
+/// Decodes a WAV file into 16kHz mono f32 samples
+fn load_audio_samples(audio_file: &Path) -> Result<Vec<f32>> {
+    let mut reader = hound::WavReader::open(audio_file)
+        .with_context(|| format!("Failed to open '{}' as WAV audio", audio_file.display()))?;
+    let spec = reader.spec();
+
+    if spec.sample_rate != WHISPER_SAMPLE_RATE {
+        return Err(anyhow!(
+            "Audio must be sampled at {} Hz (got {} Hz). Convert it first, e.g. ffmpeg -i input -ar 16000 -ac 1 output.wav",
+            WHISPER_SAMPLE_RATE,
+            spec.sample_rate
+        ));
+    }
+
+    let interleaved: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<std::result::Result<_, _>>()?,
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|v| v as f32 / scale))
+                .collect::<std::result::Result<_, _>>()?
+        }
+    };
+
+    // Downmix by averaging the channels of each frame
+    let channels = spec.channels.max(1) as usize;
+    if channels == 1 {
+        return Ok(interleaved);
+    }
+    Ok(interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect())
 }
 
 /// Save transcription outputs (txt and/or srt files)
 fn save_transcription_outputs(
-    _result: &impl std::fmt::Debug,
-    _audio_file: &Path,
-    _options: &TranscriptionOptions,
+    transcript: &Transcript,
+    audio_file: &Path,
+    options: &TranscriptionOptions,
 ) -> Result<()> {
-    // let base_path = if let Some(output_path) = &options.output_file {
-    //     output_path.clone()
-    // } else {
-    //     let stem = audio_file.file_stem().unwrap_or_default().to_string_lossy();
-    //     let current_dir = std::env::current_dir()?;
-    //     current_dir.join(stem.to_string())
-    // };
-
-    // // Save plain text transcript (Temporarily disable using result)
-    // if options.output_txt {
-    //     let txt_path = base_path.with_extension("txt");
-    //     fs::write(&txt_path, "Transcript text unavailable due to type inference issue.")?; 
-    //     println!("{} {}", "Saved transcript to:".green(), txt_path.display());
-    // }
-
-    // // Save SRT subtitle file (Temporarily disable using result)
-    // if options.output_srt {
-    //     let srt_path = base_path.with_extension("srt");
-    //     let srt_content = format!("1\n00:00:00,000 --> 00:00:01,000\nSRT generation unavailable due to type inference issue.\n");
-    //     fs::write(&srt_path, srt_content)?;
-    //     println!("{} {}", "Saved subtitles to:".green(), srt_path.display());
-    // }
+    let base_path = if let Some(output_path) = &options.output_file {
+        output_path.clone()
+    } else {
+        let stem = audio_file.file_stem().unwrap_or_default().to_string_lossy();
+        let current_dir = std::env::current_dir()?;
+        current_dir.join(stem.to_string())
+    };
+
+    if let Some(parent) = base_path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
+    // Save plain text transcript
+    if options.output_txt {
+        let txt_path = base_path.with_extension("txt");
+        let content = if options.save_timestamps {
+            generate_timestamped_text(transcript)
+        } else {
+            transcript.text()
+        };
+        fs::write(&txt_path, content)?;
+        println!("{} {}", "Saved transcript to:".green(), txt_path.display());
+    }
+
+    // Save SRT subtitle file
+    if options.output_srt {
+        let srt_path = base_path.with_extension("srt");
+        fs::write(&srt_path, generate_srt(transcript))?;
+        println!("{} {}", "Saved subtitles to:".green(), srt_path.display());
+    }
 
     Ok(())
 }
 
+/// Formats milliseconds as an SRT timestamp (HH:MM:SS,mmm)
+fn format_srt_timestamp(ms: u64) -> String {
+    let hours = ms / 3_600_000;
+    let minutes = (ms % 3_600_000) / 60_000;
+    let seconds = (ms % 60_000) / 1000;
+    let millis = ms % 1000;
+    format!("{:02}:{:02}:{:02},{:03}", hours, minutes, seconds, millis)
+}
+
 /// Generate SRT subtitle file content from transcription result
-fn generate_srt(_result: &impl std::fmt::Debug) -> String {
-    // format!("1\n00:00:00,000 --> 00:00:01,000\nSRT generation unavailable due to type inference issue.\n")
-    String::new()
-}
-
-/// Save audio from video before transcription
-pub async fn extract_audio_from_video(_video_path: &Path) -> Result<PathBuf> {
-    // // This function would extract audio from video using a library like ffmpeg
-    // // For this implementation, we'll just simulate the process
-    // println!("{} {}", "Extracting audio from video:".cyan(), video_path.display());
-    
-    // // Create a temporary file for the extracted audio
-    // let temp_dir = Builder::new().prefix("audio_extract").tempdir()?;
-    // let audio_path = temp_dir.path().join("extracted_audio.mp3");
-    
-    // // Here we would actually extract the audio using ffmpeg or similar tool
-    // // For now, just pretend we did and return the path
-    // // In a real implementation, you would call a command like:
-    // // ffmpeg -i video.mp4 -q:a 0 -map a extracted_audio.mp3
-    
-    // Ok(audio_path)
-     Err(anyhow!("Audio extraction temporarily disabled."))
+fn generate_srt(transcript: &Transcript) -> String {
+    let mut srt = String::new();
+    let cues = transcript.segments.iter().filter(|s| !s.text.is_empty());
+    for (i, segment) in cues.enumerate() {
+        srt.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            format_srt_timestamp(segment.start_ms),
+            format_srt_timestamp(segment.end_ms),
+            segment.text
+        ));
+    }
+    srt
+}
+
+/// Plain text transcript with a timestamp range before each segment
+fn generate_timestamped_text(transcript: &Transcript) -> String {
+    transcript
+        .segments
+        .iter()
+        .filter(|s| !s.text.is_empty())
+        .map(|s| {
+            format!(
+                "[{} --> {}] {}",
+                format_srt_timestamp(s.start_ms).replace(',', "."),
+                format_srt_timestamp(s.end_ms).replace(',', "."),
+                s.text
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Check if ffmpeg is installed
+pub async fn check_ffmpeg_installed() -> bool {
+    match Command::new("ffmpeg")
+        .arg("-version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status() {
+            Ok(_) => true,
+            Err(_) => false,
+        }
+}
+
+/// Save audio from video before transcription.
+///
+/// The audio is written as 16kHz mono WAV to a temporary file that is deleted
+/// when the returned `TempPath` is dropped, so keep it alive until transcription finishes.
+pub async fn extract_audio_from_video(video_path: &Path) -> Result<TempPath> {
+    if !check_ffmpeg_installed().await {
+        return Err(AppError::MissingTool { tool: "ffmpeg", install_hint: "https://ffmpeg.org/download.html" }.into());
+    }
+
+    println!("{} {}", "Extracting audio from video:".cyan(), video_path.display());
+
+    let audio_path = Builder::new()
+        .prefix("audio_extract")
+        .suffix(".wav")
+        .tempfile()?
+        .into_temp_path();
+
+    let output = Command::new("ffmpeg")
+        .arg("-y")
+        .arg("-i").arg(video_path)
+        .args(["-vn", "-ac", "1", "-ar", &WHISPER_SAMPLE_RATE.to_string()])
+        .arg(&audio_path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let last_line = stderr.lines().last().unwrap_or("unknown error");
+        return Err(anyhow!("ffmpeg failed to extract audio: {}", last_line));
+    }
+
+    Ok(audio_path)
 }
 
 /// Handle audio transcription process
 pub async fn handle_audio_transcription(
-    _input_path: &Path,
-    _options: TranscriptionOptions,
+    input_path: &Path,
+    mut options: TranscriptionOptions,
 ) -> Result<String> {
-    // let audio_path = if mime_guess::from_path(input_path).first_raw().map_or(false, |mime| mime.starts_with("video/")) {
-    //     // Extract audio from video first
-    //     extract_audio_from_video(input_path).await?
-    // } else {
-    //     input_path.to_path_buf()
-    // };
-
-    // // Perform the actual transcription
-    // transcribe_audio(&audio_path, options).await
-    Err(anyhow!("Audio transcription temporarily disabled."))
-} 
\ No newline at end of file
+    if !input_path.is_file() {
+        return Err(anyhow!("Input file '{}' does not exist.", input_path.display()));
+    }
+
+    // Video containers and compressed audio both go through ffmpeg; WAV is decoded directly
+    let is_video = mime_guess::from_path(input_path).first_raw().map_or(false, |mime| mime.starts_with("video/"));
+    let is_wav = input_path.extension().map_or(false, |ext| ext.eq_ignore_ascii_case("wav"));
+
+    // Held until the end of this function so the extracted audio outlives transcription
+    let extracted_audio = if is_video || !is_wav {
+        Some(extract_audio_from_video(input_path).await?)
+    } else {
+        None
+    };
+    let audio_path = extracted_audio.as_deref().unwrap_or(input_path);
+
+    // Name outputs after the original input rather than the temporary WAV
+    if options.output_file.is_none() && extracted_audio.is_some() {
+        let stem = input_path.file_stem().unwrap_or_default().to_string_lossy();
+        options.output_file = Some(std::env::current_dir()?.join(stem.to_string()));
+    }
+
+    // Perform the actual transcription
+    transcribe_audio(audio_path, options).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_srt_timestamp_format() {
+        assert_eq!(format_srt_timestamp(0), "00:00:00,000");
+        assert_eq!(format_srt_timestamp(1_250), "00:00:01,250");
+        assert_eq!(format_srt_timestamp(3_723_004), "01:02:03,004");
+    }
+
+    #[test]
+    fn test_generate_srt_numbers_cues() {
+        let transcript = Transcript {
+            segments: vec![
+                TranscriptSegment { start_ms: 0, end_ms: 1_500, text: "Hello".to_string() },
+                TranscriptSegment { start_ms: 1_500, end_ms: 2_000, text: String::new() },
+                TranscriptSegment { start_ms: 2_000, end_ms: 4_000, text: "world".to_string() },
+            ],
+            language: None,
+        };
+        let srt = generate_srt(&transcript);
+        assert_eq!(
+            srt,
+            "1\n00:00:00,000 --> 00:00:01,500\nHello\n\n2\n00:00:02,000 --> 00:00:04,000\nworld\n\n"
+        );
+    }
+}
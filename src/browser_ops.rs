@@ -1,10 +1,14 @@
 use colored::*;
+use log::{debug, info};
 use std::process::Command;
 use std::path::{Path, PathBuf};
 use std::fs;
 use std::time::{SystemTime, UNIX_EPOCH};
 use glob::glob;
 use dirs;
+use sysinfo::{ProcessRefreshKind, RefreshKind, System};
+use serde_json::Value;
+use crate::utils::html_escape;
 
 // Browser profile locations
 #[derive(Debug, Clone, PartialEq)]
@@ -26,6 +30,9 @@ pub enum BrowserDataType {
     Cookies,
     Bookmarks,
     Passwords,
+    Cache,
+    LocalStorage,
+    SessionStorage,
 }
 
 // Result of browser operation
@@ -36,191 +43,247 @@ pub struct BrowserOpResult {
     pub export_path: Option<PathBuf>,
 }
 
-/// Try to close (or kill) all major browsers on the host platform.
-///
-/// For browsers that are **not** running we just print a notice and continue;
-/// we only return `Err` if the underlying shell / taskkill / killall command itself
-/// cannot be executed.
-///////////////////////////////////////////////////////////////////////////////
+/// Browser processes known on the host platform, tagged with the browser they belong to.
+/// Names are what the platform's quit/kill command expects.
 #[cfg(target_os = "macos")]
-pub fn close_browsers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    println!("{}", "Attempting to close browsers on macOS…".cyan());
-
+fn known_browser_processes() -> Vec<(BrowserType, &'static str)> {
     // macOS uses the Bundle name that appears in "Activity Monitor"
-    let browsers = [
-        "Safari",
-        "Google Chrome",
-        "Google Chrome Beta",
-        "Google Chrome Canary",
-        "Chromium",
-        "Arc",
-        "Brave Browser",
-        "Vivaldi",
-        "Firefox",
-        "Firefox Developer Edition",
-        "Firefox Nightly",
-        "Microsoft Edge",
-        "Microsoft Edge Beta",
-        "Microsoft Edge Canary",
-        "Opera",
-        "Opera GX",
-        "Tor Browser",
-        "Orion",
-        "Waterfox",
-    ];
+    vec![
+        (BrowserType::Safari, "Safari"),
+        (BrowserType::Chrome, "Google Chrome"),
+        (BrowserType::Chrome, "Google Chrome Beta"),
+        (BrowserType::Chrome, "Google Chrome Canary"),
+        (BrowserType::Other("chromium".into()), "Chromium"),
+        (BrowserType::Other("arc".into()), "Arc"),
+        (BrowserType::Brave, "Brave Browser"),
+        (BrowserType::Vivaldi, "Vivaldi"),
+        (BrowserType::Firefox, "Firefox"),
+        (BrowserType::Firefox, "Firefox Developer Edition"),
+        (BrowserType::Firefox, "Firefox Nightly"),
+        (BrowserType::Edge, "Microsoft Edge"),
+        (BrowserType::Edge, "Microsoft Edge Beta"),
+        (BrowserType::Edge, "Microsoft Edge Canary"),
+        (BrowserType::Opera, "Opera"),
+        (BrowserType::Opera, "Opera GX"),
+        (BrowserType::Other("tor".into()), "Tor Browser"),
+        (BrowserType::Other("orion".into()), "Orion"),
+        (BrowserType::Other("waterfox".into()), "Waterfox"),
+    ]
+}
 
-    let mut had_errors = false;
+#[cfg(target_os = "windows")]
+fn known_browser_processes() -> Vec<(BrowserType, &'static str)> {
+    // Executable names as they appear in Task Manager
+    vec![
+        (BrowserType::Chrome, "chrome.exe"),
+        (BrowserType::Chrome, "chrome_beta.exe"),
+        (BrowserType::Chrome, "chrome_canary.exe"),
+        (BrowserType::Edge, "msedge.exe"),
+        (BrowserType::Edge, "msedgewebview2.exe"),
+        (BrowserType::Firefox, "firefox.exe"),
+        (BrowserType::Vivaldi, "vivaldi.exe"),
+        (BrowserType::Brave, "brave.exe"),
+        (BrowserType::Opera, "opera.exe"),
+        (BrowserType::Opera, "opera_gx.exe"),
+        (BrowserType::Other("arc".into()), "arc.exe"),
+        (BrowserType::Other("chromium".into()), "chromium.exe"),
+        (BrowserType::Other("waterfox".into()), "waterfox.exe"),
+        (BrowserType::Other("tor".into()), "tor.exe"),
+        (BrowserType::Other("iexplore".into()), "iexplore.exe"),
+    ]
+}
 
-    for browser in browsers {
-        let cmd = format!("osascript -e 'quit app \"{}\"'", browser);
-        println!("Running: {}", cmd.dimmed());
+#[cfg(target_os = "linux")]
+fn known_browser_processes() -> Vec<(BrowserType, &'static str)> {
+    // Process names as they appear in `ps`
+    vec![
+        (BrowserType::Chrome, "google-chrome"),
+        (BrowserType::Chrome, "google-chrome-beta"),
+        (BrowserType::Chrome, "google-chrome-unstable"),
+        (BrowserType::Other("chromium".into()), "chromium"),
+        (BrowserType::Other("chromium".into()), "chromium-browser"),
+        (BrowserType::Brave, "brave"),
+        (BrowserType::Brave, "brave-browser"),
+        (BrowserType::Vivaldi, "vivaldi"),
+        (BrowserType::Vivaldi, "vivaldi-snapshot"),
+        (BrowserType::Firefox, "firefox"),
+        (BrowserType::Firefox, "firefox-developer-edition"),
+        (BrowserType::Other("librewolf".into()), "librewolf"),
+        (BrowserType::Other("waterfox".into()), "waterfox"),
+        (BrowserType::Other("tor".into()), "tor-browser"),
+        (BrowserType::Edge, "microsoft-edge"),
+        (BrowserType::Edge, "microsoft-edge-beta"),
+        (BrowserType::Opera, "opera"),
+        (BrowserType::Opera, "opera-beta"),
+        (BrowserType::Opera, "opera-developer"),
+        (BrowserType::Other("arc".into()), "arc"), // (if/when Arc ships on Linux)
+    ]
+}
 
-        match Command::new("sh").arg("-c").arg(&cmd).status() {
-            Ok(status) if status.success() => {
-                println!("{} Closed {}", "✓".green(), browser.green());
-            }
-            Ok(_) => {
-                // Non-zero exit code usually just means the app was not running.
-                println!("  {} was not running.", browser.dimmed());
-            }
-            Err(e) => {
-                eprintln!(
-                    "{}",
-                    format!("Failed to execute osascript for {}: {}", browser, e).red()
-                );
-                had_errors = true;
-            }
-        }
-    }
+/// How long a browser gets to exit after a graceful close before we force-kill it
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+const GRACEFUL_CLOSE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
 
-    println!("{}", "-".repeat(40).dimmed());
-    if had_errors {
-        Err("One or more osascript calls failed".into())
-    } else {
-        println!("{}", "Finished attempting to close browsers.".green());
-        Ok(())
-    }
+/// What happened when we asked one browser process to quit
+enum CloseOutcome {
+    Closed,
+    ForceKilled,
+    NotRunning,
+    Unknown(Option<i32>),
 }
 
-///////////////////////////////////////////////////////////////////////////////
-#[cfg(target_os = "windows")]
-pub fn close_browsers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    println!("{}", "Attempting to close browsers on Windows…".cyan());
+/// Run one close command through the platform shell and return its exit code
+fn run_close_command(cmd: &str) -> std::io::Result<Option<i32>> {
+    info!("Running: {}", cmd.dimmed());
+    #[cfg(target_os = "windows")]
+    let status = Command::new("cmd").args(["/C", cmd]).status()?;
+    #[cfg(not(target_os = "windows"))]
+    let status = Command::new("sh").arg("-c").arg(cmd).status()?;
+    debug!("`{}` exited with {:?}", cmd, status.code());
+    Ok(status.code())
+}
 
-    // Executable names as they appear in Task Manager
-    let browsers = [
-        "chrome.exe",
-        "chrome_beta.exe",
-        "chrome_canary.exe",
-        "msedge.exe",
-        "msedgewebview2.exe",
-        "firefox.exe",
-        "vivaldi.exe",
-        "brave.exe",
-        "opera.exe",
-        "opera_gx.exe",
-        "arc.exe",
-        "chromium.exe",
-        "waterfox.exe",
-        "tor.exe",
-        "iexplore.exe",
-    ];
+/// Check whether a process with this exact name is still alive
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn is_process_running(name: &str) -> bool {
+    let sys = System::new_with_specifics(RefreshKind::new().with_processes(ProcessRefreshKind::new()));
+    sys.processes().values().any(|process| process.name().eq_ignore_ascii_case(name))
+}
 
-    let mut had_errors = false;
+/// Poll until the named process is gone or the graceful-close timeout runs out.
+/// Returns true once the process has exited.
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn wait_for_exit(name: &str) -> bool {
+    let deadline = std::time::Instant::now() + GRACEFUL_CLOSE_TIMEOUT;
+    while std::time::Instant::now() < deadline {
+        if !is_process_running(name) {
+            return true;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(500));
+    }
+    !is_process_running(name)
+}
 
-    for browser in browsers {
-        let cmd = format!("taskkill /F /IM {}", browser);
-        println!("Running: {}", cmd.dimmed());
+/// Ask one browser process to quit using the platform's tool.
+/// `osascript quit` is already graceful, so `force` has no effect on macOS.
+#[cfg(target_os = "macos")]
+fn close_process(name: &str, _force: bool) -> std::io::Result<CloseOutcome> {
+    let code = run_close_command(&format!("osascript -e 'quit app \"{}\"'", name))?;
+    // Non-zero exit code usually just means the app was not running.
+    Ok(if code == Some(0) { CloseOutcome::Closed } else { CloseOutcome::NotRunning })
+}
 
-        match Command::new("cmd").args(&["/C", &cmd]).status() {
-            Ok(status) if status.code() == Some(0) => {
-                println!("{} Closed {}", "✓".green(), browser.green());
-            }
-            Ok(status) if status.code() == Some(128) || status.code() == Some(1) => {
-                // 128 (or 1) → "process not found"
-                println!("  {} was not running.", browser.dimmed());
-            }
-            Ok(status) => {
-                println!(
-                    "  taskkill for {} finished with exit code {:?}",
-                    browser.yellow(),
-                    status.code()
-                );
-            }
-            Err(e) => {
-                eprintln!(
-                    "{}",
-                    format!("Failed to execute taskkill for {}: {}", browser, e).red()
-                );
-                had_errors = true;
-            }
-        }
+/// Without `force`, `taskkill` sends WM_CLOSE so the browser can save its session;
+/// `/F` is only used when the process outlives [`GRACEFUL_CLOSE_TIMEOUT`].
+#[cfg(target_os = "windows")]
+fn close_process(name: &str, force: bool) -> std::io::Result<CloseOutcome> {
+    let kill = format!("taskkill /F /IM {}", name);
+    let first = if force { kill.clone() } else { format!("taskkill /IM {}", name) };
+    match run_close_command(&first)? {
+        Some(0) => {}
+        // 128 → "process not found"
+        Some(128) => return Ok(CloseOutcome::NotRunning),
+        // 1 from a graceful taskkill means the process ignored the close request; force it below
+        Some(1) if !force => {}
+        code => return Ok(CloseOutcome::Unknown(code)),
+    }
+    if force || wait_for_exit(name) {
+        return Ok(CloseOutcome::Closed);
     }
 
-    println!("{}", "-".repeat(40).dimmed());
-    if had_errors {
-        Err("One or more taskkill calls failed".into())
-    } else {
-        println!("{}", "Finished attempting to close browsers.".green());
-        Ok(())
+    println!("  {} is still running, forcing it to close…", name.yellow());
+    Ok(match run_close_command(&kill)? {
+        Some(0) => CloseOutcome::ForceKilled,
+        // Exited while we were waiting
+        Some(128) => CloseOutcome::Closed,
+        code => CloseOutcome::Unknown(code),
+    })
+}
+
+/// Without `force`, `killall` sends SIGTERM; SIGKILL is only used when the process
+/// outlives [`GRACEFUL_CLOSE_TIMEOUT`].
+#[cfg(target_os = "linux")]
+fn close_process(name: &str, force: bool) -> std::io::Result<CloseOutcome> {
+    let kill = format!("killall -KILL {}", name);
+    let first = if force { kill.clone() } else { format!("killall {}", name) };
+    if run_close_command(&first)? != Some(0) {
+        return Ok(CloseOutcome::NotRunning);
     }
+    if force || wait_for_exit(name) {
+        return Ok(CloseOutcome::Closed);
+    }
+
+    println!("  {} is still running, forcing it to close…", name.yellow());
+    // killall fails here only if the process exited in the meantime
+    Ok(if run_close_command(&kill)? == Some(0) { CloseOutcome::ForceKilled } else { CloseOutcome::Closed })
+}
+
+/// Keep the processes whose browser is in `only` (all when empty) and not in `except`
+fn select_browser_processes(
+    processes: Vec<(BrowserType, &'static str)>,
+    only: &[BrowserType],
+    except: &[BrowserType],
+) -> Vec<(BrowserType, &'static str)> {
+    processes.into_iter()
+        .filter(|(browser, _)| only.is_empty() || only.contains(browser))
+        .filter(|(browser, _)| !except.contains(browser))
+        .collect()
 }
 
+/// Try to close (or kill) major browsers on the host platform.
+///
+/// `only` restricts the run to the given browsers and `except` skips them; both empty
+/// means every known browser. Browsers are asked to quit gracefully first and only
+/// force-killed if they are still running after a short timeout; `force` skips straight
+/// to the hard kill. For browsers that are **not** running we just print a
+/// notice and continue; we only return `Err` if the underlying osascript / taskkill /
+/// killall command itself cannot be executed.
 ///////////////////////////////////////////////////////////////////////////////
-#[cfg(target_os = "linux")]
-pub fn close_browsers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    println!("{}", "Attempting to close browsers on Linux…".cyan());
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+pub fn close_browsers(only: &[BrowserType], except: &[BrowserType], force: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    println!("{}", format!("Attempting to close browsers on {}…", std::env::consts::OS).cyan());
 
-    // Process names as they appear in `ps`
-    let browsers = [
-        "google-chrome",
-        "google-chrome-beta",
-        "google-chrome-unstable",
-        "chromium",
-        "chromium-browser",
-        "brave",
-        "brave-browser",
-        "vivaldi",
-        "vivaldi-snapshot",
-        "firefox",
-        "firefox-developer-edition",
-        "librewolf",
-        "waterfox",
-        "tor-browser",
-        "microsoft-edge",
-        "microsoft-edge-beta",
-        "opera",
-        "opera-beta",
-        "opera-developer",
-        "arc", // (if/when Arc ships on Linux)
-    ];
+    let targets = select_browser_processes(known_browser_processes(), only, except);
 
-    let mut had_errors = false;
+    if targets.is_empty() {
+        println!("{}", "No known browser processes match the selection.".yellow());
+        return Ok(());
+    }
 
-    for browser in browsers {
-        let cmd = format!("killall {}", browser);
-        println!("Running: {}", cmd.dimmed());
+    let mut closed = Vec::new();
+    let mut not_running = Vec::new();
+    let mut had_errors = false;
 
-        match Command::new("sh").arg("-c").arg(&cmd).status() {
-            Ok(status) if status.success() => {
-                println!("{} Killed {}", "✓".green(), browser.green());
+    for (_, name) in targets {
+        match close_process(name, force) {
+            Ok(CloseOutcome::Closed) => {
+                println!("{} Closed {}", "✓".green(), name.green());
+                closed.push(name);
             }
-            Ok(_) => {
-                println!("  {} was not running.", browser.dimmed());
+            Ok(CloseOutcome::ForceKilled) => {
+                println!("{} Force-closed {}", "✓".yellow(), name.yellow());
+                closed.push(name);
+            }
+            Ok(CloseOutcome::NotRunning) => {
+                println!("  {} was not running.", name.dimmed());
+                not_running.push(name);
+            }
+            Ok(CloseOutcome::Unknown(code)) => {
+                println!("  closing {} finished with exit code {:?}", name.yellow(), code);
             }
             Err(e) => {
-                eprintln!(
-                    "{}",
-                    format!("Failed to execute killall for {}: {}", browser, e).red()
-                );
+                eprintln!("{}", format!("Failed to run the close command for {}: {}", name, e).red());
                 had_errors = true;
             }
         }
     }
 
     println!("{}", "-".repeat(40).dimmed());
+    println!("{} {}", "Closed:".green(), if closed.is_empty() { "none".to_string() } else { closed.join(", ") });
+    println!("{} {}", "Not running:".dimmed(), if not_running.is_empty() { "none".to_string() } else { not_running.join(", ") });
+
     if had_errors {
-        Err("One or more killall calls failed".into())
+        Err("One or more close commands failed".into())
     } else {
         println!("{}", "Finished attempting to close browsers.".green());
         Ok(())
@@ -230,7 +293,7 @@ pub fn close_browsers() -> Result<(), Box<dyn std::error::Error + Send + Sync>>
 ///////////////////////////////////////////////////////////////////////////////
 // Fallback for everything else
 #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
-pub fn close_browsers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+pub fn close_browsers(_only: &[BrowserType], _except: &[BrowserType], _force: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let os_name = std::env::consts::OS;
     eprintln!(
         "{}",
@@ -241,6 +304,132 @@ pub fn close_browsers() -> Result<(), Box<dyn std::error::Error + Send + Sync>>
 
 // ----------------------------------- Browser Cleaner -----------------------------------
 
+/// Process names a browser runs under on the host platform
+fn browser_process_names(browser: &BrowserType) -> &'static [&'static str] {
+    #[cfg(target_os = "macos")]
+    let names: &[&str] = match browser {
+        BrowserType::Chrome => &["Google Chrome"],
+        BrowserType::Firefox => &["firefox"],
+        BrowserType::Safari => &["Safari"],
+        BrowserType::Edge => &["Microsoft Edge"],
+        BrowserType::Brave => &["Brave Browser"],
+        BrowserType::Opera => &["Opera"],
+        BrowserType::Vivaldi => &["Vivaldi"],
+        BrowserType::Other(_) => &[],
+    };
+    #[cfg(target_os = "windows")]
+    let names: &[&str] = match browser {
+        BrowserType::Chrome => &["chrome.exe"],
+        BrowserType::Firefox => &["firefox.exe"],
+        BrowserType::Safari => &[],
+        BrowserType::Edge => &["msedge.exe"],
+        BrowserType::Brave => &["brave.exe"],
+        BrowserType::Opera => &["opera.exe"],
+        BrowserType::Vivaldi => &["vivaldi.exe"],
+        BrowserType::Other(_) => &[],
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let names: &[&str] = match browser {
+        BrowserType::Chrome => &["chrome", "google-chrome"],
+        BrowserType::Firefox => &["firefox", "firefox-bin"],
+        BrowserType::Safari => &[],
+        BrowserType::Edge => &["msedge", "microsoft-edge"],
+        BrowserType::Brave => &["brave", "brave-browser"],
+        BrowserType::Opera => &["opera"],
+        BrowserType::Vivaldi => &["vivaldi", "vivaldi-bin"],
+        BrowserType::Other(_) => &[],
+    };
+    names
+}
+
+/// Parse a browser name as given on the command line
+pub fn parse_browser(name: &str) -> Result<BrowserType, String> {
+    match name.trim().to_lowercase().as_str() {
+        "chrome" | "google-chrome" => Ok(BrowserType::Chrome),
+        "firefox" => Ok(BrowserType::Firefox),
+        "safari" => Ok(BrowserType::Safari),
+        "edge" | "msedge" => Ok(BrowserType::Edge),
+        "brave" => Ok(BrowserType::Brave),
+        "opera" => Ok(BrowserType::Opera),
+        "vivaldi" => Ok(BrowserType::Vivaldi),
+        other => Err(format!("Unknown browser '{}' (expected chrome, firefox, safari, edge, brave, opera or vivaldi)", other)),
+    }
+}
+
+/// Parse a browser data type as given on the command line
+pub fn parse_browser_data(name: &str) -> Result<BrowserDataType, String> {
+    match name.trim().to_lowercase().replace('_', "-").as_str() {
+        "history" => Ok(BrowserDataType::History),
+        "cookies" => Ok(BrowserDataType::Cookies),
+        "bookmarks" => Ok(BrowserDataType::Bookmarks),
+        "passwords" => Ok(BrowserDataType::Passwords),
+        "cache" => Ok(BrowserDataType::Cache),
+        "local-storage" => Ok(BrowserDataType::LocalStorage),
+        "session-storage" => Ok(BrowserDataType::SessionStorage),
+        other => Err(format!("Unknown data type '{}' (expected history, cookies, bookmarks, passwords, cache, local-storage or session-storage)", other)),
+    }
+}
+
+/// The name `parse_browser` accepts for a browser
+pub fn browser_cli_name(browser: &BrowserType) -> String {
+    match browser {
+        BrowserType::Chrome => "chrome".to_string(),
+        BrowserType::Firefox => "firefox".to_string(),
+        BrowserType::Safari => "safari".to_string(),
+        BrowserType::Edge => "edge".to_string(),
+        BrowserType::Brave => "brave".to_string(),
+        BrowserType::Opera => "opera".to_string(),
+        BrowserType::Vivaldi => "vivaldi".to_string(),
+        BrowserType::Other(name) => name.to_lowercase(),
+    }
+}
+
+/// The name `parse_browser_data` accepts for a data type
+pub fn browser_data_cli_name(data: &BrowserDataType) -> &'static str {
+    match data {
+        BrowserDataType::History => "history",
+        BrowserDataType::Cookies => "cookies",
+        BrowserDataType::Bookmarks => "bookmarks",
+        BrowserDataType::Passwords => "passwords",
+        BrowserDataType::Cache => "cache",
+        BrowserDataType::LocalStorage => "local-storage",
+        BrowserDataType::SessionStorage => "session-storage",
+    }
+}
+
+/// Check whether any process belonging to the browser is currently running
+pub fn is_browser_running(browser: &BrowserType) -> bool {
+    let names = browser_process_names(browser);
+    if names.is_empty() {
+        return false;
+    }
+    
+    let sys = System::new_with_specifics(RefreshKind::new().with_processes(ProcessRefreshKind::new()));
+    sys.processes().values().any(|process| {
+        names.iter().any(|name| process.name().eq_ignore_ascii_case(name))
+    })
+}
+
+/// Kill every process of one browser and wait (up to 5 seconds) for them to exit.
+/// Returns true once the browser is no longer running.
+pub fn close_browser(browser: &BrowserType) -> bool {
+    let names = browser_process_names(browser);
+    let sys = System::new_with_specifics(RefreshKind::new().with_processes(ProcessRefreshKind::new()));
+    for process in sys.processes().values() {
+        if names.iter().any(|name| process.name().eq_ignore_ascii_case(name)) {
+            process.kill();
+        }
+    }
+    
+    for _ in 0..10 {
+        if !is_browser_running(browser) {
+            return true;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(500));
+    }
+    false
+}
+
 /// Returns the default profile directory for a given browser based on the OS.
 /// Note: For Firefox, it attempts to find the *.default* or *.default-release* profile.
 fn get_profile_dir(browser: BrowserType) -> Option<PathBuf> {
@@ -317,7 +506,29 @@ fn find_firefox_profile_dir(profiles_path: &Path) -> Option<PathBuf> {
         .find(|p| p.is_dir())
 }
 
-/// Gets the path to a specific data file within a browser's profile.
+/// Browsers keep their disk cache outside the profile on Linux and macOS
+/// (e.g. ~/.config/google-chrome/Default -> ~/.cache/google-chrome/Default).
+/// Returns the matching cache-side directory if it exists, else the profile itself.
+fn cache_root_for_profile(profile_dir: &Path) -> PathBuf {
+    let home = dirs::home_dir();
+    let cache = dirs::cache_dir();
+    let mappings = [
+        (dirs::config_dir(), cache.clone()),
+        (dirs::data_dir(), cache.clone()),
+        (home.as_ref().map(|h| h.join(".mozilla")), cache.as_ref().map(|c| c.join("mozilla"))),
+        (dirs::data_dir().map(|d| d.join("Firefox")), cache.as_ref().map(|c| c.join("Firefox"))),
+    ];
+    
+    mappings.iter()
+        .filter_map(|(from, to)| {
+            let rel = profile_dir.strip_prefix(from.as_ref()?).ok()?;
+            Some(to.as_ref()?.join(rel))
+        })
+        .find(|candidate| candidate.exists())
+        .unwrap_or_else(|| profile_dir.to_path_buf())
+}
+
+/// Gets the path to a specific data file (or directory) within a browser's profile.
 fn get_data_file_path(browser: &BrowserType, profile_dir: &Path, data_type: BrowserDataType) -> Option<PathBuf> {
     let filename = match data_type {
         BrowserDataType::History => match browser {
@@ -340,32 +551,112 @@ fn get_data_file_path(browser: &BrowserType, profile_dir: &Path, data_type: Brow
             BrowserType::Safari => return None, // Uses Keychain
             _ => "Login Data", // Chrome, Edge, Brave, Opera, Vivaldi
         },
+        BrowserDataType::Cache => {
+            return match browser {
+                BrowserType::Firefox => Some(cache_root_for_profile(profile_dir).join("cache2")),
+                BrowserType::Safari => dirs::cache_dir().map(|c| c.join("com.apple.Safari")),
+                _ => Some(cache_root_for_profile(profile_dir).join("Cache")), // Chromium family
+            };
+        }
+        BrowserDataType::LocalStorage => match browser {
+            // Legacy store; per-site data lives in storage/default/*/ls, see data_paths_to_delete
+            BrowserType::Firefox => "webappsstore.sqlite",
+            BrowserType::Safari => "LocalStorage",
+            _ => "Local Storage", // Chrome, Edge, Brave, Opera, Vivaldi
+        },
+        BrowserDataType::SessionStorage => match browser {
+            // Kept inside sessionstore.jsonlz4 next to the saved windows and tabs, so there is
+            // no file holding only session storage
+            BrowserType::Firefox | BrowserType::Safari => return None, // Safari keeps it in memory only
+            _ => "Session Storage", // Chrome, Edge, Brave, Opera, Vivaldi
+        },
     };
     Some(profile_dir.join(filename))
 }
 
+/// Everything deleting `data_type` removes. This is the data file itself, except for
+/// Firefox local storage, which also has an `ls` folder per site under `storage/default`;
+/// the rest of `storage/` (IndexedDB, cache storage, extension data) is left alone.
+fn data_paths_to_delete(browser: &BrowserType, profile_dir: &Path, data_type: BrowserDataType) -> Option<Vec<PathBuf>> {
+    let mut paths = vec![get_data_file_path(browser, profile_dir, data_type.clone())?];
+    if matches!((browser, data_type), (BrowserType::Firefox, BrowserDataType::LocalStorage)) {
+        if let Ok(sites) = fs::read_dir(profile_dir.join("storage").join("default")) {
+            let mut site_stores: Vec<PathBuf> = sites
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path().join("ls"))
+                .filter(|ls| ls.is_dir())
+                .collect();
+            site_stores.sort();
+            paths.extend(site_stores);
+        }
+    }
+    Some(paths)
+}
+
 /// Deletes browsing data for a specific browser.
-pub fn delete_browser_data(browser: BrowserType, data_type: BrowserDataType) -> Result<BrowserOpResult, Box<dyn std::error::Error + Send + Sync>> {
+///
+/// A running browser holds its SQLite files open, so deletion is refused while it
+/// runs unless `force_close` is set, in which case the browser is closed first.
+///
+/// With `dry_run` the target path is reported and neither the browser nor the data is touched.
+pub fn delete_browser_data(browser: BrowserType, data_type: BrowserDataType, force_close: bool, dry_run: bool) -> Result<BrowserOpResult, Box<dyn std::error::Error + Send + Sync>> {
     let profile_dir = get_profile_dir(browser.clone())
         .ok_or_else(|| format!("{:?} profile directory not found", browser))?;
 
-    let data_file = get_data_file_path(&browser, &profile_dir, data_type.clone())
+    let data_files = data_paths_to_delete(&browser, &profile_dir, data_type.clone())
         .ok_or_else(|| format!("{:?} {:?} data file not supported or found", browser, data_type))?;
+    let existing: Vec<&PathBuf> = data_files.iter().filter(|path| path.exists()).collect();
+    let shown = existing.iter().map(|path| path.display().to_string()).collect::<Vec<_>>().join(", ");
 
-    if data_file.exists() {
-        fs::remove_file(&data_file)?;
-        let message = format!("Deleted {:?} {:?} at {}", browser, data_type, data_file.display());
-        println!("{} {}", "✓".green(), message);
-        Ok(BrowserOpResult { success: true, message, export_path: None })
-    } else {
-        let message = format!("{:?} {:?} file not found at {}", browser, data_type, data_file.display());
-        Err(message.into())
+    if dry_run {
+        if existing.is_empty() {
+            return Err(format!("{:?} {:?} file not found at {}", browser, data_type, data_files[0].display()).into());
+        }
+        if is_browser_running(&browser) {
+            let note = if force_close { "would be closed first" } else { "is running; rerun with --force-close to delete" };
+            println!("{} {:?} {}", "Note:".yellow(), browser, note);
+        }
+        let message = format!("Would delete {:?} {:?} at {}", browser, data_type, shown);
+        println!("{} {}", "Dry run:".yellow(), message);
+        return Ok(BrowserOpResult { success: true, message, export_path: None });
+    }
+
+    if is_browser_running(&browser) {
+        if !force_close {
+            return Err(format!(
+                "{:?} is running and holds its data files open. Close it first or rerun with --force-close.",
+                browser
+            ).into());
+        }
+        println!("{} {:?}…", "Closing".yellow(), browser);
+        if !close_browser(&browser) {
+            return Err(format!("{:?} is still running after trying to close it", browser).into());
+        }
+    }
+
+    if existing.is_empty() {
+        let message = format!("{:?} {:?} file not found at {}", browser, data_type, data_files[0].display());
+        return Err(message.into());
     }
+    for data_file in &existing {
+        // Cache and storage types are directories
+        if data_file.is_dir() {
+            fs::remove_dir_all(data_file)?;
+        } else {
+            fs::remove_file(data_file)?;
+        }
+    }
+    let message = format!("Deleted {:?} {:?} at {}", browser, data_type, shown);
+    println!("{} {}", "✓".green(), message);
+    Ok(BrowserOpResult { success: true, message, export_path: None })
 }
 
 /// Exports browser data for a specific browser.
-pub fn export_browser_data(browser: BrowserType, data_type: BrowserDataType) -> Result<BrowserOpResult, Box<dyn std::error::Error + Send + Sync>> {
-    if matches!(data_type, BrowserDataType::History | BrowserDataType::Cookies) {
+///
+/// Bookmarks are converted to Netscape bookmark HTML, which every browser can import,
+/// unless `raw` is set; everything else is copied as the raw profile file.
+pub fn export_browser_data(browser: BrowserType, data_type: BrowserDataType, raw: bool) -> Result<BrowserOpResult, Box<dyn std::error::Error + Send + Sync>> {
+    if !matches!(data_type, BrowserDataType::Bookmarks | BrowserDataType::Passwords) {
          return Err(format!("Export not supported for {:?}", data_type).into());
     }
 
@@ -375,6 +666,24 @@ pub fn export_browser_data(browser: BrowserType, data_type: BrowserDataType) ->
     let source_file = get_data_file_path(&browser, &profile_dir, data_type.clone())
          .ok_or_else(|| format!("{:?} {:?} data file not supported or found", browser, data_type))?;
 
+    if source_file.exists() && data_type == BrowserDataType::Bookmarks && !raw {
+        let bookmarks = match browser {
+            BrowserType::Firefox => read_firefox_bookmarks(&source_file)?,
+            BrowserType::Safari => {
+                return Err("Converting Safari bookmarks is not supported; use --raw to copy Bookmarks.plist".into());
+            }
+            _ => read_chromium_bookmarks(&fs::read_to_string(&source_file)?)?,
+        };
+
+        let ts = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let out_path = PathBuf::from(format!("{:?}_bookmarks-{}.html", browser, ts).to_lowercase());
+        fs::write(&out_path, bookmarks_to_netscape_html(&bookmarks))?;
+
+        let message = format!("Exported {:?} bookmarks to {}", browser, out_path.display());
+        println!("{} {}", "✓".green(), message);
+        return Ok(BrowserOpResult { success: true, message, export_path: Some(out_path) });
+    }
+
     if source_file.exists() {
         let ts = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
         let extension = source_file.extension().unwrap_or_default().to_str().unwrap_or("");
@@ -407,3 +716,258 @@ pub fn export_browser_data(browser: BrowserType, data_type: BrowserDataType) ->
         Err(message.into())
     }
 }
+
+// ----------------------------------- Bookmark Export -----------------------------------
+
+/// A bookmark or folder, independent of the browser it came from
+#[derive(Debug, Clone, PartialEq)]
+pub enum BookmarkNode {
+    Folder { title: String, add_date: u64, children: Vec<BookmarkNode> },
+    Link { title: String, url: String, add_date: u64 },
+}
+
+/// Seconds between 1601-01-01 (Chromium's epoch) and the Unix epoch
+const CHROMIUM_EPOCH_OFFSET_SECS: u64 = 11_644_473_600;
+
+/// Parse a Chromium `Bookmarks` JSON file into top-level folders
+fn read_chromium_bookmarks(json: &str) -> Result<Vec<BookmarkNode>, Box<dyn std::error::Error + Send + Sync>> {
+    let parsed: Value = serde_json::from_str(json)?;
+    let roots = parsed.get("roots").and_then(|r| r.as_object())
+        .ok_or("Bookmarks file has no 'roots' section")?;
+
+    Ok(["bookmark_bar", "other", "synced"].iter()
+        .filter_map(|key| roots.get(*key))
+        .filter_map(chromium_node)
+        .collect())
+}
+
+/// Convert one Chromium bookmark node (recursively)
+fn chromium_node(node: &Value) -> Option<BookmarkNode> {
+    let title = node.get("name").and_then(|n| n.as_str()).unwrap_or("").to_string();
+    // date_added is microseconds since 1601-01-01, stored as a string
+    let add_date = node.get("date_added")
+        .and_then(|d| d.as_str())
+        .and_then(|d| d.parse::<u64>().ok())
+        .map(|micros| (micros / 1_000_000).saturating_sub(CHROMIUM_EPOCH_OFFSET_SECS))
+        .unwrap_or(0);
+
+    match node.get("type").and_then(|t| t.as_str())? {
+        "url" => Some(BookmarkNode::Link {
+            title,
+            url: node.get("url").and_then(|u| u.as_str())?.to_string(),
+            add_date,
+        }),
+        "folder" => Some(BookmarkNode::Folder {
+            title,
+            add_date,
+            children: node.get("children")
+                .and_then(|c| c.as_array())
+                .map(|children| children.iter().filter_map(chromium_node).collect())
+                .unwrap_or_default(),
+        }),
+        _ => None,
+    }
+}
+
+/// One row of Firefox's `moz_bookmarks` joined with its URL
+struct PlacesRow {
+    id: i64,
+    parent: i64,
+    kind: i64, // 1 = bookmark, 2 = folder, 3 = separator
+    title: String,
+    url: Option<String>,
+    add_date: u64, // Seconds since the Unix epoch
+    guid: String,
+}
+
+/// Read bookmarks from Firefox's `places.sqlite`.
+/// The database is copied first because Firefox keeps it locked while running. Its `-wal`
+/// and `-shm` files come along: while Firefox runs, recent changes live only in the WAL.
+fn read_firefox_bookmarks(places: &Path) -> Result<Vec<BookmarkNode>, Box<dyn std::error::Error + Send + Sync>> {
+    let dir = tempfile::tempdir()?;
+    let copy = dir.path().join("places.sqlite");
+    fs::copy(places, &copy)?;
+    for suffix in ["-wal", "-shm"] {
+        let mut name = places.as_os_str().to_os_string();
+        name.push(suffix);
+        let mut dest = copy.as_os_str().to_os_string();
+        dest.push(suffix);
+        if Path::new(&name).exists() {
+            fs::copy(&name, &dest)?;
+        }
+    }
+    // Opened writable so SQLite can read the copied WAL; only the temporary copy is touched
+    let conn = rusqlite::Connection::open(&copy)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT b.id, b.parent, b.type, COALESCE(b.title, ''), p.url, COALESCE(b.dateAdded, 0), b.guid \
+         FROM moz_bookmarks b LEFT JOIN moz_places p ON b.fk = p.id \
+         ORDER BY b.parent, b.position"
+    )?;
+    let rows: Vec<PlacesRow> = stmt
+        .query_map([], |row| Ok(PlacesRow {
+            id: row.get(0)?,
+            parent: row.get(1)?,
+            kind: row.get(2)?,
+            title: row.get(3)?,
+            url: row.get(4)?,
+            // dateAdded is microseconds since the Unix epoch
+            add_date: row.get::<_, i64>(5)?.max(0) as u64 / 1_000_000,
+            guid: row.get(6)?,
+        }))?
+        .collect::<Result<_, _>>()?;
+
+    let mut children_of: std::collections::HashMap<i64, Vec<&PlacesRow>> = std::collections::HashMap::new();
+    for row in &rows {
+        children_of.entry(row.parent).or_default().push(row);
+    }
+
+    fn build(id: i64, children_of: &std::collections::HashMap<i64, Vec<&PlacesRow>>) -> Vec<BookmarkNode> {
+        children_of.get(&id).map(|children| {
+            children.iter().filter_map(|row| match row.kind {
+                1 => row.url.as_ref()
+                    .filter(|u| !u.starts_with("place:")) // Smart queries, not real bookmarks
+                    .map(|u| BookmarkNode::Link { title: row.title.clone(), url: u.clone(), add_date: row.add_date }),
+                2 => Some(BookmarkNode::Folder {
+                    title: row.title.clone(),
+                    add_date: row.add_date,
+                    children: build(row.id, children_of),
+                }),
+                _ => None, // Separators
+            }).collect()
+        }).unwrap_or_default()
+    }
+
+    // Top-level folders (menu, toolbar, unfiled, mobile) hang off the root; skip the tags pseudo-folder
+    let root_id = rows.iter().find(|r| r.guid == "root________").map(|r| r.id).unwrap_or(1);
+    let folders = children_of.get(&root_id).map(|roots| {
+        roots.iter()
+            .filter(|r| r.guid != "tags________")
+            .map(|r| BookmarkNode::Folder {
+                title: if r.title.is_empty() { r.guid.trim_end_matches('_').to_string() } else { r.title.clone() },
+                add_date: r.add_date,
+                children: build(r.id, &children_of),
+            })
+            .collect()
+    }).unwrap_or_default();
+
+    Ok(folders)
+}
+
+/// Render bookmarks in the Netscape bookmark file format
+pub fn bookmarks_to_netscape_html(nodes: &[BookmarkNode]) -> String {
+    fn render(nodes: &[BookmarkNode], depth: usize, out: &mut String) {
+        let indent = "    ".repeat(depth);
+        for node in nodes {
+            match node {
+                BookmarkNode::Link { title, url, add_date } => {
+                    out.push_str(&format!("{}<DT><A HREF=\"{}\" ADD_DATE=\"{}\">{}</A>\n",
+                        indent, html_escape(url), add_date, html_escape(title)));
+                }
+                BookmarkNode::Folder { title, add_date, children } => {
+                    out.push_str(&format!("{}<DT><H3 ADD_DATE=\"{}\">{}</H3>\n", indent, add_date, html_escape(title)));
+                    out.push_str(&format!("{}<DL><p>\n", indent));
+                    render(children, depth + 1, out);
+                    out.push_str(&format!("{}</DL><p>\n", indent));
+                }
+            }
+        }
+    }
+
+    let mut out = String::from(
+        "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+         <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+         <TITLE>Bookmarks</TITLE>\n\
+         <H1>Bookmarks</H1>\n\
+         <DL><p>\n"
+    );
+    render(nodes, 1, &mut out);
+    out.push_str("</DL><p>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_firefox_storage_paths() {
+        let profile = tempfile::tempdir().unwrap();
+        let site = profile.path().join("storage/default/https+++example.com");
+        fs::create_dir_all(site.join("ls")).unwrap();
+        fs::create_dir_all(site.join("idb")).unwrap();
+        fs::create_dir_all(profile.path().join("storage/default/https+++other.org/cache")).unwrap();
+
+        let paths = data_paths_to_delete(&BrowserType::Firefox, profile.path(), BrowserDataType::LocalStorage).unwrap();
+        assert_eq!(paths, vec![profile.path().join("webappsstore.sqlite"), site.join("ls")]);
+        assert_eq!(get_data_file_path(&BrowserType::Firefox, profile.path(), BrowserDataType::SessionStorage), None);
+        assert_eq!(
+            data_paths_to_delete(&BrowserType::Chrome, profile.path(), BrowserDataType::LocalStorage),
+            Some(vec![profile.path().join("Local Storage")])
+        );
+    }
+
+    #[test]
+    fn test_select_browser_processes() {
+        let processes = vec![
+            (BrowserType::Chrome, "chrome"),
+            (BrowserType::Firefox, "firefox"),
+            (BrowserType::Safari, "safari"),
+        ];
+
+        let all = select_browser_processes(processes.clone(), &[], &[]);
+        assert_eq!(all.len(), 3);
+
+        let only = select_browser_processes(processes.clone(), &[BrowserType::Chrome, BrowserType::Firefox], &[]);
+        assert_eq!(only.iter().map(|(_, name)| *name).collect::<Vec<_>>(), vec!["chrome", "firefox"]);
+
+        let except = select_browser_processes(processes, &[], &[BrowserType::Safari]);
+        assert_eq!(except.iter().map(|(_, name)| *name).collect::<Vec<_>>(), vec!["chrome", "firefox"]);
+    }
+
+    #[test]
+    fn test_chromium_bookmarks_to_netscape_html() {
+        let json = r#"{
+            "roots": {
+                "bookmark_bar": {
+                    "type": "folder", "name": "Bookmarks bar", "date_added": "13300000000000000",
+                    "children": [
+                        { "type": "url", "name": "Rust & Co", "url": "https://www.rust-lang.org/", "date_added": "13300000000000000" }
+                    ]
+                },
+                "other": { "type": "folder", "name": "Other bookmarks", "children": [] }
+            }
+        }"#;
+
+        let nodes = read_chromium_bookmarks(json).unwrap();
+        assert_eq!(nodes.len(), 2);
+
+        let html = bookmarks_to_netscape_html(&nodes);
+        assert!(html.starts_with("<!DOCTYPE NETSCAPE-Bookmark-file-1>"));
+        assert!(html.contains("<DT><H3 ADD_DATE=\"1655526400\">Bookmarks bar</H3>"));
+        assert!(html.contains("<A HREF=\"https://www.rust-lang.org/\" ADD_DATE=\"1655526400\">Rust &amp; Co</A>"));
+    }
+
+    #[test]
+    fn test_firefox_bookmarks_include_wal() {
+        let dir = tempfile::tempdir().unwrap();
+        let places = dir.path().join("places.sqlite");
+        // Like a running Firefox: WAL mode, nothing checkpointed, connection still open
+        let conn = rusqlite::Connection::open(&places).unwrap();
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL; PRAGMA wal_autocheckpoint = 0;
+             CREATE TABLE moz_places (id INTEGER PRIMARY KEY, url TEXT);
+             CREATE TABLE moz_bookmarks (id INTEGER PRIMARY KEY, parent INTEGER, type INTEGER, title TEXT,
+                 fk INTEGER, position INTEGER, dateAdded INTEGER, guid TEXT);
+             INSERT INTO moz_places VALUES (1, 'https://www.rust-lang.org/');
+             INSERT INTO moz_bookmarks VALUES (1, 0, 2, '', NULL, 0, 0, 'root________');
+             INSERT INTO moz_bookmarks VALUES (2, 1, 2, 'Toolbar', NULL, 0, 0, 'toolbar_____');
+             INSERT INTO moz_bookmarks VALUES (3, 2, 1, 'Rust', 1, 0, 1655526400000000, 'aaaaaaaaaaaa');"
+        ).unwrap();
+
+        let nodes = read_firefox_bookmarks(&places).unwrap();
+        let link = BookmarkNode::Link { title: "Rust".into(), url: "https://www.rust-lang.org/".into(), add_date: 1655526400 };
+        assert_eq!(nodes, vec![BookmarkNode::Folder { title: "Toolbar".into(), add_date: 0, children: vec![link] }]);
+        drop(conn);
+    }
+}
@@ -1,14 +1,44 @@
 use colored::*;
-use meval;
+use meval::{self, Context};
 use std::error::Error;
 
 type BoxedError = Box<dyn Error + Send + Sync>;
 
+/// Unit the trigonometric functions take (and the inverse ones return).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AngleMode {
+    #[default]
+    Rad,
+    Deg,
+}
+
+impl AngleMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            AngleMode::Rad => "rad",
+            AngleMode::Deg => "deg",
+        }
+    }
+}
+
+/// Session settings for the calculator: angle unit and how many decimals to show.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CalculatorSettings {
+    pub angle: AngleMode,
+    /// `None` prints the full value
+    pub precision: Option<usize>,
+}
+
 /// Evaluates a mathematical expression string.
 pub fn evaluate_expression(expr: &str) -> Result<f64, BoxedError> {
-    match meval::eval_str(expr) {
+    evaluate_with_settings(expr, &CalculatorSettings::default())
+}
+
+/// Evaluates an expression using the given angle mode and prints it at the given precision.
+pub fn evaluate_with_settings(expr: &str, settings: &CalculatorSettings) -> Result<f64, BoxedError> {
+    match eval(expr, settings.angle) {
         Ok(result) => {
-            println!("{} {}", "=".green(), result.to_string().bold());
+            println!("{} {}", "=".green(), format_result(result, settings).bold());
             Ok(result)
         }
         Err(e) => {
@@ -18,3 +48,272 @@ pub fn evaluate_expression(expr: &str) -> Result<f64, BoxedError> {
         }
     }
 }
+
+fn eval(expr: &str, angle: AngleMode) -> Result<f64, meval::Error> {
+    let mut ctx = Context::new();
+    if angle == AngleMode::Deg {
+        ctx.func("sin", |x: f64| x.to_radians().sin())
+            .func("cos", |x: f64| x.to_radians().cos())
+            .func("tan", |x: f64| x.to_radians().tan())
+            .func("asin", |x: f64| x.asin().to_degrees())
+            .func("acos", |x: f64| x.acos().to_degrees())
+            .func("atan", |x: f64| x.atan().to_degrees())
+            .func2("atan2", |y: f64, x: f64| y.atan2(x).to_degrees());
+    }
+    meval::eval_str_with_context(rewrite_percent(expr), &ctx)
+}
+
+// `%` is read as percent or modulo depending on what follows it:
+//   * `X% of Y` (also `X % of Y`) is X/100 * Y:                  `50% of 200` = 100
+//   * `A + X%` and `A - X%` add or take off X percent of everything before them
+//     within the same brackets, like a desk calculator:         `200 + 10%` = 220
+//   * any other `X%` is just X/100:                              `200 * 10%` = 20
+//   * `%` followed directly by a number, name or `(` is modulo: `7 % 3` = 1
+// `mod` is always modulo, which is how to write e.g. `7 mod -3` (`7 % -3` is 7% minus 3).
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(String),
+    Name(String),
+    Symbol(char),
+}
+
+fn tokenize(expr: &str) -> Vec<Token> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // Exponent, as in 1.5e-3
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            tokens.push(Token::Number(chars[start..i].iter().collect()));
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Name(chars[start..i].iter().collect()));
+        } else {
+            tokens.push(Token::Symbol(c));
+            i += 1;
+        }
+    }
+    tokens
+}
+
+/// Rewrites the percent uses of `%` and the `mod` keyword into arithmetic meval understands
+fn rewrite_percent(expr: &str) -> String {
+    let tokens = tokenize(expr);
+    let mut pos = 0;
+    let mut out = Vec::new();
+    // A stray `)` ends a group early; keep going so meval reports the mismatch
+    while pos < tokens.len() {
+        let (group, closed) = rewrite_group(&tokens, &mut pos);
+        out.extend(group);
+        if closed {
+            out.push(")".to_string());
+        }
+    }
+    // Only words need a separator; everything else is joined as is
+    let mut text = String::new();
+    for token in out {
+        let is_word = |s: &str| s.chars().next().is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.');
+        if text.chars().last().is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.') && is_word(&token) {
+            text.push(' ');
+        }
+        text.push_str(&token);
+    }
+    text
+}
+
+// Whether the rewritten tokens since `segment_start` end with a complete operand
+fn ends_operand(out: &[String], segment_start: usize) -> bool {
+    out.len() > segment_start
+        && out.last().is_some_and(|t| t == ")" || t.chars().next().is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.'))
+}
+
+// Rewrites tokens up to the `)` closing the current group or the end of the input.
+// Returns the group and whether it was closed by a `)`, which is consumed but not included.
+fn rewrite_group(tokens: &[Token], pos: &mut usize) -> (Vec<String>, bool) {
+    let mut out: Vec<String> = Vec::new();
+    let mut segment_start = 0; // Start of the current function argument
+    let mut additive: Option<usize> = None; // Last binary + or - in this segment
+    let mut operand_start = 0; // Start of the most recent operand
+    let mut sign: Option<usize> = None; // Unary sign in front of the next operand
+
+    while *pos < tokens.len() {
+        let token = &tokens[*pos];
+        *pos += 1;
+        match token {
+            Token::Symbol(')') => return (out, true),
+            Token::Symbol('(') => {
+                // A function call's operand starts at its name
+                let after_name = out.last().is_some_and(|t| t.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_'));
+                if !after_name {
+                    operand_start = sign.take().unwrap_or(out.len());
+                }
+                let (inner, closed) = rewrite_group(tokens, pos);
+                out.push("(".to_string());
+                out.extend(inner);
+                if closed {
+                    out.push(")".to_string());
+                }
+            }
+            Token::Name(name) if name == "mod" => out.push("%".to_string()),
+            Token::Number(text) | Token::Name(text) => {
+                operand_start = sign.take().unwrap_or(out.len());
+                out.push(text.clone());
+            }
+            Token::Symbol(',') => {
+                out.push(",".to_string());
+                segment_start = out.len();
+                additive = None;
+                sign = None;
+            }
+            Token::Symbol(c @ ('+' | '-')) => {
+                if ends_operand(&out, segment_start) {
+                    additive = Some(out.len());
+                } else if sign.is_none() {
+                    sign = Some(out.len());
+                }
+                out.push(c.to_string());
+            }
+            Token::Symbol('%') => match tokens.get(*pos) {
+                Some(Token::Name(next)) if next == "of" => {
+                    *pos += 1;
+                    out.extend(["/", "100", "*"].map(String::from));
+                }
+                Some(Token::Number(_) | Token::Name(_) | Token::Symbol('(')) => out.push("%".to_string()),
+                next => {
+                    let ends_term = matches!(next, None | Some(Token::Symbol(')' | ',' | '+' | '-')));
+                    match additive {
+                        // `A + X%` becomes `(A) * (1 + (X) / 100)`
+                        Some(op_idx) if ends_term && operand_start == op_idx + 1 => {
+                            let percent = out.split_off(op_idx + 1);
+                            let op = out.pop().unwrap_or_default();
+                            let base = out.split_off(segment_start);
+                            out.push("(".to_string());
+                            out.extend(base);
+                            out.extend([")", "*", "(", "1", &op, "("].map(String::from));
+                            out.extend(percent);
+                            out.extend([")", "/", "100", ")"].map(String::from));
+                            additive = None;
+                            operand_start = segment_start;
+                        }
+                        _ => {
+                            let value = out.split_off(operand_start);
+                            out.push("(".to_string());
+                            out.extend(value);
+                            out.extend(["/", "100", ")"].map(String::from));
+                        }
+                    }
+                }
+            },
+            Token::Symbol(c) => out.push(c.to_string()),
+        }
+    }
+    (out, false)
+}
+
+/// Formats a result at the configured precision, dropping a negative sign on zero.
+pub fn format_result(value: f64, settings: &CalculatorSettings) -> String {
+    match settings.precision {
+        Some(digits) => {
+            let text = format!("{:.*}", digits, value);
+            // -0.0001 at two decimals would otherwise read "-0.00"
+            if text.starts_with('-') && text[1..].chars().all(|c| c == '0' || c == '.') {
+                text[1..].to_string()
+            } else {
+                text
+            }
+        }
+        None => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_angle_modes() {
+        assert!((eval("sin(pi / 2)", AngleMode::Rad).unwrap() - 1.0).abs() < 1e-12);
+        assert!((eval("sin(90)", AngleMode::Deg).unwrap() - 1.0).abs() < 1e-12);
+        assert!((eval("acos(0)", AngleMode::Deg).unwrap() - 90.0).abs() < 1e-12);
+        assert!((eval("atan2(1, 1)", AngleMode::Deg).unwrap() - 45.0).abs() < 1e-12);
+    }
+
+    fn calc(expr: &str) -> f64 {
+        eval(expr, AngleMode::Rad).unwrap()
+    }
+
+    #[test]
+    fn test_percent() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        // Desk-calculator percentages
+        assert!(close(calc("200 + 10%"), 220.0));
+        assert!(close(calc("200 - 10%"), 180.0));
+        assert!(close(calc("100 + 10% + 10%"), 121.0));
+        assert!(close(calc("2 * 100 + 10%"), 220.0));
+        assert!(close(calc("200 + -10%"), 180.0));
+        assert!(close(calc("(100 + 10%) * 2"), 220.0));
+        assert!(close(calc("max(100 + 10%, 5)"), 110.0));
+        // "of"
+        assert!(close(calc("50 % of 200"), 100.0));
+        assert!(close(calc("50% of 200"), 100.0));
+        assert!(close(calc("10 + 50% of 200"), 110.0));
+        // Anything else is a plain fraction
+        assert!(close(calc("200 * 10%"), 20.0));
+        assert!(close(calc("10%"), 0.1));
+        assert!(close(calc("200 + 3 * 10%"), 200.3));
+        assert!(close(calc("1.5e2 + 10%"), 165.0));
+    }
+
+    #[test]
+    fn test_modulo() {
+        assert_eq!(calc("7 % 3"), 1.0);
+        assert_eq!(calc("7 % (2 + 1)"), 1.0);
+        assert_eq!(calc("7 mod 3"), 1.0);
+        assert_eq!(calc("7 mod -3"), 1.0);
+        assert_eq!(calc("10 % pi").to_string(), (10.0 % std::f64::consts::PI).to_string());
+        // A sign after % doesn't start its operand: this is 7% minus 3
+        assert!((calc("7 % -3") - (0.07 - 3.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rewrite_percent() {
+        assert_eq!(rewrite_percent("sin(x) + 2"), "sin(x)+2");
+        assert_eq!(rewrite_percent("200 + 10%"), "(200)*(1+(10)/100)");
+        assert_eq!(rewrite_percent("50% of 200"), "50/100*200");
+        assert_eq!(rewrite_percent("(1 + 2"), "(1+2");
+    }
+
+    #[test]
+    fn test_format_result() {
+        let mut settings = CalculatorSettings::default();
+        assert_eq!(format_result(2.5, &settings), "2.5");
+        settings.precision = Some(4);
+        assert_eq!(format_result(1.0 / 3.0, &settings), "0.3333");
+        settings.precision = Some(2);
+        assert_eq!(format_result(-0.0001, &settings), "0.00");
+        settings.precision = Some(0);
+        assert_eq!(format_result(2.6, &settings), "3");
+    }
+}
@@ -1,11 +1,42 @@
-use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+use std::net::{Ipv4Addr, SocketAddr};
 use std::path::PathBuf;
 use crate::unit_converter_ops::UnitConverterArgs;
+use crate::file_ops::{parse_size, HashAlgorithm};
+use crate::browser_ops::{parse_browser, parse_browser_data, BrowserDataType, BrowserType};
+use crate::dns_ops::parse_dns_server;
+use crate::network_ops::{parse_mac, IpFamily, PortPreset, ScanPacing, DEFAULT_PORT_SCAN_CONCURRENCY};
+use crate::image_download_ops::ImageLicense;
+use crate::antivirus_ops::{parse_retention, parse_timestamp};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 #[command(propagate_version = true)]
 pub struct Cli {
+    /// Disable colored output (also honors the NO_COLOR environment variable)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Only print results and errors (no short flag: -q is video-download's --quality)
+    #[arg(long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Print extra detail; repeat (-vv) for even more
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Always show long listings (list, find-duplicates, analyze-disk) in $PAGER
+    #[arg(long, global = true, conflicts_with = "no_pager")]
+    pub pager: bool,
+
+    /// Never use a pager, even when a listing is taller than the terminal
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+
+    /// Print what commands that move, rename or delete files would do, without changing anything
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
     #[command(subcommand)]
     pub command: Option<Commands>, // Make the command optional for interactive mode
 }
@@ -13,22 +44,61 @@ pub struct Cli {
 #[derive(Subcommand, Debug, Clone)]
 pub enum Commands {
     /// List files and directories in a specified path
-    List {
-        /// The path to list contents of (defaults to current directory)
-        #[arg(default_value = ".")]
-        path: PathBuf,
-    },
+    List(ListArgs),
     /// Backup a directory to another location
     Backup {
         /// The source directory to backup
         source: PathBuf,
         /// The destination directory for the backup
         destination: PathBuf,
+        /// Move the directory instead of copying it (the source is removed)
+        #[arg(long = "move", conflicts_with = "incremental")]
+        move_source: bool,
+        /// Write a timestamped snapshot inside the destination, hardlinking files unchanged since the last one
+        #[arg(long)]
+        incremental: bool,
+        /// With --incremental, compare file contents instead of size and modification time
+        #[arg(long, requires = "incremental")]
+        checksum: bool,
+    },
+    /// Close all major web browsers (or a selected subset)
+    CloseBrowsers {
+        /// Only close these browsers (comma-separated, e.g. chrome,firefox)
+        #[arg(long, value_delimiter = ',', value_parser = parse_browser, conflicts_with = "except")]
+        only: Vec<BrowserType>,
+        /// Close every browser except these (comma-separated, e.g. safari)
+        #[arg(long, value_delimiter = ',', value_parser = parse_browser)]
+        except: Vec<BrowserType>,
+        /// Force-kill right away instead of asking browsers to quit gracefully first
+        #[arg(long)]
+        force: bool,
+    },
+    /// Delete or export a browser's data (history, cookies, cache, bookmarks, ...)
+    BrowserData(BrowserDataArgs),
+    /// Move screenshots into a 'Screenshots' folder (Desktop on macOS, Pictures on Windows/Linux by default)
+    OrganizeScreenshots {
+        /// Folder to look for screenshots in (defaults to where the OS saves them)
+        #[arg(long)]
+        source: Option<PathBuf>,
+        /// Folder to move them into (defaults to 'Screenshots' inside the source)
+        #[arg(long)]
+        dest: Option<PathBuf>,
+        /// File them into YYYY-MM subfolders by the date they were taken
+        #[arg(long)]
+        by_date: bool,
+    },
+    /// Rename photos to their capture date (YYYYMMDD_HHMMSS.ext) and/or file them into YYYY-MM folders
+    PhotoSort {
+        /// Folder holding the photos
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Also move them into YYYY-MM subfolders
+        #[arg(long)]
+        folders: bool,
+        /// Keep the original file names (only file them into folders)
+        #[arg(long, requires = "folders")]
+        keep_names: bool,
     },
-    /// Close all major web browsers
-    CloseBrowsers,
-    /// [macOS only] Organize screenshots on the Desktop into a 'Screenshots' folder
-    OrganizeScreenshots,
     /// Analyze disk usage for a given path, showing large files
     AnalyzeDisk {
         /// The path to analyze (defaults to current directory)
@@ -37,15 +107,58 @@ pub enum Commands {
         /// Number of largest files/directories to show
         #[arg(short, long, default_value_t = 10)]
         top: usize,
+        /// Show total size and file count per extension instead of the largest files
+        #[arg(long)]
+        by_extension: bool,
     },
-    /// [EXPERIMENTAL] Identify temporary files and cache locations
+    /// [EXPERIMENTAL] Identify temporary files and cache locations (never deletes anything yet)
     CleanSystem {
-        /// Show what would be identified without actually deleting
-        #[arg(long, default_value_t = true)]
-        dry_run: bool,
         // TODO: Add --delete flag later with confirmation
     },
-    /// Batch rename files in a directory using regex
+    /// Find (and optionally delete) empty directories and dangling symlinks
+    CleanEmpty {
+        /// The path to clean (defaults to current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Remove what is found instead of only listing it
+        #[arg(long)]
+        delete: bool,
+    },
+    /// Sort the loose files in a directory (e.g. Downloads) into subfolders
+    Organize {
+        /// The directory to organize
+        path: PathBuf,
+        /// How to group the files
+        #[arg(long, value_enum, default_value_t = OrganizeBy::Type)]
+        by: OrganizeBy,
+    },
+    /// Watch a directory and organize new files as they arrive (runs until Ctrl+C)
+    Watch {
+        /// The directory to watch
+        path: PathBuf,
+        /// How to group the files
+        #[arg(long, value_enum, default_value_t = OrganizeBy::Type)]
+        by: OrganizeBy,
+        /// Seconds a file must go without changes before it is moved
+        #[arg(long, default_value_t = 5)]
+        settle: u64,
+    },
+    /// Write or verify a sha256sum-compatible checksum manifest for a directory
+    Hashsum {
+        /// The directory to hash (defaults to current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Hash algorithm for a new manifest
+        #[arg(short, long, value_enum, default_value_t = HashAlgorithm::Sha256)]
+        algo: HashAlgorithm,
+        /// Write the manifest to this file instead of stdout
+        #[arg(short, long, conflicts_with = "verify")]
+        output: Option<PathBuf>,
+        /// Re-hash the files and report changes against this manifest
+        #[arg(long)]
+        verify: Option<PathBuf>,
+    },
+    /// Batch rename files in a directory (optionally recursively) using regex
     Rename(RenameArgs),
     /// Find duplicate files in a directory based on content hash
     FindDuplicates {
@@ -55,6 +168,12 @@ pub enum Commands {
         /// Minimum file size to consider for duplicates (e.g., 1k, 1M)
         #[arg(short, long, default_value = "1k")]
         min_size: String,
+        /// Hash used to compare contents; blake3 is much faster on large files
+        #[arg(short, long, value_enum, default_value_t = HashAlgorithm::Sha256)]
+        algo: HashAlgorithm,
+        /// How much of a file to read at a time while hashing (e.g., 64k, 1M)
+        #[arg(long, default_value = "64k")]
+        buffer_size: String,
     },
     /// Synchronize contents from a source directory to a destination (one-way)
     SyncFolders(SyncArgs),
@@ -70,10 +189,18 @@ pub enum Commands {
     Bandwidth {},
     /// Scan a host for open TCP ports
     PortScan(PortScanArgs),
+    /// Discover live devices on the local networks
+    Discover(DiscoverArgs),
+    /// Wake a device with a Wake-on-LAN magic packet
+    Wol(WolArgs),
     /// Make a simple HTTP request
     HttpRequest(HttpRequestArgs),
+    /// Show a server's TLS certificate chain, protocol version and cipher
+    HttpCert(HttpCertArgs),
     /// Manage local DNS cache
     DnsCache(DnsCacheArgs),
+    /// Query DNS records (dig-style)
+    Dns(DnsArgs),
     /// Ping a host to check connectivity and response time
     Ping(PingArgs),
     /// Perform unit conversions
@@ -84,14 +211,233 @@ pub enum Commands {
     IpInfo(IPInfoArgs),
     /// Download a file from a URL with retries, resume support, and parallel connections
     Download(DownloadArgs),
+    /// Measure latency and download (optionally upload) throughput
+    Speedtest(SpeedtestArgs),
     /// Download videos from platforms like YouTube, Vimeo, etc.
     VideoDownload(VideoDownloadArgs),
     /// Search and download images from the web
+    ///
+    /// Set PIXABAY_API_KEY and/or UNSPLASH_ACCESS_KEY (or put them in
+    /// <config dir>/terminal-pc-matrix/api_keys.env) to enable those providers;
+    /// without keys only web search is used.
     ImageDownload(ImageDownloadArgs),
     /// Display system specifications and hardware information
     PCSpecs(PCSpecsArgs),
-    // /// Transcribe audio from files (or extract audio from videos) to text
-    // AudioTranscribe(AudioTranscribeArgs),
+    /// Transcribe audio from files (or extract audio from videos) to text
+    AudioTranscribe(AudioTranscribeArgs),
+    /// Live system monitoring
+    System(SystemArgs),
+    /// Scan a file or directory for viruses with ClamAV
+    Scan(ScanArgs),
+    /// List or restore files quarantined by the antivirus scanner
+    Quarantine(QuarantineArgs),
+    /// Run the interactive text menu
+    Interactive {
+        /// Read answers to the prompts from this file (one per line) instead of stdin
+        #[arg(long)]
+        answers_file: Option<PathBuf>,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ScanArgs {
+    /// File or directory to scan
+    pub path: PathBuf,
+    /// Descend into subdirectories
+    #[arg(short, long)]
+    pub recursive: bool,
+    /// Scan through the clamd daemon (falls back to clamscan if it is not running)
+    #[arg(long, conflicts_with = "no_daemon")]
+    pub daemon: bool,
+    /// Always use clamscan, even when clamd is running
+    #[arg(long)]
+    pub no_daemon: bool,
+    /// Skip files and directories matching this glob (e.g. '*.iso'); can be repeated
+    #[arg(long)]
+    pub exclude: Vec<String>,
+    /// Skip files larger than this size (e.g. 500M, 2G)
+    #[arg(long, value_parser = parse_size)]
+    pub max_file_size: Option<u64>,
+    /// Move infected files into the quarantine (see the quarantine command)
+    #[arg(long)]
+    pub quarantine: bool,
+    /// Only scan files modified since this time (e.g. 2024-05-01, '2024-05-01 18:30' or RFC 3339)
+    #[arg(long, value_parser = parse_timestamp, conflicts_with = "changed_only")]
+    pub since: Option<std::time::SystemTime>,
+    /// Only scan files changed since this directory's last --changed-only scan; the first run,
+    /// and the first run after a signature update, scan everything
+    #[arg(long)]
+    pub changed_only: bool,
+    /// Also save the results, with scan times and signature version, to this file
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    /// Format of the --output file (defaults to json for .json files, csv otherwise)
+    #[arg(short, long, value_enum, requires = "output")]
+    pub format: Option<ExportFormat>,
+}
+
+#[derive(Args, Debug, Clone)]
+#[group(skip)]
+#[command(group(ArgGroup::new("action").required(true).args(["list", "restore", "purge", "empty"])))]
+pub struct QuarantineArgs {
+    /// List quarantined files with their ids
+    #[arg(long)]
+    pub list: bool,
+    /// Restore the quarantined file with this id to its original path
+    #[arg(long, value_name = "ID")]
+    pub restore: Option<String>,
+    /// Delete quarantined files older than --older-than
+    #[arg(long, requires = "older_than")]
+    pub purge: bool,
+    /// Age limit for --purge, e.g. 30d, 12h or 2w (a bare number means days)
+    #[arg(long, value_name = "AGE", value_parser = parse_retention, conflicts_with_all = ["list", "restore", "empty"])]
+    pub older_than: Option<chrono::Duration>,
+    /// Delete everything in the quarantine
+    #[arg(long)]
+    pub empty: bool,
+    /// Overwrite files with zeros before unlinking them
+    #[arg(long, conflicts_with_all = ["list", "restore"])]
+    pub secure: bool,
+    /// Don't ask for confirmation before --empty
+    #[arg(short, long, conflicts_with_all = ["list", "restore", "purge"])]
+    pub yes: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct BrowserDataArgs {
+    #[command(subcommand)]
+    pub command: BrowserDataCommand,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum BrowserDataCommand {
+    /// Delete data from a browser profile
+    Delete {
+        /// Browser (chrome, firefox, safari, edge, brave, opera, vivaldi)
+        #[arg(value_parser = parse_browser)]
+        browser: BrowserType,
+        /// Data type (history, cookies, cache, local-storage, session-storage)
+        #[arg(value_parser = parse_browser_data)]
+        data: BrowserDataType,
+        /// Close the browser first if it is running
+        #[arg(long)]
+        force_close: bool,
+    },
+    /// Export data from a browser profile to the current directory
+    Export {
+        /// Browser (chrome, firefox, safari, edge, brave, opera, vivaldi)
+        #[arg(value_parser = parse_browser)]
+        browser: BrowserType,
+        /// Data type (bookmarks, passwords)
+        #[arg(value_parser = parse_browser_data)]
+        data: BrowserDataType,
+        /// Copy the raw profile file instead of converting bookmarks to HTML
+        #[arg(long)]
+        raw: bool,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SystemArgs {
+    #[command(subcommand)]
+    pub command: SystemCommand,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum SystemCommand {
+    /// Redraw CPU, memory and per-core load until Ctrl+C, like a mini top
+    Watch {
+        /// Refresh interval in seconds (0.1-3600)
+        #[arg(short, long, value_parser = parse_interval, default_value_t = 1.0)]
+        interval: f64,
+    },
+    /// Live disk read/write and per-interface network throughput until Ctrl+C
+    Io {
+        /// Refresh interval in seconds (0.1-3600)
+        #[arg(short, long, value_parser = parse_interval, default_value_t = 1.0)]
+        interval: f64,
+    },
+    /// List the processes using the most CPU or memory
+    Top {
+        /// Number of processes to show
+        #[arg(short = 'n', long, default_value_t = 10)]
+        count: usize,
+        /// Sort by CPU usage or memory
+        #[arg(short, long, value_enum, default_value_t = ProcessSort::Cpu)]
+        sort: ProcessSort,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProcessSort {
+    Cpu,
+    Mem,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ListArgs {
+    /// The path to list contents of (defaults to current directory)
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+    /// Render a recursive, indented tree instead of a flat listing
+    #[arg(short, long)]
+    pub tree: bool,
+    /// Maximum depth to descend when rendering a tree
+    #[arg(short, long, requires = "tree")]
+    pub depth: Option<usize>,
+    /// Sort entries by the given key
+    #[arg(short, long, value_enum, default_value_t = ListSort::Name)]
+    pub sort: ListSort,
+    /// Reverse the sort order
+    #[arg(long)]
+    pub reverse: bool,
+    /// Show sizes in human-readable units (default)
+    #[arg(long, overrides_with = "bytes")]
+    pub human: bool,
+    /// Show sizes as raw byte counts
+    #[arg(long, overrides_with = "human")]
+    pub bytes: bool,
+}
+
+impl ListArgs {
+    /// Default listing options for the given path (flat, sorted by name).
+    pub fn for_path(path: PathBuf) -> Self {
+        Self {
+            path,
+            tree: false,
+            depth: None,
+            sort: ListSort::Name,
+            reverse: false,
+            human: true,
+            bytes: false,
+        }
+    }
+
+    /// Whether sizes are shown as raw byte counts. Of `--human` and `--bytes` the last one
+    /// given wins; human-readable is the default, including when both are set in code.
+    pub fn raw_sizes(&self) -> bool {
+        self.bytes && !self.human
+    }
+}
+
+#[derive(ValueEnum, Clone, Debug, Copy, PartialEq, Eq)]
+pub enum ListSort {
+    /// Sort alphabetically by name
+    Name,
+    /// Sort by file size (directories count as zero)
+    Size,
+    /// Sort by last modification time
+    Mtime,
+}
+
+#[derive(ValueEnum, Clone, Debug, Copy, PartialEq, Eq)]
+pub enum OrganizeBy {
+    /// Images/, Videos/, Audio/, Documents/, Archives/ and Other/
+    Type,
+    /// YYYY-MM/ folders by last modification time
+    Date,
+    /// One folder per file extension (e.g. pdf/)
+    Extension,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -105,9 +451,15 @@ pub struct RenameArgs {
     /// The replacement string (can use capture groups like $1, $2)
     #[arg(short, long)]
     pub replacement: String,
-    /// Perform a dry run without actually renaming files
-    #[arg(long)]
+    /// Only print the renames; set from the global --dry-run
+    #[arg(skip)]
     pub dry_run: bool,
+    /// Walk subdirectories and rename matching files at any depth
+    #[arg(short = 'R', long)]
+    pub recursive: bool,
+    /// Also rename directories whose names match the pattern
+    #[arg(long)]
+    pub include_dirs: bool,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -116,24 +468,163 @@ pub struct SyncArgs {
     pub source: PathBuf,
     /// The destination directory
     pub destination: PathBuf,
-    /// Perform a dry run without actually copying or deleting
-    #[arg(long)]
+    /// Only print the copies and deletions; set from the global --dry-run
+    #[arg(skip)]
     pub dry_run: bool,
     /// Delete files in the destination that are not present in the source
     #[arg(long)]
     pub delete: bool,
+    /// What to do with symbolic links in the source (default: preserve)
+    #[arg(long, value_enum, default_value_t = SymlinkPolicy::Preserve)]
+    pub symlinks: SymlinkPolicy,
+}
+
+#[derive(ValueEnum, Clone, Debug, Copy, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Recreate the link itself at the destination
+    Preserve,
+    /// Copy the files and directories the link points to
+    Follow,
+    /// Leave links out of the sync (and never delete what the destination has in their place)
+    Skip,
 }
 
 #[derive(Args, Debug, Clone)]
 pub struct PortScanArgs {
     /// The target host (IP address or hostname) to scan
     pub host: String,
-    /// Ports to scan (e.g., 80, 1-1024, 80,443,1000-2000)
-    #[arg(short, long, value_parser = parse_ports, default_value = "1-1024")]
-    pub ports: Vec<u16>,
+    /// Ports to scan (e.g., 80, 1-1024, 80,443,1000-2000, or a preset name like top-100)
+    // Spelled std::vec::Vec so clap treats the parsed list as one value
+    #[arg(short, long, value_parser = parse_ports, default_value = "top-100", group = "port_set")]
+    pub ports: std::vec::Vec<u16>,
+    /// Scan the 100 most common TCP ports (quick scan)
+    #[arg(long, group = "port_set")]
+    pub top_100: bool,
+    /// Scan the 1000 most common TCP ports (nmap's default set)
+    #[arg(long, group = "port_set")]
+    pub top_1000: bool,
+    /// Scan every port below 1024 plus common high ports (databases, proxies, dev servers)
+    #[arg(long, group = "port_set")]
+    pub common: bool,
+    /// Scan the well-known ports (1-1023)
+    #[arg(long, group = "port_set")]
+    pub well_known: bool,
+    /// Scan every port (1-65535)
+    #[arg(long, group = "port_set")]
+    pub all: bool,
     /// Timeout for each port connection in milliseconds
     #[arg(short, long, default_value_t = 100)]
     pub timeout: u64,
+    /// Print bare port numbers without service names
+    #[arg(long)]
+    pub numeric_ports: bool,
+    /// Only scan the host's IPv4 address
+    #[arg(short = '4', long, conflicts_with = "ipv6")]
+    pub ipv4: bool,
+    /// Only scan the host's IPv6 address (AAAA record)
+    #[arg(short = '6', long)]
+    pub ipv6: bool,
+    /// Most probes in flight at once
+    #[arg(short, long, default_value_t = DEFAULT_PORT_SCAN_CONCURRENCY)]
+    pub concurrency: usize,
+    /// Start at most this many probes per second (for hosts that rate-limit or alert on fast scans)
+    #[arg(long, value_name = "PPS", value_parser = clap::value_parser!(u32).range(1..))]
+    pub rate: Option<u32>,
+    /// Probe the ports in random order instead of ascending
+    #[arg(long)]
+    pub randomize: bool,
+}
+
+impl PortScanArgs {
+    /// The ports to scan: the chosen preset, or the explicit `--ports` list
+    pub fn selected_ports(&self) -> Vec<u16> {
+        let preset = if self.top_100 {
+            Some(PortPreset::Top100)
+        } else if self.top_1000 {
+            Some(PortPreset::Top1000)
+        } else if self.common {
+            Some(PortPreset::Common)
+        } else if self.well_known {
+            Some(PortPreset::WellKnown)
+        } else if self.all {
+            Some(PortPreset::All)
+        } else {
+            None
+        };
+        preset.map(PortPreset::ports).unwrap_or_else(|| self.ports.clone())
+    }
+
+    /// Address family picked by `--ipv4` / `--ipv6`; IPv4 is preferred when neither is given
+    pub fn family(&self) -> IpFamily {
+        IpFamily::from_flags(self.ipv4, self.ipv6)
+    }
+
+    /// Probe limits from `--concurrency`, `--rate` and `--randomize`
+    pub fn pacing(&self) -> ScanPacing {
+        ScanPacing { concurrency: self.concurrency, rate: self.rate, randomize: self.randomize }
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DiscoverArgs {
+    /// Ports probed on each host (e.g., 22,80,443 or 1-1024)
+    #[arg(short, long, value_parser = parse_ports, default_value = "22,80,443,3389,8080,8443")]
+    pub ports: std::vec::Vec<u16>,
+    /// Timeout for each probe in milliseconds
+    #[arg(short, long, default_value_t = 350)]
+    pub timeout: u64,
+    /// Retry each probe once after a timeout (useful on flaky wireless networks)
+    #[arg(long)]
+    pub retry: bool,
+    /// Find hosts with ARP requests, which also finds devices with no open ports (needs root; falls back to TCP)
+    #[arg(long)]
+    pub arp: bool,
+    /// Only scan IPv4 subnets
+    #[arg(short = '4', long, conflicts_with = "ipv6")]
+    pub ipv4: bool,
+    /// Only scan IPv6 networks (hosts in the neighbor table of each local /64)
+    #[arg(short = '6', long)]
+    pub ipv6: bool,
+    /// Skip reverse-DNS lookups and list devices by address (faster on networks with slow PTR records)
+    #[arg(long)]
+    pub no_resolve: bool,
+    /// Also save the discovered devices to this file
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    /// Format of the --output file (defaults to json for .json files, csv otherwise)
+    #[arg(short, long, value_enum, requires = "output")]
+    pub format: Option<ExportFormat>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// Pretty-printed JSON array
+    Json,
+}
+
+impl ExportFormat {
+    /// Format implied by a file extension: JSON for .json, CSV otherwise
+    pub fn for_path(path: &std::path::Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => ExportFormat::Json,
+            _ => ExportFormat::Csv,
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct WolArgs {
+    /// MAC address of the device to wake (e.g., 00:11:22:33:44:55 or 00-11-22-33-44-55)
+    #[arg(value_parser = parse_mac)]
+    pub mac: [u8; 6],
+    /// Broadcast address to send the magic packet to
+    #[arg(short, long, default_value = "255.255.255.255")]
+    pub broadcast: Ipv4Addr,
+    /// UDP port to send the magic packet to
+    #[arg(short, long, default_value_t = 9)]
+    pub port: u16,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -141,14 +632,77 @@ pub struct HttpRequestArgs {
     /// HTTP method
     #[arg(short, long, value_parser = clap::value_parser!(String), default_value = "GET")]
     pub method: String, // Use String, reqwest::Method isn't easily parsable by clap
+    /// Send a HEAD request and show only the status and headers (same as --method HEAD)
+    #[arg(short = 'I', long, conflicts_with_all = ["method", "graphql"])]
+    pub head: bool,
     /// Target URL
     pub url: String,
     /// Request body (for POST, PUT, etc.)
     #[arg(short, long)]
     pub body: Option<String>,
+    /// Read the request body from this file
+    #[arg(long, conflicts_with_all = ["body", "json"])]
+    pub body_file: Option<PathBuf>,
+    /// JSON request body; also sets Content-Type: application/json
+    #[arg(long, conflicts_with = "body")]
+    pub json: Option<String>,
+    /// Send this GraphQL query as a JSON POST and show the data and errors of the reply separately
+    #[arg(long, conflicts_with_all = ["body", "body_file", "json"])]
+    pub graphql: Option<String>,
+    /// Variables for the GraphQL query as a JSON object
+    #[arg(long, requires = "graphql")]
+    pub variables: Option<String>,
+    /// Send the body even with GET or HEAD, which some servers reject
+    #[arg(long)]
+    pub force_body: bool,
     /// Custom headers (format: key=value)
     #[arg(short = 'H', long, value_parser = parse_header)]
     pub headers: Vec<(String, String)>,
+    /// Read headers from this file, one `key: value` per line; -H flags override them
+    #[arg(long)]
+    pub headers_file: Option<PathBuf>,
+    /// HTTP Basic credentials (format: user:pass)
+    #[arg(short, long, conflicts_with = "bearer")]
+    pub user: Option<String>,
+    /// Bearer token for the Authorization header
+    #[arg(long)]
+    pub bearer: Option<String>,
+    /// Print only this path of a JSON response (e.g. .data.items[0].name)
+    #[arg(long)]
+    pub jq: Option<String>,
+    /// Cookies to send (format: name=value)
+    #[arg(short, long = "cookie", value_parser = parse_cookie)]
+    pub cookies: Vec<(String, String)>,
+    /// File to load cookies from and save them back to after the request
+    #[arg(long)]
+    pub cookie_jar: Option<PathBuf>,
+    /// Print the equivalent curl command before sending the request
+    #[arg(long, conflicts_with = "repeat")]
+    pub print_curl: bool,
+    /// Save the request and response to this file as a HAR log (importable in browser devtools)
+    #[arg(long, value_name = "FILE", conflicts_with = "repeat")]
+    pub save_har: Option<PathBuf>,
+    /// Keep Authorization and cookie values in the --print-curl and --save-har output instead of redacting them
+    #[arg(long)]
+    pub no_redact: bool,
+    /// Send the request N times and report latency percentiles and requests per second
+    #[arg(long)]
+    pub repeat: Option<usize>,
+    /// How many repeated requests to keep in flight at once
+    #[arg(long, default_value_t = 1, requires = "repeat")]
+    pub concurrency: usize,
+    /// Stop repeating once more than this percentage of requests has failed
+    #[arg(long, default_value_t = 50.0, requires = "repeat")]
+    pub max_error_rate: f64,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct HttpCertArgs {
+    /// Host to inspect (e.g., example.com, example.com:8443 or https://example.com)
+    pub host: String,
+    /// Port to connect to when the host doesn't include one
+    #[arg(short, long, default_value_t = 443)]
+    pub port: u16,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -158,6 +712,47 @@ pub struct DnsCacheArgs {
     pub action: DnsAction,
 }
 
+#[derive(Args, Debug, Clone)]
+pub struct DnsArgs {
+    #[command(subcommand)]
+    pub command: DnsCommand,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum DnsCommand {
+    /// Look up records for a name, or the PTR record when given an IP address
+    Lookup {
+        /// Domain name or IP address
+        name: String,
+        /// Record type to query
+        #[arg(short = 't', long = "type", value_enum, default_value_t = DnsRecordType::A)]
+        record_type: DnsRecordType,
+        /// DNS server to query, e.g. 1.1.1.1 or 9.9.9.9:53 (defaults to the system resolver)
+        #[arg(short, long, value_parser = parse_dns_server)]
+        server: Option<SocketAddr>,
+    },
+    /// Compare answers from Google, Cloudflare, Quad9 and OpenDNS to see if a change is live
+    Propagation {
+        /// Domain name to check
+        name: String,
+        /// Record type to query
+        #[arg(short = 't', long = "type", value_enum, default_value_t = DnsRecordType::A)]
+        record_type: DnsRecordType,
+    },
+}
+
+#[derive(ValueEnum, Clone, Debug, Copy, PartialEq)]
+pub enum DnsRecordType {
+    A,
+    Aaaa,
+    Mx,
+    Txt,
+    Cname,
+    Ns,
+    Soa,
+    Ptr,
+}
+
 #[derive(Args, Debug, Clone)]
 pub struct PingArgs {
     /// The target host to ping (hostname or IP address)
@@ -171,14 +766,21 @@ pub struct PingArgs {
 pub enum DnsAction {
     /// Flush the operating system's DNS cache
     Flush,
-    // View (Difficult to implement reliably cross-platform, maybe add later)
-    // View,
+    /// List cached names with their record types and TTLs (Windows, best effort on macOS)
+    View,
 }
 
 #[derive(Args, Debug, Clone)]
 pub struct WhoisArgs {
-    /// The domain name to lookup (e.g., google.com)
-    pub domain: String,
+    /// The domain name(s) to lookup (e.g., google.com)
+    #[arg(required = true)]
+    pub domains: Vec<String>,
+    /// Only report whether each domain is registered or available
+    #[arg(long)]
+    pub available: bool,
+    /// Show only registrar, creation/expiry dates and name servers instead of the full record
+    #[arg(short, long, conflicts_with = "available")]
+    pub summary: bool,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -193,6 +795,34 @@ pub struct IPInfoArgs {
     /// Show ASN (Autonomous System Number) information
     #[arg(short = 'n', long)]
     pub asn: bool,
+    
+    /// Look up offline in this MaxMind GeoLite2 .mmdb file (City/Country or ASN; repeatable).
+    /// Defaults to the paths in $GEOIP_DB; without any, ipinfo.io is queried
+    #[arg(long = "db")]
+    pub db: Vec<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SpeedtestArgs {
+    /// URL of a file to download instead of the default Cloudflare endpoint
+    #[arg(long)]
+    pub server: Option<String>,
+
+    /// Bytes to request from the default endpoint (e.g., 25M); ignored with --server
+    #[arg(long, value_parser = parse_size, default_value = "25M")]
+    pub size: u64,
+
+    /// Also measure upload throughput
+    #[arg(short, long)]
+    pub upload: bool,
+
+    /// Bytes to upload (e.g., 10M)
+    #[arg(long, value_parser = parse_size, default_value = "10M")]
+    pub upload_size: u64,
+
+    /// URL to POST the upload to; required for --upload together with --server
+    #[arg(long, requires = "upload")]
+    pub upload_url: Option<String>,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -200,7 +830,7 @@ pub struct DownloadArgs {
     /// URL of the file to download
     pub url: String,
     
-    /// Path where the file should be saved (defaults to filename from URL in current directory)
+    /// Path where the file should be saved (defaults to the server-suggested or URL filename in the current directory)
     #[arg(short, long)]
     pub output: Option<PathBuf>,
     
@@ -215,6 +845,34 @@ pub struct DownloadArgs {
     /// Number of parallel connections for downloading (set to 1 for single connection)
     #[arg(short, long, default_value_t = 1)]
     pub parallel: usize,
+
+    /// Maximum download speed in bytes/s (e.g., 500k, 2M), shared across parallel connections
+    #[arg(long, value_parser = parse_size)]
+    pub rate_limit: Option<u64>,
+
+    /// Abort the whole download if it takes longer than this many seconds
+    #[arg(long)]
+    pub max_time: Option<u64>,
+
+    /// Retry a connection that receives no data for this many seconds (0 disables)
+    #[arg(long, default_value_t = 30)]
+    pub stall_timeout: u64,
+
+    /// Alternative URL serving the same file (repeatable); used in order when the primary fails
+    #[arg(short, long = "mirror")]
+    pub mirrors: Vec<String>,
+
+    /// Expected SHA-256 checksum (hex) to verify the downloaded file against
+    #[arg(long, conflicts_with = "md5")]
+    pub sha256: Option<String>,
+
+    /// Expected MD5 checksum (hex) to verify the downloaded file against
+    #[arg(long)]
+    pub md5: Option<String>,
+
+    /// Start even when the destination doesn't seem to have room for the file
+    #[arg(long)]
+    pub force: bool,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -238,18 +896,58 @@ pub struct VideoDownloadArgs {
     #[arg(short = 'i', long)]
     pub info_only: bool,
     
+    /// Print video information as JSON (use with --info-only)
+    #[arg(long, requires = "info_only")]
+    pub json: bool,
+    
+    /// List the formats available for the video and exit
+    #[arg(long, conflicts_with = "info_only")]
+    pub list_formats: bool,
+    
+    /// Download this exact yt-dlp format id (e.g., 137+140), overriding --quality
+    #[arg(long)]
+    pub format_id: Option<String>,
+    
     /// Rate limit in bytes/s (e.g., 2M for 2MB/s)
     #[arg(short = 'r', long = "rate-limit")]
     pub rate_limit: Option<String>,
     
-    /// Number of concurrent downloads for playlists (default: 3)
-    #[arg(short = 'j', long = "concurrent", default_value_t = 3)]
-    pub concurrent: usize,
+    /// Number of concurrent downloads for playlists (default: 3; at most two per CPU core, up to 16)
+    #[arg(short = 'j', long = "concurrent")]
+    pub concurrent: Option<usize>,
+    
+    /// Playlist items to download (e.g., 1-5,8,10-)
+    #[arg(long, conflicts_with_all = ["playlist_start", "playlist_end"])]
+    pub playlist_items: Option<String>,
+    
+    /// First playlist item to download (1-based)
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub playlist_start: Option<u64>,
+    
+    /// Last playlist item to download (inclusive)
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub playlist_end: Option<u64>,
+    
+    /// Write URLs of failed playlist videos to a file for re-running (default: failed.txt)
+    #[arg(long, num_args = 0..=1, default_missing_value = "failed.txt")]
+    pub failed_list: Option<PathBuf>,
     
     /// Download subtitles if available
     #[arg(short = 's', long)]
     pub subtitles: bool,
     
+    /// Subtitle languages to fetch, comma-separated (e.g., en.*,de); implies --subtitles
+    #[arg(long, value_delimiter = ',')]
+    pub sub_langs: Vec<String>,
+    
+    /// Prefer human-written subtitles over auto-generated ones
+    #[arg(long)]
+    pub human_subs: bool,
+    
+    /// Embed subtitles into the video container instead of writing sidecar .srt files; implies --subtitles
+    #[arg(long)]
+    pub embed_subs: bool,
+    
     /// Path to cookies file for authenticated downloads
     #[arg(long = "cookies")]
     pub cookies_file: Option<String>,
@@ -265,12 +963,34 @@ pub struct VideoDownloadArgs {
     /// Number of retries on failure
     #[arg(long, default_value_t = 10)]
     pub retries: usize,
+    
+    /// Write title, artist and upload date into the file's metadata (needs ffmpeg)
+    #[arg(long)]
+    pub embed_metadata: bool,
+    
+    /// Embed the video thumbnail as cover art (needs ffmpeg)
+    #[arg(long)]
+    pub embed_thumbnail: bool,
+    
+    /// Run `yt-dlp -U` when a download fails in a way that suggests yt-dlp is out of date
+    #[arg(long)]
+    pub auto_update: bool,
+    
+    /// yt-dlp output template inside the output directory (e.g. '%(uploader)s/%(title)s.%(ext)s'); must contain %(ext)s
+    #[arg(long)]
+    pub output_template: Option<String>,
 }
 
 #[derive(Args, Debug, Clone)]
 pub struct ImageDownloadArgs {
     /// Search term for images
-    pub query: String,
+    #[arg(required_unless_present = "from_manifest")]
+    pub query: Option<String>,
+    
+    /// Download the images listed in a saved manifest.json instead of searching again
+    /// (saves into the manifest's directory unless --output-dir is given)
+    #[arg(long, conflicts_with = "query")]
+    pub from_manifest: Option<PathBuf>,
     
     /// Number of images to download
     #[arg(short, long, default_value_t = 10)]
@@ -296,9 +1016,23 @@ pub struct ImageDownloadArgs {
     #[arg(long)]
     pub unsafe_search: bool,
     
-    /// Number of concurrent downloads
-    #[arg(short = 'j', long = "concurrent", default_value_t = 5)]
-    pub concurrent: usize,
+    /// Number of concurrent downloads (default: 5; at most two per CPU core, up to 16)
+    #[arg(short = 'j', long = "concurrent")]
+    pub concurrent: Option<usize>,
+    
+    /// Re-download images that already exist in the output directory
+    #[arg(long)]
+    pub overwrite: bool,
+    
+    /// Write an index.html with thumbnails of the downloaded images
+    #[arg(long)]
+    pub index: bool,
+    
+    /// Only openly licensed images, searched on Openverse; each level also allows the
+    /// less restrictive ones (cc0 < by < by-sa). Licenses and credits go into the
+    /// manifest and index.html
+    #[arg(long, value_enum, default_value_t = ImageLicense::Any)]
+    pub license: ImageLicense,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -306,6 +1040,56 @@ pub struct PCSpecsArgs {
     /// Path to save system information (if not provided, information will be displayed on screen)
     #[arg(short, long)]
     pub output: Option<PathBuf>,
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = SpecsFormat::Text)]
+    pub format: SpecsFormat,
+    /// Show what changed between two snapshots saved with --format json
+    #[arg(long, num_args = 2, value_names = ["OLD", "NEW"], conflicts_with_all = ["output", "format"])]
+    pub compare: Vec<PathBuf>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpecsFormat {
+    /// Plain text (colored on screen)
+    Text,
+    /// Pretty-printed JSON
+    Json,
+    /// Markdown tables
+    Markdown,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct AudioTranscribeArgs {
+    /// Path to the audio or video file to transcribe
+    pub input: PathBuf,
+
+    /// Whisper model size (tiny, base, small, medium, large)
+    #[arg(short, long, default_value = "base")]
+    pub model: String,
+
+    /// Base path for the generated transcript files (defaults to input name in current directory)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Skip writing the SRT subtitle file
+    #[arg(long)]
+    pub no_srt: bool,
+
+    /// Skip writing the plain text transcript
+    #[arg(long)]
+    pub no_txt: bool,
+
+    /// Leave timestamps out of the text transcript
+    #[arg(long)]
+    pub no_timestamps: bool,
+
+    /// Spoken language as an ISO code (e.g., en, de); auto-detected when omitted
+    #[arg(short, long)]
+    pub language: Option<String>,
+
+    /// Translate the speech to English instead of transcribing it verbatim
+    #[arg(short, long)]
+    pub translate: bool,
 }
 
 // --- Parsers for Clap --- 
@@ -317,8 +1101,27 @@ pub fn parse_header(s: &str) -> Result<(String, String), String> {
         .ok_or_else(|| format!("Invalid header format: '{}'. Use key=value.", s))
 }
 
-/// Parses a port range string (e.g., "80", "1-1024", "80,443,1000-2000") into a Vec<u16>
+pub fn parse_cookie(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .ok_or_else(|| format!("Invalid cookie format: '{}'. Use name=value.", s))
+}
+
+/// Parses a refresh interval in seconds, rejecting NaN, infinities and anything outside 0.1-3600
+pub fn parse_interval(s: &str) -> Result<f64, String> {
+    let secs: f64 = s.trim().parse().map_err(|_| format!("Invalid interval: '{}'. Use a number of seconds.", s))?;
+    if !secs.is_finite() || !(0.1..=3600.0).contains(&secs) {
+        return Err(format!("Interval must be between 0.1 and 3600 seconds, got '{}'.", s));
+    }
+    Ok(secs)
+}
+
+/// Parses a port range string (e.g., "80", "1-1024", "80,443,1000-2000") into a Vec<u16>.
+/// A preset name ("top-100", "top-1000", "common", "well-known", "all") expands to its port set.
 pub fn parse_ports(port_str: &str) -> Result<Vec<u16>, String> {
+    if let Some(preset) = PortPreset::from_name(port_str.trim()) {
+        return Ok(preset.ports());
+    }
     let mut ports = Vec::new();
     for part in port_str.split(',') {
         let part = part.trim();
@@ -1,38 +1,224 @@
-use crate::cli::DnsAction;
+use crate::cli::{DnsAction, DnsRecordType};
 use colored::*;
+use log::{debug, info};
+use hickory_resolver::config::{NameServerConfigGroup, ResolverConfig, ResolverOpts};
+use hickory_resolver::proto::rr::{Record, RecordType};
+use hickory_resolver::TokioAsyncResolver;
+use futures::{stream::FuturesUnordered, StreamExt};
+use std::collections::BTreeMap;
 use std::error::Error;
+use std::net::{IpAddr, SocketAddr};
 use std::process::{Command, Output};
+use std::sync::{Arc, Mutex};
+
+/// Well-known public resolvers compared by `dns propagation`
+const PUBLIC_RESOLVERS: &[(&str, &str)] = &[
+    ("Google", "8.8.8.8"),
+    ("Cloudflare", "1.1.1.1"),
+    ("Quad9", "9.9.9.9"),
+    ("OpenDNS", "208.67.222.222"),
+];
 
 pub async fn manage_dns(action: DnsAction) -> Result<(), Box<dyn Error + Send + Sync>> {
     match action {
         DnsAction::Flush => flush_dns_cache(),
-        // DnsAction::View => view_dns_cache(), // Placeholder if view is added later
+        DnsAction::View => view_dns_cache(),
+    }
+}
+
+/// One entry of the operating system's DNS cache
+#[derive(Debug, Clone, PartialEq)]
+pub struct DnsCacheRecord {
+    pub name: String,
+    pub record_type: Option<String>,
+    pub ttl: Option<u32>,
+    pub data: Option<String>,
+}
+
+impl From<DnsRecordType> for RecordType {
+    fn from(record_type: DnsRecordType) -> Self {
+        match record_type {
+            DnsRecordType::A => RecordType::A,
+            DnsRecordType::Aaaa => RecordType::AAAA,
+            DnsRecordType::Mx => RecordType::MX,
+            DnsRecordType::Txt => RecordType::TXT,
+            DnsRecordType::Cname => RecordType::CNAME,
+            DnsRecordType::Ns => RecordType::NS,
+            DnsRecordType::Soa => RecordType::SOA,
+            DnsRecordType::Ptr => RecordType::PTR,
+        }
+    }
+}
+
+/// Parse a DNS server given as `ip` or `ip:port` (port defaults to 53)
+pub fn parse_dns_server(server: &str) -> Result<SocketAddr, String> {
+    server.parse::<SocketAddr>()
+        .or_else(|_| server.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, 53)))
+        .map_err(|_| format!("Invalid DNS server '{}' (expected an IP address, optionally with :port)", server))
+}
+
+/// Build a resolver for one server, or for the system configuration when `server` is `None`
+fn build_resolver(server: Option<SocketAddr>) -> Result<TokioAsyncResolver, Box<dyn Error + Send + Sync>> {
+    match server {
+        Some(addr) => {
+            debug!("Using DNS server {}", addr);
+            let servers = NameServerConfigGroup::from_ips_clear(&[addr.ip()], addr.port(), true);
+            let config = ResolverConfig::from_parts(None, vec![], servers);
+            Ok(TokioAsyncResolver::tokio(config, ResolverOpts::default()))
+        }
+        None => {
+            debug!("Using the system resolver configuration");
+            Ok(TokioAsyncResolver::tokio_from_system_conf()?)
+        }
+    }
+}
+
+/// Query `name` for one record type; an IP address is looked up in reverse (PTR) instead
+async fn query_records(resolver: &TokioAsyncResolver, name: &str, record_type: RecordType) -> Result<Vec<Record>, Box<dyn Error + Send + Sync>> {
+    let records = match name.parse::<IpAddr>() {
+        Ok(ip) => resolver.reverse_lookup(ip).await?.as_lookup().record_iter().cloned().collect(),
+        Err(_) => resolver.lookup(name, record_type).await?.record_iter().cloned().collect(),
+    };
+    Ok(records)
+}
+
+/// Look up DNS records dig-style and print each answer with its TTL
+pub async fn lookup_records(name: &str, record_type: DnsRecordType, server: Option<SocketAddr>) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let resolver = build_resolver(server)?;
+    let record_type = RecordType::from(record_type);
+    let server_label = server.map(|addr| addr.to_string()).unwrap_or_else(|| "system resolver".to_string());
+
+    match name.parse::<IpAddr>() {
+        Ok(_) => println!("{} {} via {}", "Reverse lookup for".cyan(), name.bold(), server_label.dimmed()),
+        Err(_) => println!("{} {} {} via {}", "Looking up".cyan(), record_type, name.bold(), server_label.dimmed()),
+    }
+
+    let records = query_records(&resolver, name, record_type).await?;
+    if records.is_empty() {
+        println!("{}", "No records found.".yellow());
+        return Ok(());
+    }
+
+    println!("{:<40} {:>7} {:<6} {}", "Name".bold(), "TTL".bold(), "Type".bold(), "Data".bold());
+    for record in &records {
+        println!(
+            "{:<40} {:>7} {:<6} {}",
+            record.name().to_string().cyan(),
+            record.ttl(),
+            record.record_type().to_string(),
+            record.data().map(|data| data.to_string()).unwrap_or_default()
+        );
     }
+    Ok(())
+}
+
+/// What one resolver answered during a propagation check
+struct ResolverAnswer {
+    answers: Vec<String>, // Sorted record data of the requested type
+    ttl: Option<u32>,     // Lowest TTL among those records
+}
+
+/// Query every public resolver concurrently and show their answers side by side,
+/// flagging resolvers that disagree with the most common answer.
+pub async fn check_propagation(name: &str, record_type: DnsRecordType) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let record_type = RecordType::from(record_type);
+    println!("{} {} {} across {} public resolvers", "Checking propagation of".cyan(), record_type, name.bold(), PUBLIC_RESOLVERS.len());
+
+    let results = Arc::new(Mutex::new(BTreeMap::<usize, Result<ResolverAnswer, String>>::new()));
+    let mut tasks = FuturesUnordered::new();
+
+    for (index, &(_, ip)) in PUBLIC_RESOLVERS.iter().enumerate() {
+        let results = results.clone();
+        let name = name.to_string();
+        let server = SocketAddr::new(ip.parse()?, 53);
+
+        tasks.push(tokio::spawn(async move {
+            let outcome = match build_resolver(Some(server)) {
+                Ok(resolver) => query_records(&resolver, &name, record_type).await.map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            let answer = outcome.map(|records| {
+                let matching: Vec<&Record> = records.iter()
+                    .filter(|record| record.record_type() == record_type || name.parse::<IpAddr>().is_ok())
+                    .collect();
+                let mut answers: Vec<String> = matching.iter()
+                    .filter_map(|record| record.data().map(|data| data.to_string()))
+                    .collect();
+                answers.sort();
+                ResolverAnswer { answers, ttl: matching.iter().map(|record| record.ttl()).min() }
+            });
+            results.lock().unwrap().insert(index, answer);
+        }));
+    }
+    while tasks.next().await.is_some() {}
+
+    let results = results.lock().unwrap();
+
+    // The answer most resolvers agree on is treated as the expected one
+    let mut counts: BTreeMap<&Vec<String>, usize> = BTreeMap::new();
+    for answer in results.values().flatten() {
+        *counts.entry(&answer.answers).or_default() += 1;
+    }
+    let consensus = counts.iter().max_by_key(|(_, count)| **count).map(|(answers, _)| *answers);
+
+    println!("{:<12} {:<17} {:>7}  {}", "Resolver".bold(), "Server".bold(), "TTL".bold(), "Answer".bold());
+    let mut disagreements = 0;
+    for (index, &(label, ip)) in PUBLIC_RESOLVERS.iter().enumerate() {
+        match results.get(&index) {
+            Some(Ok(answer)) => {
+                let agrees = Some(&answer.answers) == consensus;
+                if !agrees {
+                    disagreements += 1;
+                }
+                let text = if answer.answers.is_empty() { "(no records)".to_string() } else { answer.answers.join(", ") };
+                println!(
+                    "{:<12} {:<17} {:>7}  {} {}",
+                    label.cyan(),
+                    ip,
+                    answer.ttl.map(|ttl| ttl.to_string()).unwrap_or_else(|| "-".to_string()),
+                    if agrees { text.normal() } else { text.yellow() },
+                    if agrees { "".normal() } else { "← differs".red() }
+                );
+            }
+            Some(Err(e)) => {
+                disagreements += 1;
+                println!("{:<12} {:<17} {:>7}  {}", label.cyan(), ip, "-", format!("error: {}", e).red());
+            }
+            None => {}
+        }
+    }
+
+    if disagreements == 0 {
+        println!("{}", "✓  All resolvers agree.".green());
+    } else {
+        println!("{}", format!("{} resolver(s) disagree; the change may still be propagating.", disagreements).yellow());
+    }
+    Ok(())
 }
 
 fn flush_dns_cache() -> Result<(), Box<dyn Error + Send + Sync>> {
-    println!("{}", "Attempting to flush DNS cache...".cyan());
+    info!("{}", "Attempting to flush DNS cache...".cyan());
     println!("{}", "Note: This operation usually requires administrator/sudo privileges.".yellow());
 
     let output_result: Result<Output, std::io::Error>;
 
     #[cfg(target_os = "windows")]
     {
-        println!("Running: {}", "ipconfig /flushdns".dimmed());
+        info!("Running: {}", "ipconfig /flushdns".dimmed());
         output_result = Command::new("ipconfig").arg("/flushdns").output();
     }
 
     #[cfg(target_os = "macos")]
     {
-        println!("Running: {}", "sudo dscacheutil -flushcache".dimmed());
+        info!("Running: {}", "sudo dscacheutil -flushcache".dimmed());
         let out1 = Command::new("sudo").arg("dscacheutil").arg("-flushcache").output();
         if out1.is_err() || !out1.as_ref().unwrap().status.success() {
              println!("{}", "dscacheutil failed. Trying killall mDNSResponder...".yellow());
-             println!("Running: {}", "sudo killall -HUP mDNSResponder".dimmed());
+             info!("Running: {}", "sudo killall -HUP mDNSResponder".dimmed());
              output_result = Command::new("sudo").arg("killall").arg("-HUP").arg("mDNSResponder").output();
         } else {
             // If dscacheutil worked, try killall as well for good measure (common practice)
-            println!("Running: {}", "sudo killall -HUP mDNSResponder".dimmed());
+            info!("Running: {}", "sudo killall -HUP mDNSResponder".dimmed());
             Command::new("sudo").arg("killall").arg("-HUP").arg("mDNSResponder").output()?;
             output_result = out1; // Report status of the primary command
         }
@@ -41,21 +227,21 @@ fn flush_dns_cache() -> Result<(), Box<dyn Error + Send + Sync>> {
     #[cfg(target_os = "linux")]
     {
         // Linux DNS flushing is highly variable. Try systemd-resolved first.
-        println!("Attempting flush with systemd-resolve...");
-        println!("Running: {}", "sudo systemd-resolve --flush-caches".dimmed());
+        info!("Attempting flush with systemd-resolve...");
+        info!("Running: {}", "sudo systemd-resolve --flush-caches".dimmed());
         let systemd_output = Command::new("sudo").arg("systemd-resolve").arg("--flush-caches").output();
 
         if systemd_output.is_ok() && systemd_output.as_ref().unwrap().status.success() {
             output_result = systemd_output;
         } else {
             println!("{}", "systemd-resolve failed or not found. Trying nscd...".yellow());
-            println!("Running: {}", "sudo /etc/init.d/nscd restart".dimmed());
+            info!("Running: {}", "sudo /etc/init.d/nscd restart".dimmed());
             let nscd_output = Command::new("sudo").arg("/etc/init.d/nscd").arg("restart").output();
             if nscd_output.is_ok() && nscd_output.as_ref().unwrap().status.success() {
                 output_result = nscd_output;
             } else {
                  println!("{}", "nscd failed or not found. Trying dnsmasq...".yellow());
-                 println!("Running: {}", "sudo service dnsmasq restart".dimmed());
+                 info!("Running: {}", "sudo service dnsmasq restart".dimmed());
                  // Fallback to dnsmasq as the final attempt
                  output_result = Command::new("sudo").arg("service").arg("dnsmasq").arg("restart").output();
             }
@@ -103,8 +289,219 @@ fn flush_dns_cache() -> Result<(), Box<dyn Error + Send + Sync>> {
     }
 }
 
-// Placeholder for viewing cache - complex and OS-specific
-// fn view_dns_cache() -> Result<(), Box<dyn Error + Send + Sync>> {
-//     println!("{}", "Viewing DNS cache is not implemented yet.".yellow());
-//     Ok(())
-// } 
\ No newline at end of file
+fn view_dns_cache() -> Result<(), Box<dyn Error + Send + Sync>> {
+    info!("{}", "Reading DNS cache...".cyan());
+
+    if let Some(records) = read_dns_cache()? {
+        print_dns_cache(&records);
+    }
+    Ok(())
+}
+
+/// Read the OS DNS cache; `None` means it can't be listed here and the user was told why
+#[cfg(target_os = "windows")]
+fn read_dns_cache() -> Result<Option<Vec<DnsCacheRecord>>, Box<dyn Error + Send + Sync>> {
+    info!("Running: {}", "ipconfig /displaydns".dimmed());
+    let output = Command::new("ipconfig").arg("/displaydns").output()?;
+    if !output.status.success() {
+        return Err(format!("ipconfig /displaydns failed with status: {}", output.status).into());
+    }
+    Ok(Some(parse_displaydns(&String::from_utf8_lossy(&output.stdout))))
+}
+
+#[cfg(target_os = "macos")]
+fn read_dns_cache() -> Result<Option<Vec<DnsCacheRecord>>, Box<dyn Error + Send + Sync>> {
+    // Best effort: dscacheutil only sees the DirectoryService cache, and newer macOS
+    // releases keep most entries inside mDNSResponder where they can't be listed.
+    info!("Running: {}", "dscacheutil -cachedump -entries Host".dimmed());
+    let output = Command::new("dscacheutil").args(["-cachedump", "-entries", "Host"]).output()?;
+    let records = parse_dscacheutil_cachedump(&String::from_utf8_lossy(&output.stdout));
+    if records.is_empty() {
+        println!("{}", "dscacheutil returned no entries; this macOS version may keep its cache in mDNSResponder.".yellow());
+        println!("{}", "Hint: `sudo killall -INFO mDNSResponder` writes the cache to the system log.".yellow());
+        return Ok(None);
+    }
+    Ok(Some(records))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn read_dns_cache() -> Result<Option<Vec<DnsCacheRecord>>, Box<dyn Error + Send + Sync>> {
+    println!("{}", "Viewing the DNS cache is not supported on this resolver.".yellow());
+    Ok(None)
+}
+
+fn print_dns_cache(records: &[DnsCacheRecord]) {
+    if records.is_empty() {
+        println!("{}", "The DNS cache is empty.".yellow());
+        return;
+    }
+
+    println!("{:<45} {:<6} {:>7}  {}", "Name".bold(), "Type".bold(), "TTL".bold(), "Data".bold());
+    for record in records {
+        println!(
+            "{:<45} {:<6} {:>7}  {}",
+            record.name.cyan(),
+            record.record_type.as_deref().unwrap_or("-"),
+            record.ttl.map(|ttl| ttl.to_string()).unwrap_or_else(|| "-".to_string()),
+            record.data.as_deref().unwrap_or("").dimmed()
+        );
+    }
+    println!("{} {}", "Cached records:".green(), records.len());
+}
+
+/// Human-readable name of a numeric DNS record type
+fn record_type_name(code: &str) -> String {
+    match code.trim() {
+        "1" => "A",
+        "2" => "NS",
+        "5" => "CNAME",
+        "6" => "SOA",
+        "12" => "PTR",
+        "15" => "MX",
+        "16" => "TXT",
+        "28" => "AAAA",
+        "33" => "SRV",
+        other => other,
+    }
+    .to_string()
+}
+
+/// Parse `ipconfig /displaydns` output into records.
+///
+/// Each record is a block of `Label . . . : value` lines starting at `Record Name`;
+/// the answer itself is on the line after `Section`, labelled e.g. `A (Host) Record`.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_displaydns(output: &str) -> Vec<DnsCacheRecord> {
+    let mut records = Vec::new();
+    let mut current: Option<DnsCacheRecord> = None;
+
+    for line in output.lines() {
+        let Some((label, value)) = line.split_once(" : ") else { continue };
+        let label = label.trim().trim_end_matches(['.', ' ']).trim();
+        let value = value.trim().to_string();
+
+        match label {
+            "Record Name" => {
+                records.extend(current.take());
+                current = Some(DnsCacheRecord { name: value, record_type: None, ttl: None, data: None });
+            }
+            "Record Type" => {
+                if let Some(record) = current.as_mut() {
+                    record.record_type = Some(record_type_name(&value));
+                }
+            }
+            "Time To Live" => {
+                if let Some(record) = current.as_mut() {
+                    record.ttl = value.parse().ok();
+                }
+            }
+            "Data Length" | "Section" => {}
+            _ if label.ends_with("Record") => {
+                if let Some(record) = current.as_mut() {
+                    record.data = Some(value);
+                }
+            }
+            _ => {}
+        }
+    }
+    records.extend(current);
+    records
+}
+
+/// Parse `dscacheutil -cachedump -entries Host` output into records.
+///
+/// Each `Host` row carries the TTL; the `Key:` line below it holds the name and
+/// whether it was an IPv4 or IPv6 lookup.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_dscacheutil_cachedump(output: &str) -> Vec<DnsCacheRecord> {
+    let mut records = Vec::new();
+    let mut ttl = None;
+
+    for line in output.lines().map(str::trim) {
+        if line.starts_with("Host ") {
+            // Category, best-before date/time, last-access date/time, hits, refs, TTL
+            ttl = line.split_whitespace().nth(7).and_then(|t| t.parse().ok());
+        } else if let Some(key) = line.strip_prefix("Key:") {
+            let mut name = None;
+            let mut record_type = None;
+            for part in key.split_whitespace() {
+                match part.split_once(':') {
+                    Some(("h_name", value)) => name = Some(value.to_string()),
+                    Some(("ipv4", "1")) => record_type = Some("A".to_string()),
+                    Some(("ipv6", "1")) => record_type = Some("AAAA".to_string()),
+                    _ => {}
+                }
+            }
+            if let Some(name) = name {
+                records.push(DnsCacheRecord { name, record_type, ttl: ttl.take(), data: None });
+            }
+        }
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dns_server() {
+        assert_eq!(parse_dns_server("1.1.1.1").unwrap(), "1.1.1.1:53".parse().unwrap());
+        assert_eq!(parse_dns_server("9.9.9.9:5353").unwrap(), "9.9.9.9:5353".parse().unwrap());
+        assert_eq!(parse_dns_server("[2606:4700:4700::1111]:53").unwrap().port(), 53);
+        assert!(parse_dns_server("2606:4700:4700::1111").unwrap().is_ipv6());
+        assert!(parse_dns_server("dns.google").is_err());
+    }
+
+    #[test]
+    fn test_parse_displaydns() {
+        let output = "
+Windows IP Configuration
+
+    www.example.com
+    ----------------------------------------
+    Record Name . . . . . : www.example.com
+    Record Type . . . . . : 1
+    Time To Live  . . . . : 3021
+    Data Length . . . . . : 4
+    Section . . . . . . . : Answer
+    A (Host) Record . . . : 93.184.216.34
+
+    Record Name . . . . . : mail.example.com
+    Record Type . . . . . : 5
+    Time To Live  . . . . : 60
+    Data Length . . . . . : 8
+    Section . . . . . . . : Answer
+    CNAME Record  . . . . : example.com
+";
+        let records = parse_displaydns(output);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0], DnsCacheRecord {
+            name: "www.example.com".to_string(),
+            record_type: Some("A".to_string()),
+            ttl: Some(3021),
+            data: Some("93.184.216.34".to_string()),
+        });
+        assert_eq!(records[1].record_type.as_deref(), Some("CNAME"));
+        assert_eq!(records[1].data.as_deref(), Some("example.com"));
+    }
+
+    #[test]
+    fn test_parse_dscacheutil_cachedump() {
+        let output = "
+Cache entries (ordered as stored in the cache):
+
+    Category         Best Before         Last Access      Hits    Refs  TTL    Neg  DS Node
+    ----------  ------------------  ------------------  --------  ----  -----  ---  ---------
+    Host        05/08/19 12:03:25   05/08/19 12:02:25          1     4  60
+                 Key: h_name:www.example.com ipv4:1
+";
+        let records = parse_dscacheutil_cachedump(output);
+        assert_eq!(records, vec![DnsCacheRecord {
+            name: "www.example.com".to_string(),
+            record_type: Some("A".to_string()),
+            ttl: Some(60),
+            data: None,
+        }]);
+    }
+}
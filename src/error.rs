@@ -0,0 +1,156 @@
+//! src/error.rs
+//! ────────────
+//! Error categories shared by the core operations.
+//!
+//! The CLI turns each category into its own exit code and the GUI can decide what
+//! to show (an install link, a "check your connection" hint, ...) without parsing messages.
+
+use std::io;
+use std::time::Duration;
+use thiserror::Error;
+
+pub type Result<T, E = AppError> = std::result::Result<T, E>;
+
+/// Exit code for a general failure
+pub const EXIT_FAILURE: i32 = 1;
+/// Exit code when a required external tool (yt-dlp, ffmpeg, clamscan, ...) is missing
+pub const EXIT_MISSING_TOOL: i32 = 2;
+/// Exit code for connection, DNS and HTTP failures
+pub const EXIT_NETWORK: i32 = 3;
+/// Exit code when the OS or a server refused access
+pub const EXIT_PERMISSION: i32 = 4;
+/// Exit code for a missing file, URL or record
+pub const EXIT_NOT_FOUND: i32 = 5;
+/// Exit code for arguments or data that can't be used
+pub const EXIT_INVALID_INPUT: i32 = 6;
+/// Exit code when a server kept answering 429 Too Many Requests
+pub const EXIT_RATE_LIMITED: i32 = 7;
+/// Exit code for an operation stopped by the user, as for Ctrl-C
+pub const EXIT_CANCELLED: i32 = 130;
+
+#[derive(Debug, Error)]
+pub enum AppError {
+    /// An external program isn't installed
+    #[error("{tool} is not installed. Please install it first: {install_hint}")]
+    MissingTool { tool: &'static str, install_hint: &'static str },
+    /// Connecting, resolving or transferring failed
+    #[error("{0}")]
+    Network(String),
+    /// A server answered 429 Too Many Requests; `retry_after` is from its Retry-After header
+    #[error("{service} is rate limiting requests, try again {}", retry_hint(.retry_after))]
+    RateLimited { service: String, retry_after: Option<Duration> },
+    /// The OS or a server refused access
+    #[error("{0}")]
+    PermissionDenied(String),
+    /// A file, URL, playlist or quarantine record doesn't exist
+    #[error("{0}")]
+    NotFound(String),
+    /// Arguments or data that can't be used as given
+    #[error("{0}")]
+    InvalidInput(String),
+    /// Stopped through a `CancellationToken` or by the user
+    #[error("{0}")]
+    Cancelled(String),
+    /// Anything else
+    #[error("{0}")]
+    Other(String),
+}
+
+impl AppError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AppError::MissingTool { .. } => EXIT_MISSING_TOOL,
+            AppError::Network(_) => EXIT_NETWORK,
+            AppError::RateLimited { .. } => EXIT_RATE_LIMITED,
+            AppError::PermissionDenied(_) => EXIT_PERMISSION,
+            AppError::NotFound(_) => EXIT_NOT_FOUND,
+            AppError::InvalidInput(_) => EXIT_INVALID_INPUT,
+            AppError::Cancelled(_) => EXIT_CANCELLED,
+            AppError::Other(_) => EXIT_FAILURE,
+        }
+    }
+}
+
+fn retry_hint(retry_after: &Option<Duration>) -> String {
+    match retry_after {
+        Some(wait) => format!("in {}s", wait.as_secs().max(1)),
+        None => "later".to_string(),
+    }
+}
+
+impl From<io::Error> for AppError {
+    fn from(e: io::Error) -> Self {
+        let msg = e.to_string();
+        match e.kind() {
+            io::ErrorKind::NotFound => AppError::NotFound(msg),
+            io::ErrorKind::PermissionDenied => AppError::PermissionDenied(msg),
+            io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::AddrNotAvailable
+            | io::ErrorKind::TimedOut => AppError::Network(msg),
+            io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData => AppError::InvalidInput(msg),
+            _ => AppError::Other(msg),
+        }
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        let msg = e.to_string();
+        match e.status().map(|s| s.as_u16()) {
+            Some(401) | Some(403) => AppError::PermissionDenied(msg),
+            Some(404) | Some(410) => AppError::NotFound(msg),
+            _ if e.is_builder() => AppError::InvalidInput(msg),
+            _ => AppError::Network(msg),
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        AppError::InvalidInput(e.to_string())
+    }
+}
+
+impl From<tokio::task::JoinError> for AppError {
+    fn from(e: tokio::task::JoinError) -> Self {
+        AppError::Other(e.to_string())
+    }
+}
+
+impl From<tokio::time::error::Elapsed> for AppError {
+    fn from(e: tokio::time::error::Elapsed) -> Self {
+        AppError::Network(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_errors_are_categorized() {
+        let err = |kind| AppError::from(io::Error::new(kind, "boom"));
+        assert!(matches!(err(io::ErrorKind::NotFound), AppError::NotFound(_)));
+        assert!(matches!(err(io::ErrorKind::PermissionDenied), AppError::PermissionDenied(_)));
+        assert!(matches!(err(io::ErrorKind::ConnectionRefused), AppError::Network(_)));
+        assert!(matches!(err(io::ErrorKind::Other), AppError::Other(_)));
+        assert_eq!(err(io::ErrorKind::NotFound).to_string(), "boom");
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        let missing = AppError::MissingTool { tool: "yt-dlp", install_hint: "https://example.com" };
+        assert_eq!(missing.exit_code(), EXIT_MISSING_TOOL);
+        assert_eq!(missing.to_string(), "yt-dlp is not installed. Please install it first: https://example.com");
+        assert_eq!(AppError::Network("down".into()).exit_code(), EXIT_NETWORK);
+        assert_eq!(AppError::Cancelled("stopped".into()).exit_code(), EXIT_CANCELLED);
+
+        let limited = AppError::RateLimited { service: "Pixabay".into(), retry_after: Some(Duration::from_secs(30)) };
+        assert_eq!(limited.to_string(), "Pixabay is rate limiting requests, try again in 30s");
+        assert_eq!(limited.exit_code(), EXIT_RATE_LIMITED);
+        assert_eq!(AppError::Other("?".into()).exit_code(), EXIT_FAILURE);
+    }
+}
@@ -1,40 +1,318 @@
-use anyhow::Result;
 use colored::*;
+use bytes::Bytes;
 use futures::stream::StreamExt;
-use indicatif::{ProgressBar, ProgressStyle, MultiProgress};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
 use reqwest::{Client, StatusCode};
 use std::cmp::min;
 use std::fs::{File, OpenOptions};
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::io::{Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use tokio::sync::Semaphore;
-use tokio::task;
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::task::{self, JoinSet};
+use std::time::{Duration, Instant};
+use sysinfo::Disks;
+use tokio_util::sync::CancellationToken;
+use serde::{Deserialize, Serialize};
+use crate::error::{AppError, Result};
+use crate::file_ops::{hash_file_hex, HashAlgorithm};
+use crate::image_download_ops::sanitize_filename;
+use crate::utils::{self, Progress, ProgressCallback};
 
-/// Downloads a file from a URL, with support for retries, resuming, and parallel downloads
+/// Expected checksum of a downloaded file
+#[derive(Debug, Clone)]
+pub struct Checksum {
+    pub algorithm: HashAlgorithm,
+    pub expected: String, // Hex digest, compared case-insensitively
+}
+
+/// Options controlling how a file download behaves
+#[derive(Debug, Clone)]
+pub struct FileDownloadOptions {
+    pub retries: usize,
+    pub resume: bool,
+    pub parallel: usize,
+    pub checksum: Option<Checksum>,
+    pub mirrors: Vec<String>, // Alternative URLs serving the same file
+    pub rate_limit: Option<u64>, // Overall cap in bytes/s, split across parallel connections
+    pub max_time: Option<Duration>, // Abort the whole download once this budget is spent
+    pub stall_timeout: Option<Duration>, // Retry a connection that delivers no bytes for this long
+    pub force: bool, // Skip the free disk space check
+}
+
+impl Default for FileDownloadOptions {
+    fn default() -> Self {
+        Self {
+            retries: 5,
+            resume: false,
+            parallel: 1,
+            checksum: None,
+            mirrors: Vec::new(),
+            rate_limit: None,
+            max_time: None,
+            stall_timeout: Some(Duration::from_secs(30)),
+            force: false,
+        }
+    }
+}
+
+/// Downloads a file from a URL, with support for retries, resuming, and parallel downloads.
+///
+/// When `output_path` is `None` the file is saved in the current directory under the name
+/// from the server's `Content-Disposition` header, falling back to the last URL path segment.
+/// Mirrors are tried in order; each retry moves on to the next one and continues from the
+/// bytes already on disk. Returns the path the file was written to.
+///
+/// With a `progress` callback the terminal bars stay hidden and byte counts go to the
+/// callback instead, so a GUI can draw its own bar.
+///
+/// Cancelling `cancel` stops every connection and returns an error. Without `resume` the
+/// partial output is deleted; with it the output and its segment sidecar are kept for a later resume.
 pub async fn download_file(
     url: &str, 
-    output_path: &Path, 
-    retries: usize,
-    resume: bool,
-    parallel: usize
-) -> Result<()> {
+    output_path: Option<&Path>, 
+    options: &FileDownloadOptions,
+    progress: Option<ProgressCallback>,
+    cancel: &CancellationToken
+) -> Result<PathBuf> {
     println!("{} {}", "Downloading:".cyan().bold(), url);
-    println!("{} {}", "Output file:".cyan().bold(), output_path.display());
-    
-    // Create a client with a timeout
-    let client = Client::builder()
-        .timeout(Duration::from_secs(30))
-        .build()?;
+    if !options.mirrors.is_empty() {
+        println!("{} {}", "Mirrors:".cyan().bold(), options.mirrors.join(", "));
+    }
     
-    // First, perform a HEAD request to get the file size and check if the server supports range requests
-    let head_resp = client.head(url).send().await?;
+    // The shared client only bounds connection setup; a whole-request timeout would kill long
+    // downloads, and slow bodies are handled by the stall detector and --max-time instead
+    let client = utils::http_client();
     
-    if !head_resp.status().is_success() {
-        return Err(anyhow::anyhow!("Failed to fetch file information: HTTP status {}", head_resp.status()));
+    // First, perform a HEAD request to get the file size and check if the server supports range requests.
+    // The first URL that answers becomes the primary source.
+    let mut urls: Vec<String> = std::iter::once(url.to_string()).chain(options.mirrors.iter().cloned()).collect();
+    let mut head = None;
+    let mut rate_limits = Vec::new();
+    for (idx, candidate) in urls.iter().enumerate() {
+        match client.head(candidate).send().await {
+            Ok(resp) if resp.status().is_success() => {
+                head = Some((idx, resp));
+                break;
+            }
+            Ok(resp) if resp.status() == StatusCode::TOO_MANY_REQUESTS => {
+                let limited = AppError::RateLimited { service: utils::url_host(candidate), retry_after: utils::retry_after(resp.headers()) };
+                println!("{} {}: {}", "Skipping".yellow(), candidate, limited);
+                rate_limits.push(limited);
+            }
+            Ok(resp) => println!("{} {}: HTTP status {}", "Skipping".yellow(), candidate, resp.status()),
+            Err(e) => println!("{} {}: {}", "Skipping".yellow(), candidate, e),
+        }
     }
+    let (primary_idx, head_resp) = match head {
+        Some(head) => head,
+        // Every source said 429, so tell the user when to come back rather than "failed"
+        None if rate_limits.len() == urls.len() => return Err(rate_limits.swap_remove(0)),
+        None => return Err(AppError::Network(format!("Failed to fetch file information from any of {} URL(s)", urls.len()))),
+    };
+    urls.rotate_left(primary_idx);
+    
+    let output_path = match output_path {
+        Some(path) => path.to_path_buf(),
+        None => {
+            let header_name = head_resp.headers()
+                .get(reqwest::header::CONTENT_DISPOSITION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_content_disposition_filename);
+            PathBuf::from(sanitize_filename(&header_name.unwrap_or_else(|| filename_from_url(&urls[0]))))
+        }
+    };
+    println!("{} {}", "Output file:".cyan().bold(), output_path.display());
     
+    let fetch = fetch_file(&urls, &output_path, options, client, &head_resp, progress);
+    let fetch = async {
+        match options.max_time {
+            Some(limit) => tokio::time::timeout(limit, fetch).await.map_err(|_| {
+                AppError::Network(format!("Download aborted after exceeding the maximum time of {}s", limit.as_secs()))
+            })?,
+            None => fetch.await,
+        }
+    };
+    // Dropping the fetch future on timeout or cancellation also aborts any parallel chunk tasks
+    tokio::select! {
+        result = fetch => result?,
+        _ = cancel.cancelled() => {
+            discard_cancelled_download(&output_path, options);
+            return Err(AppError::Cancelled("Download cancelled".into()));
+        }
+    }
+
+    // Verified against the assembled file, whichever mirror served each part
+    if let Some(checksum) = &options.checksum {
+        verify_checksum(&output_path, checksum)?;
+    }
+
+    Ok(output_path)
+}
+
+/// Token bucket that throttles a single connection to a fixed byte rate
+struct RateLimiter {
+    rate: f64,     // Bytes per second
+    capacity: f64, // Maximum burst, one second worth of bytes
+    tokens: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    fn new(bytes_per_sec: u64) -> Self {
+        let rate = bytes_per_sec.max(1) as f64;
+        Self { rate, capacity: rate, tokens: rate, last: Instant::now() }
+    }
+
+    /// Takes `bytes` from the bucket, sleeping long enough to pay back any deficit
+    async fn throttle(&mut self, bytes: usize) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last = now;
+
+        self.tokens -= bytes as f64;
+        if self.tokens < 0.0 {
+            tokio::time::sleep(Duration::from_secs_f64(-self.tokens / self.rate)).await;
+        }
+    }
+}
+
+/// Counts bytes moved since a start time to report throughput
+struct ThroughputMeter {
+    bytes: u64,
+    started: Instant,
+}
+
+impl ThroughputMeter {
+    fn new() -> Self {
+        Self { bytes: 0, started: Instant::now() }
+    }
+
+    fn add(&mut self, bytes: usize) {
+        self.bytes += bytes as u64;
+    }
+
+    fn mbps(&self) -> f64 {
+        mbps(self.bytes, self.started.elapsed())
+    }
+}
+
+/// Megabits per second for `bytes` transferred in `elapsed`
+fn mbps(bytes: u64, elapsed: Duration) -> f64 {
+    bytes as f64 * 8.0 / elapsed.as_secs_f64().max(0.001) / 1_000_000.0
+}
+
+/// Waits for the next body chunk. A connection that stays silent for longer than
+/// `stall_timeout` is reported as an error so the caller's retry loop can take over.
+async fn next_chunk<S>(stream: &mut S, stall_timeout: Option<Duration>) -> Option<std::result::Result<Bytes, String>>
+where
+    S: futures::Stream<Item = reqwest::Result<Bytes>> + Unpin,
+{
+    let next = match stall_timeout {
+        Some(limit) => match tokio::time::timeout(limit, stream.next()).await {
+            Ok(next) => next,
+            Err(_) => return Some(Err(format!("no data received for {}s", limit.as_secs()))),
+        },
+        None => stream.next().await,
+    };
+    next.map(|result| result.map_err(|e| e.to_string()))
+}
+
+/// Derives a filename from the last path segment of a URL (ignoring query and fragment)
+pub fn filename_from_url(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.rsplit('/')
+        .next()
+        .filter(|s| !s.is_empty())
+        .unwrap_or("downloaded_file")
+        .to_string()
+}
+
+/// Extracts the filename from a Content-Disposition header value.
+/// Prefers the RFC 5987 `filename*=` form over the plain `filename=` parameter.
+fn parse_content_disposition_filename(value: &str) -> Option<String> {
+    let mut plain = None;
+    let mut extended = None;
+
+    for param in value.split(';').map(str::trim) {
+        let Some((key, val)) = param.split_once('=') else { continue };
+        let val = val.trim();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "filename*" => {
+                // charset'language'percent-encoded-value
+                let encoded = val.splitn(3, '\'').nth(2).unwrap_or(val);
+                extended = percent_decode(encoded);
+            }
+            "filename" => plain = Some(val.trim_matches('"').to_string()),
+            _ => {}
+        }
+    }
+
+    // Only keep the final path component in case the server sent a path
+    extended
+        .or(plain)
+        .and_then(|name| name.rsplit(['/', '\\']).next().map(str::to_string))
+        .filter(|name| !name.is_empty())
+}
+
+// Decodes %XX escapes, returning None for malformed input or invalid UTF-8
+fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = input.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// Hashes the downloaded file and compares it with the expected digest.
+/// On mismatch the file is renamed to `<name>.corrupt` so it isn't mistaken for a good copy.
+fn verify_checksum(output_path: &Path, checksum: &Checksum) -> Result<()> {
+    println!("{} {} checksum...", "Verifying".cyan(), checksum.algorithm.as_str());
+    let actual = hash_file_hex(output_path, checksum.algorithm)?;
+    let expected = checksum.expected.trim().to_lowercase();
+
+    if actual == expected {
+        println!("{} {} matches", "✓".green(), checksum.algorithm.as_str());
+        return Ok(());
+    }
+
+    let mut corrupt_name = output_path.file_name().unwrap_or_default().to_os_string();
+    corrupt_name.push(".corrupt");
+    let corrupt_path = output_path.with_file_name(corrupt_name);
+    std::fs::rename(output_path, &corrupt_path)?;
+
+    Err(AppError::InvalidInput(format!(
+        "{} mismatch: expected {}, got {}. File kept as {}",
+        checksum.algorithm.as_str(),
+        expected,
+        actual,
+        corrupt_path.display()
+    )))
+}
+
+/// Fetches the file body to disk, choosing between single and parallel download
+async fn fetch_file(
+    urls: &[String],
+    output_path: &Path,
+    options: &FileDownloadOptions,
+    client: &Client,
+    head_resp: &reqwest::Response,
+    progress: Option<ProgressCallback>
+) -> Result<()> {
+    let (retries, resume, parallel) = (options.retries, options.resume, options.parallel);
+    let rate_limit = options.rate_limit;
+    let stall_timeout = options.stall_timeout;
     let supports_range = head_resp.headers().get("accept-ranges")
         .map(|v| v.to_str().unwrap_or("").contains("bytes"))
         .unwrap_or(false);
@@ -49,37 +327,114 @@ pub async fn download_file(
         println!("{}", "Warning: Could not determine file size. Progress reporting may be inaccurate.".yellow());
     }
     
-    if parallel > 1 && (!supports_range || total_size == 0) {
+    let parallel = if parallel > 1 && (!supports_range || total_size == 0) {
         println!("{}", "Warning: The server doesn't support range requests or file size is unknown. Parallel download disabled.".yellow());
-        return download_single(url, output_path, retries, resume, total_size, &client).await;
+        1
+    } else {
+        parallel
+    };
+    
+    if !options.force {
+        check_free_space(output_path, total_size)?;
+    }
+    
+    if let Some(rate) = rate_limit {
+        println!("{} {}/s", "Rate limit:".cyan().bold(), format_bytes(rate));
     }
     
     if parallel > 1 {
-        download_parallel(url, output_path, retries, resume, total_size, parallel, &client).await
+        download_parallel(urls, output_path, retries, resume, total_size, parallel, rate_limit, stall_timeout, client, progress).await
     } else {
-        download_single(url, output_path, retries, resume, total_size, &client).await
+        download_single(urls, output_path, retries, resume, total_size, rate_limit, stall_timeout, client, progress).await
+    }
+}
+
+/// Bytes the download still has to put on disk. Whatever the output file already holds is
+/// reused, whether it is resumed or overwritten; parallel mode pre-allocates the whole output
+/// with `set_len` up front, so the full size is needed there too.
+fn required_space(output_path: &Path, total_size: u64) -> u64 {
+    let on_disk = std::fs::metadata(output_path).map(|m| m.len()).unwrap_or(0);
+    total_size.saturating_sub(on_disk)
+}
+
+/// Free space of the mount with the longest mount point containing `dir`
+fn space_on<'a>(dir: &Path, mounts: impl IntoIterator<Item = (&'a Path, u64)>) -> Option<u64> {
+    mounts
+        .into_iter()
+        .filter(|(mount_point, _)| dir.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.as_os_str().len())
+        .map(|(_, available)| available)
+}
+
+/// Drops the `\\?\` verbatim prefix Windows `canonicalize()` adds, so the path compares
+/// equal to mount points like `C:\`
+fn strip_verbatim(path: &Path) -> PathBuf {
+    let Some(s) = path.to_str() else {
+        return path.to_path_buf();
+    };
+    if let Some(rest) = s.strip_prefix(r"\\?\UNC\") {
+        return PathBuf::from(format!(r"\\{}", rest));
+    }
+    match s.strip_prefix(r"\\?\") {
+        Some(rest) if rest.as_bytes().get(1) == Some(&b':') => PathBuf::from(rest),
+        _ => path.to_path_buf(),
     }
 }
 
+/// Free bytes on the filesystem `output_path` will be written to, if it can be determined
+fn available_space(output_path: &Path) -> Option<u64> {
+    // The output directory may not exist yet, so look at its closest existing ancestor
+    let target = std::env::current_dir().ok()?.join(output_path);
+    let dir = target.ancestors().skip(1).find_map(|p| p.canonicalize().ok())?;
+    let dir = strip_verbatim(&dir);
+    let disks = Disks::new_with_refreshed_list();
+    space_on(&dir, disks.list().iter().map(|disk| (disk.mount_point(), disk.available_space())))
+}
+
+/// Refuses to start a download the destination has no room for
+fn check_free_space(output_path: &Path, total_size: u64) -> Result<()> {
+    if total_size == 0 {
+        return Ok(());
+    }
+    let Some(available) = available_space(output_path) else {
+        return Ok(());
+    };
+    let required = required_space(output_path, total_size);
+    if required > available {
+        return Err(AppError::Other(format!(
+            "Not enough disk space for {}: {} needed, {} available. Free up space or pass --force to try anyway.",
+            output_path.display(),
+            format_bytes(required),
+            format_bytes(available)
+        )));
+    }
+    Ok(())
+}
+
 /// Performs a single-threaded download with retry and resume support
 async fn download_single(
-    url: &str,
+    urls: &[String],
     output_path: &Path,
     retries: usize,
     can_resume: bool,
     total_size: u64,
-    client: &Client
+    rate_limit: Option<u64>,
+    stall_timeout: Option<Duration>,
+    client: &Client,
+    progress: Option<ProgressCallback>
 ) -> Result<()> {
     // Create parent directories if they don't exist
     if let Some(parent) = output_path.parent() {
         std::fs::create_dir_all(parent)?;
     }
     
+    let mut limiter = rate_limit.map(RateLimiter::new);
     let mut file_size: u64 = 0;
     let mut file: File;
     
-    // Check if we can resume a previous download
-    if can_resume && output_path.exists() {
+    // Check if we can resume a previous download. A pre-allocated output from an unfinished
+    // parallel run has its full length but holes, so it can't be continued byte-wise.
+    if can_resume && output_path.exists() && !sidecar_path(output_path).exists() {
         file_size = std::fs::metadata(output_path)?.len();
         
         if file_size >= total_size && total_size > 0 {
@@ -88,31 +443,41 @@ async fn download_single(
         }
         
         println!("{} {} of {} bytes", "Resuming from:".cyan(), file_size, total_size);
-        file = OpenOptions::new().write(true).append(true).open(output_path)?;
+        file = OpenOptions::new().write(true).open(output_path)?;
     } else {
         // Start a new download
         file = File::create(output_path)?;
+        remove_sidecar(output_path);
     }
     
     // Set up the progress bar
-    let pb = ProgressBar::new(total_size);
+    let pb = ProgressBar::with_draw_target(Some(total_size), draw_target(&progress));
     pb.set_style(ProgressStyle::default_bar()
         .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
         .unwrap()
         .progress_chars("#>-"));
     
     pb.set_position(file_size);
+    report_progress(&progress, &pb);
     
     let mut retry_count = 0;
     let mut success = false;
+    // Set when the last attempt got a 429, holding the server's Retry-After
+    let mut rate_limited = None;
     
     while retry_count <= retries && !success {
         if retry_count > 0 {
-            let wait_time = std::cmp::min(2u64.pow(retry_count as u32), 60);
+            let wait_time = retry_wait(retry_count, rate_limited.take());
             println!("{} {} seconds before retry {}/{}", "Waiting".yellow(), wait_time, retry_count, retries);
             tokio::time::sleep(Duration::from_secs(wait_time)).await;
         }
         
+        // Rotate through the mirrors on each attempt; the Range header picks up the remaining bytes
+        let url = &urls[retry_count % urls.len()];
+        if retry_count > 0 && urls.len() > 1 {
+            println!("{} {}", "Trying mirror:".cyan(), url);
+        }
+        
         let mut request = client.get(url);
         
         // Add range header if resuming
@@ -122,34 +487,57 @@ async fn download_single(
         
         match request.send().await {
             Ok(resp) => {
+                if resp.status() == StatusCode::TOO_MANY_REQUESTS {
+                    let retry_after = utils::retry_after(resp.headers());
+                    println!("{} Rate limited by {} (HTTP 429)", "Error:".red(), utils::url_host(url));
+                    rate_limited = Some(retry_after);
+                    retry_count += 1;
+                    if retry_after.is_some_and(|wait| wait > utils::MAX_RATE_LIMIT_WAIT) {
+                        break;
+                    }
+                    continue;
+                }
                 if !resp.status().is_success() && resp.status() != StatusCode::PARTIAL_CONTENT {
                     println!("{} {}: {}", "Error:".red(), "HTTP error", resp.status());
                     retry_count += 1;
                     continue;
                 }
                 
+                // A server that ignores the Range header sends the whole body again,
+                // so start over instead of appending it after the bytes we already have
+                if file_size > 0 && resp.status() != StatusCode::PARTIAL_CONTENT {
+                    println!("{}", "Server ignored the range request, restarting from the beginning.".yellow());
+                    file.set_len(0)?;
+                    file_size = 0;
+                    pb.set_position(0);
+                }
+                file.seek(SeekFrom::Start(file_size))?;
+                
                 let mut stream = resp.bytes_stream();
+                let mut stream_failed = false;
                 
-                while let Some(chunk_result) = stream.next().await {
+                while let Some(chunk_result) = next_chunk(&mut stream, stall_timeout).await {
                     match chunk_result {
                         Ok(chunk) => {
                             file.write_all(&chunk)?;
+                            file_size += chunk.len() as u64;
                             pb.inc(chunk.len() as u64);
+                            report_progress(&progress, &pb);
+                            if let Some(limiter) = limiter.as_mut() {
+                                limiter.throttle(chunk.len()).await;
+                            }
                         },
                         Err(e) => {
                             println!("{} {}: {}", "Error:".red(), "Failed to download chunk", e);
                             retry_count += 1;
-                            // If we're resuming, get the new file size
-                            if can_resume {
-                                file_size = file.metadata()?.len();
-                                file.flush()?;
-                            }
+                            file.flush()?;
+                            stream_failed = true;
                             break;
                         }
                     }
                 }
                 
-                success = true;
+                success = !stream_failed;
             },
             Err(e) => {
                 println!("{} {}: {}", "Error:".red(), "Failed to send request", e);
@@ -161,131 +549,195 @@ async fn download_single(
     pb.finish_with_message(if success { "Download complete".green().to_string() } else { "Download failed".red().to_string() });
     
     if !success {
-        return Err(anyhow::anyhow!("Failed to download file after {} retries", retries));
+        if let Some(retry_after) = rate_limited {
+            return Err(AppError::RateLimited { service: utils::url_host(&urls[0]), retry_after });
+        }
+        return Err(AppError::Network(format!("Failed to download file after {} retries", retries)));
     }
     
     Ok(())
 }
 
-/// Performs a parallel download with multiple connections
+// Segments per connection in parallel mode, so connections that finish early take over the rest
+const SEGMENTS_PER_CONNECTION: u64 = 4;
+// Segments smaller than this aren't worth a request of their own
+const MIN_SEGMENT_SIZE: u64 = 1024 * 1024;
+
+/// A byte range of the output fetched over one connection
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Segment {
+    idx: usize,
+    start: u64,
+    end: u64,  // Inclusive
+    done: u64, // Bytes already in the output
+}
+
+impl Segment {
+    fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+// How many segments a parallel download of `total_size` bytes is split into
+fn segment_count(total_size: u64, parallel: usize) -> usize {
+    let by_size = total_size.div_ceil(MIN_SEGMENT_SIZE).max(1);
+    min(parallel as u64 * SEGMENTS_PER_CONNECTION, by_size) as usize
+}
+
+// Segment `idx` of `count`; the last one also takes the bytes left over by the division
+fn segment_range(idx: usize, count: usize, total_size: u64) -> (u64, u64) {
+    let size = total_size / count as u64;
+    let start = idx as u64 * size;
+    let end = if idx + 1 == count { total_size - 1 } else { start + size - 1 };
+    (start, end)
+}
+
+/// Performs a parallel download with multiple connections.
+///
+/// The output is pre-allocated to its full size and split into several segments per
+/// connection. Every connection takes the next unfinished segment from a shared queue and
+/// writes it straight into the output at its offset, so one slow range doesn't leave the
+/// other connections idle and nothing has to be reassembled afterwards. With resuming
+/// enabled, per-segment progress is kept in `<output>.segments` so a later `--resume` run
+/// with the same `--parallel` value only fetches the missing ranges.
 async fn download_parallel(
-    url: &str,
+    urls: &[String],
     output_path: &Path,
     retries: usize,
     can_resume: bool,
     total_size: u64,
     parallel: usize,
-    client: &Client
+    rate_limit: Option<u64>,
+    stall_timeout: Option<Duration>,
+    client: &Client,
+    progress: Option<ProgressCallback>
 ) -> Result<()> {
     // Create parent directories if they don't exist
     if let Some(parent) = output_path.parent() {
         std::fs::create_dir_all(parent)?;
     }
     
-    // Initialize the file with zeros to pre-allocate space
+    // Progress from an earlier run only counts if the output it describes is still there
+    let segment_total = segment_count(total_size, parallel);
+    let resumed = if can_resume {
+        load_segment_progress(output_path, total_size, segment_total)
+            .filter(|_| std::fs::metadata(output_path).is_ok_and(|m| m.len() == total_size))
+    } else {
+        None
+    };
+    
+    // Pre-allocate the whole file; segments are written into it at their offsets
     let file = OpenOptions::new()
         .create(true)
         .write(true)
-        .truncate(!can_resume)
+        .truncate(resumed.is_none())
         .open(output_path)?;
-    
-    if !can_resume || !output_path.exists() {
-        file.set_len(total_size)?;
+    file.set_len(total_size)?;
+    drop(file);
+    // Write the sidecar before any segment starts: a full-size output without one would
+    // look complete to a later run if this one is interrupted before the first save
+    let done = resumed.unwrap_or_else(|| vec![0; segment_total]);
+    let tracker = Arc::new(SegmentTracker::new(can_resume.then(|| sidecar_path(output_path)), total_size, done.clone()));
+    tracker.save()?;
+    // Without resume a half-written output is useless, so Ctrl-C deletes it
+    if !can_resume {
+        utils::track_partial_file(output_path);
     }
     
-    // Calculate chunk sizes
-    let chunk_size = total_size / parallel as u64;
-    let mut download_tasks = Vec::new();
+    let mut download_tasks = JoinSet::new();
     let client = Arc::new(client.clone());
+    let urls = Arc::new(urls.to_vec());
     
     // Set up a multi-progress bar
-    let multi_progress = MultiProgress::new();
+    let multi_progress = MultiProgress::with_draw_target(draw_target(&progress));
     let main_pb = multi_progress.add(ProgressBar::new(total_size));
     main_pb.set_style(ProgressStyle::default_bar()
         .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
         .unwrap()
         .progress_chars("#>-"));
     
-    // Limit concurrent downloads with a semaphore
-    let semaphore = Arc::new(Semaphore::new(parallel));
-    
-    // Spawn a separate task to run the progress bars
-    let _mp_handle = task::spawn_blocking(move || {
-        // Remove the call to join() since it doesn't exist
-        // Just keep the multi_progress alive in this thread
-        // multi_progress will be dropped when this task completes
-    });
-    
-    // Create one task per chunk
-    for i in 0..parallel {
-        let start = i as u64 * chunk_size;
-        let mut end = min((i as u64 + 1) * chunk_size - 1, total_size - 1);
-        if i == parallel - 1 {
-            end = total_size - 1; // Make sure the last chunk gets any remaining bytes
-        }
-        
-        // Skip already completed chunks (for resume)
-        let temp_path = get_temp_path(output_path, i);
-        let mut current_pos = 0;
-        
-        if can_resume && temp_path.exists() {
-            if let Ok(metadata) = std::fs::metadata(&temp_path) {
-                current_pos = metadata.len();
-                if current_pos >= end - start + 1 {
-                    // This chunk is already complete
-                    println!("{} {}", "Chunk".green(), i + 1);
-                    continue;
-                }
-            }
+    // Queue every segment that still needs bytes
+    let mut pending = VecDeque::new();
+    for (idx, &done) in done.iter().enumerate() {
+        let (start, end) = segment_range(idx, segment_total, total_size);
+        let segment = Segment { idx, start, end, done: min(done, end - start + 1) };
+        main_pb.inc(segment.done);
+        if segment.done < segment.len() {
+            pending.push_back(segment);
         }
-        
-        let client_clone = client.clone();
-        let url = url.to_string();
-        let semaphore_clone = semaphore.clone();
+    }
+    report_progress(&progress, &main_pb);
+    if pending.len() < segment_total {
+        println!("{} {} of {} segments already downloaded", "Resuming:".cyan().bold(), segment_total - pending.len(), segment_total);
+    }
+    
+    let connections = min(parallel, pending.len());
+    let pending = Arc::new(Mutex::new(pending));
+    let failed = Arc::new(AtomicBool::new(false));
+    
+    // Every connection gets an equal share of the overall rate budget
+    let connection_rate = rate_limit.map(|rate| (rate / connections.max(1) as u64).max(1));
+    
+    for conn in 0..connections {
+        let client = client.clone();
+        let urls = urls.clone();
+        let pending = pending.clone();
+        let failed = failed.clone();
+        let tracker = tracker.clone();
         let output_path = output_path.to_path_buf();
-        let pb = multi_progress.add(ProgressBar::new(end - start + 1));
+        let pb = multi_progress.add(ProgressBar::new(0));
+        let total_pb = main_pb.clone();
+        let progress = progress.clone();
         
         pb.set_style(ProgressStyle::default_bar()
-            .template(&format!("{{spinner:.green}} Chunk {} [{{bar:20.cyan/blue}}] {{bytes}}/{{total_bytes}}", i + 1))
+            .template(&format!("{{spinner:.green}} Connection {} [{{bar:20.cyan/blue}}] {{bytes}}/{{total_bytes}} {{msg}}", conn + 1))
             .unwrap()
             .progress_chars("#>-"));
         
-        pb.set_position(current_pos);
-        
-        // Download a single chunk
-        let task = task::spawn(async move {
-            let _permit = semaphore_clone.acquire().await.unwrap();
-            
-            let chunk_result = download_chunk(
-                &url, 
-                &output_path, 
-                start, 
-                end, 
-                retries,
-                can_resume,
-                current_pos,
-                pb.clone(),
-                i,
-                &client_clone
-            ).await;
-            
+        // Keep taking segments until none are left or another connection gave up
+        download_tasks.spawn(async move {
+            let mut file = OpenOptions::new().write(true).open(&output_path)?;
+            let mut limiter = connection_rate.map(RateLimiter::new);
+            while !failed.load(Ordering::Relaxed) {
+                let next = pending.lock().unwrap().pop_front();
+                let Some(segment) = next else { break };
+                
+                pb.set_length(segment.len());
+                pb.set_position(segment.done);
+                pb.set_message(format!("segment {}/{}", segment.idx + 1, segment_total));
+                
+                let result = download_segment(
+                    &urls,
+                    &mut file,
+                    segment,
+                    retries,
+                    &tracker,
+                    &mut limiter,
+                    stall_timeout,
+                    &pb,
+                    &total_pb,
+                    &progress,
+                    &client
+                ).await;
+                if let Err(e) = result {
+                    failed.store(true, Ordering::Relaxed);
+                    pb.finish_and_clear();
+                    return Err(e);
+                }
+            }
             pb.finish_and_clear();
-            chunk_result
+            Ok::<(), AppError>(())
         });
-        
-        download_tasks.push(task);
     }
     
-    // Wait for all downloads to complete
+    // Wait for all connections to finish
     let mut success = true;
-    for task in download_tasks {
-        match task.await {
+    while let Some(task_result) = download_tasks.join_next().await {
+        match task_result {
             Ok(result) => {
                 if let Err(e) = result {
-                    println!("{} {}", "Chunk error:".red(), e);
+                    println!("{} {}", "Segment error:".red(), e);
                     success = false;
-                } else {
-                    main_pb.inc(chunk_size);
                 }
             },
             Err(e) => {
@@ -297,129 +749,338 @@ async fn download_parallel(
     
     main_pb.finish_with_message(if success { "Download complete".green().to_string() } else { "Download failed".red().to_string() });
     
-    // If the download was successful, combine all chunks into the final file
     if success {
-        let mut output_file = OpenOptions::new()
-            .write(true)
-            .open(output_path)?;
-        
-        for i in 0..parallel {
-            let temp_path = get_temp_path(output_path, i);
-            if temp_path.exists() {
-                let start = i as u64 * chunk_size;
-                let mut temp_file = File::open(&temp_path)?;
-                let temp_size = temp_file.metadata()?.len();
-                
-                let mut buffer = vec![0u8; temp_size as usize];
-                temp_file.read_exact(&mut buffer)?;
-                
-                output_file.seek(SeekFrom::Start(start))?;
-                output_file.write_all(&buffer)?;
-                
-                // Remove the temporary file
-                std::fs::remove_file(temp_path)?;
+        utils::untrack_partial_file(output_path);
+        remove_sidecar(output_path);
+        return Ok(());
+    }
+    
+    if can_resume {
+        if let Err(e) = tracker.save() {
+            eprintln!("{} {}", "Failed to save segment progress:".yellow(), e);
+        }
+        println!(
+            "{} Partial download kept at {}. Rerun with --resume and --parallel {} to continue.",
+            "Note:".yellow(),
+            output_path.display(),
+            parallel
+        );
+    }
+    Err(AppError::Network("Failed to download one or more segments".into()))
+}
+
+// How often the segment sidecar is rewritten while a parallel download runs
+const SIDECAR_SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Bytes written per segment, as saved in `<output>.segments`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SegmentProgress {
+    total_size: u64,
+    done: Vec<u64>,
+}
+
+/// Per-segment progress shared by the connections of a parallel download.
+/// Counts are only raised after the bytes are written, so the sidecar never claims more
+/// than the output holds; at worst a resume fetches the last second of data again.
+struct SegmentTracker {
+    sidecar: Option<PathBuf>, // Only written when resuming is enabled
+    total_size: u64,
+    done: Mutex<Vec<u64>>,
+    last_save: Mutex<Instant>,
+}
+
+impl SegmentTracker {
+    fn new(sidecar: Option<PathBuf>, total_size: u64, done: Vec<u64>) -> Self {
+        Self { sidecar, total_size, done: Mutex::new(done), last_save: Mutex::new(Instant::now()) }
+    }
+
+    /// Records `bytes` more written to segment `idx`, saving the sidecar now and then
+    fn add(&self, idx: usize, bytes: u64) {
+        self.done.lock().unwrap()[idx] += bytes;
+        let due = {
+            let mut last_save = self.last_save.lock().unwrap();
+            let due = last_save.elapsed() >= SIDECAR_SAVE_INTERVAL;
+            if due {
+                *last_save = Instant::now();
+            }
+            due
+        };
+        if due {
+            if let Err(e) = self.save() {
+                eprintln!("{} {}", "Failed to save segment progress:".yellow(), e);
             }
         }
     }
-    
-    if !success {
-        return Err(anyhow::anyhow!("Failed to download one or more chunks"));
+
+    fn save(&self) -> std::io::Result<()> {
+        let Some(path) = &self.sidecar else { return Ok(()) };
+        let state = SegmentProgress { total_size: self.total_size, done: self.done.lock().unwrap().clone() };
+        std::fs::write(path, serde_json::to_string(&state)?)
     }
-    
-    Ok(())
 }
 
-/// Downloads a single chunk of the file
-async fn download_chunk(
-    url: &str,
-    output_path: &Path,
-    start: u64,
-    end: u64,
+// The sidecar recording which parts of a parallel download are already in the output
+fn sidecar_path(output_path: &Path) -> PathBuf {
+    let mut name = output_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".segments");
+    output_path.with_file_name(name)
+}
+
+// Saved per-segment progress, if it belongs to a download of this size and segmentation
+fn load_segment_progress(output_path: &Path, total_size: u64, segments: usize) -> Option<Vec<u64>> {
+    let text = std::fs::read_to_string(sidecar_path(output_path)).ok()?;
+    let state: SegmentProgress = serde_json::from_str(&text).ok()?;
+    (state.total_size == total_size && state.done.len() == segments).then_some(state.done)
+}
+
+fn remove_sidecar(output_path: &Path) {
+    let path = sidecar_path(output_path);
+    if path.exists() {
+        if let Err(e) = std::fs::remove_file(&path) {
+            eprintln!("{} {}: {}", "Failed to remove".yellow(), path.display(), e);
+        }
+    }
+}
+
+/// Downloads one segment straight into the output file at its offset, continuing after
+/// `segment.done`. Segments start on different mirrors and move to the next one on every retry.
+async fn download_segment(
+    urls: &[String],
+    file: &mut File,
+    segment: Segment,
     retries: usize,
-    can_resume: bool,
-    current_pos: u64,
-    pb: ProgressBar,
-    chunk_idx: usize,
+    tracker: &SegmentTracker,
+    limiter: &mut Option<RateLimiter>,
+    stall_timeout: Option<Duration>,
+    pb: &ProgressBar,
+    total_pb: &ProgressBar,
+    progress: &Option<ProgressCallback>,
     client: &Client
 ) -> Result<()> {
-    let temp_path = get_temp_path(output_path, chunk_idx);
-    
-    // Create or open temporary file for this chunk
-    let mut file = if can_resume && temp_path.exists() && current_pos > 0 {
-        let file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .append(true)
-            .open(&temp_path)?;
-        file
-    } else {
-        let file = File::create(&temp_path)?;
-        file
-    };
-    
+    let Segment { idx, start, end, done } = segment;
     let mut retry_count = 0;
     let mut success = false;
-    let actual_start = start + current_pos;
+    let mut written = done;
     
-    // Don't retry if we've completed the chunk
-    if actual_start > end {
+    // Don't retry if we've completed the segment
+    if start + written > end {
         return Ok(());
     }
     
+    let segment_len = segment.len();
+    let mut last_error = String::new();
+    let mut rate_limited = None;
+    
+    // Each segment retries on its own, resuming from the bytes it already wrote,
+    // so one flaky connection doesn't fail the whole download
     while retry_count <= retries && !success {
         if retry_count > 0 {
-            let wait_time = std::cmp::min(2u64.pow(retry_count as u32), 60);
+            let wait_time = retry_wait(retry_count, rate_limited.take());
+            pb.println(format!("{} Segment {} failed ({}), retry {}/{} in {}s", "⚠".yellow(), idx + 1, last_error, retry_count, retries, wait_time));
             tokio::time::sleep(Duration::from_secs(wait_time)).await;
         }
         
-        let range = format!("bytes={}-{}", actual_start, end);
+        // Continue from whatever this segment already has on disk
+        let url = &urls[(idx + retry_count) % urls.len()];
+        let range = format!("bytes={}-{}", start + written, end);
+        file.seek(SeekFrom::Start(start + written))?;
         
         match client.get(url)
             .header(reqwest::header::RANGE, range)
             .send()
             .await {
                 Ok(resp) => {
-                    if resp.status() != StatusCode::PARTIAL_CONTENT && resp.status() != StatusCode::OK {
+                    if resp.status() == StatusCode::TOO_MANY_REQUESTS {
+                        let retry_after = utils::retry_after(resp.headers());
+                        last_error = "rate limited (HTTP 429)".to_string();
+                        rate_limited = Some(retry_after);
+                        retry_count += 1;
+                        if retry_after.is_some_and(|wait| wait > utils::MAX_RATE_LIMIT_WAIT) {
+                            break;
+                        }
+                        continue;
+                    }
+                    // Anything but 206 means the server sent more than this segment's range
+                    if resp.status() != StatusCode::PARTIAL_CONTENT {
+                        last_error = format!("HTTP {}", resp.status());
                         retry_count += 1;
                         continue;
                     }
                     
                     let mut stream = resp.bytes_stream();
+                    let mut stream_failed = false;
                     
-                    while let Some(chunk_result) = stream.next().await {
+                    while let Some(chunk_result) = next_chunk(&mut stream, stall_timeout).await {
                         match chunk_result {
                             Ok(chunk) => {
-                                file.write_all(&chunk)?;
+                                // Never write past the segment into the next one
+                                let chunk = &chunk[..min(chunk.len() as u64, segment_len - written) as usize];
+                                file.write_all(chunk)?;
+                                written += chunk.len() as u64;
+                                tracker.add(idx, chunk.len() as u64);
                                 pb.inc(chunk.len() as u64);
+                                total_pb.inc(chunk.len() as u64);
+                                report_progress(progress, total_pb);
+                                if let Some(limiter) = limiter.as_mut() {
+                                    limiter.throttle(chunk.len()).await;
+                                }
+                                if written == segment_len {
+                                    break;
+                                }
                             },
-                            Err(_) => {
+                            Err(e) => {
+                                last_error = e.to_string();
                                 retry_count += 1;
-                                file.flush()?;
+                                stream_failed = true;
                                 break;
                             }
                         }
                     }
                     
-                    success = true;
+                    // A connection that closes early without an error still needs another attempt
+                    if !stream_failed && written < segment_len {
+                        last_error = format!("connection closed after {} of {} bytes", written, segment_len);
+                        retry_count += 1;
+                        continue;
+                    }
+                    
+                    success = !stream_failed;
                 },
-                Err(_) => {
+                Err(e) => {
+                    last_error = e.to_string();
                     retry_count += 1;
                 }
             }
     }
     
     if !success {
-        return Err(anyhow::anyhow!("Failed to download chunk {} after {} retries", chunk_idx, retries));
+        if let Some(retry_after) = rate_limited {
+            return Err(AppError::RateLimited { service: utils::url_host(&urls[0]), retry_after });
+        }
+        return Err(AppError::Network(format!("Failed to download segment {} after {} retries: {}", idx + 1, retries, last_error)));
     }
     
     Ok(())
 }
 
-// Helper function to get the temporary path for a chunk
-fn get_temp_path(output_path: &Path, chunk_idx: usize) -> PathBuf {
-    let filename = output_path.file_name().unwrap().to_str().unwrap();
-    let parent = output_path.parent().unwrap_or_else(|| Path::new(""));
-    parent.join(format!("{}.part{}", filename, chunk_idx))
+// Seconds to wait before retry `attempt`: what a 429 asked for, otherwise exponential backoff
+fn retry_wait(attempt: usize, rate_limited: Option<Option<Duration>>) -> u64 {
+    match rate_limited {
+        Some(Some(wait)) => wait.as_secs().max(1),
+        _ => min(2u64.pow(attempt as u32), 60),
+    }
+}
+
+// Terminal bars are only drawn when nobody else is reporting progress
+fn draw_target(progress: &Option<ProgressCallback>) -> ProgressDrawTarget {
+    if progress.is_some() { ProgressDrawTarget::hidden() } else { ProgressDrawTarget::stderr() }
+}
+
+// Forward a bar's position to the progress callback, if there is one
+fn report_progress(progress: &Option<ProgressCallback>, pb: &ProgressBar) {
+    if let Some(callback) = progress {
+        callback(Progress { done: pb.position(), total: pb.length().unwrap_or(0) });
+    }
+}
+
+// Clean up after a cancelled download unless the user asked to be able to resume it
+fn discard_cancelled_download(output_path: &Path, options: &FileDownloadOptions) {
+    if options.resume {
+        println!("{} Partial download kept at {} for --resume.", "Note:".yellow(), output_path.display());
+        return;
+    }
+    if output_path.exists() {
+        if let Err(e) = std::fs::remove_file(output_path) {
+            eprintln!("{} {}: {}", "Failed to remove".yellow(), output_path.display(), e);
+        }
+    }
+    utils::untrack_partial_file(output_path);
+    remove_sidecar(output_path);
+}
+
+/// Endpoint used by `speed_test` when no server is given; serves `/__down?bytes=N` and accepts `/__up`
+pub const DEFAULT_SPEEDTEST_SERVER: &str = "https://speed.cloudflare.com";
+const LATENCY_SAMPLES: usize = 5;
+
+/// Options for `speed_test`
+#[derive(Debug, Clone, Default)]
+pub struct SpeedTestOptions {
+    pub server: Option<String>,       // Download URL; the Cloudflare endpoint when None
+    pub download_bytes: u64,          // Size requested from the default endpoint
+    pub upload_bytes: Option<u64>,    // Measure upload with this many bytes
+    pub upload_url: Option<String>,   // Required for upload together with a custom server
+}
+
+/// Measures latency, download throughput and optionally upload throughput
+pub async fn speed_test(options: &SpeedTestOptions) -> Result<()> {
+    let download_url = match &options.server {
+        Some(url) => url.clone(),
+        None => format!("{}/__down?bytes={}", DEFAULT_SPEEDTEST_SERVER, options.download_bytes),
+    };
+    let upload_url = match (&options.upload_url, &options.server) {
+        (Some(url), _) => Some(url.clone()),
+        (None, None) => Some(format!("{}/__up", DEFAULT_SPEEDTEST_SERVER)),
+        (None, Some(_)) => None,
+    };
+    if options.upload_bytes.is_some() && upload_url.is_none() {
+        return Err(AppError::InvalidInput("An upload URL is required when testing against a custom server".into()));
+    }
+
+    let client = utils::http_client();
+    println!("{} {}", "Speed test server:".cyan().bold(), download_url);
+
+    // Latency: time to response headers, after one warm-up request opens the connection.
+    // A custom server gets HEAD requests so its file isn't downloaded each time.
+    let latency_request = || match &options.server {
+        Some(url) => client.head(url),
+        None => client.get(format!("{}/__down?bytes=0", DEFAULT_SPEEDTEST_SERVER)),
+    };
+    latency_request().send().await?.error_for_status()?;
+    let mut samples = Vec::with_capacity(LATENCY_SAMPLES);
+    for _ in 0..LATENCY_SAMPLES {
+        let started = Instant::now();
+        latency_request().send().await?.error_for_status()?;
+        samples.push(started.elapsed().as_secs_f64() * 1000.0);
+    }
+    let min = samples.iter().cloned().fold(f64::MAX, f64::min);
+    let avg = samples.iter().sum::<f64>() / samples.len() as f64;
+    println!("{} {:.1} ms (min {:.1} ms)", "Latency:".cyan().bold(), avg, min);
+
+    // Download
+    let resp = client.get(&download_url).send().await?.error_for_status()?;
+    let pb = ProgressBar::new(resp.content_length().unwrap_or(0));
+    pb.set_style(ProgressStyle::default_bar()
+        .template("{spinner:.green} Download [{bar:40.cyan/blue}] {bytes}/{total_bytes} {msg}")
+        .unwrap()
+        .progress_chars("#>-"));
+    let mut meter = ThroughputMeter::new();
+    let mut stream = resp.bytes_stream();
+    while let Some(chunk) = next_chunk(&mut stream, Some(Duration::from_secs(30))).await {
+        let chunk = chunk.map_err(|e| AppError::Network(format!("Download failed: {}", e)))?;
+        meter.add(chunk.len());
+        pb.inc(chunk.len() as u64);
+        pb.set_message(format!("{:.1} Mbps", meter.mbps()));
+    }
+    pb.finish_and_clear();
+    println!("{} {:.1} Mbps ({} in {:.1}s)", "Download:".cyan().bold(), meter.mbps(),
+        format_bytes(meter.bytes), meter.started.elapsed().as_secs_f64());
+
+    // Upload: one POST of zeros, timed until the server has answered
+    if let (Some(bytes), Some(url)) = (options.upload_bytes, upload_url) {
+        let body = vec![0u8; bytes as usize];
+        let spinner = ProgressBar::new_spinner();
+        spinner.set_message(format!("Uploading {}...", format_bytes(bytes)));
+        spinner.enable_steady_tick(Duration::from_millis(100));
+        let started = Instant::now();
+        let result = client.post(&url).body(body).send().await.and_then(|r| r.error_for_status());
+        let elapsed = started.elapsed();
+        spinner.finish_and_clear();
+        result?;
+        println!("{} {:.1} Mbps ({} in {:.1}s)", "Upload:".cyan().bold(), mbps(bytes, elapsed),
+            format_bytes(bytes), elapsed.as_secs_f64());
+    }
+
+    Ok(())
 }
 
 // Helper to format bytes to human-readable form
@@ -434,4 +1095,107 @@ fn format_bytes(bytes: u64) -> String {
     }
     
     format!("{:.2} {}", size, UNITS[unit_index])
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mbps() {
+        assert_eq!(mbps(12_500_000, Duration::from_secs(1)), 100.0);
+        assert_eq!(mbps(1_000_000, Duration::from_secs(8)), 1.0);
+    }
+
+    #[test]
+    fn test_required_space() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("file.bin");
+        assert_eq!(required_space(&output, 1000), 1000);
+        std::fs::write(&output, vec![0u8; 400]).unwrap();
+        assert_eq!(required_space(&output, 1000), 600);
+        assert_eq!(required_space(&output, 100), 0);
+    }
+
+    #[test]
+    fn test_segments() {
+        const MIB: u64 = 1024 * 1024;
+        assert_eq!(segment_count(100 * MIB, 4), 16);
+        assert_eq!(segment_count(3 * MIB, 4), 3);
+        assert_eq!(segment_count(10, 4), 1);
+        // Segments cover the whole file without gaps or overlap
+        let (total, count) = (100 * MIB + 7, segment_count(100 * MIB + 7, 4));
+        let mut next = 0;
+        for idx in 0..count {
+            let (start, end) = segment_range(idx, count, total);
+            assert_eq!(start, next);
+            next = end + 1;
+        }
+        assert_eq!(next, total);
+    }
+
+    #[test]
+    fn test_segment_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("file.bin");
+        assert_eq!(sidecar_path(&output), dir.path().join("file.bin.segments"));
+        let tracker = SegmentTracker::new(Some(sidecar_path(&output)), 1000, vec![0, 0]);
+        tracker.add(1, 300);
+        tracker.save().unwrap();
+        assert_eq!(load_segment_progress(&output, 1000, 2), Some(vec![0, 300]));
+        // Progress for a different size or segmentation isn't reused
+        assert_eq!(load_segment_progress(&output, 2000, 2), None);
+        assert_eq!(load_segment_progress(&output, 1000, 3), None);
+        remove_sidecar(&output);
+        assert_eq!(load_segment_progress(&output, 1000, 2), None);
+    }
+
+    #[test]
+    fn test_space_on() {
+        let mounts = [(Path::new("/"), 10), (Path::new("/home"), 20), (Path::new("/home/user/data"), 30)];
+        assert_eq!(space_on(Path::new("/home/user/file"), mounts), Some(20));
+        assert_eq!(space_on(Path::new("/home/user/data/x"), mounts), Some(30));
+        assert_eq!(space_on(Path::new("/tmp"), mounts), Some(10));
+        assert_eq!(space_on(Path::new("/tmp"), []), None);
+    }
+
+    #[test]
+    fn test_strip_verbatim() {
+        assert_eq!(strip_verbatim(Path::new(r"\\?\C:\Users\me")), PathBuf::from(r"C:\Users\me"));
+        assert_eq!(strip_verbatim(Path::new(r"\\?\UNC\server\share\dir")), PathBuf::from(r"\\server\share\dir"));
+        assert_eq!(strip_verbatim(Path::new(r"\\?\Volume{abc}\dir")), PathBuf::from(r"\\?\Volume{abc}\dir"));
+        assert_eq!(strip_verbatim(Path::new("/home/user")), PathBuf::from("/home/user"));
+    }
+
+    #[test]
+    fn test_retry_wait() {
+        assert_eq!(retry_wait(1, None), 2);
+        assert_eq!(retry_wait(10, None), 60);
+        assert_eq!(retry_wait(1, Some(None)), 2);
+        assert_eq!(retry_wait(1, Some(Some(Duration::from_secs(30)))), 30);
+        assert_eq!(retry_wait(3, Some(Some(Duration::ZERO))), 1);
+    }
+
+    #[test]
+    fn test_content_disposition_filename() {
+        assert_eq!(
+            parse_content_disposition_filename("attachment; filename=\"report.pdf\""),
+            Some("report.pdf".to_string())
+        );
+        assert_eq!(
+            parse_content_disposition_filename("attachment; filename=\"a.txt\"; filename*=UTF-8''na%C3%AFve%20file.txt"),
+            Some("naïve file.txt".to_string())
+        );
+        assert_eq!(
+            parse_content_disposition_filename("attachment; filename=\"../../etc/passwd\""),
+            Some("passwd".to_string())
+        );
+        assert_eq!(parse_content_disposition_filename("inline"), None);
+    }
+
+    #[test]
+    fn test_filename_from_url() {
+        assert_eq!(filename_from_url("https://example.com/files/archive.zip?token=1"), "archive.zip");
+        assert_eq!(filename_from_url("https://example.com/download/"), "downloaded_file");
+    }
+}
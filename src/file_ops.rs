@@ -1,16 +1,24 @@
 use colored::*;
+use log::info;
 use dirs;
 use fs_extra::dir as fsx_dir;
 use humansize::{format_size, DECIMAL};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::{self, Read};
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant, SystemTime};
 use walkdir::{DirEntry, WalkDir};
+use notify::{EventKind, RecursiveMode, Watcher};
 use regex::Regex;
-use ring::digest::{Context, Digest, SHA256};
-use data_encoding::HEXUPPER;
-use crate::cli::{RenameArgs, SyncArgs};
+use ring::digest::{Context, SHA256};
+use data_encoding::HEXLOWER;
+use md5::{Digest as _, Md5};
+use crate::cli::{ListArgs, ListSort, OrganizeBy, RenameArgs, SymlinkPolicy, SyncArgs};
+use crate::utils::{Progress, ProgressCallback};
+use tokio_util::sync::CancellationToken;
+use serde::Serialize;
 
 // --- Struct for File Information (for UI) ---
 #[derive(Debug, Clone)] // Clone needed for potential data passing
@@ -20,12 +28,31 @@ pub struct FileInfo {
     pub file_type: String, // "Dir", "File", "Link/Other"
     pub size_bytes: Option<u64>, // None for directories
     pub size_human: String, // Formatted size string
+    pub modified: Option<SystemTime>, // Last modification time, if available
 }
 
 // --- Existing File Ops ---
 
+// Sort a listing in place according to the listing options
+pub fn sort_file_infos(entries: &mut [FileInfo], sort: ListSort, reverse: bool) {
+    match sort {
+        ListSort::Name => entries.sort_by(|a, b| a.name.cmp(&b.name)),
+        ListSort::Size => entries.sort_by(|a, b| a.size_bytes.unwrap_or(0).cmp(&b.size_bytes.unwrap_or(0)).then_with(|| a.name.cmp(&b.name))),
+        ListSort::Mtime => entries.sort_by(|a, b| a.modified.cmp(&b.modified).then_with(|| a.name.cmp(&b.name))),
+    }
+    if reverse {
+        entries.reverse();
+    }
+}
+
+// Format a size for the listing according to the --human/--bytes toggle
+fn format_list_size(bytes: u64, raw: bool) -> String {
+    if raw { bytes.to_string() } else { format_size(bytes, DECIMAL) }
+}
+
 // Function to list directory contents
-pub fn list_directory(path: &Path) -> io::Result<()> {
+pub fn list_directory(args: &ListArgs) -> io::Result<()> {
+    let path = args.path.as_path();
     if !path.is_dir() {
         eprintln!(
             "{}",
@@ -34,48 +61,77 @@ pub fn list_directory(path: &Path) -> io::Result<()> {
         return Err(io::Error::new(io::ErrorKind::NotADirectory, "Path is not a directory"));
     }
     let absolute_path = path.canonicalize()?;
-    println!(
-        "{}",
-        format!("Contents of: {}", absolute_path.display()).magenta().bold()
-    );
-    println!("{:<35} {:<15} {:>15}", "Name".cyan().bold(), "Type".cyan().bold(), "Size".cyan().bold());
-    println!("{}", "-".repeat(67).dimmed());
+    let mut lines = vec![format!("Contents of: {}", absolute_path.display()).magenta().bold().to_string()];
 
-    let mut entries = Vec::new();
-    for entry_result in fs::read_dir(path)? {
-        match entry_result {
-            Ok(entry) => entries.push(entry),
-            Err(e) => eprintln!("{}", format!("Error reading entry: {}", e).red()),
-        }
+    if args.tree {
+        lines.push(absolute_path.display().to_string().blue().bold().to_string());
+        print_tree(path, "", 1, args, &mut lines);
+        crate::utils::maybe_page(&lines.join("\n"));
+        return Ok(());
     }
-    entries.sort_by_key(|dir_entry| dir_entry.file_name());
+
+    lines.push(format!("{:<35} {:<15} {:>15}", "Name".cyan().bold(), "Type".cyan().bold(), "Size".cyan().bold()));
+    lines.push("-".repeat(67).dimmed().to_string());
+
+    let mut entries = get_directory_listing(path)?;
+    sort_file_infos(&mut entries, args.sort, args.reverse);
 
     for entry in entries {
-        let entry_path = entry.path();
-        let file_name_os = entry.file_name();
-        let file_name = file_name_os.to_string_lossy();
-        match fs::metadata(&entry_path) {
-            Ok(metadata) => {
-                let file_type_str = if metadata.is_dir() { "Dir".blue().bold() } else if metadata.is_file() { "File".normal() } else { "Link/Other".dimmed() };
-                let size_str = if metadata.is_file() {
-                    format_size(metadata.len(), DECIMAL)
-                } else {
-                    "-".dimmed().to_string()
-                };
-                let name_display = if metadata.is_dir() { file_name.blue().bold() } else { file_name.normal() };
-                println!("{:<35} {:<15} {:>15}", name_display, file_type_str, size_str);
-            }
-            Err(e) => {
-                eprintln!("{}", format!("Error accessing metadata for '{}': {}", file_name, e).red());
-                println!("{:<35} {:<15} {:>15}", file_name.red(), "Error".red().bold(), "-".red());
-            }
-        }
+        let size_str = match entry.size_bytes {
+            Some(bytes) => format_list_size(bytes, args.raw_sizes()),
+            None => "-".dimmed().to_string(),
+        };
+        let (name_display, file_type_str) = match entry.file_type.as_str() {
+            "Dir" => (entry.name.blue().bold(), entry.file_type.blue().bold()),
+            "File" => (entry.name.normal(), entry.file_type.normal()),
+            "Error" => (entry.name.red(), entry.file_type.red().bold()),
+            _ => (entry.name.normal(), entry.file_type.dimmed()),
+        };
+        lines.push(format!("{:<35} {:<15} {:>15}", name_display, file_type_str, size_str));
     }
+    crate::utils::maybe_page(&lines.join("\n"));
     Ok(())
 }
 
-// Function to backup a directory
-pub fn backup_directory(source: &Path, destination: &Path) -> Result<(), fs_extra::error::Error> {
+// Recursively render an indented tree into `lines`, honouring the depth limit
+fn print_tree(path: &Path, prefix: &str, depth: usize, args: &ListArgs, lines: &mut Vec<String>) {
+    if args.depth.map_or(false, |max| depth > max) {
+        return;
+    }
+    let mut entries = match get_directory_listing(path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{}", format!("Error reading '{}': {}", path.display(), e).red());
+            return;
+        }
+    };
+
+    sort_file_infos(&mut entries, args.sort, args.reverse);
+
+    let count = entries.len();
+    for (i, entry) in entries.into_iter().enumerate() {
+        let last = i + 1 == count;
+        let branch = if last { "└── " } else { "├── " };
+        if entry.file_type == "Dir" {
+            lines.push(format!("{}{}{}", prefix.dimmed(), branch.dimmed(), entry.name.blue().bold()));
+            let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            print_tree(&entry.path, &child_prefix, depth + 1, args, lines);
+        } else if let Some(bytes) = entry.size_bytes {
+            lines.push(format!(
+                "{}{}{} {}",
+                prefix.dimmed(),
+                branch.dimmed(),
+                entry.name,
+                format!("({})", format_list_size(bytes, args.raw_sizes())).dimmed()
+            ));
+        } else {
+            lines.push(format!("{}{}{}", prefix.dimmed(), branch.dimmed(), entry.name.dimmed()));
+        }
+    }
+}
+
+// Function to backup a directory; `progress` receives the bytes copied so far
+pub fn backup_directory(source: &Path, destination: &Path, progress: Option<ProgressCallback>) -> Result<(), fs_extra::error::Error> {
     if !source.is_dir() {
         eprintln!("{}", format!("Error: Source '{}' is not a valid directory.", source.display()).red().bold());
     }
@@ -95,7 +151,13 @@ pub fn backup_directory(source: &Path, destination: &Path) -> Result<(), fs_extr
     let mut options = fsx_dir::CopyOptions::new();
     options.overwrite = true;
     options.copy_inside = true;
-    match fsx_dir::copy(source, destination, &options) {
+    let handler = |info: fsx_dir::TransitProcess| {
+        if let Some(callback) = &progress {
+            callback(Progress { done: info.copied_bytes, total: info.total_bytes });
+        }
+        fsx_dir::TransitProcessResult::ContinueOrAbort
+    };
+    match fsx_dir::copy_with_progress(source, destination, &options, handler) {
         Ok(bytes_copied) => {
             println!("{}", format!("Success: Copied {} to '{}'", format_size(bytes_copied, DECIMAL), destination.display()).green().bold());
             Ok(())
@@ -107,14 +169,295 @@ pub fn backup_directory(source: &Path, destination: &Path) -> Result<(), fs_extr
     }
 }
 
-// Function to organize screenshots on macOS Desktop
-#[cfg(target_os = "macos")]
-pub fn organize_screenshots() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    println!("{}", "Organizing macOS Desktop screenshots...".cyan());
-    let desktop_dir = dirs::desktop_dir().ok_or("Desktop directory not found")?;
-    let screenshots_dir = desktop_dir.join("Screenshots");
+// Move a directory like backup_directory copies it (into `destination` when that
+// exists, otherwise to `destination` itself). A plain rename is tried first; across
+// filesystems each file is copied, checked against the original and only then deleted.
+pub fn move_directory(source: &Path, destination: &Path) -> Result<(), fs_extra::error::Error> {
+    if !source.is_dir() {
+        return Err(fs_extra::error::Error::new(fs_extra::error::ErrorKind::NotFound, &format!("Source '{}' is not a valid directory.", source.display())));
+    }
+    let target = if destination.is_dir() {
+        destination.join(source.file_name().unwrap_or_default())
+    } else {
+        destination.to_path_buf()
+    };
+    if target.exists() {
+        return Err(fs_extra::error::Error::new(fs_extra::error::ErrorKind::AlreadyExists, &format!("Destination '{}' already exists.", target.display())));
+    }
+    if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    println!("{}", format!("Starting move: '{}' -> '{}'...", source.display().to_string().cyan(), target.display().to_string().cyan()));
+
+    match fs::rename(source, &target) {
+        Ok(()) => {
+            println!("{}", format!("Success: Moved to '{}'", target.display()).green().bold());
+            return Ok(());
+        }
+        // Typically EXDEV (different filesystem); copying below handles it
+        Err(e) => info!("{}", format!("Rename failed ({}), falling back to copy and delete", e).dimmed()),
+    }
+
+    let bytes_moved = copy_then_remove(source, &target)?;
+    println!("{}", format!("Success: Moved {} to '{}'", format_size(bytes_moved, DECIMAL), target.display()).green().bold());
+    Ok(())
+}
+
+// move_directory's cross-filesystem path. Everything is copied first (links recreated as
+// links, files checked byte-for-byte against the original) and the source is only removed
+// once the whole tree is safely in `target`, so an interruption never loses data.
+fn copy_then_remove(source: &Path, target: &Path) -> Result<u64, fs_extra::error::Error> {
+    let mut bytes_moved = 0;
+    for entry in WalkDir::new(source) {
+        // An unreadable entry aborts the move before anything is deleted
+        let entry = entry.map_err(io::Error::from)?;
+        let rel = entry.path().strip_prefix(source).unwrap_or(entry.path());
+        let dest = target.join(rel);
+        if entry.path_is_symlink() {
+            // Copying would follow the link: fail on dangling ones and duplicate the target
+            create_symlink(&fs::read_link(entry.path())?, &dest, entry.path().is_dir())?;
+            continue;
+        }
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&dest)?;
+            continue;
+        }
+        fs::copy(entry.path(), &dest)?;
+        let copied = hash_file_hex(entry.path(), HashAlgorithm::Sha256)? == hash_file_hex(&dest, HashAlgorithm::Sha256)?;
+        if !copied {
+            return Err(fs_extra::error::Error::new(fs_extra::error::ErrorKind::Other, &format!("Copy of '{}' does not match the original; source left in place.", entry.path().display())));
+        }
+        bytes_moved += fs::metadata(&dest)?.len();
+    }
+
+    for entry in WalkDir::new(source).contents_first(true) {
+        let entry = entry.map_err(io::Error::from)?;
+        if entry.path_is_symlink() {
+            remove_symlink(entry.path())?;
+        } else if entry.file_type().is_dir() {
+            fs::remove_dir(entry.path())?;
+        } else {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(bytes_moved)
+}
+
+// Snapshot directories are named after their start time, so they sort chronologically
+const SNAPSHOT_NAME_FORMAT: &str = "%Y-%m-%d_%H%M%S";
+// Suffix of a snapshot still being written; it is never used as a base for hardlinks
+const INCOMPLETE_SNAPSHOT_SUFFIX: &str = ".incomplete";
+
+// Newest finished snapshot in `root`, if any
+fn latest_snapshot(root: &Path) -> Option<PathBuf> {
+    fs::read_dir(root).ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .filter(|name| chrono::NaiveDateTime::parse_from_str(name, SNAPSHOT_NAME_FORMAT).is_ok())
+        .max()
+        .map(|name| root.join(name))
+}
+
+// Whether `previous` can stand in for `current`: same size and either the same
+// modification time or, with `checksum`, the same SHA-256
+fn is_unchanged(current: &Path, current_meta: &fs::Metadata, previous: &Path, checksum: bool) -> io::Result<bool> {
+    let previous_meta = match fs::symlink_metadata(previous) {
+        Ok(meta) if meta.is_file() => meta,
+        _ => return Ok(false),
+    };
+    if previous_meta.len() != current_meta.len() {
+        return Ok(false);
+    }
+    if checksum {
+        Ok(hash_file_hex(current, HashAlgorithm::Sha256)? == hash_file_hex(previous, HashAlgorithm::Sha256)?)
+    } else {
+        Ok(previous_meta.modified()? == current_meta.modified()?)
+    }
+}
+
+// Back up `source` into a new timestamped snapshot under `destination`, rsync --link-dest
+// style: files unchanged since the previous snapshot are hardlinked to it instead of copied
+pub fn incremental_backup(source: &Path, destination: &Path, checksum: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if !source.is_dir() {
+        return Err(format!("Source '{}' is not a valid directory.", source.display()).into());
+    }
+    fs::create_dir_all(destination)?;
+    let previous = latest_snapshot(destination);
+    let name = chrono::Local::now().format(SNAPSHOT_NAME_FORMAT).to_string();
+    let snapshot = destination.join(&name);
+    if snapshot.exists() {
+        return Err(format!("Snapshot '{}' already exists; try again in a second.", snapshot.display()).into());
+    }
+    let staging = destination.join(format!("{}{}", name, INCOMPLETE_SNAPSHOT_SUFFIX));
+
+    println!("{}", format!("Starting incremental backup: '{}' -> '{}'...", source.display().to_string().cyan(), snapshot.display().to_string().cyan()));
+    match &previous {
+        Some(prev) => println!("Linking unchanged files to {}", prev.display().to_string().dimmed()),
+        None => println!("{}", "No previous snapshot found; copying everything.".dimmed()),
+    }
+
+    let (mut linked, mut copied, mut error_count) = (0u64, 0u64, 0u64);
+    let (mut linked_bytes, mut copied_bytes) = (0u64, 0u64);
+    for entry in WalkDir::new(source) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                eprintln!("{}", format!("Error reading source: {}", e).red());
+                error_count += 1;
+                continue;
+            }
+        };
+        let rel = entry.path().strip_prefix(source).unwrap_or(entry.path());
+        let dest = staging.join(rel);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&dest)?;
+            continue;
+        }
+        if !entry.file_type().is_file() {
+            info!("{}", format!("Skipping non-regular file {}", entry.path().display()).dimmed());
+            continue;
+        }
+
+        let result = entry.metadata().map_err(io::Error::from).and_then(|meta| {
+            let base = previous.as_ref().map(|prev| prev.join(rel));
+            if let Some(base) = base.filter(|base| is_unchanged(entry.path(), &meta, base, checksum).unwrap_or(false)) {
+                // Fall back to a copy when the filesystem can't hardlink
+                if fs::hard_link(&base, &dest).is_ok() {
+                    linked += 1;
+                    linked_bytes += meta.len();
+                    return Ok(());
+                }
+            }
+            fs::copy(entry.path(), &dest)?;
+            // Keep the mtime so the next snapshot can recognise the file as unchanged
+            fs::File::options().write(true).open(&dest)?.set_modified(meta.modified()?)?;
+            copied += 1;
+            copied_bytes += meta.len();
+            Ok(())
+        });
+        if let Err(e) = result {
+            eprintln!("{}", format!("Error backing up '{}': {}", entry.path().display(), e).red());
+            error_count += 1;
+        }
+    }
+
+    fs::rename(&staging, &snapshot)?;
+    println!("{}", format!("Success: Snapshot written to '{}'", snapshot.display()).green().bold());
+    println!("  Linked: {} file(s), {}", linked.to_string().green(), format_size(linked_bytes, DECIMAL));
+    println!("  Copied: {} file(s), {}", copied.to_string().yellow(), format_size(copied_bytes, DECIMAL));
+    if error_count > 0 { println!("{}", format!("Encountered {} error(s); those files are missing from the snapshot.", error_count).yellow()); }
+    Ok(())
+}
+
+// One file a backup would write: where it comes from, where it would go, and whether it
+// would be copied, moved or (in an incremental snapshot) hardlinked to the previous one
+#[derive(Debug, PartialEq)]
+struct PlannedBackup {
+    from: PathBuf,
+    to: PathBuf,
+    action: &'static str,
+    bytes: u64,
+}
+
+// The files `backup_directory`, `move_directory` or `incremental_backup` would write,
+// in source order, without touching anything
+fn plan_backup(source: &Path, destination: &Path, move_source: bool, incremental: bool, checksum: bool) -> io::Result<Vec<PlannedBackup>> {
+    if !source.is_dir() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("Source '{}' is not a valid directory.", source.display())));
+    }
+    let previous = if incremental { latest_snapshot(destination) } else { None };
+    let target = if incremental {
+        destination.join(chrono::Local::now().format(SNAPSHOT_NAME_FORMAT).to_string())
+    } else if destination.is_dir() {
+        destination.join(source.file_name().unwrap_or_default())
+    } else {
+        destination.to_path_buf()
+    };
+
+    let mut plan = Vec::new();
+    for entry in WalkDir::new(source).sort_by_file_name() {
+        let entry = entry.map_err(io::Error::from)?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = entry.path().strip_prefix(source).unwrap_or(entry.path());
+        let meta = entry.metadata().map_err(io::Error::from)?;
+        let unchanged = previous.as_ref()
+            .is_some_and(|prev| is_unchanged(entry.path(), &meta, &prev.join(rel), checksum).unwrap_or(false));
+        let action = if unchanged { "link" } else if move_source && !incremental { "move" } else { "copy" };
+        plan.push(PlannedBackup { from: entry.path().to_path_buf(), to: target.join(rel), action, bytes: meta.len() });
+    }
+    Ok(plan)
+}
+
+// Print what a backup would do, file by file, for --dry-run
+pub fn preview_backup(source: &Path, destination: &Path, move_source: bool, incremental: bool, checksum: bool) -> io::Result<()> {
+    let plan = plan_backup(source, destination, move_source, incremental, checksum)?;
+    for item in &plan {
+        println!("Would {} {} -> {}", item.action, item.from.display(), item.to.display().to_string().yellow());
+    }
+    let bytes: u64 = plan.iter().map(|item| item.bytes).sum();
+    println!("{}", format!("Dry run: {} file(s), {}", plan.len(), format_size(bytes, DECIMAL)).cyan());
+    Ok(())
+}
+
+// Where the OS saves screenshots by default: the Desktop on macOS,
+// Pictures\Screenshots on Windows and Pictures (or Pictures/Screenshots) on Linux
+fn default_screenshot_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "macos") {
+        dirs::desktop_dir()
+    } else {
+        let pictures = dirs::picture_dir()?;
+        let screenshots = pictures.join("Screenshots");
+        if cfg!(target_os = "windows") || screenshots.is_dir() { Some(screenshots) } else { Some(pictures) }
+    }
+}
+
+// Whether a file name looks like a screenshot on any platform: macOS "Screen Shot ..." /
+// "Screenshot ...", Windows "Screenshot (N).png", GNOME "Screenshot from YYYY-MM-DD ..."
+// and KDE "Screenshot_YYYYMMDD_..."
+fn is_screenshot_name(filename: &str) -> bool {
+    let is_image = Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ["png", "jpg", "jpeg"].contains(&ext.to_ascii_lowercase().as_str()));
+    is_image && ["Screen Shot ", "Screenshot ", "Screenshot_"].iter().any(|prefix| filename.starts_with(prefix))
+}
+
+// "YYYY-MM" folder for a screenshot: the date in its name when there is one
+// (all the platform patterns embed it except Windows' numbered files), else its modified time
+fn screenshot_month(filename: &str, path: &Path) -> Option<String> {
+    lazy_static::lazy_static! {
+        static ref DATE_IN_NAME: Regex = Regex::new(r"(\d{4})-?(\d{2})-?\d{2}").unwrap();
+    }
+    if let Some(caps) = DATE_IN_NAME.captures(filename) {
+        return Some(format!("{}-{}", &caps[1], &caps[2]));
+    }
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    Some(chrono::DateTime::<chrono::Local>::from(modified).format("%Y-%m").to_string())
+}
 
-    if !screenshots_dir.exists() {
+// Move screenshots from `source` (default: where the OS saves them) into `dest`
+// (default: a Screenshots folder inside `source`), optionally into YYYY-MM subfolders.
+// With `dry_run` the moves are only printed.
+pub fn organize_screenshots(source: Option<&Path>, dest: Option<&Path>, by_date: bool, dry_run: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let source_dir = match source {
+        Some(path) => path.to_path_buf(),
+        None => default_screenshot_dir().ok_or("Default screenshot directory not found; pass --source")?,
+    };
+    // Windows already saves into a Screenshots folder, so leave them there unless sorting by date
+    let screenshots_dir = match dest {
+        Some(path) => path.to_path_buf(),
+        None if source_dir.file_name().is_some_and(|name| name == "Screenshots") => source_dir.clone(),
+        None => source_dir.join("Screenshots"),
+    };
+    let mode = if dry_run { " (dry run)" } else { "" };
+    println!("{}", format!("Organizing screenshots in '{}'{}...", source_dir.display(), mode).cyan());
+
+    if !screenshots_dir.exists() && dry_run {
+        println!("Would create directory: {}", screenshots_dir.display().to_string().yellow());
+    } else if !screenshots_dir.exists() {
         println!("Creating directory: {}", screenshots_dir.display().to_string().yellow());
         fs::create_dir_all(&screenshots_dir).map_err(|e| format!("Error creating Screenshots directory '{}': {}", screenshots_dir.display(), e))?;
         println!("Directory created: {}", screenshots_dir.display().to_string().green());
@@ -124,18 +467,35 @@ pub fn organize_screenshots() -> Result<(), Box<dyn std::error::Error + Send + S
 
     let mut moved_count = 0;
     let mut error_count = 0;
-    println!("Scanning Desktop: {}", desktop_dir.display().to_string().cyan());
+    println!("Scanning: {}", source_dir.display().to_string().cyan());
 
-    for entry_result in fs::read_dir(&desktop_dir)? {
+    for entry_result in fs::read_dir(&source_dir)? {
         match entry_result {
             Ok(entry) => {
                 let path = entry.path();
                 if path.is_file() {
                     if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
-                        if (filename.starts_with("Screen Shot ") || filename.starts_with("Screenshot ")) && filename.ends_with(".png") {
-                            let destination = screenshots_dir.join(filename);
-                            println!("  Moving '{}' -> {}", filename.dimmed(), screenshots_dir.file_name().unwrap_or_default().to_string_lossy().blue());
-                            match fs::rename(&path, &destination) {
+                        if is_screenshot_name(filename) {
+                            let target_dir = match by_date.then(|| screenshot_month(filename, &path)).flatten() {
+                                Some(month) => screenshots_dir.join(month),
+                                None => screenshots_dir.clone(),
+                            };
+                            let destination = target_dir.join(filename);
+                            if destination == path {
+                                continue;
+                            }
+                            if destination.exists() {
+                                eprintln!("{}", format!("    Skipping '{}': already exists in {}", filename, target_dir.display()).yellow());
+                                error_count += 1;
+                                continue;
+                            }
+                            println!("  {} '{}' -> {}", if dry_run { "Would move" } else { "Moving" }, filename.dimmed(),
+                                target_dir.file_name().unwrap_or_default().to_string_lossy().blue());
+                            if dry_run {
+                                moved_count += 1;
+                                continue;
+                            }
+                            match fs::create_dir_all(&target_dir).and_then(|_| fs::rename(&path, &destination)) {
                                 Ok(_) => moved_count += 1,
                                 Err(e) => {
                                     eprintln!("{}", format!("    Error moving '{}': {}", filename, e).red());
@@ -147,22 +507,309 @@ pub fn organize_screenshots() -> Result<(), Box<dyn std::error::Error + Send + S
                 }
             }
             Err(e) => {
-                eprintln!("{}", format!("Error reading Desktop entry: {}", e).red());
+                eprintln!("{}", format!("Error reading entry in '{}': {}", source_dir.display(), e).red());
                 error_count += 1;
             }
         }
     }
 
-    if moved_count > 0 { println!("{}", format!("Successfully moved {} screenshot(s).", moved_count).green()); }
+    if moved_count > 0 && dry_run {
+        println!("{}", format!("Would move {} screenshot(s). Run again without --dry-run to move them.", moved_count).yellow());
+    } else if moved_count > 0 {
+        println!("{}", format!("Successfully moved {} screenshot(s).", moved_count).green());
+    }
     if error_count > 0 { println!("{}", format!("Encountered {} error(s).", error_count).yellow()); }
-    if moved_count == 0 && error_count == 0 { println!("{}", "No new screenshots found on the Desktop to move.".dimmed()); }
+    if moved_count == 0 && error_count == 0 { println!("{}", "No new screenshots found to move.".dimmed()); }
     Ok(())
 }
 
-#[cfg(not(target_os = "macos"))]
-pub fn organize_screenshots() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    eprintln!("{}", "Organize screenshots is only supported on macOS.".yellow().bold());
-    Err("Feature not supported on this OS".into())
+// Photo formats kamadak-exif can read EXIF from
+const PHOTO_EXTENSIONS: &[&str] = &["jpg", "jpeg", "tif", "tiff", "heic", "heif", "png", "webp"];
+// Name given to each photo by `photo-sort`, e.g. 20240309_142210.jpg
+const PHOTO_NAME_FORMAT: &str = "%Y%m%d_%H%M%S";
+
+// EXIF stores dates as "YYYY:MM:DD HH:MM:SS"
+fn parse_exif_datetime(text: &str) -> Option<chrono::NaiveDateTime> {
+    chrono::NaiveDateTime::parse_from_str(text.trim_end_matches('\0').trim(), "%Y:%m:%d %H:%M:%S").ok()
+}
+
+// When a photo was taken: EXIF DateTimeOriginal, else the file's modified time
+fn photo_timestamp(path: &Path) -> io::Result<(chrono::NaiveDateTime, bool)> {
+    let from_exif = fs::File::open(path).ok().and_then(|file| {
+        let exif = exif::Reader::new().read_from_container(&mut io::BufReader::new(file)).ok()?;
+        let field = exif.get_field(exif::Tag::DateTimeOriginal, exif::In::PRIMARY)?;
+        match &field.value {
+            exif::Value::Ascii(values) => parse_exif_datetime(&String::from_utf8_lossy(values.first()?)),
+            _ => None,
+        }
+    });
+    if let Some(taken) = from_exif {
+        return Ok((taken, true));
+    }
+    let modified = fs::metadata(path)?.modified()?;
+    Ok((chrono::DateTime::<chrono::Local>::from(modified).naive_local(), false))
+}
+
+// First free "<stem>.ext" in `dir`, then "<stem>_1.ext", "<stem>_2.ext", ... for photos taken in the same second.
+// `current` is the photo being renamed, which may already have the right name.
+fn unique_photo_destination(dir: &Path, stem: &str, ext: &str, current: &Path, planned: &HashSet<PathBuf>) -> PathBuf {
+    std::iter::once(format!("{}.{}", stem, ext))
+        .chain((1..).map(|n| format!("{}_{}.{}", stem, n, ext)))
+        .map(|name| dir.join(name))
+        .find(|candidate| candidate == current || (!candidate.exists() && !planned.contains(candidate)))
+        .unwrap()
+}
+
+// Rename the photos directly inside `dir` to their capture time (YYYYMMDD_HHMMSS.ext) and/or
+// move them into YYYY-MM folders. Photos without EXIF fall back to their modified time.
+pub fn sort_photos(dir: &Path, rename: bool, folders: bool, dry_run: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if !dir.is_dir() {
+        return Err(format!("Path is not a directory: {}", dir.display()).into());
+    }
+    if !rename && !folders {
+        return Err("Nothing to do: keep the names only when filing into dated folders".into());
+    }
+    let mode = if dry_run { " (dry run)" } else { "" };
+    println!("{}", format!("Sorting photos in '{}'{}...", dir.display(), mode).cyan());
+
+    let mut photos: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| is_loose_file(path))
+        .filter(|path| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|ext| PHOTO_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+        })
+        .collect();
+    photos.sort();
+
+    let mut planned = HashSet::new();
+    let mut moved_count = 0;
+    let mut mtime_count = 0;
+    let mut error_count = 0;
+    for path in photos {
+        let filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let (taken, from_exif) = match photo_timestamp(&path) {
+            Ok(timestamp) => timestamp,
+            Err(e) => {
+                eprintln!("{}", format!("  Error reading '{}': {}", filename, e).red());
+                error_count += 1;
+                continue;
+            }
+        };
+        if !from_exif {
+            mtime_count += 1;
+        }
+        let target_dir = if folders { dir.join(taken.format("%Y-%m").to_string()) } else { dir.to_path_buf() };
+        let destination = if rename {
+            let ext = path.extension().unwrap_or_default().to_string_lossy().to_ascii_lowercase();
+            unique_photo_destination(&target_dir, &taken.format(PHOTO_NAME_FORMAT).to_string(), &ext, &path, &planned)
+        } else {
+            unique_destination(&target_dir, &filename, &planned)
+        };
+        if destination == path {
+            continue;
+        }
+        let shown = destination.strip_prefix(dir).unwrap_or(&destination).display().to_string();
+        let source_note = if from_exif { "" } else { " (no EXIF date, used modified time)" };
+        println!("  {} -> {}{}", filename.dimmed(), shown.blue(), source_note.dimmed());
+        if !dry_run {
+            if let Err(e) = fs::create_dir_all(&target_dir).and_then(|_| fs::rename(&path, &destination)) {
+                eprintln!("{}", format!("  Error moving '{}': {}", filename, e).red());
+                error_count += 1;
+                continue;
+            }
+        }
+        planned.insert(destination);
+        moved_count += 1;
+    }
+
+    if moved_count == 0 && error_count == 0 {
+        println!("{}", "No photos to sort.".dimmed());
+    } else if dry_run {
+        println!("{}", format!("Would move {} photo(s). Run again without --dry-run to move them.", moved_count).yellow());
+    } else {
+        println!("{}", format!("Successfully sorted {} photo(s).", moved_count).green());
+    }
+    if mtime_count > 0 { println!("{}", format!("{} photo(s) had no EXIF capture date; their modified time was used.", mtime_count).dimmed()); }
+    if error_count > 0 { println!("{}", format!("Encountered {} error(s).", error_count).yellow()); }
+    Ok(())
+}
+
+const DOCUMENT_EXTENSIONS: &[&str] = &["pdf", "doc", "docx", "odt", "rtf", "txt", "md", "xls", "xlsx", "ods", "csv", "ppt", "pptx", "odp", "epub"];
+const ARCHIVE_EXTENSIONS: &[&str] = &["zip", "rar", "7z", "tar", "gz", "tgz", "bz2", "xz", "zst", "iso", "dmg"];
+
+// Folder a file goes into for `organize --by type`
+fn type_folder(path: &Path) -> &'static str {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_ascii_lowercase();
+    if DOCUMENT_EXTENSIONS.contains(&ext.as_str()) {
+        return "Documents";
+    }
+    if ARCHIVE_EXTENSIONS.contains(&ext.as_str()) {
+        return "Archives";
+    }
+    match mime_guess::from_path(path).first_raw() {
+        Some(mime) if mime.starts_with("image/") => "Images",
+        Some(mime) if mime.starts_with("video/") => "Videos",
+        Some(mime) if mime.starts_with("audio/") => "Audio",
+        _ => "Other",
+    }
+}
+
+// Subfolder a file goes into under the chosen grouping
+fn organize_folder(path: &Path, by: OrganizeBy) -> io::Result<String> {
+    Ok(match by {
+        OrganizeBy::Type => type_folder(path).to_string(),
+        OrganizeBy::Date => {
+            let modified = fs::metadata(path)?.modified()?;
+            chrono::DateTime::<chrono::Local>::from(modified).format("%Y-%m").to_string()
+        }
+        OrganizeBy::Extension => match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) => ext.to_ascii_lowercase(),
+            None => "no-extension".to_string(),
+        },
+    })
+}
+
+// First free path for `filename` in `dir`: "name.ext", then "name (1).ext", "name (2).ext", ...
+// `planned` holds destinations already claimed by earlier moves (which a dry run never makes)
+fn unique_destination(dir: &Path, filename: &str, planned: &HashSet<PathBuf>) -> PathBuf {
+    let candidate = dir.join(filename);
+    if !candidate.exists() && !planned.contains(&candidate) {
+        return candidate;
+    }
+    let name = Path::new(filename);
+    let stem = name.file_stem().and_then(|s| s.to_str()).unwrap_or(filename);
+    let ext = name.extension().and_then(|e| e.to_str()).map(|e| format!(".{}", e)).unwrap_or_default();
+    (1..)
+        .map(|n| dir.join(format!("{} ({}){}", stem, n, ext)))
+        .find(|candidate| !candidate.exists() && !planned.contains(candidate))
+        .unwrap()
+}
+
+// A regular, non-hidden file
+fn is_loose_file(path: &Path) -> bool {
+    path.is_file() && !path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with('.'))
+}
+
+// Work out where `path` goes under `dir` and, unless `dry_run`, move it there.
+// Returns the subfolder name and the (collision-free) destination.
+fn organize_file(dir: &Path, path: &Path, by: OrganizeBy, dry_run: bool, planned: &HashSet<PathBuf>) -> io::Result<(String, PathBuf)> {
+    let folder = organize_folder(path, by)?;
+    let target_dir = dir.join(&folder);
+    let filename = path.file_name().unwrap_or_default().to_string_lossy();
+    let destination = unique_destination(&target_dir, &filename, planned);
+    if !dry_run {
+        fs::create_dir_all(&target_dir)?;
+        fs::rename(path, &destination)?;
+    }
+    Ok((folder, destination))
+}
+
+// Sort the loose files directly inside `dir` into subfolders by type, date or extension
+pub fn organize_directory(dir: &Path, by: OrganizeBy, dry_run: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if !dir.is_dir() {
+        return Err(format!("Path is not a directory: {}", dir.display()).into());
+    }
+    let mode = if dry_run { " (dry run)" } else { "" };
+    println!("{}", format!("Organizing '{}' by {}{}...", dir.display(), format!("{:?}", by).to_lowercase(), mode).cyan());
+
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| is_loose_file(path))
+        .collect();
+    files.sort();
+
+    let mut planned = HashSet::new();
+    let mut moved_count = 0;
+    let mut error_count = 0;
+    for path in files {
+        let filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        match organize_file(dir, &path, by, dry_run, &planned) {
+            Ok((folder, destination)) => {
+                let new_name = destination.file_name().unwrap_or_default().to_string_lossy();
+                println!("  {} -> {}/{}", filename.dimmed(), folder.blue(), new_name);
+                planned.insert(destination);
+                moved_count += 1;
+            }
+            Err(e) => {
+                eprintln!("{}", format!("  Error moving '{}': {}", filename, e).red());
+                error_count += 1;
+            }
+        }
+    }
+
+    if moved_count == 0 && error_count == 0 {
+        println!("{}", "No loose files to organize.".dimmed());
+    } else if dry_run {
+        println!("{}", format!("Would move {} file(s). Run again without --dry-run to move them.", moved_count).yellow());
+    } else {
+        println!("{}", format!("Successfully moved {} file(s).", moved_count).green());
+    }
+    if error_count > 0 { println!("{}", format!("Encountered {} error(s).", error_count).yellow()); }
+    Ok(())
+}
+
+// Extensions browsers and download tools use while a file is still being written
+const IN_PROGRESS_EXTENSIONS: &[&str] = &["part", "crdownload", "download", "partial", "tmp", "ytdl"];
+
+// Watch `dir` and organize each new file once it has had no events for `settle`,
+// so downloads still being written aren't moved. Runs until Ctrl-C.
+// With `dry_run` each file's destination is printed but nothing is moved.
+pub fn watch_directory(dir: &Path, by: OrganizeBy, settle: Duration, dry_run: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if !dir.is_dir() {
+        return Err(format!("Path is not a directory: {}", dir.display()).into());
+    }
+    // Event paths come back absolute on some platforms, so compare against the canonical path
+    let dir = &dir.canonicalize()?;
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    println!("{}", format!("Watching '{}', organizing new files by {}{} (Ctrl+C to stop)...",
+        dir.display(), format!("{:?}", by).to_lowercase(), if dry_run { " (dry run)" } else { "" }).cyan());
+
+    // Last time each path saw an event; a path is organized once it has been quiet for `settle`
+    let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+    let mut organized_count = 0;
+    loop {
+        match rx.recv_timeout(Duration::from_millis(500)) {
+            Ok(Ok(event)) => {
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    for path in event.paths.into_iter().filter(|p| p.parent() == Some(dir)) {
+                        pending.insert(path, Instant::now());
+                    }
+                }
+            }
+            Ok(Err(e)) => eprintln!("{}", format!("Watch error: {}", e).red()),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return Err("File watcher stopped unexpectedly".into()),
+        }
+
+        let settled: Vec<PathBuf> = pending.iter()
+            .filter(|(_, last_event)| last_event.elapsed() >= settle)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in settled {
+            pending.remove(&path);
+            let in_progress = path.extension().and_then(|e| e.to_str())
+                .is_some_and(|ext| IN_PROGRESS_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()));
+            if in_progress || !is_loose_file(&path) {
+                continue;
+            }
+            let filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            match organize_file(dir, &path, by, dry_run, &HashSet::new()) {
+                Ok((folder, destination)) => {
+                    organized_count += 1;
+                    let new_name = destination.file_name().unwrap_or_default().to_string_lossy();
+                    println!("  {} -> {}/{} {}", filename.dimmed(), folder.blue(), new_name,
+                        format!("({} organized)", organized_count).dimmed());
+                }
+                Err(e) => eprintln!("{}", format!("  Error moving '{}': {}", filename, e).red()),
+            }
+        }
+    }
 }
 
 // Helper to filter out directory walk errors we can ignore
@@ -180,8 +827,69 @@ fn is_permission_error(entry: &Result<DirEntry, walkdir::Error>) -> bool {
 }
 
 // Function for disk analysis
-pub fn analyze_disk(path_to_analyze: &Path, top: usize) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+pub fn analyze_disk(path_to_analyze: &Path, top: usize, by_extension: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     println!("{}", format!("Analyzing disk usage for '{}', showing top {}...", path_to_analyze.display(), top).cyan());
+    let analysis = analyze_disk_result(path_to_analyze, top)?;
+    let mut lines = Vec::new();
+
+    if by_extension {
+        lines.push(format!("\n{}:", format!("Top {} File Types by Size", analysis.by_extension.len()).magenta().bold()));
+        if analysis.by_extension.is_empty() {
+            lines.push("No files found in the specified path.".dimmed().to_string());
+        }
+        for usage in &analysis.by_extension {
+            lines.push(format!("  {:<12} {:>10} ({} file{})", usage.extension.cyan(), format_size(usage.total_size, DECIMAL).green(),
+                usage.file_count, if usage.file_count == 1 { "" } else { "s" }));
+        }
+        if analysis.error_count > 0 { lines.push(format!("\n{}", format!("Encountered {} error(s) reading file metadata.", analysis.error_count).yellow())); }
+        crate::utils::maybe_page(&lines.join("\n"));
+        return Ok(());
+    }
+
+    lines.push(format!("\n{}:", format!("Top {} Largest Files Found", analysis.largest_files.len()).magenta().bold()));
+    if analysis.largest_files.is_empty() && analysis.error_count == 0 {
+        lines.push("No files found in the specified path.".dimmed().to_string());
+    } else {
+        for file in &analysis.largest_files {
+            lines.push(format!("  {} - {}", format_size(file.size, DECIMAL).green(), file.path.display()));
+        }
+    }
+
+    if analysis.error_count > 0 { lines.push(format!("\n{}", format!("Encountered {} error(s) reading file metadata.", analysis.error_count).yellow())); }
+
+    lines.push(format!("\n{}: Directory size analysis is not yet implemented.", "Note".yellow()));
+    crate::utils::maybe_page(&lines.join("\n"));
+    Ok(())
+}
+
+/// A file and its size in bytes
+#[derive(Debug, Clone, Serialize)]
+pub struct FileSize {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Space used by one file extension
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtensionUsage {
+    pub extension: String, // ".mp4", or "(none)" for files without one
+    pub total_size: u64,
+    pub file_count: u32,
+}
+
+/// Result of `analyze_disk_result`
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskAnalysis {
+    pub path: PathBuf,
+    pub total_size: u64,
+    pub file_count: usize,
+    pub error_count: usize,
+    pub largest_files: Vec<FileSize>,        // Top N, largest first
+    pub by_extension: Vec<ExtensionUsage>,   // Top N, largest first
+}
+
+// Walk `path_to_analyze` and collect the largest files and per-extension totals
+pub fn analyze_disk_result(path_to_analyze: &Path, top: usize) -> Result<DiskAnalysis, Box<dyn std::error::Error + Send + Sync>> {
     let mut files: Vec<(u64, PathBuf)> = Vec::new();
     let mut error_count = 0;
 
@@ -203,21 +911,42 @@ pub fn analyze_disk(path_to_analyze: &Path, top: usize) -> Result<(), Box<dyn st
         }
     }
 
+    let mut by_extension = extension_breakdown(&files);
+    by_extension.truncate(top);
+    let total_size = files.iter().map(|(size, _)| size).sum();
+    let file_count = files.len();
+
     files.sort_by(|a, b| b.0.cmp(&a.0));
+    let largest_files = files.into_iter().take(top).map(|(size, path)| FileSize { path, size }).collect();
+
+    Ok(DiskAnalysis {
+        path: path_to_analyze.to_path_buf(),
+        total_size,
+        file_count,
+        error_count,
+        largest_files,
+        by_extension,
+    })
+}
 
-    println!("\n{}:", format!("Top {} Largest Files Found", std::cmp::min(top, files.len())).magenta().bold());
-    if files.is_empty() && error_count == 0 {
-        println!("{}", "No files found in the specified path.".dimmed());
-    } else {
-        for (size, path) in files.iter().take(top) {
-            println!("  {} - {}", format_size(*size, DECIMAL).green(), path.display());
-        }
+// Total size and file count per lower-cased extension, largest first;
+// files without an extension are grouped under "(none)"
+fn extension_breakdown(files: &[(u64, PathBuf)]) -> Vec<ExtensionUsage> {
+    let mut by_ext: HashMap<String, (u64, u32)> = HashMap::new();
+    for (size, path) in files {
+        let ext = path.extension()
+            .map(|e| format!(".{}", e.to_string_lossy().to_lowercase()))
+            .unwrap_or_else(|| "(none)".to_string());
+        let entry = by_ext.entry(ext).or_insert((0, 0));
+        entry.0 += size;
+        entry.1 += 1;
     }
 
-    if error_count > 0 { println!("\n{}", format!("Encountered {} error(s) reading file metadata.", error_count).yellow()); }
-
-    println!("\n{}: Directory size analysis is not yet implemented.", "Note".yellow());
-    Ok(())
+    let mut categories: Vec<ExtensionUsage> = by_ext.into_iter()
+        .map(|(extension, (total_size, file_count))| ExtensionUsage { extension, total_size, file_count })
+        .collect();
+    categories.sort_by(|a, b| b.total_size.cmp(&a.total_size).then_with(|| a.extension.cmp(&b.extension)));
+    categories
 }
 
 // Helper function to calculate directory size
@@ -310,72 +1039,106 @@ pub fn clean_system(dry_run: bool) -> Result<(), Box<dyn std::error::Error + Sen
 // Batch Rename Files
 pub fn rename_files(args: &RenameArgs) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let mode = if args.dry_run { "(Dry Run)".yellow() } else { "".normal() };
+    let scope = if args.recursive { " (recursive)".cyan() } else { "".normal() };
     println!(
-        "{} Batch Renaming in '{}' {}...",
+        "{} Batch Renaming in '{}'{} {}...",
         "Running:".cyan(),
         args.directory.display(),
+        scope,
         mode
     );
     println!("Pattern: '{}'", args.pattern.dimmed());
     println!("Replacement: '{}'", args.replacement.dimmed());
 
+    if !args.directory.is_dir() {
+        return Err(format!("'{}' is not a valid directory.", args.directory.display()).into());
+    }
+
     let re = Regex::new(&args.pattern).map_err(|e| format!("Invalid Regex Pattern: {}", e))?;
     let mut rename_count = 0;
     let mut error_count = 0;
     let mut skipped_count = 0;
 
-    for entry_result in fs::read_dir(&args.directory)? {
-        match entry_result {
-            Ok(entry) => {
-                let path = entry.path();
-                if path.is_file() {
-                    if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
-                        if re.is_match(filename) {
-                            let new_filename = re.replace_all(filename, &args.replacement[..]).to_string();
-                            if new_filename != filename {
-                                let new_path = args.directory.join(&new_filename);
-                                println!("  Rename '{}' -> '{}'", filename.dimmed(), new_filename.green());
-                                if !args.dry_run {
-                                    if new_path.exists() {
-                                        eprintln!("    {}: '{}' already exists. Skipping.", "Warning".yellow(), new_filename);
-                                        skipped_count += 1;
-                                        continue;
-                                    }
-                                    match fs::rename(&path, &new_path) {
-                                        Ok(_) => rename_count += 1,
-                                        Err(e) => {
-                                            eprintln!("    {}: {}", "Error renaming".red(), e);
-                                            error_count += 1;
-                                        }
-                                    }
-                                } else {
-                                    if new_path.exists() {
-                                        println!("    {}: '{}' already exists (potential conflict).", "Warning".yellow(), new_filename);
-                                        skipped_count += 1;
-                                    } else {
-                                        rename_count += 1;
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+    // Contents-first ordering means a directory is only visited after everything
+    // inside it, so renaming it never invalidates a path we still have to process.
+    let max_depth = if args.recursive { usize::MAX } else { 1 };
+    let walker = WalkDir::new(&args.directory)
+        .min_depth(1)
+        .max_depth(max_depth)
+        .contents_first(true)
+        .sort_by_file_name();
+
+    for entry_result in walker {
+        let entry = match entry_result {
+            Ok(entry) => entry,
             Err(e) => {
                 eprintln!("{}: {}", "Error reading directory entry".red(), e);
                 error_count += 1;
+                continue;
+            }
+        };
+
+        let is_dir = entry.file_type().is_dir();
+        if is_dir && !args.include_dirs {
+            continue;
+        }
+        if !is_dir && !entry.file_type().is_file() {
+            continue;
+        }
+
+        let path = entry.path();
+        let filename = match path.file_name().and_then(|n| n.to_str()) {
+            Some(name) => name,
+            None => continue,
+        };
+        if !re.is_match(filename) {
+            continue;
+        }
+        let new_filename = re.replace_all(filename, &args.replacement[..]).to_string();
+        if new_filename == filename {
+            continue;
+        }
+
+        let new_path = path.with_file_name(&new_filename);
+        let relative_old = path.strip_prefix(&args.directory).unwrap_or(path);
+        let relative_new = new_path.strip_prefix(&args.directory).unwrap_or(&new_path);
+        println!(
+            "  Rename '{}' -> '{}'",
+            relative_old.display().to_string().dimmed(),
+            relative_new.display().to_string().green()
+        );
+
+        if !args.dry_run {
+            if new_path.exists() {
+                eprintln!("    {}: '{}' already exists. Skipping.", "Warning".yellow(), relative_new.display());
+                skipped_count += 1;
+                continue;
+            }
+            match fs::rename(path, &new_path) {
+                Ok(_) => rename_count += 1,
+                Err(e) => {
+                    eprintln!("    {}: {}", "Error renaming".red(), e);
+                    error_count += 1;
+                }
+            }
+        } else {
+            if new_path.exists() {
+                println!("    {}: '{}' already exists (potential conflict).", "Warning".yellow(), relative_new.display());
+                skipped_count += 1;
+            } else {
+                rename_count += 1;
             }
         }
     }
 
     println!("{}", "-".repeat(40).dimmed());
     if args.dry_run {
-        println!("{} file(s) would be renamed.", rename_count.to_string().green());
+        println!("{} item(s) would be renamed.", rename_count.to_string().green());
     } else {
-        println!("{} file(s) successfully renamed.", rename_count.to_string().green());
+        println!("{} item(s) successfully renamed.", rename_count.to_string().green());
     }
     if skipped_count > 0 {
-        println!("{} file(s) skipped (target existed).", skipped_count.to_string().yellow());
+        println!("{} item(s) skipped (target existed).", skipped_count.to_string().yellow());
     }
     if error_count > 0 {
         println!("{} error(s) occurred.", error_count.to_string().yellow());
@@ -388,7 +1151,7 @@ pub fn rename_files(args: &RenameArgs) -> Result<(), Box<dyn std::error::Error +
 }
 
 // Helper to parse human-readable size string (e.g., "1k", "10M", "2G")
-fn parse_size(size_str: &str) -> Result<u64, String> {
+pub fn parse_size(size_str: &str) -> Result<u64, String> {
     let size_str = size_str.trim().to_lowercase();
     let num_part = size_str.trim_end_matches(|c: char| !c.is_ascii_digit() && c != '.');
     let unit_part = size_str.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
@@ -411,27 +1174,400 @@ fn parse_size(size_str: &str) -> Result<u64, String> {
     Ok((num * multiplier).round() as u64)
 }
 
-// Helper to calculate SHA256 hash of a file
-fn hash_file(path: &Path) -> io::Result<Digest> {
-    let file = fs::File::open(path)?;
-    let mut reader = io::BufReader::new(file);
-    let mut context = Context::new(&SHA256);
-    let mut buffer = [0; 8192];
+/// Hash algorithms supported for checksums, manifests and duplicate detection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum HashAlgorithm {
+    Sha256,
+    Md5,
+    /// Much faster than SHA-256 on large files; fine for spotting identical content
+    Blake3,
+}
+
+impl HashAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "SHA-256",
+            HashAlgorithm::Md5 => "MD5",
+            HashAlgorithm::Blake3 => "BLAKE3",
+        }
+    }
+}
+
+/// Read size used when hashing unless a caller picks another
+pub const DEFAULT_HASH_BUFFER: usize = 64 * 1024;
+
+/// How `hash_file_with` reads a file
+#[derive(Clone)]
+pub struct HashOptions {
+    pub algorithm: HashAlgorithm,
+    pub buffer_size: usize,
+    /// Bytes of the file hashed so far
+    pub progress: Option<ProgressCallback>,
+    /// Stops hashing with an `Interrupted` error between reads
+    pub cancel: Option<CancellationToken>,
+}
+
+impl HashOptions {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        HashOptions { algorithm, buffer_size: DEFAULT_HASH_BUFFER, progress: None, cancel: None }
+    }
+}
+
+// Stream a file through the given hash algorithm and return the lowercase hex digest
+pub fn hash_file_hex(path: &Path, algorithm: HashAlgorithm) -> io::Result<String> {
+    hash_file_with(path, &HashOptions::new(algorithm))
+}
+
+// Like hash_file_hex, with the buffer size, progress reporting and cancellation from `options`
+pub fn hash_file_with(path: &Path, options: &HashOptions) -> io::Result<String> {
+    match options.algorithm {
+        HashAlgorithm::Sha256 => {
+            let mut context = Context::new(&SHA256);
+            stream_file(path, options, |chunk| context.update(chunk))?;
+            Ok(HEXLOWER.encode(context.finish().as_ref()))
+        }
+        HashAlgorithm::Md5 => {
+            let mut hasher = Md5::new();
+            stream_file(path, options, |chunk| hasher.update(chunk))?;
+            Ok(HEXLOWER.encode(hasher.finalize().as_slice()))
+        }
+        HashAlgorithm::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            stream_file(path, options, |chunk| { hasher.update(chunk); })?;
+            Ok(hasher.finalize().to_hex().to_string())
+        }
+    }
+}
+
+// Feed a file to `update` one buffer at a time, so even multi-GB files use constant memory
+fn stream_file(path: &Path, options: &HashOptions, mut update: impl FnMut(&[u8])) -> io::Result<()> {
+    let mut file = fs::File::open(path)?;
+    let total = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+    let mut buffer = vec![0; options.buffer_size.max(1)];
+    let mut done = 0;
 
     loop {
-        let count = reader.read(&mut buffer)?;
-        if count == 0 {
-            break;
+        if options.cancel.as_ref().is_some_and(|cancel| cancel.is_cancelled()) {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "Hashing cancelled"));
+        }
+        let count = match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(count) => count,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        update(&buffer[..count]);
+        done += count as u64;
+        if let Some(progress) = &options.progress {
+            progress(Progress { done, total });
+        }
+    }
+    Ok(())
+}
+
+// Every regular file under `root`, as sorted '/'-separated relative paths
+fn collect_relative_files(root: &Path) -> Vec<String> {
+    let mut files: Vec<String> = WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| !is_permission_error(&Ok(e.clone())))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.path().strip_prefix(root).ok().map(|rel| {
+            rel.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+        }))
+        .collect();
+    files.sort();
+    files
+}
+
+// One `sha256sum`-style line; like coreutils, names containing '\\' or a newline
+// are escaped and the line is prefixed with '\\'
+fn format_manifest_line(hash: &str, rel_path: &str) -> String {
+    if rel_path.contains('\\') || rel_path.contains('\n') {
+        format!("\\{}  {}", hash, rel_path.replace('\\', "\\\\").replace('\n', "\\n"))
+    } else {
+        format!("{}  {}", hash, rel_path)
+    }
+}
+
+// Parses a line written by `format_manifest_line` (or by sha256sum/md5sum, which
+// may mark binary mode with '*' in place of the second space)
+fn parse_manifest_line(line: &str) -> Option<(String, String)> {
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let (hash, rest) = line.split_once(' ')?;
+    let path = rest.strip_prefix(' ').or_else(|| rest.strip_prefix('*'))?;
+    if hash.is_empty() || path.is_empty() || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let path = if escaped { unescape_manifest_path(path) } else { path.to_string() };
+    Some((hash.to_lowercase(), path))
+}
+
+// Undo format_manifest_line's escaping ("\\\\" -> '\\', "\\n" -> newline)
+fn unescape_manifest_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => { out.push('\n'); chars.next(); }
+            ('\\', Some('\\')) => { out.push('\\'); chars.next(); }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// Comment line naming the algorithm a manifest was written with; sha256sum/md5sum skip
+// lines starting with '#', so the manifest stays checkable with coreutils
+const MANIFEST_ALGORITHM_PREFIX: &str = "# algorithm: ";
+
+// The algorithm a manifest records in its header. Manifests without one (written by
+// coreutils) are SHA-256, or MD5 when the digests are 32 hex digits long.
+fn manifest_algorithm(content: &str, entries: &[(String, String)]) -> Result<HashAlgorithm, String> {
+    let header = content.lines().find_map(|line| line.trim().strip_prefix(MANIFEST_ALGORITHM_PREFIX));
+    match header.map(str::trim) {
+        Some(name) => [HashAlgorithm::Sha256, HashAlgorithm::Md5, HashAlgorithm::Blake3]
+            .into_iter()
+            .find(|algorithm| algorithm.as_str().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("Unknown manifest algorithm '{}'", name)),
+        None if entries.first().is_some_and(|(hash, _)| hash.len() == 32) => Ok(HashAlgorithm::Md5),
+        None => Ok(HashAlgorithm::Sha256),
+    }
+}
+
+// Where `output` would sit inside `root`, as a manifest-style relative path
+fn manifest_relative_path(root: &Path, output: &Path) -> Option<String> {
+    let dir = output.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let full = fs::canonicalize(dir).ok()?.join(output.file_name()?);
+    let rel = full.strip_prefix(fs::canonicalize(root).ok()?).ok()?;
+    Some(rel.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/"))
+}
+
+/// Differences between a checksum manifest and the files on disk
+#[derive(Debug, Default, PartialEq)]
+pub struct ManifestDiff {
+    pub changed: Vec<String>,
+    pub missing: Vec<String>,
+    pub added: Vec<String>,
+}
+
+// Write a sha256sum/md5sum-compatible manifest of every file under `root`
+pub fn write_hash_manifest(root: &Path, algorithm: HashAlgorithm, output: Option<&Path>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if !root.is_dir() {
+        return Err(format!("Path is not a directory: {}", root.display()).into());
+    }
+    // Don't hash the manifest we are about to (re)write
+    let manifest_rel = output.and_then(|o| manifest_relative_path(root, o));
+
+    let files = collect_relative_files(root);
+    info!("{}", format!("Hashing {} file(s) with {}...", files.len(), algorithm.as_str()).cyan());
+
+    let mut lines = Vec::with_capacity(files.len());
+    let mut error_count = 0;
+    for rel in files.iter().filter(|rel| Some(rel.as_str()) != manifest_rel.as_deref()) {
+        match hash_file_hex(&root.join(rel), algorithm) {
+            Ok(hash) => lines.push(format_manifest_line(&hash, rel)),
+            Err(e) => {
+                eprintln!("{}: {} - {}", "Error hashing".red(), rel, e);
+                error_count += 1;
+            }
+        }
+    }
+
+    let mut manifest = format!("{}{}\n", MANIFEST_ALGORITHM_PREFIX, algorithm.as_str());
+    manifest.extend(lines.iter().map(|line| format!("{}\n", line)));
+    match output {
+        Some(path) => {
+            fs::write(path, manifest)?;
+            println!("{} {} ({} file(s))", "Manifest written to".green(), path.display(), lines.len());
         }
-        context.update(&buffer[..count]);
+        None => print!("{}", manifest),
+    }
+    if error_count > 0 {
+        return Err(format!("{} file(s) could not be hashed", error_count).into());
+    }
+    Ok(())
+}
+
+// Compare parsed manifest entries against the files currently under `root`
+fn diff_manifest(root: &Path, entries: &[(String, String)], algorithm: HashAlgorithm, manifest_path: &Path) -> ManifestDiff {
+    let mut diff = ManifestDiff::default();
+    let listed: HashSet<&str> = entries.iter().map(|(_, path)| path.as_str()).collect();
+
+    for (expected, rel) in entries {
+        match hash_file_hex(&root.join(rel), algorithm) {
+            Ok(actual) if actual == *expected => {}
+            Ok(_) => diff.changed.push(rel.clone()),
+            Err(_) => diff.missing.push(rel.clone()),
+        }
+    }
+
+    let manifest_canonical = fs::canonicalize(manifest_path).ok();
+    for rel in collect_relative_files(root) {
+        if listed.contains(rel.as_str()) {
+            continue;
+        }
+        if manifest_canonical.is_some() && fs::canonicalize(root.join(&rel)).ok() == manifest_canonical {
+            continue;
+        }
+        diff.added.push(rel);
+    }
+    diff
+}
+
+// Re-hash the files under `root` and report what changed since `manifest` was written
+pub fn verify_hash_manifest(root: &Path, manifest: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let content = fs::read_to_string(manifest)
+        .map_err(|e| format!("Failed to read manifest {}: {}", manifest.display(), e))?;
+    let entries: Vec<(String, String)> = content.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(parse_manifest_line)
+        .collect();
+    if entries.is_empty() {
+        return Err(format!("No checksum lines found in {}", manifest.display()).into());
     }
 
-    Ok(context.finish())
+    let algorithm = manifest_algorithm(&content, &entries)?;
+
+    info!("{}", format!("Verifying {} file(s) against {} with {}...", entries.len(), manifest.display(), algorithm.as_str()).cyan());
+    let diff = diff_manifest(root, &entries, algorithm, manifest);
+
+    for rel in &diff.changed { println!("{} {}", "CHANGED".red().bold(), rel); }
+    for rel in &diff.missing { println!("{} {}", "MISSING".red(), rel); }
+    for rel in &diff.added { println!("{}   {}", "ADDED".yellow(), rel); }
+
+    let ok = entries.len() - diff.changed.len() - diff.missing.len();
+    println!("\n{} {} OK, {} changed, {} missing, {} added",
+        "Summary:".bold(), ok.to_string().green(), diff.changed.len(), diff.missing.len(), diff.added.len());
+
+    if !diff.changed.is_empty() || !diff.missing.is_empty() {
+        return Err(format!("Verification failed: {} changed, {} missing", diff.changed.len(), diff.missing.len()).into());
+    }
+    Ok(())
+}
+
+/// Empty directories and dangling symlinks found under a path
+#[derive(Debug, Default)]
+pub struct EmptyScan {
+    pub empty_dirs: Vec<PathBuf>,
+    pub dangling_links: Vec<PathBuf>,
+    pub error_count: u32,
+    /// Entries the walk couldn't read; their parents are never reported as empty
+    pub skipped: u32,
+}
+
+// Walks bottom-up (children before their parent), so a directory holding only
+// empty directories or dangling links counts as empty too. With `delete` each
+// hit is removed as soon as it is found. The root itself is never reported.
+fn scan_empty(root: &Path, delete: bool) -> EmptyScan {
+    let mut scan = EmptyScan::default();
+    let mut removable: HashSet<PathBuf> = HashSet::new();
+
+    let walker = WalkDir::new(root)
+        .min_depth(1)
+        .contents_first(true);
+
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                // is_permission_error prints what was skipped
+                is_permission_error(&Err(e));
+                scan.skipped += 1;
+                continue;
+            }
+        };
+        let path = entry.path();
+        let found = if entry.path_is_symlink() {
+            // metadata() follows the link, so NotFound means the target is gone; any other
+            // error (unreadable target, link loop) leaves the link alone
+            match fs::metadata(path) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                _ => continue,
+            }
+            scan.dangling_links.push(path.to_path_buf());
+            true
+        } else if entry.file_type().is_dir() {
+            let is_empty = fs::read_dir(path)
+                .map(|children| children.flatten().all(|child| removable.contains(&child.path())))
+                .unwrap_or(false);
+            if !is_empty { continue; }
+            scan.empty_dirs.push(path.to_path_buf());
+            true
+        } else {
+            false
+        };
+        if !found { continue; }
+
+        removable.insert(path.to_path_buf());
+        if delete {
+            let result = if entry.file_type().is_dir() { fs::remove_dir(path) } else { fs::remove_file(path) };
+            if let Err(e) = result {
+                eprintln!("{}: {} - {}", "Failed to remove".red(), path.display(), e);
+                scan.error_count += 1;
+            }
+        }
+    }
+    scan
+}
+
+// Find (and optionally delete) empty directories and dangling symlinks
+pub fn clean_empty(path: &Path, delete: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if !path.is_dir() {
+        return Err(format!("Path is not a directory: {}", path.display()).into());
+    }
+    let mode = if delete { "removing" } else { "listing" };
+    println!("{}", format!("Scanning '{}' for empty directories and dangling symlinks ({})...", path.display(), mode).cyan());
+
+    let scan = scan_empty(path, delete);
+
+    println!("\n{} ({}):", "Empty directories".magenta().bold(), scan.empty_dirs.len());
+    for dir in &scan.empty_dirs {
+        println!("  {}", dir.display());
+    }
+    println!("\n{} ({}):", "Dangling symlinks".magenta().bold(), scan.dangling_links.len());
+    for link in &scan.dangling_links {
+        println!("  {}", link.display());
+    }
+
+    let total = scan.empty_dirs.len() + scan.dangling_links.len();
+    if total == 0 {
+        println!("\n{}", "Nothing to clean.".green());
+    } else if delete {
+        println!("\n{}", format!("Removed {} item(s).", total.saturating_sub(scan.error_count as usize)).green());
+    } else {
+        println!("\n{}", "Run again with --delete to remove them.".yellow());
+    }
+    if scan.error_count > 0 { println!("{}", format!("Encountered {} error(s) while removing.", scan.error_count).yellow()); }
+    if scan.skipped > 0 { println!("{}", format!("Skipped {} unreadable item(s); the folders holding them were left alone.", scan.skipped).yellow()); }
+    Ok(())
+}
+
+/// One group of files with identical content
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateSet {
+    pub hash: String, // Upper-case hex digest from the algorithm the scan used
+    pub size: u64,
+    pub paths: Vec<PathBuf>,
+}
+
+/// Result of `find_duplicates_result`
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateScan {
+    pub min_size: u64,
+    pub potential_duplicates: usize, // Files sharing their size with another file
+    pub hashed_files: usize,
+    pub error_count: usize,
+    pub sets: Vec<DuplicateSet>,
 }
 
 // Find Duplicate Files
-pub fn find_duplicates(path_to_search: &Path, min_size_str: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+pub fn find_duplicates(path_to_search: &Path, min_size_str: &str, algorithm: HashAlgorithm, buffer_size_str: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let min_size = parse_size(min_size_str).map_err(|e| Box::<dyn std::error::Error + Send + Sync>::from(format!("Invalid minimum size: {}", e)))?;
+    let buffer_size = parse_size(buffer_size_str).map_err(|e| Box::<dyn std::error::Error + Send + Sync>::from(format!("Invalid buffer size: {}", e)))?;
     println!(
         "{} Scanning '{}' for duplicate files larger than {}...",
         "🔍".cyan(),
@@ -439,13 +1575,50 @@ pub fn find_duplicates(path_to_search: &Path, min_size_str: &str) -> Result<(),
         format_size(min_size, DECIMAL).yellow()
     );
 
+    let pb = crate::utils::bytes_progress_bar();
+    let options = HashOptions {
+        buffer_size: buffer_size.clamp(1, 64 * 1024 * 1024) as usize,
+        progress: Some(crate::utils::progress_bar_callback(&pb)),
+        ..HashOptions::new(algorithm)
+    };
+    let scan = find_duplicates_result(path_to_search, min_size, &options);
+    pb.finish_and_clear();
+    let scan = scan?;
+    let mut lines = vec![
+        format!("Found {} potential duplicate file(s) based on size.", scan.potential_duplicates.to_string().yellow()),
+        format!("Hashed {} file(s).", scan.hashed_files.to_string().dimmed()),
+        "-".repeat(40).dimmed().to_string(),
+    ];
+    if scan.sets.is_empty() {
+        lines.push("No duplicate files found.".green().to_string());
+    } else {
+        lines.push(format!("Found {} set(s) of duplicate files:", scan.sets.len().to_string().yellow()));
+        for (i, set) in scan.sets.iter().enumerate() {
+            lines.push(format!("\n{}. Set ({} files):", format!("{}", i + 1).magenta(), set.paths.len()));
+            for path in &set.paths {
+                lines.push(format!("  - {}", path.display()));
+            }
+        }
+    }
+    if scan.error_count > 0 {
+        lines.push(format!("\nEncountered {} error(s) during process.", scan.error_count.to_string().yellow()));
+    }
+
+    crate::utils::maybe_page(&lines.join("\n"));
+    Ok(())
+}
+
+// Group files of at least `min_size` bytes by size, then hash only the size collisions.
+// `hash.progress` receives bytes hashed across all of those files; cancelling `hash.cancel`
+// stops the scan with an error.
+pub fn find_duplicates_result(path_to_search: &Path, min_size: u64, hash: &HashOptions) -> Result<DuplicateScan, Box<dyn std::error::Error + Send + Sync>> {
     let mut files_by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
-    let mut hash_map: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    let mut hash_map: HashMap<String, (u64, Vec<PathBuf>)> = HashMap::new();
     let mut error_count = 0;
-    let mut potential_dup_files = 0;
+    let mut potential_duplicates = 0;
     let mut hashed_files = 0;
 
-    println!("{}", "Phase 1: Grouping files by size...".dimmed());
+    info!("{}", "Phase 1: Grouping files by size...".dimmed());
     let walker = WalkDir::new(path_to_search)
         .into_iter()
         .filter_entry(|e| !is_permission_error(&Ok(e.clone())))
@@ -471,21 +1644,32 @@ pub fn find_duplicates(path_to_search: &Path, min_size_str: &str) -> Result<(),
 
     for paths in files_by_size.values() {
         if paths.len() > 1 {
-            potential_dup_files += paths.len();
+            potential_duplicates += paths.len();
         }
     }
-    println!("Found {} potential duplicate file(s) based on size.", potential_dup_files.to_string().yellow());
 
-    println!("{}", "Phase 2: Hashing potential duplicates...".dimmed());
-    for (_, paths) in files_by_size.into_iter() {
+    info!("{}", format!("Phase 2: Hashing potential duplicates with {}...", hash.algorithm.as_str()).dimmed());
+    let total_bytes: u64 = files_by_size.iter().filter(|(_, paths)| paths.len() > 1).map(|(size, paths)| size * paths.len() as u64).sum();
+    let mut hashed_bytes = 0;
+    for (size, paths) in files_by_size.into_iter() {
         if paths.len() > 1 {
             for path in paths {
                 hashed_files += 1;
-                match hash_file(&path) {
+                // Report this file's progress on top of the bytes already hashed
+                let file_options = HashOptions {
+                    progress: hash.progress.clone().map(|overall| -> ProgressCallback {
+                        let base = hashed_bytes;
+                        std::sync::Arc::new(move |p: Progress| overall(Progress { done: base + p.done, total: total_bytes }))
+                    }),
+                    ..hash.clone()
+                };
+                let result = hash_file_with(&path, &file_options);
+                hashed_bytes += size;
+                match result {
                     Ok(digest) => {
-                        let hash_string = HEXUPPER.encode(digest.as_ref());
-                        hash_map.entry(hash_string).or_default().push(path);
+                        hash_map.entry(digest.to_uppercase()).or_insert_with(|| (size, Vec::new())).1.push(path);
                     }
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => return Err(e.into()),
                     Err(e) => {
                         eprintln!("{}: {} - {}", "Error hashing file".red(), path.display(), e);
                         error_count += 1;
@@ -494,33 +1678,21 @@ pub fn find_duplicates(path_to_search: &Path, min_size_str: &str) -> Result<(),
             }
         }
     }
-    println!("Hashed {} file(s).", hashed_files.to_string().dimmed());
 
-    let duplicate_sets: Vec<Vec<PathBuf>> = hash_map
-        .into_values()
-        .filter(|paths| paths.len() > 1)
+    let sets = hash_map
+        .into_iter()
+        .filter(|(_, (_, paths))| paths.len() > 1)
+        .map(|(hash, (size, paths))| DuplicateSet { hash, size, paths })
         .collect();
 
-    println!("{}", "-".repeat(40).dimmed());
-    if duplicate_sets.is_empty() {
-        println!("{}", "No duplicate files found.".green());
-    } else {
-        println!("Found {} set(s) of duplicate files:", duplicate_sets.len().to_string().yellow());
-        for (i, set) in duplicate_sets.iter().enumerate() {
-            println!("\n{}. Set ({} files):", format!("{}", i + 1).magenta(), set.len());
-            for path in set {
-                println!("  - {}", path.display());
-            }
-        }
-    }
-    if error_count > 0 {
-        println!("\nEncountered {} error(s) during process.", error_count.to_string().yellow());
-    }
-
-    Ok(())
+    Ok(DuplicateScan { min_size, potential_duplicates, hashed_files, error_count, sets })
 }
 
 // Sync Folders (One-Way)
+//
+// Symbolic links follow `args.symlinks`: preserve (the default) recreates each link at the
+// destination, follow copies what it points to, and skip leaves it out. Either way the
+// --delete pass leaves alone whatever sits at a source link's path.
 pub fn sync_folders(args: &SyncArgs) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let mode = if args.dry_run { "(Dry Run)".yellow() } else { "".normal() };
     let delete_mode = if args.delete { " (with delete)".yellow() } else { "".normal() };
@@ -552,10 +1724,13 @@ pub fn sync_folders(args: &SyncArgs) -> Result<(), Box<dyn std::error::Error + S
     let mut updated_count = 0;
     let mut deleted_count = 0;
     let mut error_count = 0;
-    let mut src_relative_paths: HashMap<PathBuf, fs::Metadata> = HashMap::new();
+    let mut src_relative_paths: HashSet<PathBuf> = HashSet::new();
+    // Preserved or skipped source links; the delete pass doesn't look inside these paths
+    let mut src_links: HashSet<PathBuf> = HashSet::new();
 
-    println!("{}", "Phase 1: Scanning source & updating destination...".dimmed());
-    for entry_result in WalkDir::new(&args.source).into_iter().filter_map(|e| e.ok()) {
+    info!("{}", "Phase 1: Scanning source & updating destination...".dimmed());
+    let walker = WalkDir::new(&args.source).follow_links(args.symlinks == SymlinkPolicy::Follow);
+    for entry_result in walker.into_iter().filter_map(|e| e.ok()) {
         let src_path = entry_result.path();
         let relative_path = match src_path.strip_prefix(&args.source) {
              Ok(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
@@ -563,11 +1738,40 @@ pub fn sync_folders(args: &SyncArgs) -> Result<(), Box<dyn std::error::Error + S
         };
 
         let dest_path = args.destination.join(&relative_path);
+        src_relative_paths.insert(relative_path.clone());
+
+        if entry_result.path_is_symlink() && args.symlinks != SymlinkPolicy::Follow {
+            src_links.insert(relative_path.clone());
+            if args.symlinks == SymlinkPolicy::Preserve {
+                match sync_symlink(src_path, &dest_path, args.dry_run) {
+                    Ok(LinkSync::Created) => copied_count += 1,
+                    Ok(LinkSync::Updated) => updated_count += 1,
+                    Ok(LinkSync::Unchanged) => {}
+                    Err(e) => {
+                        eprintln!("    {}: {}: {}", "Error syncing link".red(), dest_path.display(), e);
+                        error_count += 1;
+                    }
+                }
+            } else {
+                info!("  Skipping link: {}", src_path.display());
+            }
+            continue;
+        }
+
+        // A link in the destination where the source has a real file would be written through
+        if is_symlink(&dest_path) {
+            println!("  Replacing link: {}", dest_path.display().to_string().yellow());
+            if !args.dry_run {
+                if let Err(e) = remove_symlink(&dest_path) {
+                    eprintln!("    {}: {}", "Error removing link".red(), e);
+                    error_count += 1;
+                    continue;
+                }
+            }
+        }
 
         match fs::metadata(src_path) {
             Ok(src_meta) => {
-                src_relative_paths.insert(relative_path.clone(), src_meta.clone());
-
                 if src_meta.is_dir() {
                     if !args.dry_run && !dest_path.exists() {
                         println!("  Creating directory: {}", dest_path.display().to_string().cyan());
@@ -635,7 +1839,7 @@ pub fn sync_folders(args: &SyncArgs) -> Result<(), Box<dyn std::error::Error + S
     }
 
     if args.delete {
-         println!("{}", "\nPhase 2: Scanning destination for extra items...".dimmed());
+         info!("{}", "\nPhase 2: Scanning destination for extra items...".dimmed());
          for entry_result in WalkDir::new(&args.destination).contents_first(true).into_iter().filter_map(|e| e.ok()) {
              let dest_path = entry_result.path();
              let relative_path = match dest_path.strip_prefix(&args.destination) {
@@ -643,10 +1847,21 @@ pub fn sync_folders(args: &SyncArgs) -> Result<(), Box<dyn std::error::Error + S
                  _ => continue,
              };
 
-             if !src_relative_paths.contains_key(&relative_path) {
+             let under_link = relative_path.ancestors().any(|ancestor| src_links.contains(ancestor));
+             if !src_relative_paths.contains(&relative_path) && !under_link {
                  println!("  Deleting extra item: {}", dest_path.display().to_string().red());
                  if !args.dry_run {
-                     match fs::metadata(dest_path) {
+                     // Look at links themselves so only the link is removed, never its target
+                     match fs::symlink_metadata(dest_path) {
+                         Ok(meta) if meta.file_type().is_symlink() => {
+                             match remove_symlink(dest_path) {
+                                 Ok(()) => deleted_count += 1,
+                                 Err(e) => {
+                                     eprintln!("    {}: Could not delete link '{}': {}", "Error".red(), dest_path.display(), e);
+                                     error_count += 1;
+                                 }
+                             }
+                         }
                          Ok(meta) => {
                              if meta.is_dir() {
                                  if let Err(e) = fs::remove_dir(dest_path) {
@@ -696,6 +1911,77 @@ pub fn sync_folders(args: &SyncArgs) -> Result<(), Box<dyn std::error::Error + S
     Ok(())
 }
 
+// What sync_symlink did to the destination
+#[derive(Debug, PartialEq, Eq)]
+enum LinkSync {
+    Created,
+    Updated,
+    Unchanged,
+}
+
+// Recreate the source link at `dest` unless an identical link is already there
+fn sync_symlink(src: &Path, dest: &Path, dry_run: bool) -> io::Result<LinkSync> {
+    let target = fs::read_link(src)?;
+    let outcome = match fs::symlink_metadata(dest) {
+        Ok(meta) if meta.file_type().is_symlink() => {
+            if fs::read_link(dest)? == target {
+                return Ok(LinkSync::Unchanged);
+            }
+            LinkSync::Updated
+        }
+        Ok(meta) if meta.is_dir() => {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, "a directory is in the way of the link"));
+        }
+        Ok(_) => LinkSync::Updated,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => LinkSync::Created,
+        Err(e) => return Err(e),
+    };
+
+    if outcome == LinkSync::Created {
+        println!("  Copying new link: {} -> {}", dest.display().to_string().green(), target.display());
+    } else {
+        println!("  Updating link: {} -> {}", dest.display().to_string().yellow(), target.display());
+    }
+    if dry_run {
+        return Ok(outcome);
+    }
+    if outcome == LinkSync::Updated {
+        remove_symlink(dest)?;
+    } else if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    create_symlink(&target, dest, src.is_dir())?;
+    Ok(outcome)
+}
+
+fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path).map(|meta| meta.file_type().is_symlink()).unwrap_or(false)
+}
+
+// Remove a link (or a plain file standing where a link goes) without touching any target.
+// Windows removes links to directories with remove_dir.
+fn remove_symlink(path: &Path) -> io::Result<()> {
+    #[cfg(windows)]
+    if is_symlink(path) && path.is_dir() {
+        return fs::remove_dir(path);
+    }
+    fs::remove_file(path)
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, link: &Path, _target_is_dir: bool) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn create_symlink(target: &Path, link: &Path, target_is_dir: bool) -> io::Result<()> {
+    if target_is_dir {
+        std::os::windows::fs::symlink_dir(target, link)
+    } else {
+        std::os::windows::fs::symlink_file(target, link)
+    }
+}
+
 // Search Files by Name
 pub fn search_files(path_to_search: &Path, query: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     println!(
@@ -735,8 +2021,8 @@ pub fn search_files(path_to_search: &Path, query: &str) -> Result<(), Box<dyn st
     Ok(())
 }
 
-// --- New Function for UI: Get Directory Listing ---
-pub fn get_directory_listing(path: &Path) -> Result<Vec<FileInfo>, io::Error> {
+// --- Directory Listing (shared by the console listing and the UI) ---
+pub fn get_directory_listing(path: &Path) -> io::Result<Vec<FileInfo>> {
     if !path.is_dir() {
         return Err(io::Error::new(io::ErrorKind::NotADirectory, format!("Path '{}' is not a directory", path.display())));
     }
@@ -759,9 +2045,13 @@ pub fn get_directory_listing(path: &Path) -> Result<Vec<FileInfo>, io::Error> {
         let entry_path = entry.path();
         let file_name_os = entry.file_name();
         let file_name = file_name_os.to_string_lossy().to_string();
+        // Symlinks are reported as such rather than followed, so tree views can't loop
+        let is_symlink = entry.file_type().map(|t| t.is_symlink()).unwrap_or(false);
         match fs::metadata(&entry_path) {
             Ok(metadata) => {
-                let (file_type_str, size_bytes, size_human) = if metadata.is_dir() {
+                let (file_type_str, size_bytes, size_human) = if is_symlink {
+                    ("Link/Other".to_string(), None, "-".to_string())
+                } else if metadata.is_dir() {
                     ("Dir".to_string(), None, "-".to_string())
                 } else if metadata.is_file() {
                     let bytes = metadata.len();
@@ -776,6 +2066,7 @@ pub fn get_directory_listing(path: &Path) -> Result<Vec<FileInfo>, io::Error> {
                     file_type: file_type_str,
                     size_bytes,
                     size_human,
+                    modified: metadata.modified().ok(),
                 });
             }
             Err(e) => {
@@ -787,9 +2078,279 @@ pub fn get_directory_listing(path: &Path) -> Result<Vec<FileInfo>, io::Error> {
                     file_type: "Error".to_string(),
                     size_bytes: None,
                     size_human: "-".to_string(),
+                    modified: None,
                 });
             }
         }
     }
     Ok(entries_info)
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_incremental_backup_links_unchanged_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("src");
+        let backups = dir.path().join("backups");
+        fs::create_dir_all(source.join("sub")).unwrap();
+        fs::write(source.join("same.txt"), b"unchanged").unwrap();
+        fs::write(source.join("sub/edit.txt"), b"v1").unwrap();
+
+        incremental_backup(&source, &backups, false).unwrap();
+        let first = latest_snapshot(&backups).unwrap();
+        // Pretend the first snapshot was taken earlier so the second gets a new name
+        let renamed = backups.join("2000-01-01_000000");
+        fs::rename(&first, &renamed).unwrap();
+
+        fs::write(source.join("sub/edit.txt"), b"v2, longer").unwrap();
+        incremental_backup(&source, &backups, true).unwrap();
+        let second = latest_snapshot(&backups).unwrap();
+        assert_ne!(second, renamed);
+        assert_eq!(fs::read(second.join("sub/edit.txt")).unwrap(), b"v2, longer");
+        assert_eq!(fs::read(renamed.join("sub/edit.txt")).unwrap(), b"v1");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let linked = fs::metadata(second.join("same.txt")).unwrap();
+            assert_eq!(linked.ino(), fs::metadata(renamed.join("same.txt")).unwrap().ino());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_then_remove_keeps_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("src");
+        fs::create_dir_all(source.join("real")).unwrap();
+        fs::write(source.join("real/file.txt"), b"data").unwrap();
+        std::os::unix::fs::symlink("real", source.join("dir_link")).unwrap();
+        std::os::unix::fs::symlink("real/file.txt", source.join("file_link")).unwrap();
+        std::os::unix::fs::symlink("missing", source.join("dangling")).unwrap();
+
+        let target = dir.path().join("moved");
+        assert_eq!(copy_then_remove(&source, &target).unwrap(), 4);
+        assert!(!source.exists());
+        assert_eq!(fs::read(target.join("real/file.txt")).unwrap(), b"data");
+        assert_eq!(fs::read_link(target.join("dir_link")).unwrap(), PathBuf::from("real"));
+        assert_eq!(fs::read_link(target.join("file_link")).unwrap(), PathBuf::from("real/file.txt"));
+        assert_eq!(fs::read_link(target.join("dangling")).unwrap(), PathBuf::from("missing"));
+    }
+
+    #[test]
+    fn test_plan_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("src");
+        fs::create_dir_all(source.join("sub")).unwrap();
+        fs::write(source.join("a.txt"), b"abc").unwrap();
+        fs::write(source.join("sub/b.txt"), b"de").unwrap();
+
+        let fresh = dir.path().join("fresh");
+        let plan = plan_backup(&source, &fresh, false, false, false).unwrap();
+        assert_eq!(plan, vec![
+            PlannedBackup { from: source.join("a.txt"), to: fresh.join("a.txt"), action: "copy", bytes: 3 },
+            PlannedBackup { from: source.join("sub/b.txt"), to: fresh.join("sub/b.txt"), action: "copy", bytes: 2 },
+        ]);
+        // An existing destination receives the source folder itself
+        let plan = plan_backup(&source, dir.path(), true, false, false).unwrap();
+        assert_eq!(plan[0].to, dir.path().join("src/a.txt"));
+        assert_eq!(plan[0].action, "move");
+        assert!(!fresh.exists());
+
+        let backups = dir.path().join("backups");
+        incremental_backup(&source, &backups, false).unwrap();
+        fs::write(source.join("sub/b.txt"), b"changed").unwrap();
+        let actions: Vec<_> = plan_backup(&source, &backups, false, true, false).unwrap().into_iter().map(|item| item.action).collect();
+        assert_eq!(actions, ["link", "copy"]);
+        assert!(plan_backup(&dir.path().join("missing"), &backups, false, false, false).is_err());
+    }
+
+    #[test]
+    fn test_organize_helpers() {
+        assert_eq!(type_folder(Path::new("report.PDF")), "Documents");
+        assert_eq!(type_folder(Path::new("photo.jpg")), "Images");
+        assert_eq!(type_folder(Path::new("clip.mp4")), "Videos");
+        assert_eq!(type_folder(Path::new("backup.tar.gz")), "Archives");
+        assert_eq!(type_folder(Path::new("mystery")), "Other");
+        assert_eq!(organize_folder(Path::new("notes.TXT"), OrganizeBy::Extension).unwrap(), "txt");
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), b"x").unwrap();
+        let mut planned = HashSet::new();
+        let first = unique_destination(dir.path(), "a.txt", &planned);
+        assert_eq!(first, dir.path().join("a (1).txt"));
+        planned.insert(first);
+        assert_eq!(unique_destination(dir.path(), "a.txt", &planned), dir.path().join("a (2).txt"));
+        assert_eq!(unique_destination(dir.path(), "b", &planned), dir.path().join("b"));
+    }
+
+    #[test]
+    fn test_screenshot_names() {
+        assert!(is_screenshot_name("Screen Shot 2020-05-01 at 10.00.00.png"));
+        assert!(is_screenshot_name("Screenshot (12).png"));
+        assert!(is_screenshot_name("Screenshot from 2024-03-09 14-22-10.png"));
+        assert!(is_screenshot_name("Screenshot_20240309_142210.PNG"));
+        assert!(!is_screenshot_name("Screenshot notes.txt"));
+        assert!(!is_screenshot_name("holiday.png"));
+
+        let missing = Path::new("/nonexistent/file.png");
+        assert_eq!(screenshot_month("Screenshot from 2024-03-09 14-22-10.png", missing).as_deref(), Some("2024-03"));
+        assert_eq!(screenshot_month("Screenshot_20231130_080000.png", missing).as_deref(), Some("2023-11"));
+        assert_eq!(screenshot_month("Screenshot (12).png", missing), None);
+    }
+
+    #[test]
+    fn test_photo_names() {
+        let taken = parse_exif_datetime("2024:03:09 14:22:10\0").unwrap();
+        assert_eq!(taken.format(PHOTO_NAME_FORMAT).to_string(), "20240309_142210");
+        assert_eq!(parse_exif_datetime("0000:00:00 00:00:00"), None);
+        assert_eq!(parse_exif_datetime("2024-03-09"), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let existing = dir.path().join("20240309_142210.jpg");
+        fs::write(&existing, b"x").unwrap();
+        let other = dir.path().join("IMG_0001.jpg");
+        let mut planned = HashSet::new();
+        assert_eq!(unique_photo_destination(dir.path(), "20240309_142210", "jpg", &existing, &planned), existing);
+        let first = unique_photo_destination(dir.path(), "20240309_142210", "jpg", &other, &planned);
+        assert_eq!(first, dir.path().join("20240309_142210_1.jpg"));
+        planned.insert(first);
+        assert_eq!(unique_photo_destination(dir.path(), "20240309_142210", "jpg", &other, &planned), dir.path().join("20240309_142210_2.jpg"));
+    }
+
+    #[test]
+    fn test_manifest_line_roundtrip() {
+        let hash = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+        assert_eq!(format_manifest_line(hash, "dir/a file.txt"), format!("{}  dir/a file.txt", hash));
+        assert_eq!(parse_manifest_line(&format_manifest_line(hash, "dir/a file.txt")), Some((hash.to_string(), "dir/a file.txt".to_string())));
+        assert_eq!(parse_manifest_line(&format_manifest_line(hash, "odd\\name")), Some((hash.to_string(), "odd\\name".to_string())));
+        assert_eq!(parse_manifest_line(&format_manifest_line(hash, "a\\nb\nc")), Some((hash.to_string(), "a\\nb\nc".to_string())));
+        assert_eq!(parse_manifest_line(&format!("{} *binary.bin", hash)), Some((hash.to_string(), "binary.bin".to_string())));
+        assert_eq!(parse_manifest_line("not a checksum line"), None);
+    }
+
+    #[test]
+    fn test_manifest_algorithm() {
+        let sha = vec![("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string(), "a".to_string())];
+        let md5 = vec![("d41d8cd98f00b204e9800998ecf8427e".to_string(), "a".to_string())];
+        assert_eq!(manifest_algorithm("# algorithm: BLAKE3\n", &sha), Ok(HashAlgorithm::Blake3));
+        assert_eq!(manifest_algorithm("# algorithm: md5\n", &md5), Ok(HashAlgorithm::Md5));
+        assert_eq!(manifest_algorithm("", &sha), Ok(HashAlgorithm::Sha256));
+        assert_eq!(manifest_algorithm("", &md5), Ok(HashAlgorithm::Md5));
+        assert!(manifest_algorithm("# algorithm: crc32\n", &sha).is_err());
+    }
+
+    #[test]
+    fn test_diff_manifest() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("sub")).unwrap();
+        fs::write(root.path().join("same.txt"), "same").unwrap();
+        fs::write(root.path().join("sub/changed.txt"), "before").unwrap();
+        fs::write(root.path().join("gone.txt"), "gone").unwrap();
+
+        let entries: Vec<(String, String)> = collect_relative_files(root.path()).into_iter()
+            .map(|rel| (hash_file_hex(&root.path().join(&rel), HashAlgorithm::Sha256).unwrap(), rel))
+            .collect();
+
+        fs::write(root.path().join("sub/changed.txt"), "after").unwrap();
+        fs::remove_file(root.path().join("gone.txt")).unwrap();
+        fs::write(root.path().join("new.txt"), "new").unwrap();
+
+        let diff = diff_manifest(root.path(), &entries, HashAlgorithm::Sha256, &root.path().join("manifest.txt"));
+        assert_eq!(diff, ManifestDiff {
+            changed: vec!["sub/changed.txt".to_string()],
+            missing: vec!["gone.txt".to_string()],
+            added: vec!["new.txt".to_string()],
+        });
+    }
+
+    #[test]
+    fn test_hash_file_with() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("abc.txt");
+        fs::write(&file, "abc").unwrap();
+
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let options = HashOptions {
+            buffer_size: 2,
+            progress: Some(std::sync::Arc::new(move |p: Progress| recorder.lock().unwrap().push(p.done))),
+            ..HashOptions::new(HashAlgorithm::Blake3)
+        };
+        assert_eq!(hash_file_with(&file, &options).unwrap(), "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85");
+        assert_eq!(*seen.lock().unwrap(), vec![2, 3]);
+        assert_eq!(hash_file_hex(&file, HashAlgorithm::Sha256).unwrap(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(hash_file_hex(&file, HashAlgorithm::Md5).unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let cancelled = HashOptions { cancel: Some(cancel), ..HashOptions::new(HashAlgorithm::Sha256) };
+        assert_eq!(hash_file_with(&file, &cancelled).unwrap_err().kind(), io::ErrorKind::Interrupted);
+    }
+
+    #[test]
+    fn test_scan_empty_is_bottom_up() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("outer/inner")).unwrap();
+        fs::create_dir_all(root.path().join("kept")).unwrap();
+        fs::write(root.path().join("kept/file.txt"), "data").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(root.path().join("missing"), root.path().join("outer/dangling")).unwrap();
+
+        let listed = scan_empty(root.path(), false);
+        assert_eq!(listed.empty_dirs, vec![root.path().join("outer/inner"), root.path().join("outer")]);
+        #[cfg(unix)]
+        assert_eq!(listed.dangling_links, vec![root.path().join("outer/dangling")]);
+        assert!(root.path().join("outer/inner").exists());
+
+        // A link whose target can't be resolved for another reason than absence is kept
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("loop", root.path().join("kept/loop")).unwrap();
+            assert_eq!(scan_empty(root.path(), false).dangling_links, vec![root.path().join("outer/dangling")]);
+        }
+
+        let removed = scan_empty(root.path(), true);
+        assert_eq!(removed.error_count, 0);
+        #[cfg(unix)]
+        assert!(is_symlink(&root.path().join("kept/loop")));
+        assert!(!root.path().join("outer").exists());
+        assert!(root.path().join("kept/file.txt").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sync_symlink_policies() {
+        let src = tempfile::tempdir().unwrap();
+        fs::create_dir_all(src.path().join("real")).unwrap();
+        fs::write(src.path().join("real/file.txt"), "data").unwrap();
+        std::os::unix::fs::symlink("real", src.path().join("link")).unwrap();
+
+        let sync = |dest: &Path, symlinks| {
+            let args = SyncArgs { source: src.path().to_path_buf(), destination: dest.to_path_buf(), dry_run: false, delete: true, symlinks };
+            sync_folders(&args).unwrap();
+        };
+
+        let preserved = tempfile::tempdir().unwrap();
+        sync(preserved.path(), SymlinkPolicy::Preserve);
+        assert_eq!(fs::read_link(preserved.path().join("link")).unwrap(), PathBuf::from("real"));
+        // A second run with --delete keeps the preserved link
+        sync(preserved.path(), SymlinkPolicy::Preserve);
+        assert!(is_symlink(&preserved.path().join("link")));
+
+        let followed = tempfile::tempdir().unwrap();
+        sync(followed.path(), SymlinkPolicy::Follow);
+        assert!(!is_symlink(&followed.path().join("link")));
+        assert_eq!(fs::read_to_string(followed.path().join("link/file.txt")).unwrap(), "data");
+
+        let skipped = tempfile::tempdir().unwrap();
+        fs::create_dir_all(skipped.path().join("link")).unwrap();
+        fs::write(skipped.path().join("link/own.txt"), "mine").unwrap();
+        sync(skipped.path(), SymlinkPolicy::Skip);
+        assert!(skipped.path().join("link/own.txt").exists());
+        assert!(skipped.path().join("real/file.txt").exists());
+    }
+}
@@ -1,28 +1,250 @@
 use colored::*;
-use reqwest::{Client, Method, header::{HeaderMap, HeaderName, HeaderValue}};
-use std::collections::HashMap;
+use log::info;
+use futures::{stream::FuturesUnordered, StreamExt};
+use reqwest::{Method, RequestBuilder, Url, header::{HeaderMap, HeaderName, HeaderValue}};
+use cookie_store::CookieStore;
+use reqwest_cookie_store::CookieStoreMutex;
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use serde_json;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use x509_parser::extensions::GeneralName;
+use x509_parser::prelude::{parse_x509_certificate, X509Certificate};
+use serde_json::{self, json, Value};
+use crate::utils;
 
-pub async fn make_request(
+// Reads a cookie jar written by save_cookie_jar; a missing file is an empty jar
+fn load_cookie_jar(path: &Path) -> Result<CookieStore, Box<dyn Error + Send + Sync>> {
+    if !path.exists() {
+        return Ok(CookieStore::default());
+    }
+    let reader = BufReader::new(File::open(path)?);
+    cookie_store::serde::json::load(reader).map_err(|e| format!("Failed to read cookie jar {}: {}", path.display(), e).into())
+}
+
+// Session cookies are kept too, so a login in one run is still valid in the next
+fn save_cookie_jar(path: &Path, store: &CookieStore) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    cookie_store::serde::json::save_incl_expired_and_nonpersistent(store, &mut writer)
+        .map_err(|e| format!("Failed to write cookie jar {}: {}", path.display(), e).into())
+}
+
+/// Credentials sent in the Authorization header
+#[derive(Debug, Clone)]
+pub enum HttpAuth {
+    Basic { user: String, password: Option<String> },
+    Bearer(String),
+}
+
+impl HttpAuth {
+    /// Parses `user:pass` (or just `user`) for HTTP Basic auth
+    pub fn basic(credentials: &str) -> Self {
+        match credentials.split_once(':') {
+            Some((user, password)) => HttpAuth::Basic { user: user.to_string(), password: Some(password.to_string()) },
+            None => HttpAuth::Basic { user: credentials.to_string(), password: None },
+        }
+    }
+}
+
+/// Extra behaviour for `make_request` beyond method, URL, body and headers
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    pub auth: Option<HttpAuth>,
+    pub cookies: Vec<(String, String)>,
+    pub cookie_jar: Option<PathBuf>, // Loaded before the request and saved after it
+    pub json_path: Option<String>, // jq-style path (e.g. .data.items[0].name) to print instead of the whole body
+    pub graphql: bool, // Print the `data` and `errors` of a GraphQL reply under their own headings
+    pub force_body: bool, // Send a body with GET or HEAD even though some servers reject that
+    pub print_curl: bool, // Print the request as a curl command before sending it
+    pub save_har: Option<PathBuf>, // Write the request and response to this file as a HAR log
+    pub no_redact: bool, // Keep Authorization and cookie values in the curl command and HAR file
+}
+
+/// One step of a `--jq` path
+#[derive(Debug, Clone, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+// Parses ".data.items[0].name" (leading dot optional) into its segments
+fn parse_json_path(path: &str) -> Result<Vec<PathSegment>, String> {
+    let mut segments = Vec::new();
+    let mut chars = path.trim().chars().peekable();
+    let mut key = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                if !key.is_empty() {
+                    segments.push(PathSegment::Key(std::mem::take(&mut key)));
+                }
+            }
+            '[' => {
+                if !key.is_empty() {
+                    segments.push(PathSegment::Key(std::mem::take(&mut key)));
+                }
+                let index: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                let index = index.trim().parse().map_err(|_| format!("Invalid array index '[{}]' in path '{}'", index, path))?;
+                segments.push(PathSegment::Index(index));
+            }
+            _ => key.push(c),
+        }
+    }
+    if !key.is_empty() {
+        segments.push(PathSegment::Key(key));
+    }
+    Ok(segments)
+}
+
+// Walks `value` along the path; None when a key or index is missing
+fn select_json_path<'a>(value: &'a Value, segments: &[PathSegment]) -> Option<&'a Value> {
+    segments.iter().try_fold(value, |current, segment| match segment {
+        PathSegment::Key(key) => current.get(key.as_str()),
+        PathSegment::Index(index) => current.get(*index),
+    })
+}
+
+// Pretty-prints JSON with jq-like colours: keys blue, strings green, numbers yellow
+fn colorize_json(value: &Value, indent: usize) -> String {
+    let pad = "  ".repeat(indent + 1);
+    let closing_pad = "  ".repeat(indent);
+    match value {
+        Value::Null => "null".dimmed().to_string(),
+        Value::Bool(b) => b.to_string().magenta().to_string(),
+        Value::Number(n) => n.to_string().yellow().to_string(),
+        Value::String(_) => value.to_string().green().to_string(),
+        Value::Array(items) if items.is_empty() => "[]".to_string(),
+        Value::Array(items) => {
+            let inner = items
+                .iter()
+                .map(|item| format!("{}{}", pad, colorize_json(item, indent + 1)))
+                .collect::<Vec<_>>()
+                .join(",\n");
+            format!("[\n{}\n{}]", inner, closing_pad)
+        }
+        Value::Object(map) if map.is_empty() => "{}".to_string(),
+        Value::Object(map) => {
+            let inner = map
+                .iter()
+                .map(|(key, item)| {
+                    format!("{}{}: {}", pad, Value::String(key.clone()).to_string().blue().bold(), colorize_json(item, indent + 1))
+                })
+                .collect::<Vec<_>>()
+                .join(",\n");
+            format!("{{\n{}\n{}}}", inner, closing_pad)
+        }
+    }
+}
+
+/// Wraps a GraphQL query and its optional JSON variables into the standard
+/// `{"query": .., "variables": ..}` request body
+pub fn graphql_body(query: &str, variables: Option<&str>) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let mut body = serde_json::Map::new();
+    body.insert("query".to_string(), Value::String(query.to_string()));
+    if let Some(text) = variables {
+        let value: Value = serde_json::from_str(text).map_err(|e| format!("Invalid GraphQL variables: {}", e))?;
+        if !value.is_object() {
+            return Err("GraphQL variables must be a JSON object".into());
+        }
+        body.insert("variables".to_string(), value);
+    }
+    Ok(Value::Object(body).to_string())
+}
+
+// Shows `data` and `errors` separately; replies without either are printed whole
+fn print_graphql_response(value: &Value) {
+    let data = value.get("data").filter(|data| !data.is_null());
+    let errors = value.get("errors").and_then(Value::as_array).filter(|errors| !errors.is_empty());
+    if data.is_none() && errors.is_none() {
+        println!("{}", colorize_json(value, 0));
+        return;
+    }
+    if let Some(data) = data {
+        println!("{}", "Data:".green().bold());
+        println!("{}", colorize_json(data, 0));
+    }
+    if let Some(errors) = errors {
+        println!("{} {}", "Errors:".red().bold(), errors.len());
+        for error in errors {
+            match error.get("message").and_then(Value::as_str) {
+                Some(message) => println!("  {} {}", "-".red(), message.red()),
+                None => println!("  {} {}", "-".red(), colorize_json(error, 1)),
+            }
+            if let Some(path) = error.get("path").and_then(Value::as_array) {
+                let path = path
+                    .iter()
+                    .map(|segment| segment.as_str().map(str::to_string).unwrap_or_else(|| segment.to_string()))
+                    .collect::<Vec<_>>()
+                    .join(".");
+                println!("    {} {}", "path:".dimmed(), path);
+            }
+        }
+    }
+}
+
+// One `key: value` per line; blank lines and lines starting with # are skipped
+fn parse_headers(text: &str) -> Result<Vec<(String, String)>, String> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(number, line)| {
+            line.split_once(':')
+                .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+                .filter(|(k, _)| !k.is_empty())
+                .ok_or_else(|| format!("line {}: expected 'key: value', got '{}'", number, line))
+        })
+        .collect()
+}
+
+/// Reads request headers from a file with one `key: value` per line
+pub fn read_headers_file(path: &Path) -> Result<Vec<(String, String)>, Box<dyn Error + Send + Sync>> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read headers file {}: {}", path.display(), e))?;
+    parse_headers(&text).map_err(|e| format!("Invalid headers file {}: {}", path.display(), e).into())
+}
+
+/// Reads a request body from a file
+pub fn read_body_file(path: &Path) -> Result<String, Box<dyn Error + Send + Sync>> {
+    std::fs::read_to_string(path).map_err(|e| format!("Failed to read body file {}: {}", path.display(), e).into())
+}
+
+// Builds the client and request shared by make_request and benchmark_request;
+// the cookie store is returned so it can be saved back to the jar afterwards
+fn prepare_request(
     method_str: &str,
     url: &str,
     body: Option<&str>,
     headers_map: &HashMap<String, String>,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
-    println!(
-        "{} {} {}",
-        "Running:".cyan(),
-        method_str.to_uppercase().yellow(),
-        url.cyan()
-    );
-
-    let client = Client::new();
+    options: &RequestOptions,
+) -> Result<(RequestBuilder, Arc<CookieStoreMutex>), Box<dyn Error + Send + Sync>> {
+    // Cookies set by responses (e.g. a login) are sent on redirects and, with a jar file, on later runs
+    let parsed_url = Url::parse(url).map_err(|e| format!("Invalid URL '{}': {}", url, e))?;
+    let mut store = match &options.cookie_jar {
+        Some(path) => load_cookie_jar(path)?,
+        None => CookieStore::default(),
+    };
+    for (name, value) in &options.cookies {
+        store
+            .parse(&format!("{}={}", name, value), &parsed_url)
+            .map_err(|e| format!("Invalid cookie '{}': {}", name, e))?;
+    }
+    let store = Arc::new(CookieStoreMutex::new(store));
+    let client = utils::http_client_builder().cookie_provider(store.clone()).build()?;
 
     // Parse method
     let method = Method::from_str(&method_str.to_uppercase())
         .map_err(|_| format!("Invalid HTTP method: {}", method_str))?;
+    check_body_allowed(&method, body.is_some(), options.force_body)?;
 
     // Build headers
     let mut headers = HeaderMap::new();
@@ -44,13 +266,233 @@ pub async fn make_request(
 
     // Build request
     let mut request_builder = client.request(method, url).headers(headers);
+    match &options.auth {
+        Some(HttpAuth::Basic { user, password }) => request_builder = request_builder.basic_auth(user, password.as_ref()),
+        Some(HttpAuth::Bearer(token)) => request_builder = request_builder.bearer_auth(token),
+        None => {}
+    }
     if let Some(body_content) = body {
         request_builder = request_builder.body(body_content.to_string());
+    }
+    Ok((request_builder, store))
+}
+
+// GET and HEAD bodies have no defined meaning and some servers and proxies reject them
+fn check_body_allowed(method: &Method, has_body: bool, force: bool) -> Result<(), String> {
+    if has_body && !force && (method == Method::GET || method == Method::HEAD) {
+        return Err(format!("{} requests don't carry a body; pass --force-body to send it anyway", method));
+    }
+    Ok(())
+}
+
+// Methods listed in an OPTIONS reply: `Allow`, or the CORS preflight header when that's all there is
+fn allowed_methods(headers: &HeaderMap) -> Vec<String> {
+    let header = headers.get(reqwest::header::ALLOW)
+        .or_else(|| headers.get(reqwest::header::ACCESS_CONTROL_ALLOW_METHODS));
+    header
+        .and_then(|value| value.to_str().ok())
+        .map(|value| {
+            value.split(',')
+                .map(|method| method.trim().to_uppercase())
+                .filter(|method| !method.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Headers that carry credentials, hidden in exported requests unless --no-redact
+fn is_credential_header(name: &str) -> bool {
+    name.eq_ignore_ascii_case("authorization") || name.eq_ignore_ascii_case("proxy-authorization")
+}
+
+// "Bearer abc123" -> "Bearer <redacted>"; the scheme stays so a reader knows what to fill in
+fn redact_credential(value: &str) -> String {
+    match value.split_once(' ') {
+        Some((scheme, _)) => format!("{} <redacted>", scheme),
+        None => "<redacted>".to_string(),
+    }
+}
+
+// Cookie names stay so a reader can see which session was used; values are hidden
+fn redact_cookies(cookies: &[(String, String)]) -> Vec<(String, String)> {
+    cookies.iter().map(|(name, _)| (name.clone(), "<redacted>".to_string())).collect()
+}
+
+// "a=1; b=2" -> "a=<redacted>; b=<redacted>". A Set-Cookie only carries one cookie, and
+// what follows it (Path, Expires, ...) are attributes, which stay
+fn redact_cookie_header(value: &str, set_cookie: bool) -> String {
+    value.split("; ")
+        .enumerate()
+        .map(|(i, part)| match part.split_once('=') {
+            Some((name, _)) if !set_cookie || i == 0 => format!("{}=<redacted>", name),
+            _ => part.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+// Name/value pairs of `headers`, with credentials and cookie values redacted when asked
+fn export_headers(headers: &HeaderMap, redact: bool) -> Vec<(String, String)> {
+    headers.iter()
+        .map(|(name, value)| {
+            let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+            let value = match name.as_str() {
+                _ if !redact => value,
+                name if is_credential_header(name) => redact_credential(&value),
+                "cookie" => redact_cookie_header(&value, false),
+                "set-cookie" => redact_cookie_header(&value, true),
+                _ => value,
+            };
+            (name.as_str().to_string(), value)
+        })
+        .collect()
+}
+
+fn request_body_text(request: &reqwest::Request) -> Option<String> {
+    request.body()
+        .and_then(reqwest::Body::as_bytes)
+        .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+}
+
+// The request as a curl command, one option per line
+fn curl_command(request: &reqwest::Request, cookies: &[(String, String)], redact: bool) -> String {
+    let body = request_body_text(request);
+    let mut parts = vec![format!("curl {}", utils::shell_quote(request.url().as_str()))];
+    // curl picks GET on its own, or POST when there's data
+    let implied = if body.is_some() { Method::POST } else { Method::GET };
+    if request.method() == Method::HEAD && body.is_none() {
+        parts.push("--head".to_string());
+    } else if *request.method() != implied {
+        parts.push(format!("-X {}", request.method()));
+    }
+    for (name, value) in export_headers(request.headers(), redact) {
+        parts.push(format!("-H {}", utils::shell_quote(&format!("{}: {}", name, value))));
+    }
+    if !cookies.is_empty() {
+        let cookies = if redact { redact_cookies(cookies) } else { cookies.to_vec() };
+        let cookies = cookies.iter().map(|(name, value)| format!("{}={}", name, value)).collect::<Vec<_>>().join("; ");
+        parts.push(format!("-b {}", utils::shell_quote(&cookies)));
+    }
+    if let Some(body) = body {
+        parts.push(format!("--data-raw {}", utils::shell_quote(&body)));
+    }
+    parts.join(" \\\n  ")
+}
+
+/// One request and its response, as recorded by `--save-har`
+struct HarExchange<'a> {
+    request: &'a reqwest::Request,
+    cookies: &'a [(String, String)],
+    started: chrono::DateTime<chrono::Local>,
+    elapsed: Duration,
+    status: reqwest::StatusCode,
+    version: reqwest::Version,
+    headers: &'a HeaderMap,
+    body: &'a str,
+}
+
+// A HAR 1.2 log holding the single exchange, as browser devtools import it
+fn har_document(exchange: &HarExchange<'_>, redact: bool) -> Value {
+    let pairs = |pairs: Vec<(String, String)>| pairs.into_iter()
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect::<Vec<_>>();
+    let header = |headers: &HeaderMap, name| headers.get(name).and_then(|value| value.to_str().ok()).unwrap_or("").to_string();
+    let request = exchange.request;
+    let http_version = format!("{:?}", exchange.version);
+    let elapsed_ms = exchange.elapsed.as_secs_f64() * 1000.0;
+
+    let mut har_request = json!({
+        "method": request.method().as_str(),
+        "url": request.url().as_str(),
+        "httpVersion": http_version,
+        "cookies": pairs(if redact { redact_cookies(exchange.cookies) } else { exchange.cookies.to_vec() }),
+        "headers": pairs(export_headers(request.headers(), redact)),
+        "queryString": pairs(request.url().query_pairs().into_owned().collect()),
+        "headersSize": -1,
+        "bodySize": 0,
+    });
+    if let Some(body) = request_body_text(request) {
+        har_request["bodySize"] = json!(body.len());
+        har_request["postData"] = json!({ "mimeType": header(request.headers(), reqwest::header::CONTENT_TYPE), "text": body });
+    }
+
+    json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+            "entries": [{
+                "startedDateTime": exchange.started.to_rfc3339(),
+                "time": elapsed_ms,
+                "request": har_request,
+                "response": {
+                    "status": exchange.status.as_u16(),
+                    "statusText": exchange.status.canonical_reason().unwrap_or(""),
+                    "httpVersion": http_version,
+                    "cookies": [],
+                    "headers": pairs(export_headers(exchange.headers, redact)),
+                    "content": {
+                        "size": exchange.body.len(),
+                        "mimeType": header(exchange.headers, reqwest::header::CONTENT_TYPE),
+                        "text": exchange.body,
+                    },
+                    "redirectURL": header(exchange.headers, reqwest::header::LOCATION),
+                    "headersSize": -1,
+                    "bodySize": exchange.body.len(),
+                },
+                "cache": {},
+                // Only the total is measured, so it all counts as waiting for the server
+                "timings": { "send": 0, "wait": elapsed_ms, "receive": 0 },
+            }],
+        }
+    })
+}
+
+fn save_cookies(options: &RequestOptions, store: &CookieStoreMutex) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if let Some(path) = &options.cookie_jar {
+        let store = store.lock().map_err(|_| "Cookie jar lock poisoned")?;
+        save_cookie_jar(path, &store)?;
+        info!("{} {}", "Cookies saved to".dimmed(), path.display());
+    }
+    Ok(())
+}
+
+pub async fn make_request(
+    method_str: &str,
+    url: &str,
+    body: Option<&str>,
+    headers_map: &HashMap<String, String>,
+    options: &RequestOptions,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    println!(
+        "{} {} {}",
+        "Running:".cyan(),
+        method_str.to_uppercase().yellow(),
+        url.cyan()
+    );
+
+    let (request_builder, store) = prepare_request(method_str, url, body, headers_map, options)?;
+    if let Some(body_content) = body {
         println!("Body: {}", body_content.dimmed());
     }
 
+    // A copy of what is about to be sent, for --print-curl and --save-har
+    let exported = if options.print_curl || options.save_har.is_some() {
+        let request = request_builder.try_clone().ok_or("The request can't be copied for export")?.build()?;
+        let cookies: Vec<(String, String)> = store.lock().map_err(|_| "Cookie jar lock poisoned")?
+            .get_request_values(request.url())
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        Some((request, cookies))
+    } else {
+        None
+    };
+    if let (true, Some((request, cookies))) = (options.print_curl, &exported) {
+        println!("{}\n{}", "curl:".magenta(), curl_command(request, cookies, !options.no_redact));
+    }
+
     // Send request and measure time
-    println!("{}", "Sending request...".dimmed());
+    info!("{}", "Sending request...".dimmed());
+    let started = chrono::Local::now();
     let start_time = std::time::Instant::now();
     let response = request_builder.send().await?;
     let duration = start_time.elapsed();
@@ -77,21 +519,66 @@ pub async fn make_request(
         println!("  {}: {}", name.as_str().cyan(), value.to_str()?.dimmed());
     }
 
+    if method_str.eq_ignore_ascii_case("OPTIONS") {
+        let methods = allowed_methods(response.headers());
+        if methods.is_empty() {
+            println!("{}", "No Allow header in the response.".yellow());
+        } else {
+            println!("{}", "Allowed methods:".magenta());
+            for method in methods {
+                println!("  {}", method.green());
+            }
+        }
+    }
+
+    // A HEAD reply has no body, only the status and headers above
+    let is_head = method_str.eq_ignore_ascii_case("HEAD");
+    let (version, response_headers) = (response.version(), response.headers().clone());
+    let response_body = if is_head { String::new() } else { response.text().await? };
+
+    if let (Some(path), Some((request, cookies))) = (&options.save_har, &exported) {
+        let exchange = HarExchange {
+            request,
+            cookies,
+            started,
+            elapsed: duration,
+            status,
+            version,
+            headers: &response_headers,
+            body: &response_body,
+        };
+        let har = serde_json::to_string_pretty(&har_document(&exchange, !options.no_redact))?;
+        std::fs::write(path, har).map_err(|e| format!("Failed to write HAR file {}: {}", path.display(), e))?;
+        println!("{} {}", "HAR saved to".green(), path.display());
+    }
+
+    save_cookies(options, &store)?;
+    if is_head {
+        return Ok(());
+    }
+
     // Print Response Body
     println!("{}", "Body:".magenta());
-    let response_body = response.text().await?;
     if response_body.is_empty() {
         println!("{}", "(Empty response body)".dimmed());
     } else {
         // Attempt to pretty-print if JSON, otherwise print plain text
-        match serde_json::from_str::<serde_json::Value>(&response_body) {
-            Ok(json_value) => {
-                match serde_json::to_string_pretty(&json_value) {
-                    Ok(pretty_json) => println!("{}", pretty_json.green()),
-                    Err(_) => println!("{}", response_body), // Fallback to plain text if pretty print fails
+        match serde_json::from_str::<Value>(&response_body) {
+            Ok(json_value) => match &options.json_path {
+                Some(path) => {
+                    let segments = parse_json_path(path)?;
+                    match select_json_path(&json_value, &segments) {
+                        Some(selected) => println!("{}", colorize_json(selected, 0)),
+                        None => println!("{} '{}' {}", "Path".yellow(), path, "does not exist in the response.".yellow()),
+                    }
                 }
-            }
+                None if options.graphql => print_graphql_response(&json_value),
+                None => println!("{}", colorize_json(&json_value, 0)),
+            },
             Err(_) => {
+                if options.json_path.is_some() {
+                    println!("{}", "Response is not JSON; --jq ignored.".yellow());
+                }
                 // Not JSON, print as plain text
                 println!("{}", response_body);
             }
@@ -99,4 +586,464 @@ pub async fn make_request(
     }
 
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// How hard `benchmark_request` hits the endpoint
+#[derive(Debug, Clone)]
+pub struct BenchmarkOptions {
+    pub repeat: usize,
+    pub concurrency: usize,
+    pub max_error_rate: f64, // Percent of failed requests at which the run stops early
+}
+
+/// Latency summary over a set of timed requests
+#[derive(Debug, Clone, PartialEq)]
+struct LatencyStats {
+    min: Duration,
+    avg: Duration,
+    p50: Duration,
+    p90: Duration,
+    p99: Duration,
+    max: Duration,
+}
+
+// Nearest-rank percentile over already sorted samples
+fn percentile(sorted: &[Duration], pct: f64) -> Duration {
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn latency_stats(samples: &mut [Duration]) -> Option<LatencyStats> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_unstable();
+    let total: Duration = samples.iter().sum();
+    Some(LatencyStats {
+        min: samples[0],
+        avg: total / samples.len() as u32,
+        p50: percentile(samples, 50.0),
+        p90: percentile(samples, 90.0),
+        p99: percentile(samples, 99.0),
+        max: samples[samples.len() - 1],
+    })
+}
+
+// Don't judge the error rate on the first couple of responses
+const MIN_SAMPLES_FOR_ABORT: usize = 10;
+
+/// Sends the same request `repeat` times, `concurrency` at a time, and reports latency
+/// percentiles and throughput. Transport errors and 4xx/5xx responses count as failures.
+pub async fn benchmark_request(
+    method_str: &str,
+    url: &str,
+    body: Option<&str>,
+    headers_map: &HashMap<String, String>,
+    options: &RequestOptions,
+    bench: &BenchmarkOptions,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let concurrency = bench.concurrency.max(1);
+    println!(
+        "{} {} {} ({} requests, {} concurrent)",
+        "Benchmarking:".cyan(),
+        method_str.to_uppercase().yellow(),
+        url.cyan(),
+        bench.repeat,
+        concurrency
+    );
+    let (request_builder, store) = prepare_request(method_str, url, body, headers_map, options)?;
+
+    let send_one = |builder: RequestBuilder| async move {
+        let start_time = std::time::Instant::now();
+        let result = match builder.send().await {
+            // Read the body too, so the latency covers the whole response
+            Ok(response) => {
+                let status = response.status();
+                response.bytes().await.map(|_| status)
+            }
+            Err(e) => Err(e),
+        };
+        (result, start_time.elapsed())
+    };
+
+    let started = std::time::Instant::now();
+    let mut in_flight = FuturesUnordered::new();
+    let mut sent = 0;
+    let mut completed = 0;
+    let mut latencies = Vec::with_capacity(bench.repeat);
+    let mut statuses: BTreeMap<u16, usize> = BTreeMap::new();
+    let mut failures = 0;
+    let mut last_error = None;
+    let mut aborted = false;
+    loop {
+        while !aborted && sent < bench.repeat && in_flight.len() < concurrency {
+            let builder = request_builder.try_clone().ok_or("Request body can't be repeated")?;
+            in_flight.push(send_one(builder));
+            sent += 1;
+        }
+        let Some((result, elapsed)) = in_flight.next().await else { break };
+        completed += 1;
+        match result {
+            Ok(status) => {
+                latencies.push(elapsed);
+                *statuses.entry(status.as_u16()).or_default() += 1;
+                if status.is_client_error() || status.is_server_error() {
+                    failures += 1;
+                }
+            }
+            Err(e) => {
+                failures += 1;
+                last_error = Some(e.to_string());
+            }
+        }
+        let error_rate = failures as f64 * 100.0 / completed as f64;
+        if !aborted && completed >= MIN_SAMPLES_FOR_ABORT && error_rate > bench.max_error_rate {
+            println!("{} error rate {:.1}% is above {:.1}%; stopping early.", "Aborting:".red(), error_rate, bench.max_error_rate);
+            aborted = true;
+        }
+    }
+    let wall_time = started.elapsed();
+    save_cookies(options, &store)?;
+
+    println!("{}", "-".repeat(40).dimmed());
+    println!("Requests:  {} completed, {} failed", completed, failures);
+    println!("Duration:  {:.2?}", wall_time);
+    println!("Rate:      {:.1} req/s", completed as f64 / wall_time.as_secs_f64().max(f64::EPSILON));
+    if !statuses.is_empty() {
+        let summary = statuses.iter().map(|(code, n)| format!("{} x{}", code, n)).collect::<Vec<_>>().join(", ");
+        println!("Statuses:  {}", summary);
+    }
+    if let Some(e) = last_error {
+        println!("{} {}", "Last error:".red(), e);
+    }
+    match latency_stats(&mut latencies) {
+        Some(stats) => {
+            println!("{}", "Latency:".magenta());
+            println!("  min {:>10.2?}", stats.min);
+            println!("  avg {:>10.2?}", stats.avg);
+            println!("  p50 {:>10.2?}", stats.p50);
+            println!("  p90 {:>10.2?}", stats.p90);
+            println!("  p99 {:>10.2?}", stats.p99);
+            println!("  max {:>10.2?}", stats.max);
+        }
+        None => println!("{}", "No responses received.".yellow()),
+    }
+    if aborted {
+        return Err(format!("Benchmark aborted after {} of {} requests: error rate above {}%", completed, bench.repeat, bench.max_error_rate).into());
+    }
+    Ok(())
+}
+
+/// Certificates expiring within this many days are flagged
+const CERT_EXPIRY_WARNING_DAYS: i64 = 30;
+
+// Runs the normal webpki checks but only records their outcome, so expired or
+// mismatched certificates can still be inspected
+#[derive(Debug)]
+struct RecordingVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    outcome: Mutex<Option<Result<(), String>>>,
+}
+
+impl ServerCertVerifier for RecordingVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let outcome = self
+            .inner
+            .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)
+            .map(|_| ())
+            .map_err(|e| e.to_string());
+        *self.outcome.lock().unwrap_or_else(|e| e.into_inner()) = Some(outcome);
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+// Accepts "example.com", "example.com:8443", "[2001:db8::1]:8443" or "https://example.com/path";
+// a bare IPv6 address is taken whole, since its last group can't be told apart from a port
+fn split_host_port(target: &str, default_port: u16) -> Result<(String, u16), String> {
+    let target = target.trim();
+    let target = target.strip_prefix("https://").unwrap_or(target);
+    let target = target.split('/').next().unwrap_or(target);
+    if let Some(bracketed) = target.strip_prefix('[') {
+        let (host, rest) = bracketed.split_once(']').ok_or_else(|| format!("Missing ']' in {}", target))?;
+        let port = match rest.strip_prefix(':') {
+            Some(port) => port.parse().map_err(|_| format!("Invalid port: {}", port))?,
+            None if rest.is_empty() => default_port,
+            None => return Err(format!("Unexpected '{}' after ]", rest)),
+        };
+        return Ok((host.to_string(), port));
+    }
+    match target.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') => {
+            let port = port.parse().map_err(|_| format!("Invalid port: {}", port))?;
+            Ok((host.to_string(), port))
+        }
+        _ => Ok((target.to_string(), default_port)),
+    }
+}
+
+fn days_until(timestamp: i64) -> i64 {
+    (timestamp - chrono::Utc::now().timestamp()).div_euclid(86_400)
+}
+
+fn print_certificate(position: usize, cert: &X509Certificate<'_>) {
+    let label = if position == 0 { format!("Certificate #{} (leaf)", position) } else { format!("Certificate #{}", position) };
+    println!("{}", label.magenta().bold());
+    println!("  {} {}", "Subject:".cyan(), cert.subject());
+    println!("  {} {}", "Issuer: ".cyan(), cert.issuer());
+
+    if let Ok(Some(san)) = cert.subject_alternative_name() {
+        let names: Vec<String> = san
+            .value
+            .general_names
+            .iter()
+            .filter_map(|name| match name {
+                GeneralName::DNSName(dns) => Some(dns.to_string()),
+                GeneralName::IPAddress(bytes) => match bytes.len() {
+                    4 => Some(std::net::Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]).to_string()),
+                    16 => <[u8; 16]>::try_from(*bytes).ok().map(|b| std::net::Ipv6Addr::from(b).to_string()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        if !names.is_empty() {
+            println!("  {} {}", "SANs:   ".cyan(), names.join(", "));
+        }
+    }
+
+    let validity = cert.validity();
+    let days_left = days_until(validity.not_after.timestamp());
+    let expiry = if days_left < 0 {
+        format!("expired {} day(s) ago", -days_left).red().bold()
+    } else if days_left <= CERT_EXPIRY_WARNING_DAYS {
+        format!("expires in {} day(s)", days_left).yellow().bold()
+    } else {
+        format!("expires in {} day(s)", days_left).green()
+    };
+    println!("  {} {} → {}", "Valid:  ".cyan(), validity.not_before, validity.not_after);
+    println!("  {} {}", "Expiry: ".cyan(), expiry);
+}
+
+/// Connects to `target` over TLS and prints the server's certificate chain,
+/// the negotiated protocol version and cipher suite.
+pub async fn inspect_certificate(target: &str, default_port: u16) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (host, port) = split_host_port(target, default_port)?;
+    println!("{} {}:{}", "Inspecting TLS certificate for".cyan(), host.yellow(), port);
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let roots = Arc::new(RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.to_vec() });
+    let verifier = Arc::new(RecordingVerifier {
+        inner: WebPkiServerVerifier::builder_with_provider(roots, provider.clone()).build()?,
+        outcome: Mutex::new(None),
+    });
+    let config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .dangerous()
+        .with_custom_certificate_verifier(verifier.clone())
+        .with_no_client_auth();
+
+    let server_name = ServerName::try_from(host.clone()).map_err(|_| format!("Invalid host name: {}", host))?;
+    let tcp = TcpStream::connect((host.as_str(), port)).await?;
+    let tls = TlsConnector::from(Arc::new(config)).connect(server_name, tcp).await?;
+    let (_, connection) = tls.get_ref();
+
+    println!("{}", "-".repeat(40).dimmed());
+    if let Some(version) = connection.protocol_version() {
+        println!("{} {:?}", "TLS version:".cyan(), version);
+    }
+    if let Some(suite) = connection.negotiated_cipher_suite() {
+        println!("{} {:?}", "Cipher:     ".cyan(), suite.suite());
+    }
+    match verifier.outcome.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some(Ok(())) => println!("{} {}", "Trust:      ".cyan(), "valid chain for this host name".green()),
+        Some(Err(e)) => println!("{} {}", "Trust:      ".cyan(), e.red()),
+        None => {}
+    }
+    println!("{}", "-".repeat(40).dimmed());
+
+    for (position, der) in connection.peer_certificates().unwrap_or_default().iter().enumerate() {
+        match parse_x509_certificate(der.as_ref()) {
+            Ok((_, cert)) => print_certificate(position, &cert),
+            Err(e) => println!("{} {}", format!("Certificate #{}:", position).magenta().bold(), format!("could not be parsed ({})", e).red()),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use reqwest::Client;
+
+    #[test]
+    fn test_select_json_path() {
+        let value = json!({ "data": { "items": [{ "name": "first" }, { "name": "second" }] } });
+        let segments = parse_json_path(".data.items[1].name").unwrap();
+        assert_eq!(select_json_path(&value, &segments), Some(&json!("second")));
+        let segments = parse_json_path("data.items").unwrap();
+        assert_eq!(select_json_path(&value, &segments).map(|v| v.as_array().unwrap().len()), Some(2));
+        let segments = parse_json_path(".data.missing").unwrap();
+        assert_eq!(select_json_path(&value, &segments), None);
+        let segments = parse_json_path(".data.items[5]").unwrap();
+        assert_eq!(select_json_path(&value, &segments), None);
+        assert!(parse_json_path(".items[x]").is_err());
+        assert!(parse_json_path(".").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_graphql_body() {
+        let body: Value = serde_json::from_str(&graphql_body("{ viewer { login } }", None).unwrap()).unwrap();
+        assert_eq!(body, json!({ "query": "{ viewer { login } }" }));
+        let body: Value = serde_json::from_str(&graphql_body("query($id: ID!) { node(id: $id) { id } }", Some(r#"{"id": "42"}"#)).unwrap()).unwrap();
+        assert_eq!(body["variables"], json!({ "id": "42" }));
+        assert!(graphql_body("{ a }", Some("[1]")).is_err());
+        assert!(graphql_body("{ a }", Some("{")).is_err());
+    }
+
+    #[test]
+    fn test_check_body_allowed() {
+        assert!(check_body_allowed(&Method::GET, true, false).is_err());
+        assert!(check_body_allowed(&Method::HEAD, true, false).is_err());
+        assert!(check_body_allowed(&Method::GET, true, true).is_ok());
+        assert!(check_body_allowed(&Method::GET, false, false).is_ok());
+        assert!(check_body_allowed(&Method::POST, true, false).is_ok());
+    }
+
+    #[test]
+    fn test_allowed_methods() {
+        let mut headers = HeaderMap::new();
+        assert!(allowed_methods(&headers).is_empty());
+        headers.insert(reqwest::header::ACCESS_CONTROL_ALLOW_METHODS, HeaderValue::from_static("GET, POST"));
+        assert_eq!(allowed_methods(&headers), vec!["GET", "POST"]);
+        headers.insert(reqwest::header::ALLOW, HeaderValue::from_static("get,head, ,OPTIONS"));
+        assert_eq!(allowed_methods(&headers), vec!["GET", "HEAD", "OPTIONS"]);
+    }
+
+    #[test]
+    fn test_latency_stats() {
+        let mut samples: Vec<Duration> = (1..=100).rev().map(Duration::from_millis).collect();
+        let stats = latency_stats(&mut samples).unwrap();
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.max, Duration::from_millis(100));
+        assert_eq!(stats.p50, Duration::from_millis(50));
+        assert_eq!(stats.p90, Duration::from_millis(90));
+        assert_eq!(stats.p99, Duration::from_millis(99));
+        assert_eq!(stats.avg, Duration::from_micros(50_500));
+        let stats = latency_stats(&mut [Duration::from_millis(7)]).unwrap();
+        assert_eq!(stats.p99, Duration::from_millis(7));
+        assert!(latency_stats(&mut []).is_none());
+    }
+
+    #[test]
+    fn test_parse_headers() {
+        let headers = parse_headers("# auth\nAuthorization: Bearer abc\n\nX-Trace:  a:b \n").unwrap();
+        assert_eq!(headers, vec![
+            ("Authorization".to_string(), "Bearer abc".to_string()),
+            ("X-Trace".to_string(), "a:b".to_string()),
+        ]);
+        assert!(parse_headers("Accept: */*\nno-colon").unwrap_err().starts_with("line 2"));
+        assert!(parse_headers(": value").is_err());
+    }
+
+    #[test]
+    fn test_split_host_port() {
+        assert_eq!(split_host_port("example.com", 443), Ok(("example.com".to_string(), 443)));
+        assert_eq!(split_host_port("example.com:8443", 443), Ok(("example.com".to_string(), 8443)));
+        assert_eq!(split_host_port("https://example.com/login", 443), Ok(("example.com".to_string(), 443)));
+        assert!(split_host_port("example.com:https", 443).is_err());
+        assert_eq!(split_host_port("[2001:db8::1]:8443", 443), Ok(("2001:db8::1".to_string(), 8443)));
+        assert_eq!(split_host_port("https://[::1]/path", 443), Ok(("::1".to_string(), 443)));
+        assert_eq!(split_host_port("2001:db8::1", 443), Ok(("2001:db8::1".to_string(), 443)));
+        assert!(split_host_port("[::1]:", 443).is_err());
+        assert!(split_host_port("[::1", 443).is_err());
+        assert!(split_host_port("[::1]x", 443).is_err());
+    }
+
+    #[test]
+    fn test_curl_command() {
+        let request = Client::new().post("https://api.example.com/items?page=2")
+            .bearer_auth("secret-token")
+            .header("Content-Type", "application/json")
+            .body(r#"{"name":"it's"}"#)
+            .build()
+            .unwrap();
+        let cookies = vec![("session".to_string(), "abc".to_string())];
+        assert_eq!(curl_command(&request, &cookies, true), [
+            "curl 'https://api.example.com/items?page=2'",
+            "-H 'authorization: Bearer <redacted>'",
+            "-H 'content-type: application/json'",
+            "-b 'session=<redacted>'",
+            r#"--data-raw '{"name":"it'\''s"}'"#,
+        ].join(" \\\n  "));
+        let unredacted = curl_command(&request, &cookies, false);
+        assert!(unredacted.contains("Bearer secret-token") && unredacted.contains("-b session=abc"));
+
+        let head = Client::new().head("https://example.com/").build().unwrap();
+        assert_eq!(curl_command(&head, &[], true), "curl https://example.com/ \\\n  --head");
+        let get_with_body = Client::new().get("https://example.com/").body("x").build().unwrap();
+        assert!(curl_command(&get_with_body, &[], true).contains("-X GET"));
+    }
+
+    #[test]
+    fn test_har_document() {
+        let request = Client::new().put("https://example.com/a?x=1").basic_auth("me", Some("pw")).body("hello").build().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(reqwest::header::CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+        headers.append(reqwest::header::SET_COOKIE, HeaderValue::from_static("sid=abc; Path=/; HttpOnly"));
+        let cookies = vec![("session".to_string(), "abc".to_string())];
+        let exchange = HarExchange {
+            request: &request,
+            cookies: &cookies,
+            started: chrono::Local::now(),
+            elapsed: Duration::from_millis(250),
+            status: reqwest::StatusCode::CREATED,
+            version: reqwest::Version::HTTP_11,
+            headers: &headers,
+            body: "done",
+        };
+        let entry = &har_document(&exchange, true)["log"]["entries"][0];
+        assert_eq!(entry["time"], json!(250.0));
+        assert_eq!(entry["request"]["method"], "PUT");
+        assert_eq!(entry["request"]["httpVersion"], "HTTP/1.1");
+        assert_eq!(entry["request"]["queryString"], json!([{ "name": "x", "value": "1" }]));
+        assert_eq!(entry["request"]["headers"][0], json!({ "name": "authorization", "value": "Basic <redacted>" }));
+        assert_eq!(entry["request"]["cookies"], json!([{ "name": "session", "value": "<redacted>" }]));
+        assert_eq!(entry["response"]["headers"][1], json!({ "name": "set-cookie", "value": "sid=<redacted>; Path=/; HttpOnly" }));
+        let unredacted = har_document(&exchange, false);
+        assert_eq!(unredacted["log"]["entries"][0]["request"]["cookies"][0]["value"], "abc");
+        assert_eq!(entry["request"]["postData"]["text"], "hello");
+        assert_eq!(entry["response"]["status"], 201);
+        assert_eq!(entry["response"]["statusText"], "Created");
+        assert_eq!(entry["response"]["content"], json!({ "size": 4, "mimeType": "text/plain", "text": "done" }));
+    }
+}
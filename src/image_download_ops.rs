@@ -1,8 +1,9 @@
 use anyhow::{Result, Context};
 use colored::*;
-use reqwest::{Client, header};
+use reqwest::{header, RequestBuilder};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::fs;
 use std::sync::Arc;
 use tokio::sync::Semaphore;
@@ -12,8 +13,11 @@ use tokio::io::AsyncWriteExt;
 use tokio::fs::File;
 use std::time::Duration;
 use regex::Regex;
+use crate::utils;
 use lazy_static::lazy_static;
 use rand::seq::SliceRandom;
+use crate::utils::html_escape;
+use crate::error::AppError;
 
 lazy_static! {
     static ref USER_AGENTS: Vec<&'static str> = vec![
@@ -38,7 +42,10 @@ pub struct ImageSearchOptions {
     pub max_height: Option<u32>,
     pub color: Option<String>,
     pub safe_search: bool,
-    pub concurrent_downloads: usize,
+    pub concurrent_downloads: usize, // Clamped to 1..=utils::max_download_concurrency()
+    pub overwrite: bool, // Re-download images that already exist in the output directory
+    pub html_index: bool, // Write an index.html with thumbnails after downloading
+    pub license: ImageLicense, // Anything but Any limits the search to Openverse, which reports licenses
 }
 
 impl Default for ImageSearchOptions {
@@ -54,13 +61,115 @@ impl Default for ImageSearchOptions {
             max_height: None,
             color: None,
             safe_search: true,
-            concurrent_downloads: 5,
+            concurrent_downloads: utils::default_download_concurrency(5),
+            overwrite: false,
+            html_index: false,
+            license: ImageLicense::Any,
         }
     }
 }
 
+/// Most restrictive license an image may carry. Each level also accepts the
+/// ones that ask less of the user, so `by` includes CC0 and public domain images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ImageLicense {
+    /// CC0 or public domain: no attribution needed
+    Cc0,
+    /// Also CC BY: credit the author
+    By,
+    /// Also CC BY-SA: credit the author and share adaptations alike
+    BySa,
+    /// No license filter (all providers)
+    #[default]
+    Any,
+}
+
+impl ImageLicense {
+    /// Value for Openverse's `license` parameter, or None when not filtering
+    fn openverse_param(self) -> Option<&'static str> {
+        match self {
+            ImageLicense::Cc0 => Some("cc0,pdm"),
+            ImageLicense::By => Some("cc0,pdm,by"),
+            ImageLicense::BySa => Some("cc0,pdm,by,by-sa"),
+            ImageLicense::Any => None,
+        }
+    }
+}
+
+/// File in the output directory recording each search result and whether it was downloaded
+pub const MANIFEST_FILE: &str = "manifest.json";
+/// Plain list of downloaded URLs written by older versions; still honoured when skipping
+const LEGACY_MANIFEST_FILE: &str = ".downloaded_urls.txt";
+
+/// Upper bound on result pages requested from any one provider per search term
+const MAX_SEARCH_PAGES: usize = 10;
+/// How many times a search or download waits out a 429 before reporting the rate limit
+const RATE_LIMIT_RETRIES: u32 = 2;
+/// Largest page size Pixabay accepts
+const PIXABAY_MAX_PER_PAGE: usize = 200;
+/// Largest page size Unsplash accepts
+const UNSPLASH_MAX_PER_PAGE: usize = 30;
+/// Largest page size Openverse allows without an API token
+const OPENVERSE_MAX_PER_PAGE: usize = 20;
+
+/// Environment variable holding the Pixabay API key
+pub const PIXABAY_KEY_ENV: &str = "PIXABAY_API_KEY";
+/// Environment variable holding the Unsplash access key
+pub const UNSPLASH_KEY_ENV: &str = "UNSPLASH_ACCESS_KEY";
+
+/// API keys for the image search providers.
+///
+/// Keys are read from the `PIXABAY_API_KEY` and `UNSPLASH_ACCESS_KEY` environment
+/// variables first, then from `<config dir>/terminal-pc-matrix/api_keys.env`, which
+/// holds `KEY=value` lines using the same names. A provider without a key is skipped;
+/// Openverse and the Bing web search need no key and are always available as a fallback.
+#[derive(Debug, Clone, Default)]
+pub struct ImageApiKeys {
+    pub pixabay: Option<String>,
+    pub unsplash: Option<String>,
+}
+
+impl ImageApiKeys {
+    /// Load keys from the environment, falling back to the config file
+    pub fn load() -> Self {
+        let file_values = api_keys_file()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|contents| parse_key_file(&contents))
+            .unwrap_or_default();
+        
+        let lookup = |name: &str| {
+            std::env::var(name).ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .or_else(|| file_values.get(name).cloned())
+        };
+        
+        Self {
+            pixabay: lookup(PIXABAY_KEY_ENV),
+            unsplash: lookup(UNSPLASH_KEY_ENV),
+        }
+    }
+}
+
+/// Location of the optional API key file
+pub fn api_keys_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("terminal-pc-matrix").join("api_keys.env"))
+}
+
+/// Parse `KEY=value` lines, ignoring blanks and `#` comments
+fn parse_key_file(contents: &str) -> std::collections::HashMap<String, String> {
+    contents
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().trim_matches('"').to_string()))
+        .filter(|(_, value)| !value.is_empty())
+        .collect()
+}
+
 /// Represents an image found during search
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImageResult {
     pub url: String,
     pub width: u32,
@@ -68,26 +177,28 @@ pub struct ImageResult {
     pub source: String,
     pub description: Option<String>,
     pub thumbnail_url: Option<String>,
+    /// License the image is published under, e.g. "CC BY-SA 2.0"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// Credit line to publish alongside the image
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribution: Option<String>,
 }
 
-/// Create a reqwest client with random user agent
-fn create_client() -> Result<Client> {
-    let mut headers = header::HeaderMap::new();
-    headers.insert(
-        header::ACCEPT,
-        header::HeaderValue::from_static("text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8"),
-    );
-    
+/// Longest any one search or image request may take
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// GET `url` through the shared client dressed as a browser, for scraped pages and image hosts.
+/// The search APIs are asked with the app's own user agent instead.
+fn browser_get(url: &str) -> RequestBuilder {
     // Pick a random user agent to avoid detection
     let user_agent = USER_AGENTS.choose(&mut rand::thread_rng()).unwrap_or(&USER_AGENTS[0]);
     
-    let client = Client::builder()
-        .default_headers(headers)
-        .user_agent(*user_agent)
-        .timeout(Duration::from_secs(30))
-        .build()?;
-    
-    Ok(client)
+    utils::http_client()
+        .get(url)
+        .header(header::USER_AGENT, *user_agent)
+        .header(header::ACCEPT, "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
+        .timeout(REQUEST_TIMEOUT)
 }
 
 /// Search for images using various APIs and web sources
@@ -104,6 +215,17 @@ pub async fn search_images(options: &ImageSearchOptions) -> Result<Vec<ImageResu
     
     println!("{} {} separate search terms", "Found".green(), search_terms.len());
     
+    // Providers without a configured key are skipped instead of failing with 401
+    let api_keys = ImageApiKeys::load();
+    // Only Openverse reports licenses, so a license filter rules out every other provider
+    let licensed_only = options.license != ImageLicense::Any;
+    if licensed_only {
+        println!("{} searching Openverse only", "License filter set:".cyan());
+    } else if api_keys.pixabay.is_none() && api_keys.unsplash.is_none() {
+        println!("{} set {} or {} for better results; using Openverse and web search only",
+            "No image API keys configured:".yellow(), PIXABAY_KEY_ENV, UNSPLASH_KEY_ENV);
+    }
+    
     let mut all_results = Vec::new();
     
     // Search for each term separately
@@ -118,19 +240,21 @@ pub async fn search_images(options: &ImageSearchOptions) -> Result<Vec<ImageResu
         let mut term_results = Vec::new();
         
         // Try Pixabay API first (free API with generous limits)
-        match search_pixabay(&term_options).await {
-            Ok(images) => {
-                println!("{} {} images from Pixabay for '{}'", "Found".green(), images.len(), term);
-                term_results.extend(images);
-            },
-            Err(e) => {
-                println!("{} from Pixabay: {}", "Search error".yellow(), e);
+        if let (Some(key), false) = (&api_keys.pixabay, licensed_only) {
+            match search_pixabay(&term_options, key).await {
+                Ok(images) => {
+                    println!("{} {} images from Pixabay for '{}'", "Found".green(), images.len(), term);
+                    term_results.extend(images);
+                },
+                Err(e) => {
+                    println!("{} from Pixabay: {}", "Search error".yellow(), e);
+                }
             }
         }
         
         // If we need more results, try Unsplash
-        if term_results.len() < term_options.count {
-            match search_unsplash(&term_options).await {
+        if let (Some(key), true) = (&api_keys.unsplash, !licensed_only && term_results.len() < term_options.count) {
+            match search_unsplash(&term_options, key).await {
                 Ok(images) => {
                     println!("{} {} additional images from Unsplash for '{}'", "Found".green(), images.len(), term);
                     term_results.extend(images);
@@ -141,8 +265,21 @@ pub async fn search_images(options: &ImageSearchOptions) -> Result<Vec<ImageResu
             }
         }
         
-        // If we still need more or both APIs failed, try web scraping
+        // Openverse needs no key and carries license details
         if term_results.len() < term_options.count {
+            match search_openverse(&term_options).await {
+                Ok(images) => {
+                    println!("{} {} additional images from Openverse for '{}'", "Found".green(), images.len(), term);
+                    term_results.extend(images);
+                },
+                Err(e) => {
+                    println!("{} from Openverse: {}", "Search error".yellow(), e);
+                }
+            }
+        }
+        
+        // If we still need more or the APIs failed, try web scraping
+        if !licensed_only && term_results.len() < term_options.count {
             match search_bing_images(&term_options).await {
                 Ok(images) => {
                     println!("{} {} additional images from web search for '{}'", "Found".green(), images.len(), term);
@@ -184,14 +321,23 @@ pub async fn search_images(options: &ImageSearchOptions) -> Result<Vec<ImageResu
     Ok(all_results)
 }
 
+/// Fetch one page of search results, waiting out short rate limits.
+///
+/// When a later page stays rate limited this returns `None` instead of an error, so the
+/// caller keeps the `kept` results earlier pages returned rather than throwing them away.
+async fn send_page(request: RequestBuilder, service: &str, page: usize, kept: usize) -> Result<Option<reqwest::Response>> {
+    match utils::send_with_rate_limit(request, service, RATE_LIMIT_RETRIES).await {
+        Ok(response) => Ok(Some(response)),
+        Err(e @ AppError::RateLimited { .. }) if page > 1 => {
+            println!("{} {}; keeping {} results", "Stopping early:".yellow(), e, kept);
+            Ok(None)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
 /// Search Pixabay API for images
-async fn search_pixabay(options: &ImageSearchOptions) -> Result<Vec<ImageResult>> {
-    // Pixabay API key - this is a free API key with rate limits
-    // In production, this should be stored in an environment variable or config file
-    let api_key = "30908129-8fb1c0b20e978aea862cfc42c";
-    
-    let client = create_client()?;
-    
+async fn search_pixabay(options: &ImageSearchOptions, api_key: &str) -> Result<Vec<ImageResult>> {
     // Clean the query - remove commas and replace spaces with +
     let clean_query = options.query
         .replace(',', " ")
@@ -199,39 +345,56 @@ async fn search_pixabay(options: &ImageSearchOptions) -> Result<Vec<ImageResult>
         .collect::<Vec<&str>>()
         .join("+");
     
-    let mut params = vec![
+    let mut base_params = vec![
         ("key", api_key.to_string()),
         ("q", clean_query),
         ("image_type", "photo".to_string()),
-        ("per_page", options.count.to_string()),
     ];
     
     if options.safe_search {
-        params.push(("safesearch", "true".to_string()));
+        base_params.push(("safesearch", "true".to_string()));
     }
     
     if let Some(min_width) = options.min_width {
-        params.push(("min_width", min_width.to_string()));
+        base_params.push(("min_width", min_width.to_string()));
     }
     
     if let Some(min_height) = options.min_height {
-        params.push(("min_height", min_height.to_string()));
+        base_params.push(("min_height", min_height.to_string()));
     }
     
-    let response = client.get("https://pixabay.com/api/")
-        .query(&params)
-        .send()
-        .await?;
-    
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!("Pixabay API returned error: {}", response.status()));
-    }
-    
-    let json: Value = response.json().await?;
-    
+    // Pixabay caps per_page, so walk pages until we have enough or run out of hits
+    let per_page = options.count.clamp(3, PIXABAY_MAX_PER_PAGE);
     let mut results = Vec::new();
     
-    if let Some(hits) = json.get("hits").and_then(|h| h.as_array()) {
+    for page in 1..=MAX_SEARCH_PAGES {
+        let mut params = base_params.clone();
+        params.push(("per_page", per_page.to_string()));
+        params.push(("page", page.to_string()));
+        
+        let request = utils::http_client().get("https://pixabay.com/api/")
+            .timeout(REQUEST_TIMEOUT)
+            .query(&params);
+        let Some(response) = send_page(request, "Pixabay", page, results.len()).await? else {
+            break;
+        };
+        
+        if !response.status().is_success() {
+            // Pixabay answers 400 once we page past the available hits
+            if page > 1 {
+                break;
+            }
+            return Err(anyhow::anyhow!("Pixabay API returned error: {}", response.status()));
+        }
+        
+        let json: Value = response.json().await?;
+        
+        let hits = match json.get("hits").and_then(|h| h.as_array()) {
+            Some(hits) if !hits.is_empty() => hits,
+            _ => break,
+        };
+        let page_len = hits.len();
+        
         for hit in hits {
             if let (Some(url), Some(width), Some(height)) = (
                 hit.get("largeImageURL").and_then(|u| u.as_str()),
@@ -240,6 +403,7 @@ async fn search_pixabay(options: &ImageSearchOptions) -> Result<Vec<ImageResult>
             ) {
                 let description = hit.get("tags").and_then(|t| t.as_str()).map(|s| s.to_string());
                 let thumbnail = hit.get("previewURL").and_then(|u| u.as_str()).map(|s| s.to_string());
+                let attribution = hit.get("user").and_then(|u| u.as_str()).map(|user| format!("Image by {} on Pixabay", user));
                 
                 results.push(ImageResult {
                     url: url.to_string(),
@@ -248,16 +412,24 @@ async fn search_pixabay(options: &ImageSearchOptions) -> Result<Vec<ImageResult>
                     source: "Pixabay".to_string(),
                     description,
                     thumbnail_url: thumbnail,
+                    license: Some("Pixabay Content License".to_string()),
+                    attribution,
                 });
             }
         }
+        
+        let total_hits = json.get("totalHits").and_then(|t| t.as_u64()).unwrap_or(0) as usize;
+        if results.len() >= options.count || page_len < per_page || page * per_page >= total_hits {
+            break;
+        }
     }
     
+    results.truncate(options.count);
     Ok(results)
 }
 
 /// Search Unsplash API for images
-async fn search_unsplash(options: &ImageSearchOptions) -> Result<Vec<ImageResult>> {
+async fn search_unsplash(options: &ImageSearchOptions, access_key: &str) -> Result<Vec<ImageResult>> {
     // Clean the query - remove commas and replace spaces with +
     let clean_query = options.query
         .replace(',', " ")
@@ -265,36 +437,46 @@ async fn search_unsplash(options: &ImageSearchOptions) -> Result<Vec<ImageResult
         .collect::<Vec<&str>>()
         .join(" ");
     
-    // Demo API key that might be rate-limited, use a more reliable API option
-    // Use a different free API key for Unsplash
-    let access_key = "4DO3rlZ4NbLqvki5PWOeQMVVYAK-iKcGIY07us9tSCM";
-    
-    let client = create_client()?;
-    
-    let mut params = vec![
+    let per_page = options.count.clamp(1, UNSPLASH_MAX_PER_PAGE);
+    let mut base_params = vec![
         ("query", clean_query),
-        ("per_page", options.count.to_string()),
+        ("per_page", per_page.to_string()),
     ];
     
     if let Some(color) = &options.color {
-        params.push(("color", color.clone()));
-    }
-    
-    let response = client.get("https://api.unsplash.com/search/photos")
-        .header("Authorization", format!("Client-ID {}", access_key))
-        .query(&params)
-        .send()
-        .await?;
-    
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!("Unsplash API returned error: {}", response.status()));
+        base_params.push(("color", color.clone()));
     }
     
-    let json: Value = response.json().await?;
-    
     let mut results = Vec::new();
     
-    if let Some(results_array) = json.get("results").and_then(|r| r.as_array()) {
+    for page in 1..=MAX_SEARCH_PAGES {
+        let mut params = base_params.clone();
+        params.push(("page", page.to_string()));
+        
+        let request = utils::http_client().get("https://api.unsplash.com/search/photos")
+            .timeout(REQUEST_TIMEOUT)
+            .header("Authorization", format!("Client-ID {}", access_key))
+            .query(&params);
+        let Some(response) = send_page(request, "Unsplash", page, results.len()).await? else {
+            break;
+        };
+        
+        if !response.status().is_success() {
+            // Keep what earlier pages returned (e.g. when the hourly quota runs out mid-search)
+            if page > 1 {
+                break;
+            }
+            return Err(anyhow::anyhow!("Unsplash API returned error: {}", response.status()));
+        }
+        
+        let json: Value = response.json().await?;
+        
+        let results_array = match json.get("results").and_then(|r| r.as_array()) {
+            Some(array) if !array.is_empty() => array,
+            _ => break,
+        };
+        let page_len = results_array.len();
+        
         for result in results_array {
             if let Some(urls) = result.get("urls") {
                 let url = urls.get("full").and_then(|u| u.as_str()).unwrap_or_else(|| {
@@ -314,6 +496,10 @@ async fn search_unsplash(options: &ImageSearchOptions) -> Result<Vec<ImageResult
                     .and_then(|d| d.as_str())
                     .or_else(|| result.get("alt_description").and_then(|d| d.as_str()))
                     .map(|s| s.to_string());
+                let attribution = result.get("user")
+                    .and_then(|u| u.get("name"))
+                    .and_then(|n| n.as_str())
+                    .map(|name| format!("Photo by {} on Unsplash", name));
                 
                 results.push(ImageResult {
                     url: url.to_string(),
@@ -322,18 +508,144 @@ async fn search_unsplash(options: &ImageSearchOptions) -> Result<Vec<ImageResult
                     source: "Unsplash".to_string(),
                     description,
                     thumbnail_url: thumbnail,
+                    license: Some("Unsplash License".to_string()),
+                    attribution,
                 });
             }
         }
+        
+        let total_pages = json.get("total_pages").and_then(|t| t.as_u64()).unwrap_or(0) as usize;
+        if results.len() >= options.count || page_len < per_page || page >= total_pages {
+            break;
+        }
     }
     
+    results.truncate(options.count);
     Ok(results)
 }
 
+/// Search Openverse (openly licensed images from Flickr, Wikimedia Commons and others).
+/// Needs no key and is the only provider that reports a license and attribution per image.
+async fn search_openverse(options: &ImageSearchOptions) -> Result<Vec<ImageResult>> {
+    let clean_query = options.query
+        .replace(',', " ")
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ");
+    
+    let per_page = options.count.clamp(1, OPENVERSE_MAX_PER_PAGE);
+    let mut base_params = vec![
+        ("q", clean_query),
+        ("page_size", per_page.to_string()),
+    ];
+    
+    if let Some(license) = options.license.openverse_param() {
+        base_params.push(("license", license.to_string()));
+    }
+    
+    if !options.safe_search {
+        base_params.push(("mature", "true".to_string()));
+    }
+    
+    let mut results = Vec::new();
+    
+    for page in 1..=MAX_SEARCH_PAGES {
+        let mut params = base_params.clone();
+        params.push(("page", page.to_string()));
+        
+        let request = utils::http_client().get("https://api.openverse.org/v1/images/")
+            .timeout(REQUEST_TIMEOUT)
+            .header(header::ACCEPT, "application/json")
+            .query(&params);
+        let Some(response) = send_page(request, "Openverse", page, results.len()).await? else {
+            break;
+        };
+        
+        if !response.status().is_success() {
+            // Anonymous requests are refused past a fixed number of pages
+            if page > 1 {
+                break;
+            }
+            return Err(anyhow::anyhow!("Openverse API returned error: {}", response.status()));
+        }
+        
+        let json: Value = response.json().await?;
+        
+        let results_array = match json.get("results").and_then(|r| r.as_array()) {
+            Some(array) if !array.is_empty() => array,
+            _ => break,
+        };
+        let page_len = results_array.len();
+        
+        for result in results_array {
+            let Some(image) = parse_openverse_result(result) else {
+                continue;
+            };
+            
+            // Openverse only filters by rough size, so apply the exact minimums when known
+            let too_small = (image.width > 0 && options.min_width.is_some_and(|min| image.width < min))
+                || (image.height > 0 && options.min_height.is_some_and(|min| image.height < min));
+            if !too_small {
+                results.push(image);
+            }
+        }
+        
+        let page_count = json.get("page_count").and_then(|p| p.as_u64()).unwrap_or(0) as usize;
+        if results.len() >= options.count || page_len < per_page || page >= page_count {
+            break;
+        }
+    }
+    
+    results.truncate(options.count);
+    Ok(results)
+}
+
+/// Turn one entry of an Openverse `results` array into an `ImageResult`
+fn parse_openverse_result(result: &Value) -> Option<ImageResult> {
+    let url = result.get("url").and_then(|u| u.as_str()).filter(|u| u.starts_with("http"))?;
+    let text = |key: &str| result.get(key).and_then(|v| v.as_str()).filter(|s| !s.is_empty());
+    
+    let license = text("license").map(|code| openverse_license_label(code, text("license_version")));
+    // Openverse builds a complete credit line; fall back to title and creator without it
+    let attribution = text("attribution").map(str::to_string).or_else(|| {
+        let creator = text("creator")?;
+        Some(match text("title") {
+            Some(title) => format!("\"{}\" by {}", title, creator),
+            None => format!("By {}", creator),
+        })
+    });
+    let source = match text("source").or_else(|| text("provider")) {
+        Some(provider) => format!("Openverse ({})", provider),
+        None => "Openverse".to_string(),
+    };
+    
+    Some(ImageResult {
+        url: url.to_string(),
+        width: result.get("width").and_then(|w| w.as_u64()).unwrap_or(0) as u32,
+        height: result.get("height").and_then(|h| h.as_u64()).unwrap_or(0) as u32,
+        source,
+        description: text("title").map(str::to_string),
+        thumbnail_url: text("thumbnail").map(str::to_string),
+        license,
+        attribution,
+    })
+}
+
+/// Human-readable name for an Openverse license code, e.g. `by-sa` + `2.0` -> "CC BY-SA 2.0"
+fn openverse_license_label(code: &str, version: Option<&str>) -> String {
+    let name = match code.to_ascii_lowercase().as_str() {
+        "cc0" => "CC0".to_string(),
+        "pdm" => "Public Domain Mark".to_string(),
+        other => format!("CC {}", other.to_ascii_uppercase()),
+    };
+    match version {
+        Some(version) => format!("{} {}", name, version),
+        None => name,
+    }
+}
+
 /// Search for images using web scraping (Bing Images)
 async fn search_bing_images(options: &ImageSearchOptions) -> Result<Vec<ImageResult>> {
-    let client = create_client()?;
-    
     // Clean the query - remove commas and replace spaces with +
     let clean_query = options.query
         .replace(',', " ")
@@ -341,18 +653,45 @@ async fn search_bing_images(options: &ImageSearchOptions) -> Result<Vec<ImageRes
         .collect::<Vec<&str>>()
         .join("+");
     
-    let url = format!("https://www.bing.com/images/search?q={}&form=HDRSC2&first=1", clean_query);
+    let mut results: Vec<ImageResult> = Vec::new();
+    let mut seen_urls = std::collections::HashSet::new();
+    let mut first = 1;
     
-    let response = client.get(&url)
-        .send()
-        .await?;
-    
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!("Bing Images returned error status: {}", response.status()));
+    // Bing pages through results with the `first` offset
+    for page in 1..=MAX_SEARCH_PAGES {
+        let url = format!("https://www.bing.com/images/search?q={}&form=HDRSC2&first={}", clean_query, first);
+        
+        let request = browser_get(&url);
+        let Some(response) = send_page(request, "Bing Images", page, results.len()).await? else {
+            break;
+        };
+        
+        if !response.status().is_success() {
+            if page > 1 {
+                break;
+            }
+            return Err(anyhow::anyhow!("Bing Images returned error status: {}", response.status()));
+        }
+        
+        let html = response.text().await?;
+        let page_results = parse_bing_html(&html, options, options.count - results.len());
+        let page_len = page_results.len();
+        
+        let before = results.len();
+        results.extend(page_results.into_iter().filter(|img| seen_urls.insert(img.url.clone())));
+        
+        // Stop once we have enough or a page brings nothing new
+        if results.len() >= options.count || results.len() == before {
+            break;
+        }
+        first += page_len;
     }
     
-    let html = response.text().await?;
-    
+    Ok(results)
+}
+
+/// Extract up to `wanted` images from a Bing Images results page
+fn parse_bing_html(html: &str, options: &ImageSearchOptions, wanted: usize) -> Vec<ImageResult> {
     // Extract image data from the HTML
     // Bing stores image data in a JSON-like structure within script tags
     let mut results = Vec::new();
@@ -367,7 +706,7 @@ async fn search_bing_images(options: &ImageSearchOptions) -> Result<Vec<ImageRes
     }
     
     // Try the first regex pattern
-    for cap in IMAGE_REGEX.captures_iter(&html) {
+    for cap in IMAGE_REGEX.captures_iter(html) {
         let url = &cap[1];
         let thumbnail = &cap[2];
         let alt_text = &cap[3];
@@ -411,16 +750,18 @@ async fn search_bing_images(options: &ImageSearchOptions) -> Result<Vec<ImageRes
             source: "Bing".to_string(),
             description: Some(alt_text.to_string()),
             thumbnail_url: Some(thumbnail.to_string()),
+            license: None,
+            attribution: None,
         });
         
-        if results.len() >= options.count {
+        if results.len() >= wanted {
             break;
         }
     }
     
     // If we didn't find enough images, try the alternate pattern
-    if results.len() < options.count {
-        for cap in ALT_IMAGE_REGEX.captures_iter(&html) {
+    if results.len() < wanted {
+        for cap in ALT_IMAGE_REGEX.captures_iter(html) {
             let url = &cap[1];
             let alt_text = &cap[2];
             
@@ -436,9 +777,11 @@ async fn search_bing_images(options: &ImageSearchOptions) -> Result<Vec<ImageRes
                 source: "Bing".to_string(),
                 description: Some(alt_text.to_string()),
                 thumbnail_url: Some(url.to_string()),
+                license: None,
+                attribution: None,
             });
             
-            if results.len() >= options.count {
+            if results.len() >= wanted {
                 break;
             }
         }
@@ -447,7 +790,7 @@ async fn search_bing_images(options: &ImageSearchOptions) -> Result<Vec<ImageRes
     // If still no results, try a different approach - look for "src" attributes in img tags
     if results.is_empty() {
         let img_regex = Regex::new(r#"<img[^>]+src="([^"]+)"[^>]*>"#).unwrap();
-        for cap in img_regex.captures_iter(&html) {
+        for cap in img_regex.captures_iter(html) {
             let url = &cap[1];
             
             // Skip tiny images, data URLs, or non-URLs
@@ -462,30 +805,32 @@ async fn search_bing_images(options: &ImageSearchOptions) -> Result<Vec<ImageRes
                 source: "Bing".to_string(),
                 description: None,
                 thumbnail_url: Some(url.to_string()),
+                license: None,
+                attribution: None,
             });
             
-            if results.len() >= options.count {
+            if results.len() >= wanted {
                 break;
             }
         }
     }
     
-    Ok(results)
+    results
 }
 
 /// Download a batch of images to a directory
 pub async fn download_images(
     images: &[ImageResult],
     output_dir: &Path,
-    concurrent_downloads: usize,
+    options: &ImageSearchOptions,
 ) -> Result<()> {
     // Create output directory if it doesn't exist
     fs::create_dir_all(output_dir)?;
     
     println!("{} {} images to {}", "Downloading".cyan().bold(), images.len(), output_dir.display());
     
-    // Setup for concurrent downloads
-    let semaphore = Arc::new(Semaphore::new(concurrent_downloads));
+    // Setup for concurrent downloads, capped by utils::max_download_concurrency()
+    let semaphore = Arc::new(Semaphore::new(utils::clamp_download_concurrency(options.concurrent_downloads)));
     
     // Setup progress display
     let mp = MultiProgress::new();
@@ -495,18 +840,44 @@ pub async fn download_images(
         .unwrap()
         .progress_chars("#>-"));
     
-    // Clone client for all downloads
-    let client = create_client()?;
+    // Only Unsplash filters by color itself, so every download is checked here
+    if let Some(color) = &options.color {
+        if named_color(color).is_none() {
+            let known: Vec<&str> = NAMED_COLORS.iter().map(|(name, _)| *name).collect();
+            println!("{} '{}'; not filtering by color (known: {})", "Unknown color".yellow(), color, known.join(", "));
+        }
+    }
+    
+    // What earlier runs into this directory fetched, rejected or failed on
+    let manifest_path = output_dir.join(MANIFEST_FILE);
+    let mut manifest = if manifest_path.exists() {
+        ImageManifest::load(&manifest_path).unwrap_or_else(|e| {
+            println!("{} {:#}; starting a new one", "Ignoring manifest:".yellow(), e);
+            ImageManifest::default()
+        })
+    } else {
+        ImageManifest::default()
+    };
+    if manifest.query.is_empty() {
+        manifest.query = options.query.clone();
+    }
+    manifest.merge_results(images);
+    let legacy_manifest = read_legacy_manifest(&output_dir.join(LEGACY_MANIFEST_FILE));
+    
+    // Save the search before downloading, so an interrupted run can be picked up with --from-manifest
+    if let Err(e) = manifest.save(&manifest_path) {
+        println!("{} {:#}", "Could not save manifest:".yellow(), e);
+    }
     
     // Create download tasks
     let download_tasks = images.iter().enumerate().map(|(i, image)| {
         // Clone what we need for the task
         let semaphore = Arc::clone(&semaphore);
-        let client = client.clone();
         let url = image.url.clone();
         let _source = image.source.clone();
         let output_dir = output_dir.to_path_buf();
         let main_pb = main_pb.clone();
+        let options = options.clone();
         
         // Create a progress bar for this download
         let pb = mp.add(ProgressBar::new(0));
@@ -515,31 +886,62 @@ pub async fn download_images(
             .unwrap()
             .progress_chars("#>-"));
         
+        // Extract filename from URL and sanitize it
+        let filename = extract_filename_from_url(&url, i).unwrap_or_else(|| {
+            format!("image_{:03}.jpg", i+1)
+        });
+        
+        let output_path = output_dir.join(&filename);
+        
+        // Decide before any task runs, so files written by this batch don't count.
+        // Failed and pending entries are retried; downloaded and rejected ones are not.
+        let previous = manifest.entry(&url).filter(|_| !options.overwrite);
+        let skip_to = match previous.map(|entry| (entry.status, &entry.file)) {
+            Some((DownloadStatus::Downloaded, Some(file))) if output_dir.join(file).exists() => Some(output_dir.join(file)),
+            Some((DownloadStatus::Rejected, _)) => Some(output_path.clone()),
+            _ if !options.overwrite && (
+                legacy_manifest.contains(&url)
+                    || fs::metadata(&output_path).map(|m| m.len() > 0).unwrap_or(false)
+            ) => Some(output_path.clone()),
+            _ => None,
+        };
+        
         async move {
+            if let Some(path) = skip_to {
+                main_pb.inc(1);
+                pb.finish_and_clear();
+                return (url, ImageCheck::Skipped(path));
+            }
+            
             // Acquire permit from semaphore
             let _permit = semaphore.acquire().await.unwrap();
             
-            // Extract filename from URL and sanitize it
-            let filename = extract_filename_from_url(&url, i).unwrap_or_else(|| {
-                format!("image_{:03}.jpg", i+1)
-            });
-            
-            let output_path = output_dir.join(&filename);
-            
-            // Download the file
-            let success = match download_single_image(&client, &url, &output_path, pb.clone()).await {
-                Ok(()) => true,
+            // Download the file, then check what actually arrived
+            let outcome = match download_single_image(&url, &output_path, pb.clone()).await {
+                Ok(()) => {
+                    let path = output_path.clone();
+                    match tokio::task::spawn_blocking(move || validate_downloaded_image(&path, &options)).await {
+                        Ok(Ok(check)) => check,
+                        Ok(Err(e)) => ImageCheck::Rejected(e.to_string()),
+                        Err(e) => ImageCheck::Rejected(format!("validation task failed: {}", e)),
+                    }
+                }
                 Err(e) => {
                     println!("{} {}: {}", "Failed to download".red(), filename, e);
-                    false
+                    ImageCheck::Failed(e.to_string())
                 }
             };
             
+            if let ImageCheck::Rejected(reason) | ImageCheck::OffColor(reason) = &outcome {
+                println!("{} {}: {}", "Rejected".yellow(), filename, reason);
+                let _ = fs::remove_file(&output_path);
+            }
+            
             // Update main progress
             main_pb.inc(1);
             pb.finish_and_clear();
             
-            (success, output_path)
+            (url, outcome)
         }
     });
     
@@ -555,11 +957,72 @@ pub async fn download_images(
     let _ = mp_handle.await;
     
     // Count successes
-    let successful = results.iter().filter(|(success, _)| *success).count();
+    let successful = results.iter().filter(|(_, r)| matches!(r, ImageCheck::Accepted { .. })).count();
+    let rejected = results.iter().filter(|(_, r)| matches!(r, ImageCheck::Rejected(_))).count();
+    let skipped = results.iter().filter(|(_, r)| matches!(r, ImageCheck::Skipped(_))).count();
     
     main_pb.finish_with_message(format!("{}/{} images downloaded", successful, images.len()).green().to_string());
     
-    if successful > 0 {
+    if skipped > 0 {
+        println!("{} {} images already in {} (use --overwrite to fetch them again)", "Skipped".yellow(), skipped, output_dir.display());
+    }
+    
+    if rejected > 0 {
+        println!("{} {} images that were unreadable or failed the size filter", "Rejected".yellow(), rejected);
+    }
+    
+    let off_color = results.iter().filter(|(_, r)| matches!(r, ImageCheck::OffColor(_))).count();
+    if off_color > 0 {
+        println!("{} {} images whose dominant color isn't {}", "Filtered out".yellow(), off_color, options.color.as_deref().unwrap_or_default());
+    }
+    
+    let failed = results.iter().filter(|(_, r)| matches!(r, ImageCheck::Failed(_))).count();
+    if failed > 0 {
+        println!("{} {} images failed to download; run again to retry them", "Failed:".red(), failed);
+    }
+    
+    // Remember what happened to each image so the next run into this folder only fills the gaps
+    for (url, outcome) in &results {
+        match outcome {
+            ImageCheck::Accepted { path, .. } => manifest.set_status(url, DownloadStatus::Downloaded, Some(path), None),
+            ImageCheck::Rejected(reason) | ImageCheck::OffColor(reason) => manifest.set_status(url, DownloadStatus::Rejected, None, Some(reason)),
+            ImageCheck::Failed(error) => manifest.set_status(url, DownloadStatus::Failed, None, Some(error)),
+            // Found on disk or in the legacy list: record it properly now
+            ImageCheck::Skipped(path) if path.exists() => manifest.set_status(url, DownloadStatus::Downloaded, Some(path), None),
+            ImageCheck::Skipped(_) => {}
+        }
+    }
+    if let Err(e) = manifest.save(&manifest_path) {
+        println!("{} {:#}", "Could not save manifest:".yellow(), e);
+    }
+    
+    if options.html_index {
+        let entries: Vec<IndexEntry> = images.iter().zip(&results)
+            .filter_map(|(image, (_, outcome))| match outcome {
+                ImageCheck::Accepted { path, width, height } => Some(IndexEntry {
+                    image: image.clone(),
+                    path: path.clone(),
+                    width: *width,
+                    height: *height,
+                }),
+                ImageCheck::Skipped(path) if path.exists() => Some(IndexEntry {
+                    image: image.clone(),
+                    path: path.clone(),
+                    width: image.width,
+                    height: image.height,
+                }),
+                _ => None,
+            })
+            .collect();
+        
+        let dir = output_dir.to_path_buf();
+        match tokio::task::spawn_blocking(move || write_html_index(&dir, &entries)).await? {
+            Ok(index_path) => println!("{} {}", "Wrote image index to".green(), index_path.display()),
+            Err(e) => println!("{} {}", "Could not write image index:".yellow(), e),
+        }
+    }
+    
+    if successful > 0 || skipped > 0 {
         println!("{} {} {} {}", "Successfully downloaded".green().bold(), successful, "images to", output_dir.display());
         Ok(())
     } else {
@@ -567,16 +1030,307 @@ pub async fn download_images(
     }
 }
 
+/// Result of checking a downloaded image
+#[derive(Debug, Clone)]
+enum ImageCheck {
+    /// Kept on disk (possibly renamed to its real extension)
+    Accepted { path: PathBuf, width: u32, height: u32 },
+    /// Downloaded but deleted: not a readable image or outside the size limits
+    Rejected(String),
+    /// Downloaded but deleted: its dominant color is too far from the requested one
+    OffColor(String),
+    /// The download itself failed
+    Failed(String),
+    /// Already handled by an earlier run (downloaded to this path, or rejected)
+    Skipped(PathBuf),
+}
+
+/// Where an image in the manifest got to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadStatus {
+    /// Found by the search but not fetched yet (or the run was interrupted)
+    Pending,
+    Downloaded,
+    /// Fetched but not a readable image or outside the size limits
+    Rejected,
+    /// The download failed; retried on the next run
+    Failed,
+}
+
+/// One search result and what happened to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    #[serde(flatten)]
+    pub image: ImageResult,
+    pub status: DownloadStatus,
+    /// File name inside the output directory once downloaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Why the image was rejected or failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Search results saved next to the downloaded images (`manifest.json`), so a
+/// re-run only fetches what is missing and a search can be downloaded again later
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImageManifest {
+    pub query: String,
+    pub images: Vec<ManifestEntry>,
+}
+
+impl ImageManifest {
+    /// Read a manifest written by `download_images`
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read manifest {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid manifest {}", path.display()))
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write manifest {}", path.display()))
+    }
+
+    /// The search results in their original order
+    pub fn image_results(&self) -> Vec<ImageResult> {
+        self.images.iter().map(|entry| entry.image.clone()).collect()
+    }
+
+    fn entry(&self, url: &str) -> Option<&ManifestEntry> {
+        self.images.iter().find(|entry| entry.image.url == url)
+    }
+
+    // Add results not listed yet; ones already listed keep their status
+    fn merge_results(&mut self, images: &[ImageResult]) {
+        for image in images {
+            if self.entry(&image.url).is_none() {
+                self.images.push(ManifestEntry { image: image.clone(), status: DownloadStatus::Pending, file: None, error: None });
+            }
+        }
+    }
+
+    fn set_status(&mut self, url: &str, status: DownloadStatus, file: Option<&Path>, error: Option<&str>) {
+        if let Some(entry) = self.images.iter_mut().find(|entry| entry.image.url == url) {
+            entry.status = status;
+            entry.file = file.and_then(|f| f.file_name()).map(|name| name.to_string_lossy().into_owned());
+            entry.error = error.map(str::to_string);
+        }
+    }
+}
+
+/// Load the set of URLs recorded in a legacy download manifest (missing file means empty)
+fn read_legacy_manifest(path: &Path) -> std::collections::HashSet<String> {
+    fs::read_to_string(path)
+        .map(|contents| {
+            contents.lines()
+                .map(|line| line.trim())
+                .filter(|line| !line.is_empty())
+                .map(|line| line.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Read the real format and dimensions of a downloaded image, enforce the size
+/// limits from the search options and fix the file extension if the URL lied
+fn validate_downloaded_image(path: &Path, options: &ImageSearchOptions) -> Result<ImageCheck> {
+    let reader = image::ImageReader::open(path)?.with_guessed_format()?;
+    let format = match reader.format() {
+        Some(format) => format,
+        None => return Ok(ImageCheck::Rejected("not a recognised image format".to_string())),
+    };
+    
+    let (width, height) = match reader.into_dimensions() {
+        Ok(dimensions) => dimensions,
+        Err(e) => return Ok(ImageCheck::Rejected(format!("could not decode image: {}", e))),
+    };
+    
+    let limits = [
+        (options.min_width, width, "narrower", true),
+        (options.min_height, height, "shorter", true),
+        (options.max_width, width, "wider", false),
+        (options.max_height, height, "taller", false),
+    ];
+    for (limit, actual, word, is_min) in limits {
+        if let Some(limit) = limit {
+            if (is_min && actual < limit) || (!is_min && actual > limit) {
+                return Ok(ImageCheck::Rejected(format!("{}x{} is {} than {}px", width, height, word, limit)));
+            }
+        }
+    }
+    
+    if let Some(target) = options.color.as_deref().and_then(named_color) {
+        let decoded = image::ImageReader::open(path)?.with_guessed_format()?.decode()?;
+        let dominant = dominant_color(&decoded);
+        if !color_matches(dominant, target) {
+            return Ok(ImageCheck::OffColor(format!(
+                "dominant color #{:02x}{:02x}{:02x} isn't {}",
+                dominant[0], dominant[1], dominant[2], options.color.as_deref().unwrap_or_default()
+            )));
+        }
+    }
+    
+    // Rename when the extension doesn't match the real format
+    let valid_exts = format.extensions_str();
+    let current_ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    let mut final_path = path.to_path_buf();
+    if let Some(real_ext) = valid_exts.first() {
+        if !valid_exts.contains(&current_ext.as_str()) {
+            final_path = path.with_extension(real_ext);
+            fs::rename(path, &final_path)?;
+        }
+    }
+    
+    Ok(ImageCheck::Accepted { path: final_path, width, height })
+}
+
+/// Colors accepted by `--color`, with the RGB value images are compared against
+const NAMED_COLORS: &[(&str, [u8; 3])] = &[
+    ("red", [200, 30, 30]),
+    ("orange", [240, 140, 20]),
+    ("yellow", [240, 220, 40]),
+    ("green", [40, 160, 50]),
+    ("teal", [0, 128, 128]),
+    ("blue", [30, 80, 200]),
+    ("purple", [120, 50, 160]),
+    ("magenta", [220, 40, 180]),
+    ("pink", [245, 160, 190]),
+    ("brown", [120, 75, 40]),
+    ("black", [15, 15, 15]),
+    ("gray", [128, 128, 128]),
+    ("white", [240, 240, 240]),
+];
+
+/// Colors closer than this (Euclidean RGB distance) always count as a match
+const COLOR_TOLERANCE: f64 = 60.0;
+
+/// RGB value for a color name (case-insensitive, "grey" allowed)
+fn named_color(name: &str) -> Option<[u8; 3]> {
+    let name = name.trim().to_lowercase();
+    let name = if name == "grey" { "gray" } else { name.as_str() };
+    NAMED_COLORS.iter().find(|(known, _)| *known == name).map(|(_, rgb)| *rgb)
+}
+
+fn color_distance(a: [u8; 3], b: [u8; 3]) -> f64 {
+    a.iter().zip(&b).map(|(&x, &y)| (x as f64 - y as f64).powi(2)).sum::<f64>().sqrt()
+}
+
+/// Most common color in the image: pixels are grouped into coarse RGB buckets
+/// and the average of the fullest bucket is returned
+fn dominant_color(img: &image::DynamicImage) -> [u8; 3] {
+    let small = img.thumbnail(64, 64).to_rgb8();
+    let mut buckets: std::collections::HashMap<[u8; 3], (u64, [u64; 3])> = std::collections::HashMap::new();
+    for pixel in small.pixels() {
+        let [r, g, b] = pixel.0;
+        let (count, sum) = buckets.entry([r >> 5, g >> 5, b >> 5]).or_default();
+        *count += 1;
+        sum[0] += r as u64;
+        sum[1] += g as u64;
+        sum[2] += b as u64;
+    }
+    buckets.values()
+        .max_by_key(|(count, _)| *count)
+        .map(|(count, sum)| [(sum[0] / count) as u8, (sum[1] / count) as u8, (sum[2] / count) as u8])
+        .unwrap_or_default()
+}
+
+/// Whether a dominant color counts as the target: within the tolerance, or at
+/// least closer to the target than to any other named color
+fn color_matches(dominant: [u8; 3], target: [u8; 3]) -> bool {
+    let distance = color_distance(dominant, target);
+    distance <= COLOR_TOLERANCE
+        || NAMED_COLORS.iter().all(|(_, other)| color_distance(dominant, *other) >= distance)
+}
+
+/// Directory (inside the output directory) holding index thumbnails
+const THUMBNAIL_DIR: &str = ".thumbs";
+/// Longest side of a generated thumbnail in pixels
+const THUMBNAIL_SIZE: u32 = 320;
+
+/// An image on disk together with the search result it came from
+struct IndexEntry {
+    image: ImageResult,
+    path: PathBuf,
+    width: u32,
+    height: u32,
+}
+
+/// Write `index.html` in the output directory showing each image with its
+/// source, dimensions and description. Returns the path of the index.
+fn write_html_index(output_dir: &Path, entries: &[IndexEntry]) -> Result<PathBuf> {
+    let thumbs_dir = output_dir.join(THUMBNAIL_DIR);
+    fs::create_dir_all(&thumbs_dir)?;
+    
+    let mut cards = String::new();
+    for entry in entries {
+        let file_name = entry.path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        
+        // Fall back to the full image when it can't be decoded for a thumbnail
+        let thumb_name = format!("{}.jpg", file_name);
+        let thumb_src = match image::open(&entry.path) {
+            Ok(img) if img.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgb8().save(thumbs_dir.join(&thumb_name)).is_ok() => {
+                format!("{}/{}", THUMBNAIL_DIR, thumb_name)
+            }
+            _ => file_name.to_string(),
+        };
+        
+        cards.push_str(&format!(
+            "<figure><a href=\"{file}\"><img src=\"{thumb}\" loading=\"lazy\" alt=\"{alt}\"></a>\
+             <figcaption><strong>{name}</strong><br>{w}x{h} &middot; <a href=\"{url}\">{source}</a><br>{desc}{credit}</figcaption></figure>\n",
+            file = html_escape(file_name),
+            thumb = html_escape(&thumb_src),
+            alt = html_escape(entry.image.description.as_deref().unwrap_or(file_name)),
+            name = html_escape(file_name),
+            w = entry.width,
+            h = entry.height,
+            url = html_escape(&entry.image.url),
+            source = html_escape(&entry.image.source),
+            desc = html_escape(entry.image.description.as_deref().unwrap_or("")),
+            credit = credit_html(&entry.image),
+        ));
+    }
+    
+    let html = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Image index ({count})</title>\n\
+         <style>body{{font-family:sans-serif;background:#111;color:#ddd}}\
+         main{{display:grid;grid-template-columns:repeat(auto-fill,minmax({size}px,1fr));gap:12px}}\
+         figure{{margin:0;background:#222;padding:8px;border-radius:6px}}\
+         img{{max-width:100%;display:block;margin:auto}}a{{color:#8cf}}\
+         figcaption{{font-size:12px;word-break:break-word}}</style></head>\n\
+         <body><h1>{count} images</h1><main>\n{cards}</main></body></html>\n",
+        count = entries.len(),
+        size = THUMBNAIL_SIZE,
+        cards = cards,
+    );
+    
+    let index_path = output_dir.join("index.html");
+    fs::write(&index_path, html)?;
+    Ok(index_path)
+}
+
+/// License and attribution lines for an index caption (empty when the provider gave neither)
+fn credit_html(image: &ImageResult) -> String {
+    let mut credit = String::new();
+    if let Some(license) = &image.license {
+        credit.push_str(&format!("<br><em>{}</em>", html_escape(license)));
+    }
+    if let Some(attribution) = &image.attribution {
+        credit.push_str(&format!("<br><small>{}</small>", html_escape(attribution)));
+    }
+    credit
+}
+
 /// Download a single image with progress
 async fn download_single_image(
-    client: &Client,
     url: &str,
     output_path: &Path,
     progress_bar: ProgressBar,
 ) -> Result<()> {
-    // Make the request
-    let response = client.get(url)
-        .send()
+    // Make the request, waiting out short rate limits from the image host
+    let response = utils::send_with_rate_limit(browser_get(url), &utils::url_host(url), RATE_LIMIT_RETRIES)
         .await
         .with_context(|| format!("Failed to download image from {}", url))?;
     
@@ -588,8 +1342,9 @@ async fn download_single_image(
     let content_length = response.content_length().unwrap_or(0);
     progress_bar.set_length(content_length);
     
-    // Open file for writing
+    // Open file for writing; Ctrl-C deletes it until the download completes
     let mut file = File::create(output_path).await?;
+    utils::track_partial_file(output_path);
     
     // Stream the download with progress updates
     let mut stream = response.bytes_stream();
@@ -605,6 +1360,7 @@ async fn download_single_image(
     
     // Ensure file is fully written
     file.flush().await?;
+    utils::untrack_partial_file(output_path);
     
     progress_bar.finish_with_message("Complete".green().to_string());
     Ok(())
@@ -630,16 +1386,18 @@ fn extract_filename_from_url(url: &str, fallback_index: usize) -> Option<String>
 }
 
 /// Sanitize a filename to be safe for filesystem
-fn sanitize_filename(filename: &str) -> String {
+pub(crate) fn sanitize_filename(filename: &str) -> String {
     // Remove any characters that are not safe for filenames
     let sanitized: String = filename
         .chars()
         .map(|c| if c.is_alphanumeric() || c == '.' || c == '-' || c == '_' { c } else { '_' })
+        // Ensure the filename isn't too long (counted in chars so we never split a code point)
+        .take(100)
         .collect();
     
-    // Ensure the filename isn't too long
-    if sanitized.len() > 100 {
-        sanitized[0..100].to_string()
+    // Names made only of dots ("." / "..") would point outside the target directory
+    if sanitized.chars().all(|c| c == '.') {
+        "_".repeat(sanitized.len().max(1))
     } else {
         sanitized
     }
@@ -658,4 +1416,66 @@ pub fn display_image_info(image: &ImageResult) {
     if let Some(thumb) = &image.thumbnail_url {
         println!("{}: {}", "Thumbnail".green(), thumb);
     }
-} 
\ No newline at end of file
+    
+    if let Some(license) = &image.license {
+        println!("{}: {}", "License".green(), license);
+    }
+    
+    if let Some(attribution) = &image.attribution {
+        println!("{}: {}", "Attribution".green(), attribution);
+    }
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_filter() {
+        assert_eq!(named_color("Grey"), Some([128, 128, 128]));
+        assert_eq!(named_color("chartreuse"), None);
+
+        let mut img = image::RgbImage::from_pixel(10, 10, image::Rgb([230, 20, 25]));
+        for x in 0..3 {
+            img.put_pixel(x, 0, image::Rgb([0, 0, 255]));
+        }
+        let dominant = dominant_color(&image::DynamicImage::ImageRgb8(img));
+        assert_eq!(dominant, [230, 20, 25]);
+
+        assert!(color_matches(dominant, named_color("red").unwrap()));
+        assert!(!color_matches(dominant, named_color("blue").unwrap()));
+        assert!(color_matches([20, 60, 150], named_color("blue").unwrap()));
+        assert!(!color_matches([250, 250, 250], named_color("gray").unwrap()));
+    }
+
+    #[test]
+    fn test_openverse_result() {
+        let result = serde_json::json!({
+            "url": "https://live.staticflickr.com/1/2_3.jpg",
+            "width": 1024,
+            "height": 768,
+            "title": "Harbour",
+            "creator": "Jane",
+            "license": "by-sa",
+            "license_version": "2.0",
+            "attribution": "\"Harbour\" by Jane is licensed under CC BY-SA 2.0.",
+            "source": "flickr",
+            "thumbnail": "https://api.openverse.org/v1/images/x/thumb/",
+        });
+        let image = parse_openverse_result(&result).unwrap();
+        assert_eq!(image.license.as_deref(), Some("CC BY-SA 2.0"));
+        assert_eq!(image.attribution.as_deref(), Some("\"Harbour\" by Jane is licensed under CC BY-SA 2.0."));
+        assert_eq!(image.source, "Openverse (flickr)");
+        assert_eq!((image.width, image.height), (1024, 768));
+
+        let bare = serde_json::json!({"url": "https://example.com/a.png", "creator": "Sam", "license": "cc0", "license_version": "1.0"});
+        let image = parse_openverse_result(&bare).unwrap();
+        assert_eq!(image.license.as_deref(), Some("CC0 1.0"));
+        assert_eq!(image.attribution.as_deref(), Some("By Sam"));
+        assert!(parse_openverse_result(&serde_json::json!({"title": "no url"})).is_none());
+
+        assert_eq!(openverse_license_label("pdm", None), "Public Domain Mark");
+        assert_eq!(ImageLicense::By.openverse_param(), Some("cc0,pdm,by"));
+        assert_eq!(ImageLicense::Any.openverse_param(), None);
+    }
+}
@@ -1,7 +1,7 @@
-use crate::cli::{RenameArgs, SyncArgs, PortScanArgs, DnsCacheArgs, DnsAction, parse_ports, parse_header};
+use crate::cli::{ListArgs, ListSort, RenameArgs, SyncArgs, SymlinkPolicy, PortScanArgs, DnsCacheArgs, DnsAction, DnsRecordType, SpecsFormat, ExportFormat, parse_ports, parse_header, parse_cookie};
 use crate::file_ops; // Assuming file_ops will contain the implementations
 use crate::browser_ops::{self, BrowserType, BrowserDataType};
-use crate::utils::prompt;
+use crate::utils::{self, is_end_of_input, is_go_back, prompt, prompt_menu, prompt_optional, prompt_parse, prompt_with_default, prompt_yes_no};
 use crate::network_ops;
 use crate::http_ops;
 use crate::dns_ops;
@@ -17,7 +17,7 @@ use crate::audio_text_ops;
 
 use colored::*;
 use std::error::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::collections::HashMap; // Needed for http headers
 use std::io::{self}; // Remove Write
 use anyhow::{anyhow, Result}; // Add anyhow macro import
@@ -25,98 +25,310 @@ use clap::{Arg, ArgAction, Command as ClapCommand};
 
 type BoxedError = Box<dyn Error + Send + Sync>;
 
+// Menu entries: the number typed to pick one, and its name (also what the palette searches)
+const OPERATIONS: &[(&str, &str)] = &[
+    ("1", "List files in a folder"),
+    ("2", "Backup a folder"),
+    ("3", "Close all major web browsers"),
+    ("4", "Organize screenshots"),
+    ("5", "Analyze Disk Usage"),
+    ("6", "[EXPERIMENTAL] Identify Temporary Files"),
+    ("7", "Batch Rename Files (Regex)"),
+    ("8", "Find Duplicate Files"),
+    ("9", "Sync Folders (One-Way)"),
+    ("10", "Search Files by Name"),
+    ("11", "Network Bandwidth Snapshot"),
+    ("12", "Scan Host Ports"),
+    ("13", "Make HTTP Request"),
+    ("14", "Flush / View DNS Cache"),
+    ("15", "Discover Network Devices"),
+    ("16", "Ping Host"),
+    ("17", "Browser Management"),
+    ("18", "Calculator"),
+    ("19", "WHOIS Lookup"),
+    ("20", "IP/Geo/ASN Information"),
+    ("21", "Download File"),
+    ("22", "Video Downloader"),
+    ("23", "Image Downloader"),
+    ("24", "Antivirus Scanner"),
+    ("25", "PC Specs"),
+    ("26", "Audio Transcribe"),
+    ("27", "DNS Record Lookup"),
+    ("28", "Wake-on-LAN"),
+];
+
+// File remembering the last operation run, so the palette can offer it first next time
+fn last_operation_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("terminal-pc-matrix").join("last_operation"))
+}
+
+fn load_last_operation() -> Option<&'static str> {
+    let saved = std::fs::read_to_string(last_operation_file()?).ok()?;
+    operation_key(saved.trim())
+}
+
+fn save_last_operation(key: &str) {
+    if let Some(path) = last_operation_file() {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = std::fs::write(path, key);
+    }
+}
+
+// The OPERATIONS key equal to `choice`, if it is one
+fn operation_key(choice: &str) -> Option<&'static str> {
+    OPERATIONS.iter().map(|(key, _)| *key).find(|key| *key == choice)
+}
+
+fn operation_label(key: &str) -> &'static str {
+    OPERATIONS.iter().find(|(k, _)| *k == key).map(|(_, label)| *label).unwrap_or("")
+}
+
+// Operations in palette order: the last one used first, then the menu order
+fn palette_entries(last: Option<&str>) -> Vec<(&'static str, &'static str)> {
+    let mut entries: Vec<_> = OPERATIONS.to_vec();
+    if let Some(pos) = entries.iter().position(|(key, _)| Some(*key) == last) {
+        let recent = entries.remove(pos);
+        entries.insert(0, recent);
+    }
+    entries
+}
+
+// Operations whose name contains every word of `query`, ignoring case
+fn matching_operations<'a>(entries: &[(&'a str, &'a str)], query: &str) -> Vec<(&'a str, &'a str)> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    entries.iter()
+        .filter(|(_, label)| {
+            let label = label.to_lowercase();
+            words.iter().all(|word| label.contains(word.as_str()))
+        })
+        .copied()
+        .collect()
+}
+
+// Command palette: fuzzy-search the operations by name and return the chosen key.
+// At a terminal this is an interactive FuzzySelect; with scripted input it narrows by
+// substring and asks for a number when more than one operation matches.
+fn pick_operation(query: &str, last: Option<&str>) -> Result<Option<&'static str>, BoxedError> {
+    let entries = palette_entries(last);
+    if utils::prompt_is_terminal() {
+        let items: Vec<String> = entries.iter()
+            .map(|(key, label)| if Some(*key) == last { format!("{} (last used)", label) } else { label.to_string() })
+            .collect();
+        let picked = dialoguer::FuzzySelect::with_theme(&dialoguer::theme::ColorfulTheme::default())
+            .with_prompt("Search operations (Esc to cancel)")
+            .with_initial_text(query)
+            .items(&items)
+            .default(0)
+            .interact_opt()?;
+        return Ok(picked.map(|index| entries[index].0));
+    }
+
+    let matches = matching_operations(&entries, query);
+    match matches.len() {
+        0 => {
+            eprintln!("{} '{}'", "No operation matches".red(), query);
+            Ok(None)
+        }
+        1 => Ok(Some(matches[0].0)),
+        _ => {
+            for (i, (_, label)) in matches.iter().enumerate() {
+                println!("  {} {}", format!("{}.", i + 1).cyan(), label);
+            }
+            let pick: usize = prompt_parse("Which one", 1)?;
+            Ok(matches.get(pick.wrapping_sub(1)).map(|(key, _)| *key))
+        }
+    }
+}
+
 // Function to run the interactive menu (now async)
 pub async fn start_interactive_mode() -> Result<(), BoxedError> {
+    let mut last_operation = load_last_operation();
     loop {
         println!("\n{}", "--- Options ---".magenta().bold());
-        println!("  {} List files in a folder", "1.".cyan());
-        println!("  {} Backup a folder", "2.".cyan());
-        println!("  {} Close all major web browsers", "3.".cyan());
-        println!("  {} [macOS only] Organize Desktop screenshots", "4.".cyan());
-        println!("  {} Analyze Disk Usage", "5.".cyan());
-        println!("  {} [EXPERIMENTAL] Identify Temporary Files", "6.".cyan());
-        println!("  {} Batch Rename Files (Regex)", "7.".cyan());
-        println!("  {} Find Duplicate Files", "8.".cyan());
-        println!("  {} Sync Folders (One-Way)", "9.".cyan());
-        println!("  {} Search Files by Name", "10.".cyan());
-        println!("  {} Network Bandwidth Snapshot", "11.".cyan());
-        println!("  {} Scan Host Ports", "12.".cyan());
-        println!("  {} Make HTTP Request", "13.".cyan());
-        println!("  {} Flush DNS Cache", "14.".cyan());
-        println!("  {} Discover Network Devices", "15.".cyan());
-        println!("  {} Ping Host", "16.".cyan());
-        println!("  {} Browser Management", "17.".cyan());
-        println!("  {} Calculator", "18.".cyan());
-        println!("  {} WHOIS Lookup", "19.".cyan());
-        println!("  {} IP/Geo/ASN Information", "20.".cyan());
-        println!("  {} Download File", "21.".cyan());
-        println!("  {} Video Downloader", "22.".cyan());
-        println!("  {} Image Downloader", "23.".cyan());
-        println!("  {} Antivirus Scanner", "24.".cyan());
-        println!("  {} PC Specs", "25.".cyan());
-        println!("  {} Audio Transcribe", "26.".cyan());
+        for (key, label) in OPERATIONS {
+            println!("  {} {}", format!("{}.", key).cyan(), label);
+        }
         println!("  {} Quit", "q.".yellow());
+        if let Some(key) = last_operation {
+            println!("{}", format!("Last used: {}. {}", key, operation_label(key)).dimmed());
+        }
+        println!("{}", "(Type a number, / or part of a name to search, and b, or Enter where there is no default, to return to this menu)".dimmed());
 
-        let choice = prompt(&"Choose an option".bold().to_string())?;
-
-        // Wrap handlers in blocks to manage scope and add separators
-        let handler_result = match choice.as_str() {
-            "1" => { handle_list().await }
-            "2" => { handle_backup().await }
-            "3" => { handle_close_browsers().await }
-            "4" => { handle_organize_screenshots().await }
-            "5" => { handle_analyze_disk().await }
-            "6" => { handle_clean_system().await }
-            "7" => { handle_rename().await }
-            "8" => { handle_find_duplicates().await }
-            "9" => { handle_sync_folders().await }
-            "10" => { handle_search_files().await }
-            "11" => { handle_bandwidth().await }
-            "12" => { handle_port_scan().await }
-            "13" => { handle_http_request().await }
-            "14" => { handle_dns_flush().await }
-            "15" => { handle_network_devices().await }
-            "16" => { handle_ping().await }
-            "17" => { handle_browser_management().await }
-            "18" => { handle_calculator().await }
-            "19" => { handle_whois_lookup().await }
-            "20" => { handle_ip_info().await }
-            "21" => { handle_file_download().await }
-            "22" => { handle_video_download().await }
-            "23" => { handle_image_download().await }
-            "24" => { handle_antivirus().await }
-            "25" => { handle_pc_specs().await }
-            "26" => { handle_audio_transcribe().await.map_err(|e| format!("{}", e)) }
-            "q" => {
-                println!("{}", "Exiting application.".yellow());
-                break; // Exit loop
-            }
-            _ => {
-                eprintln!("{}", "Invalid choice.".red());
-                Ok(()) // Continue loop on invalid choice
+        let choice = match prompt_menu(&"Choose an option".bold().to_string()) {
+            Ok(choice) => choice,
+            // Ctrl-D at the menu quits like `q`
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                println!("\n{}", "Exiting application.".yellow());
+                break;
             }
+            Err(e) => return Err(e.into()),
         };
 
-        // Print separator after handler execution (unless quitting)
-        if choice != "q" {
-            println!("{}", "---".dimmed());
+        if choice == "q" {
+            println!("{}", "Exiting application.".yellow());
+            break;
         }
 
+        // Anything that isn't a menu number opens the palette, searching for what was typed
+        let picked = if let Some(key) = operation_key(&choice) {
+            Ok(Some(key))
+        } else if choice.is_empty() {
+            Ok(None)
+        } else {
+            pick_operation(choice.trim_start_matches('/').trim(), last_operation)
+        };
+
+        let handler_result = match picked {
+            Ok(Some(key)) => {
+                last_operation = Some(key);
+                save_last_operation(key);
+                let result = run_operation(key).await;
+                // Print separator after handler execution
+                println!("{}", "---".dimmed());
+                result
+            }
+            Ok(None) => Ok(()),
+            Err(e) => Err(e),
+        };
+
         // Handle errors from the executed handler
         if let Err(e) = handler_result {
-             eprintln!("{}: {}", "Operation failed".red().bold(), e);
-             // Decide whether to continue or break on error? For now, continue.
+            if is_go_back(e.as_ref()) {
+                println!("{}", "Back to main menu.".dimmed());
+            } else if is_end_of_input(e.as_ref()) {
+                println!("\n{}", "Exiting application.".yellow());
+                break;
+            } else {
+                eprintln!("{}: {}", "Operation failed".red().bold(), e);
+            }
+        }
+    }
+    Ok(())
+}
+
+// Run the operation behind a menu key from OPERATIONS; callers only pass keys that
+// operation_key or the palette took from that table
+async fn run_operation(key: &str) -> Result<(), BoxedError> {
+    match key {
+        "1" => { handle_list().await }
+        "2" => { handle_backup().await }
+        "3" => { handle_close_browsers().await }
+        "4" => { handle_organize_screenshots().await }
+        "5" => { handle_analyze_disk().await }
+        "6" => { handle_clean_system().await }
+        "7" => { handle_rename().await }
+        "8" => { handle_find_duplicates().await }
+        "9" => { handle_sync_folders().await }
+        "10" => { handle_search_files().await }
+        "11" => { handle_bandwidth().await }
+        "12" => { handle_port_scan().await }
+        "13" => { handle_http_request().await }
+        "14" => { handle_dns_cache().await }
+        "15" => { handle_network_devices().await }
+        "16" => { handle_ping().await }
+        "17" => { handle_browser_management().await }
+        "18" => { handle_calculator().await }
+        "19" => { handle_whois_lookup().await }
+        "20" => { handle_ip_info().await }
+        "21" => { handle_file_download().await }
+        "22" => { handle_video_download().await }
+        "23" => { handle_image_download().await }
+        "24" => { handle_antivirus().await }
+        "25" => { handle_pc_specs().await }
+        "26" => { handle_audio_transcribe().await.map_err(BoxedError::from) }
+        "27" => { handle_dns_lookup().await }
+        "28" => { handle_wake_on_lan().await }
+        _ => unreachable!("'{}' is not an OPERATIONS key", key),
+    }
+}
+
+// The non-interactive command that repeats what a handler was just told to do, built from
+// its answers and printed before it runs, so the operation can be scripted next time
+struct CliEquivalent(Vec<String>);
+
+impl CliEquivalent {
+    fn new(command: &str) -> Self {
+        CliEquivalent(vec![env!("CARGO_PKG_NAME").to_string(), command.to_string()])
+    }
+
+    // A positional argument
+    fn arg(mut self, value: impl std::fmt::Display) -> Self {
+        self.0.push(utils::shell_quote(&value.to_string()));
+        self
+    }
+
+    // `--name` when `on`
+    fn flag(mut self, name: &str, on: bool) -> Self {
+        if on {
+            self.0.push(format!("--{}", name));
+        }
+        self
+    }
+
+    // `--name value` when there is a value
+    fn opt(mut self, name: &str, value: Option<impl std::fmt::Display>) -> Self {
+        if let Some(value) = value {
+            self.0.push(format!("--{}", name));
+            self.0.push(utils::shell_quote(&value.to_string()));
+        }
+        self
+    }
+
+    // `--name value` unless the value is what the command uses anyway
+    fn opt_unless_default<T: std::fmt::Display + PartialEq>(self, name: &str, value: T, default: T) -> Self {
+        self.opt(name, (value != default).then_some(value))
+    }
+
+    fn print(&self) {
+        println!("{} {}", "Command:".dimmed(), self.0.join(" ").cyan());
+    }
+}
+
+// Ask whether to open `url` in the browser; a failure to open is reported, not fatal
+fn offer_to_open(question: &str, url: &str) -> io::Result<()> {
+    if prompt_yes_no(question, false)? {
+        if let Err(e) = utils::open_in_browser(url) {
+            eprintln!("{} {} ({})", "Could not open".yellow(), url, e);
         }
     }
     Ok(())
 }
 
+// The name clap expects on the command line for a value-enum option
+fn value_name<T: clap::ValueEnum>(value: T) -> String {
+    value.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default()
+}
+
 // Helper functions for interactive choices
 async fn handle_list() -> Result<(), BoxedError> {
     println!("{}", "List Directory".magenta());
-    let path_str = prompt("Enter the folder path to list (default: .)")?;
-    let path = if path_str.is_empty() { PathBuf::from(".") } else { PathBuf::from(path_str) };
-    file_ops::list_directory(&path).map_err(|e| e.into())
+    let path = PathBuf::from(prompt_with_default("Enter the folder path to list", ".")?);
+    let mut args = ListArgs::for_path(path);
+
+    args.tree = prompt_yes_no("Show as recursive tree?", false)?;
+    if args.tree {
+        let depth_str = prompt_optional("Maximum depth (leave empty for unlimited)")?;
+        args.depth = depth_str.trim().parse().ok();
+    }
+
+    let sort_str = prompt_with_default("Sort by (name/size/mtime)", "name")?;
+    args.sort = match sort_str.to_lowercase().as_str() {
+        "size" => ListSort::Size,
+        "mtime" => ListSort::Mtime,
+        _ => ListSort::Name,
+    };
+    args.reverse = prompt_yes_no("Reverse order?", false)?;
+
+    CliEquivalent::new("list")
+        .arg(args.path.display())
+        .flag("tree", args.tree)
+        .opt("depth", args.depth)
+        .opt_unless_default("sort", value_name(args.sort), value_name(ListSort::Name))
+        .flag("reverse", args.reverse)
+        .print();
+    file_ops::list_directory(&args).map_err(|e| e.into())
 }
 
 async fn handle_backup() -> Result<(), BoxedError> {
@@ -131,17 +343,57 @@ async fn handle_backup() -> Result<(), BoxedError> {
     }
     let source_path = PathBuf::from(source_str);
     let destination_path = PathBuf::from(destination_str);
-    file_ops::backup_directory(&source_path, &destination_path).map_err(|e| e.into())
+    let command = CliEquivalent::new("backup").arg(source_path.display()).arg(destination_path.display());
+    if prompt_yes_no("Incremental snapshot (hardlinks files unchanged since the last one)?", false)? {
+        let checksum = prompt_yes_no("Compare file contents instead of size and modification time?", false)?;
+        command.flag("incremental", true).flag("checksum", checksum).print();
+        return file_ops::incremental_backup(&source_path, &destination_path, checksum);
+    }
+    let move_source = prompt_yes_no("Move instead of copy (removes the source)?", false)?;
+    command.flag("move", move_source).print();
+    if move_source {
+        file_ops::move_directory(&source_path, &destination_path).map_err(|e| e.into())
+    } else {
+        let pb = utils::bytes_progress_bar();
+        let result = file_ops::backup_directory(&source_path, &destination_path, Some(utils::progress_bar_callback(&pb)));
+        pb.finish_and_clear();
+        result.map_err(|e| e.into())
+    }
 }
 
 async fn handle_close_browsers() -> Result<(), BoxedError> {
     println!("{}", "Close Browsers".magenta());
-    browser_ops::close_browsers()
+    let except_str = prompt_optional("Browsers to keep open, comma-separated (e.g., firefox; leave empty to close all)")?;
+    let except_names: Vec<String> = except_str.split(',')
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect();
+    let except = except_names.iter()
+        .map(|name| browser_ops::parse_browser(name))
+        .collect::<Result<Vec<_>, _>>()?;
+    let force = prompt_yes_no("Force-kill without waiting for a graceful close?", false)?;
+    CliEquivalent::new("close-browsers")
+        .opt("except", (!except_names.is_empty()).then(|| except_names.join(",")))
+        .flag("force", force)
+        .print();
+    browser_ops::close_browsers(&[], &except, force)
 }
 
 async fn handle_organize_screenshots() -> Result<(), BoxedError> {
     println!("{}", "Organize Screenshots".magenta());
-    file_ops::organize_screenshots()
+    let source_str = prompt_optional("Folder to look for screenshots in (leave empty for the OS default)")?;
+    let dest_str = prompt_optional("Folder to move them into (leave empty for 'Screenshots' inside the source)")?;
+    let by_date = prompt_yes_no("Sort into YYYY-MM subfolders?", false)?;
+    let dry_run = prompt_yes_no("Perform dry run?", false)?;
+    let source = (!source_str.is_empty()).then(|| PathBuf::from(source_str));
+    let dest = (!dest_str.is_empty()).then(|| PathBuf::from(dest_str));
+    CliEquivalent::new("organize-screenshots")
+        .opt("source", source.as_ref().map(|p| p.display()))
+        .opt("dest", dest.as_ref().map(|p| p.display()))
+        .flag("by-date", by_date)
+        .flag("dry-run", dry_run)
+        .print();
+    file_ops::organize_screenshots(source.as_deref(), dest.as_deref(), by_date, dry_run)
 }
 
 async fn handle_analyze_disk() -> Result<(), BoxedError> {
@@ -152,50 +404,72 @@ async fn handle_analyze_disk() -> Result<(), BoxedError> {
     } else {
         PathBuf::from(path_str)
     };
-    let top_str = prompt("Show top N files by size (default: 10)")?;
-    let top = top_str.parse().unwrap_or(10);
-    file_ops::analyze_disk(&path, top)
+    let top = prompt_parse("Show top N files by size", 10)?;
+    let by_extension = prompt_yes_no("Group by file extension instead?", false)?;
+    CliEquivalent::new("analyze-disk")
+        .arg(path.display())
+        .opt_unless_default("top", top, 10)
+        .flag("by-extension", by_extension)
+        .print();
+    file_ops::analyze_disk(&path, top, by_extension)
 }
 
 async fn handle_clean_system() -> Result<(), BoxedError> {
     println!("{}", "Clean System Cache/Temporary Files".magenta());
     let msg = "This is an EXPERIMENTAL feature that will show temporary and cache files."; 
     println!("{} {}", "⚠️".yellow(), msg.yellow());
+    CliEquivalent::new("clean-system").print();
     file_ops::clean_system(true) // Always dry-run for now
 }
 
 async fn handle_rename() -> Result<(), BoxedError> {
     println!("{}", "Batch Rename Files".magenta());
-    let dir_str = prompt("Enter directory containing files to rename (default: .)")?;
+    let dir = PathBuf::from(prompt_with_default("Enter directory containing files to rename", ".")?);
     let pattern_str = prompt("Enter regex pattern to match filenames")?;
     if pattern_str.is_empty() {
         return Err("Pattern cannot be empty.".into());
     }
-    let replacement_str = prompt("Enter replacement string (use $1, $2 for captures)")?;
-    let dry_run_str = prompt("Perform dry run? (yes/no, default: yes)")?;
-
-    let dir = if dir_str.is_empty() { PathBuf::from(".") } else { PathBuf::from(dir_str) };
-    let dry_run = !dry_run_str.trim().eq_ignore_ascii_case("no");
+    let replacement_str = prompt_optional("Enter replacement string (use $1, $2 for captures)")?;
+    let recursive = prompt_yes_no("Include subdirectories?", false)?;
+    let include_dirs = prompt_yes_no("Rename matching directory names too?", false)?;
+    let dry_run = prompt_yes_no("Perform dry run?", true)?;
 
     let args = RenameArgs {
         directory: dir,
         pattern: pattern_str,
         replacement: replacement_str,
         dry_run,
+        recursive,
+        include_dirs,
     };
 
+    CliEquivalent::new("rename")
+        .opt("directory", Some(args.directory.display()))
+        .opt("pattern", Some(&args.pattern))
+        .opt("replacement", Some(&args.replacement))
+        .flag("recursive", args.recursive)
+        .flag("include-dirs", args.include_dirs)
+        .flag("dry-run", args.dry_run)
+        .print();
     file_ops::rename_files(&args)
 }
 
 async fn handle_find_duplicates() -> Result<(), BoxedError> {
     println!("{}", "Find Duplicate Files".magenta());
-    let path_str = prompt("Enter directory to search for duplicates (default: .)")?;
-    let min_size_str = prompt("Enter minimum file size (e.g., 1k, default: 1k)")?;
-
-    let path = if path_str.is_empty() { PathBuf::from(".") } else { PathBuf::from(path_str) };
-    let min_size = if min_size_str.is_empty() { "1k".to_string() } else { min_size_str };
+    let path = PathBuf::from(prompt_with_default("Enter directory to search for duplicates", ".")?);
+    let min_size = prompt_with_default("Enter minimum file size (e.g., 1k)", "1k")?;
+    let algorithm = match prompt_with_default("Hash algorithm (sha256, blake3 or md5)", "sha256")?.to_lowercase().as_str() {
+        "blake3" => file_ops::HashAlgorithm::Blake3,
+        "md5" => file_ops::HashAlgorithm::Md5,
+        _ => file_ops::HashAlgorithm::Sha256,
+    };
 
-    file_ops::find_duplicates(&path, &min_size)
+    CliEquivalent::new("find-duplicates")
+        .arg(path.display())
+        .opt_unless_default("min-size", min_size.as_str(), "1k")
+        .opt_unless_default("algo", value_name(algorithm), value_name(file_ops::HashAlgorithm::Sha256))
+        .print();
+    file_ops::find_duplicates(&path, &min_size, algorithm, "64k")
 }
 
 async fn handle_sync_folders() -> Result<(), BoxedError> {
@@ -208,29 +482,41 @@ async fn handle_sync_folders() -> Result<(), BoxedError> {
     if dest_str.is_empty() {
         return Err("Destination path cannot be empty.".into());
     }
-    let delete_str = prompt("Delete extra files in destination? (yes/no, default: no)")?;
-    let dry_run_str = prompt("Perform dry run? (yes/no, default: yes)")?;
+    let delete = prompt_yes_no("Delete extra files in destination?", false)?;
+    let symlinks = match prompt_with_default("Symbolic links: preserve, follow or skip", "preserve")?.to_lowercase().as_str() {
+        "follow" => SymlinkPolicy::Follow,
+        "skip" => SymlinkPolicy::Skip,
+        _ => SymlinkPolicy::Preserve,
+    };
+    let dry_run = prompt_yes_no("Perform dry run?", true)?;
 
     let sync_args = SyncArgs {
         source: PathBuf::from(source_str),
         destination: PathBuf::from(dest_str),
-        dry_run: !dry_run_str.trim().eq_ignore_ascii_case("no"),
-        delete: delete_str.trim().eq_ignore_ascii_case("yes"),
+        dry_run,
+        delete,
+        symlinks,
     };
 
-     file_ops::sync_folders(&sync_args)
+    CliEquivalent::new("sync-folders")
+        .arg(sync_args.source.display())
+        .arg(sync_args.destination.display())
+        .flag("delete", sync_args.delete)
+        .opt_unless_default("symlinks", value_name(sync_args.symlinks), value_name(SymlinkPolicy::Preserve))
+        .flag("dry-run", sync_args.dry_run)
+        .print();
+    file_ops::sync_folders(&sync_args)
 }
 
 async fn handle_search_files() -> Result<(), BoxedError> {
     println!("{}", "Search Files".magenta());
-    let path_str = prompt("Enter directory to search within (default: .)")?;
+    let path = PathBuf::from(prompt_with_default("Enter directory to search within", ".")?);
     let query_str = prompt("Enter filename pattern to search for")?;
     if query_str.is_empty() {
         return Err("Search query cannot be empty.".into());
     }
 
-    let path = if path_str.is_empty() { PathBuf::from(".") } else { PathBuf::from(path_str) };
-
+    CliEquivalent::new("search-files").arg(path.display()).arg(&query_str).print();
     file_ops::search_files(&path, &query_str)
 }
 
@@ -238,6 +524,7 @@ async fn handle_search_files() -> Result<(), BoxedError> {
 
 async fn handle_bandwidth() -> Result<(), BoxedError> {
     println!("{}", "Network Bandwidth Snapshot".magenta());
+    CliEquivalent::new("bandwidth").print();
     network_ops::get_bandwidth_snapshot().await.map_err(|e| anyhow!("{}", e).into())
 }
 
@@ -247,13 +534,37 @@ async fn handle_port_scan() -> Result<(), BoxedError> {
     if host.is_empty() {
         return Err("Host cannot be empty.".into());
     }
-    let ports_str = prompt("Enter ports (e.g., 80, 1-1024, default: 1-1024)")?;
-    let ports = parse_ports(if ports_str.is_empty() { "1-1024" } else { &ports_str })?;
+    let ports_str = prompt_with_default("Enter ports (e.g., 80, 1-1024, top-100, top-1000, common, well-known, all)", "top-100")?;
+    let ports = parse_ports(&ports_str)?;
+    let rate: u32 = prompt_parse("Probes per second (0 for no limit)", 0)?;
+    let randomize = prompt_yes_no("Probe ports in random order?", false)?;
 
     // For now, use a default timeout. Could add prompt later.
-    let args = PortScanArgs { host, ports, timeout: 100 }; 
+    let args = PortScanArgs {
+        host,
+        ports,
+        top_100: false,
+        top_1000: false,
+        common: false,
+        well_known: false,
+        all: false,
+        timeout: 100,
+        numeric_ports: false,
+        ipv4: false,
+        ipv6: false,
+        concurrency: network_ops::DEFAULT_PORT_SCAN_CONCURRENCY,
+        rate: (rate > 0).then_some(rate),
+        randomize,
+    };
 
-    network_ops::scan_ports(&args.host, &args.ports, args.timeout).await.map_err(|e| anyhow!("{}", e).into())
+    CliEquivalent::new("port-scan")
+        .arg(&args.host)
+        .opt("ports", Some(ports_str.trim()))
+        .opt("rate", args.rate)
+        .flag("randomize", args.randomize)
+        .print();
+    let (cancel, _ctrl_c) = utils::handle_ctrl_c();
+    network_ops::scan_ports(&args.host, &args.selected_ports(), args.timeout, args.numeric_ports, args.family(), &args.pacing(), &cancel).await.map_err(|e| anyhow!("{}", e).into())
 }
 
 async fn handle_http_request() -> Result<(), BoxedError> {
@@ -262,48 +573,124 @@ async fn handle_http_request() -> Result<(), BoxedError> {
     if url.is_empty() {
         return Err("URL cannot be empty.".into());
     }
-    let method_str = prompt("Enter HTTP method (default: GET)")?;
-    let method = if method_str.is_empty() { "GET".to_string() } else { method_str.to_uppercase() };
+    let method = prompt_with_default("Enter HTTP method", "GET")?.to_uppercase();
 
+    let mut command = CliEquivalent::new("http-request").opt_unless_default("method", method.as_str(), "GET").arg(&url);
     let mut body: Option<String> = None;
     if method == "POST" || method == "PUT" || method == "PATCH" {
-        body = Some(prompt(&format!("Enter request body for {}", method))?);
+        let body_str = prompt_optional(&format!("Enter request body for {} (or @path to read it from a file)", method))?;
+        body = Some(match body_str.strip_prefix('@') {
+            Some(path) => {
+                command = command.opt("body-file", Some(path.trim()));
+                http_ops::read_body_file(Path::new(path.trim()))?
+            }
+            None => {
+                command = command.opt("body", Some(&body_str));
+                body_str
+            }
+        });
     }
 
-    let mut headers_map: HashMap<String, String> = HashMap::new();
+    let headers_file = prompt_optional("Headers file (one 'key: value' per line; leave empty to enter headers one by one)")?;
+    let mut headers_map: HashMap<String, String> = if headers_file.trim().is_empty() {
+        HashMap::new()
+    } else {
+        command = command.opt("headers-file", Some(headers_file.trim()));
+        http_ops::read_headers_file(Path::new(headers_file.trim()))?.into_iter().collect()
+    };
     loop {
-        let header_str = prompt("Add header (key=value) or press Enter to continue")?;
+        let header_str = prompt_optional("Add header (key=value) or press Enter to continue")?;
         if header_str.is_empty() {
             break;
         }
         match parse_header(&header_str) {
             Ok((key, value)) => {
+                command = command.opt("headers", Some(format!("{}={}", key, value)));
                 headers_map.insert(key, value);
             }
             Err(e) => eprintln!("{}: {}", "Invalid header format".yellow(), e),
         }
     }
 
-    // Convert HashMap to Vec<(String, String)> if needed by http_ops::make_request
-    // Or adjust make_request to accept HashMap
+    let mut cookies = Vec::new();
+    loop {
+        let cookie_str = prompt_optional("Add cookie (name=value) or press Enter to continue")?;
+        if cookie_str.is_empty() {
+            break;
+        }
+        match parse_cookie(&cookie_str) {
+            Ok(cookie) => {
+                command = command.opt("cookie", Some(format!("{}={}", cookie.0, cookie.1)));
+                cookies.push(cookie);
+            }
+            Err(e) => eprintln!("{}: {}", "Invalid cookie format".yellow(), e),
+        }
+    }
+    let jar_str = prompt_optional("Cookie jar file to load/save (leave empty for none)")?;
+    let cookie_jar = if jar_str.trim().is_empty() { None } else { Some(std::path::PathBuf::from(jar_str.trim())) };
+    let json_path = prompt_optional("JSON path to extract (e.g., .data.items[0]; leave empty for the whole body)")?;
+    let print_curl = prompt_yes_no("Print the equivalent curl command?", false)?;
+    let har_str = prompt_optional("Save the request and response as a HAR file (leave empty to skip)")?;
+    let options = http_ops::RequestOptions {
+        cookies,
+        cookie_jar,
+        json_path: if json_path.trim().is_empty() { None } else { Some(json_path.trim().to_string()) },
+        print_curl,
+        save_har: if har_str.trim().is_empty() { None } else { Some(std::path::PathBuf::from(har_str.trim())) },
+        ..Default::default()
+    };
+    command
+        .opt("cookie-jar", options.cookie_jar.as_ref().map(|p| p.display()))
+        .flag("print-curl", options.print_curl)
+        .opt("save-har", options.save_har.as_ref().map(|p| p.display()))
+        .opt("jq", options.json_path.as_ref())
+        .print();
 
-    http_ops::make_request(&method, &url, body.as_deref(), &headers_map).await
+    http_ops::make_request(&method, &url, body.as_deref(), &headers_map, &options).await
 
 }
 
-async fn handle_dns_flush() -> Result<(), BoxedError> {
-    println!("{}", "Flush DNS Cache".magenta());
-    let args = DnsCacheArgs { action: DnsAction::Flush };
+async fn handle_dns_cache() -> Result<(), BoxedError> {
+    println!("{}", "DNS Cache".magenta());
+    let action_str = prompt_with_default("Action (flush/view)", "flush")?;
+    let action = match action_str.to_lowercase().as_str() {
+        "flush" => DnsAction::Flush,
+        "view" => DnsAction::View,
+        other => return Err(format!("Unknown DNS cache action: {}", other).into()),
+    };
+    let args = DnsCacheArgs { action };
+    CliEquivalent::new("dns-cache").arg(value_name(args.action)).print();
     dns_ops::manage_dns(args.action).await
 }
 
 // New handler for network device discovery
 async fn handle_network_devices() -> Result<(), BoxedError> {
     println!("{}", "Network Device Discovery".magenta());
-    let timeout_str = prompt("Enter scan timeout in ms (default: 100)")?;
-    let timeout = timeout_str.parse().unwrap_or(100);
-    
-    network_ops::discover_network_devices(timeout).await.map_err(|e| anyhow!("{}", e).into())
+    let timeout = prompt_parse("Enter scan timeout in ms", 100)?;
+    let default_ports = network_ops::DEFAULT_PROBE_PORTS.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(",");
+    let ports = parse_ports(&prompt_with_default("Enter probe ports", &default_ports)?)?;
+    let retry = prompt_yes_no("Retry timed-out probes once?", false)?;
+    let arp = prompt_yes_no("Use ARP requests to also find devices without open ports (needs root)?", false)?;
+    let family = match prompt_with_default("Networks to scan (ipv4, ipv6 or both)", "both")?.to_lowercase().as_str() {
+        "ipv4" | "4" => network_ops::IpFamily::V4,
+        "ipv6" | "6" => network_ops::IpFamily::V6,
+        _ => network_ops::IpFamily::Any,
+    };
+    let resolve = prompt_yes_no("Look up host names (slower on networks with slow reverse DNS)?", true)?;
+    let options = network_ops::DiscoveryOptions { ports, timeout_ms: timeout, retry, arp, family, resolve };
+    let port_list = options.ports.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(",");
+    CliEquivalent::new("discover")
+        .opt_unless_default("ports", port_list, default_ports)
+        .opt_unless_default("timeout", options.timeout_ms, 350)
+        .flag("retry", retry)
+        .flag("arp", arp)
+        .flag("ipv4", family == network_ops::IpFamily::V4)
+        .flag("ipv6", family == network_ops::IpFamily::V6)
+        .flag("no-resolve", !resolve)
+        .print();
+    
+    let (cancel, _ctrl_c) = utils::handle_ctrl_c();
+    network_ops::discover_network_devices(&options, &cancel).await.map(|_| ()).map_err(|e| anyhow!("{}", e).into())
 } 
 
 // Handler for ping functionality
@@ -314,18 +701,51 @@ async fn handle_ping() -> Result<(), BoxedError> {
         return Err("Host cannot be empty.".into());
     }
     
-    let count_str = prompt("Number of ping packets to send (default: 4)")?;
-    let count = count_str.parse().unwrap_or(4);
+    let count = prompt_parse("Number of ping packets to send", 4)?;
+    CliEquivalent::new("ping").arg(&host).opt_unless_default("count", count, 4).print();
     
     network_ops::ping_host(&host, count).await.map_err(|e| anyhow!("{}", e).into())
 }
 
+// Checkbox list: at a terminal a MultiSelect (space toggles, Enter confirms); with scripted
+// input the options are numbered and the answer is a comma-separated list of numbers
+fn pick_many(title: &str, labels: &[&str]) -> Result<Vec<usize>, BoxedError> {
+    if utils::prompt_is_terminal() {
+        let picked = dialoguer::MultiSelect::with_theme(&dialoguer::theme::ColorfulTheme::default())
+            .with_prompt(format!("{} (space to toggle, Enter to confirm)", title))
+            .items(labels)
+            .interact_opt()?;
+        return Ok(picked.unwrap_or_default());
+    }
+
+    println!("{}:", title);
+    for (i, label) in labels.iter().enumerate() {
+        println!("  {}. {}", i + 1, label);
+    }
+    let answer = prompt("Enter numbers (comma-separated, e.g. 1,3)")?;
+    Ok(parse_picks(&answer, labels.len())?)
+}
+
+// Zero-based indices for a "1,3"-style answer picking from `count` numbered options,
+// in the order given and without repeats
+fn parse_picks(answer: &str, count: usize) -> Result<Vec<usize>, String> {
+    let mut picked = Vec::new();
+    for part in answer.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+        let number: usize = part.parse().map_err(|_| format!("Invalid number: {}", part))?;
+        if number == 0 || number > count {
+            return Err(format!("No option {}", number));
+        }
+        if !picked.contains(&(number - 1)) {
+            picked.push(number - 1);
+        }
+    }
+    Ok(picked)
+}
+
 // Handler for Browser Management
 async fn handle_browser_management() -> Result<(), BoxedError> {
     println!("{}", "Browser Management".magenta());
 
-    // Choose browser
-    println!("Select browser:");
     let browsers = [
         (BrowserType::Chrome, "Chrome"),
         (BrowserType::Firefox, "Firefox"),
@@ -336,72 +756,95 @@ async fn handle_browser_management() -> Result<(), BoxedError> {
         (BrowserType::Vivaldi, "Vivaldi"),
         // Add other supported browsers here
     ];
-    for (i, (_, name)) in browsers.iter().enumerate() {
-        println!("  {}. {}", i + 1, name);
-    }
-    let browser_choice_str = prompt("Enter browser number")?;
-    let browser_idx: usize = browser_choice_str.parse().map_err(|_| "Invalid number")?;
-    if browser_idx == 0 || browser_idx > browsers.len() {
-        return Err("Invalid browser selection.".into());
+    let browser_labels: Vec<&str> = browsers.iter().map(|(_, name)| *name).collect();
+    let selected_browsers = pick_many("Select browsers", &browser_labels)?;
+    if selected_browsers.is_empty() {
+        return Err("No browser selected.".into());
     }
-    let (selected_browser, browser_name) = browsers[browser_idx - 1].clone();
 
-    // Choose operation
-    println!("Select operation for {}:", browser_name.cyan());
     let operations = [
         (BrowserDataType::History, "Delete History"),
         (BrowserDataType::Cookies, "Delete Cookies"),
+        (BrowserDataType::Cache, "Delete Cache"),
+        (BrowserDataType::LocalStorage, "Delete Local Storage"),
+        (BrowserDataType::SessionStorage, "Delete Session Storage"),
         (BrowserDataType::Bookmarks, "Export Bookmarks"),
         (BrowserDataType::Passwords, "Export Passwords (experimental; Safari not supported)"),
     ];
-    for (i, (_, name)) in operations.iter().enumerate() {
-        // Disable password export for Safari explicitly
-        if selected_browser == BrowserType::Safari && operations[i].0 == BrowserDataType::Passwords {
-             println!("  {}. {} {}", i + 1, name, "(Not Supported)".dimmed());
-        } else {
-            println!("  {}. {}", i + 1, name);
-        }
+    let operation_labels: Vec<&str> = operations.iter().map(|(_, name)| *name).collect();
+    let selected_operations = pick_many("Select operations", &operation_labels)?;
+    if selected_operations.is_empty() {
+        return Err("No operation selected.".into());
     }
-    let op_choice_str = prompt("Enter operation number")?;
-    let op_idx: usize = op_choice_str.parse().map_err(|_| "Invalid number")?;
-    if op_idx == 0 || op_idx > operations.len() {
-        return Err("Invalid operation selection.".into());
-    }
-    let (selected_operation, _) = operations[op_idx - 1].clone();
+    let is_delete = |data: &BrowserDataType| !matches!(data, BrowserDataType::Bookmarks | BrowserDataType::Passwords);
 
-    // Prevent Safari password export attempt
-    if selected_browser == BrowserType::Safari && selected_operation == BrowserDataType::Passwords {
-        return Err("Password export is not supported for Safari.".into());
+    // Ask the follow-up questions once, before anything runs
+    let raw = selected_operations.iter().any(|&i| operations[i].0 == BrowserDataType::Bookmarks)
+        && prompt_yes_no("Copy the raw profile file instead of portable HTML for bookmarks?", false)?;
+    let deleting = selected_operations.iter().any(|&i| is_delete(&operations[i].0));
+    let mut force_close = Vec::new();
+    for &b in &selected_browsers {
+        let (browser, name) = &browsers[b];
+        let close = deleting && browser_ops::is_browser_running(browser)
+            && prompt_yes_no(&format!("{} is running and its files are locked. Close it now?", name), false)?;
+        force_close.push(close);
     }
 
-    // Execute operation
-    println!("Performing {:?} on {:?}...", selected_operation, selected_browser);
-    match selected_operation {
-        BrowserDataType::History | BrowserDataType::Cookies => {
-            match browser_ops::delete_browser_data(selected_browser, selected_operation) {
-                Ok(result) => {
-                    if result.success {
-                        println!("{}", result.message.green());
-                    } else {
-                        // This case shouldn't happen if Ok is returned, but handle defensively
-                        eprintln!("{}: {}", "Operation reported non-success but no error".yellow(), result.message);
-                    }
-                },
-                Err(e) => eprintln!("{}: {}", "Error deleting data".red(), e),
+    // Run every browser × operation pair, carrying on past failures
+    let mut outcomes: Vec<(String, &str, Result<String, String>)> = Vec::new();
+    for (&b, &close) in selected_browsers.iter().zip(&force_close) {
+        let (browser, browser_name) = browsers[b].clone();
+        for &o in &selected_operations {
+            let (operation, operation_name) = operations[o].clone();
+
+            // Prevent Safari password export attempt
+            if browser == BrowserType::Safari && operation == BrowserDataType::Passwords {
+                outcomes.push((browser_name.to_string(), operation_name, Err("not supported for Safari".to_string())));
+                continue;
             }
-        }
-        BrowserDataType::Bookmarks | BrowserDataType::Passwords => {
-            match browser_ops::export_browser_data(selected_browser, selected_operation) {
-                Ok(result) => {
-                    if result.success {
-                        println!("{}", result.message.green());
-                    } else {
-                        // This case shouldn't happen if Ok is returned, but handle defensively
-                        eprintln!("{}: {}", "Operation reported non-success but no error".yellow(), result.message);
-                    }
-                },
-                Err(e) => eprintln!("{}: {}", "Error exporting data".red(), e),
+
+            println!("\n{} {} on {}...", "Performing".cyan(), operation_name, browser_name);
+            let result = if is_delete(&operation) {
+                CliEquivalent::new("browser-data")
+                    .arg("delete")
+                    .arg(browser_ops::browser_cli_name(&browser))
+                    .arg(browser_ops::browser_data_cli_name(&operation))
+                    .flag("force-close", close)
+                    .print();
+                browser_ops::delete_browser_data(browser.clone(), operation, close, false)
+            } else {
+                CliEquivalent::new("browser-data")
+                    .arg("export")
+                    .arg(browser_ops::browser_cli_name(&browser))
+                    .arg(browser_ops::browser_data_cli_name(&operation))
+                    .flag("raw", raw && operation == BrowserDataType::Bookmarks)
+                    .print();
+                browser_ops::export_browser_data(browser.clone(), operation.clone(), raw && operation == BrowserDataType::Bookmarks)
+            };
+            let outcome = match result {
+                Ok(result) if result.success => {
+                    println!("{}", result.message.green());
+                    Ok(result.message)
+                }
+                // This case shouldn't happen if Ok is returned, but handle defensively
+                Ok(result) => Err(format!("reported non-success: {}", result.message)),
+                Err(e) => Err(e.to_string()),
+            };
+            if let Err(e) = &outcome {
+                eprintln!("{}: {}", "Failed".red(), e);
             }
+            outcomes.push((browser_name.to_string(), operation_name, outcome));
+        }
+    }
+
+    let failed: Vec<_> = outcomes.iter().filter(|(_, _, outcome)| outcome.is_err()).collect();
+    println!("\n{} {} succeeded, {} failed",
+        "Summary:".cyan().bold(),
+        (outcomes.len() - failed.len()).to_string().green(),
+        if failed.is_empty() { "0".normal() } else { failed.len().to_string().red() });
+    for (browser_name, operation_name, outcome) in failed {
+        if let Err(e) = outcome {
+            println!("  {} {} / {}: {}", "✗".red(), browser_name, operation_name, e);
         }
     }
 
@@ -410,9 +853,10 @@ async fn handle_browser_management() -> Result<(), BoxedError> {
 
 // Handler for Calculator
 async fn handle_calculator() -> Result<(), BoxedError> {
-    println!("{}", "Simple Calculator (Type 'q' to exit)".magenta());
+    println!("{}", "Simple Calculator (Type 'q' to exit, 'help' for commands)".magenta());
+    let mut settings = calculator_ops::CalculatorSettings::default();
     loop {
-        let expr = prompt(">>")?;
+        let expr = prompt_optional(&format!("{}>>", settings.angle.label()))?;
         if expr.eq_ignore_ascii_case("q") {
             break;
         }
@@ -420,16 +864,93 @@ async fn handle_calculator() -> Result<(), BoxedError> {
             continue;
         }
 
-        // evaluation happens synchronously within the async handler
-        match calculator_ops::evaluate_expression(&expr) {
-            Ok(_) => { /* Result already printed by evaluate_expression */ }
-            Err(e) => eprintln!("{}: {}", "Calculation Error".red(), e),
+        let words: Vec<&str> = expr.split_whitespace().collect();
+        match words.as_slice() {
+            ["mode", "deg"] => settings.angle = calculator_ops::AngleMode::Deg,
+            ["mode", "rad"] => settings.angle = calculator_ops::AngleMode::Rad,
+            ["precision", "off"] => {
+                settings.precision = None;
+                println!("{}", "Showing full precision.".dimmed());
+            }
+            ["precision", digits] if digits.parse::<usize>().map_or(false, |d| d <= 15) => {
+                settings.precision = digits.parse().ok();
+                println!("{}", format!("Showing {} decimal places.", digits).dimmed());
+            }
+            // A known command with bad arguments, or bare words that aren't a constant
+            ["mode", ..] | ["precision", ..] | ["help"] => print_calculator_help(),
+            _ if is_unknown_command(&expr) => print_calculator_help(),
+            // evaluation happens synchronously within the async handler
+            _ => match calculator_ops::evaluate_with_settings(&expr, &settings) {
+                Ok(_) => { /* Result already printed by evaluate_with_settings */ }
+                Err(e) => eprintln!("{}: {}", "Calculation Error".red(), e),
+            },
         }
     }
     Ok(())
 }
 
+// Only letters and spaces, and not just the constants pi/e, means the user meant a command
+fn is_unknown_command(input: &str) -> bool {
+    input.chars().all(|c| c.is_ascii_alphabetic() || c.is_whitespace())
+        && input.split_whitespace().any(|w| w != "pi" && w != "e")
+}
+
+fn print_calculator_help() {
+    println!("{}", "Calculator commands:".yellow());
+    println!("  mode deg | mode rad   angle unit for sin/cos/tan and their inverses");
+    println!("  precision <0-15>      decimal places to show (precision off for full)");
+    println!("  q                     back to the main menu");
+    println!("Anything else is evaluated as an expression, e.g. 2 * sin(30) + sqrt(16)");
+    println!("Percentages: 200 + 10% = 220, 50% of 200 = 100; 7 % 3 and 7 mod 3 are modulo");
+}
+
 // Handler for WHOIS Lookup
+async fn handle_dns_lookup() -> Result<(), BoxedError> {
+    println!("{}", "DNS Record Lookup".magenta());
+    let name = prompt("Enter domain name or IP address")?;
+    if name.is_empty() {
+        return Err("Name cannot be empty.".into());
+    }
+    let type_str = prompt_with_default("Record type (A, AAAA, MX, TXT, CNAME, NS, SOA, PTR)", "A")?;
+    let record_type = match type_str.to_lowercase().as_str() {
+        "a" => DnsRecordType::A,
+        "aaaa" => DnsRecordType::Aaaa,
+        "mx" => DnsRecordType::Mx,
+        "txt" => DnsRecordType::Txt,
+        "cname" => DnsRecordType::Cname,
+        "ns" => DnsRecordType::Ns,
+        "soa" => DnsRecordType::Soa,
+        "ptr" => DnsRecordType::Ptr,
+        other => return Err(format!("Unknown record type: {}", other).into()),
+    };
+    let server_str = prompt_optional("DNS server (e.g., 1.1.1.1; leave empty for the system resolver)")?;
+    let server = if server_str.trim().is_empty() {
+        None
+    } else {
+        Some(dns_ops::parse_dns_server(server_str.trim())?)
+    };
+    CliEquivalent::new("dns")
+        .arg("lookup")
+        .arg(&name)
+        .opt_unless_default("type", value_name(record_type), value_name(DnsRecordType::A))
+        .opt("server", server)
+        .print();
+    dns_ops::lookup_records(&name, record_type, server).await
+}
+
+async fn handle_wake_on_lan() -> Result<(), BoxedError> {
+    println!("{}", "Wake-on-LAN".magenta());
+    let mac = network_ops::parse_mac(&prompt("Enter the device's MAC address (e.g., 00:11:22:33:44:55)")?)?;
+    let broadcast = prompt_parse("Broadcast address", std::net::Ipv4Addr::BROADCAST)?;
+    let port = prompt_parse("UDP port", 9)?;
+    CliEquivalent::new("wol")
+        .arg(mac.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":"))
+        .opt_unless_default("broadcast", broadcast, std::net::Ipv4Addr::BROADCAST)
+        .opt_unless_default("port", port, 9)
+        .print();
+    Ok(network_ops::wake_on_lan(mac, broadcast, port).await?)
+}
+
 async fn handle_whois_lookup() -> Result<(), BoxedError> {
     println!("{}", "WHOIS Lookup".magenta());
     let domain = prompt("Enter domain name to lookup (e.g., google.com)")?;
@@ -437,9 +958,16 @@ async fn handle_whois_lookup() -> Result<(), BoxedError> {
         return Err("Domain name cannot be empty.".into());
     }
     
+    let summary = prompt_yes_no("Show only registrar, dates and name servers?", true)?;
+    
+    CliEquivalent::new("whois").arg(&domain).flag("summary", summary).print();
     match whois_ops::lookup_domain(&domain).await {
         Ok(result) => {
-            println!("{}", result);
+            if summary {
+                whois_ops::print_summary(&domain, &whois_ops::parse_whois(&result), chrono::Local::now().date_naive());
+            } else {
+                println!("{}", result);
+            }
             Ok(())
         },
         Err(e) => Err(anyhow!("WHOIS lookup failed: {}", e).into())
@@ -454,13 +982,17 @@ async fn handle_ip_info() -> Result<(), BoxedError> {
         return Err("IP address cannot be empty.".into());
     }
     
-    let show_abuse_str = prompt("Include abuse contact information? (yes/no, default: no)")?;
-    let show_abuse = show_abuse_str.trim().eq_ignore_ascii_case("yes");
+    let show_abuse = prompt_yes_no("Include abuse contact information?", false)?;
+    let show_asn = prompt_yes_no("Show ASN information?", false)?;
+    CliEquivalent::new("ip-info").arg(&ip).flag("abuse", show_abuse).flag("asn", show_asn).print();
     
-    let show_asn_str = prompt("Show ASN information? (yes/no, default: no)")?;
-    let show_asn = show_asn_str.trim().eq_ignore_ascii_case("yes");
-    
-    ip_info_ops::lookup_ip_info(&ip, show_abuse, show_asn).await.map_err(|e| anyhow!("IP info lookup failed: {}", e).into())
+    // Offline databases come from $GEOIP_DB here, so the command above stays the same
+    let info = ip_info_ops::lookup_ip_info(&ip, show_abuse, show_asn, &ip_info_ops::geoip_databases(Vec::new())).await
+        .map_err(|e| anyhow!("IP info lookup failed: {}", e))?;
+    if let Some(url) = ip_info_ops::map_url(&info) {
+        offer_to_open("\nShow the location on a map in your browser?", &url)?;
+    }
+    Ok(())
 }
 
 // Handler for File Download
@@ -472,32 +1004,53 @@ async fn handle_file_download() -> Result<(), BoxedError> {
         return Err("URL cannot be empty.".into());
     }
     
-    // Extract filename from URL if output is not specified
-    let default_filename = {
-        let url_parts: Vec<&str> = url.split('/').collect();
-        url_parts.last()
-            .filter(|s| !s.is_empty())
-            .unwrap_or(&"downloaded_file")
-            .to_string()
-    };
-    
-    let output_str = prompt(&format!("Enter output path (default: {})", default_filename))?;
+    // Leaving the output empty lets the server's suggested filename win over the URL-derived one
+    let default_filename = file_download_ops::filename_from_url(&url);
+    let output_str = prompt_optional(&format!("Enter output path (default: server filename or {})", default_filename))?;
     let output_path = if output_str.is_empty() {
-        PathBuf::from(default_filename)
+        None
     } else {
-        PathBuf::from(output_str)
+        Some(PathBuf::from(output_str))
     };
     
-    let retries_str = prompt("Number of retries (default: 5)")?;
-    let retries = retries_str.parse().unwrap_or(5);
-    
-    let resume_str = prompt("Resume download if file exists? (yes/no, default: yes)")?;
-    let resume = !resume_str.trim().eq_ignore_ascii_case("no");
-    
-    let parallel_str = prompt("Number of parallel connections (default: 1)")?;
-    let parallel = parallel_str.parse().unwrap_or(1);
+    let mut options = file_download_ops::FileDownloadOptions::default();
+
+    options.retries = prompt_parse("Number of retries", 5)?;
+    options.resume = prompt_yes_no("Resume download if file exists?", true)?;
+    options.parallel = prompt_parse("Number of parallel connections", 1)?;
+
+    let rate_str = prompt_optional("Rate limit in bytes/s? (e.g., 2M, leave empty for unlimited)")?;
+    if !rate_str.is_empty() {
+        options.rate_limit = Some(file_ops::parse_size(&rate_str)?);
+    }
+    let mut command = CliEquivalent::new("download")
+        .arg(&url)
+        .opt("output", output_path.as_ref().map(|p| p.display()))
+        .opt_unless_default("retries", options.retries, 5)
+        .flag("resume", options.resume)
+        .opt_unless_default("parallel", options.parallel, 1)
+        .opt("rate-limit", (!rate_str.is_empty()).then_some(&rate_str));
+
+    loop {
+        let mirror = prompt_optional("Add mirror URL or press Enter to continue")?;
+        if mirror.is_empty() {
+            break;
+        }
+        command = command.opt("mirror", Some(&mirror));
+        options.mirrors.push(mirror);
+    }
+
+    let sha256_str = prompt_optional("Expected SHA-256 checksum (leave empty to skip verification)")?;
+    command.opt("sha256", (!sha256_str.is_empty()).then_some(&sha256_str)).print();
+    if !sha256_str.is_empty() {
+        options.checksum = Some(file_download_ops::Checksum { algorithm: file_ops::HashAlgorithm::Sha256, expected: sha256_str });
+    }
     
-    file_download_ops::download_file(&url, &output_path, retries, resume, parallel).await.map_err(|e| anyhow!("Download failed: {}", e).into())
+    let (cancel, _ctrl_c) = utils::handle_ctrl_c();
+    file_download_ops::download_file(&url, output_path.as_deref(), &options, None, &cancel)
+        .await
+        .map(|_| ())
+        .map_err(|e| anyhow!("Download failed: {}", e).into())
 }
 
 // Handler for Video Download
@@ -518,10 +1071,8 @@ async fn handle_video_download() -> Result<(), BoxedError> {
     }
     
     // Ask if the user wants to download or just get info
-    let info_mode_str = prompt("Just display video info? (yes/no, default: no)")?;
-    let info_mode = info_mode_str.trim().eq_ignore_ascii_case("yes");
-    
-    if info_mode {
+    if prompt_yes_no("Just display video info?", false)? {
+        CliEquivalent::new("video-download").arg(&url).flag("info-only", true).print();
         // Show video information
         match video_download_ops::get_video_info(&url).await {
             Ok(info) => {
@@ -534,16 +1085,17 @@ async fn handle_video_download() -> Result<(), BoxedError> {
     }
     
     // Get output directory
-    let output_dir_str = prompt("Enter output directory (default: current directory)")?;
-    let output_dir = if output_dir_str.is_empty() {
-        PathBuf::from(".")
-    } else {
-        PathBuf::from(output_dir_str)
-    };
+    let output_dir = PathBuf::from(prompt_with_default("Enter output directory", ".")?);
     
     // Create download options struct with defaults
     let mut options = video_download_ops::DownloadOptions::default();
     
+    let template_str = prompt_optional(&format!("Output filename template (leave empty for {})", video_download_ops::DEFAULT_OUTPUT_TEMPLATE))?;
+    if !template_str.trim().is_empty() {
+        video_download_ops::validate_output_template(template_str.trim())?;
+        options.output_template = Some(template_str.trim().to_string());
+    }
+    
     // Get quality preference
     println!("\n{}", "Quality Options:".cyan());
     println!("  1. Best quality");
@@ -553,7 +1105,7 @@ async fn handle_video_download() -> Result<(), BoxedError> {
     println!("  5. Lowest quality (saves bandwidth)");
     println!("  6. Audio only (MP3)");
     
-    let quality_choice = prompt("Select quality (1-6, default: 1)")?;
+    let quality_choice = prompt_with_default("Select quality (1-6)", "1")?;
     options.quality = match quality_choice.as_str() {
         "2" => video_download_ops::VideoQuality::HD1080,
         "3" => video_download_ops::VideoQuality::HD720,
@@ -563,51 +1115,70 @@ async fn handle_video_download() -> Result<(), BoxedError> {
         _ => video_download_ops::VideoQuality::Best,
     };
     
+    // Offer an exact format id for users who know which stream they want
+    if prompt_yes_no("List available formats to pick a specific one?", false)? {
+        match video_download_ops::list_formats(&url).await {
+            Ok(formats) => video_download_ops::print_formats_table(&formats),
+            Err(e) => println!("{} {}", "Could not list formats:".yellow(), e),
+        }
+        let format_id_str = prompt_optional("Format id (e.g., 137+140, leave empty to use the selected quality)")?;
+        if !format_id_str.trim().is_empty() {
+            options.format_id = Some(format_id_str.trim().to_string());
+        }
+    }
+    
     // Check if audio only is selected
     options.audio_only = options.quality == video_download_ops::VideoQuality::AudioOnly;
     
     // If not audio only, ask if they want to extract audio
     if !options.audio_only {
-        let extract_audio_str = prompt("Extract audio only? (yes/no, default: no)")?;
-        options.audio_only = extract_audio_str.trim().eq_ignore_ascii_case("yes");
+        options.audio_only = prompt_yes_no("Extract audio only?", false)?;
     }
     
     // Ask about performance optimizations
     println!("\n{}", "Performance Options:".cyan());
     
     // Ask about parallel downloads for playlists
-    let parallel_str = prompt("Number of parallel downloads for playlists (1-10, default: 3)")?;
-    if !parallel_str.is_empty() {
-        if let Ok(parallel) = parallel_str.parse::<usize>() {
-            if parallel > 0 && parallel <= 10 {
-                options.concurrent_downloads = parallel;
-            }
-        }
+    let parallel: usize = prompt_parse(&format!("Number of parallel downloads for playlists (1-{})", utils::max_download_concurrency()), options.concurrent_downloads)?;
+    options.concurrent_downloads = utils::clamp_download_concurrency(parallel);
+    
+    // Ask which playlist items to fetch
+    let items_str = prompt_optional("Playlist items to download (e.g., 1-5,8,10-, leave empty for all)")?;
+    if !items_str.trim().is_empty() {
+        options.playlist_items = Some(items_str.trim().to_string());
+    }
+    let failed_list_str = prompt_optional("Save URLs of failed playlist videos to a file? (leave empty to skip, e.g., failed.txt)")?;
+    if !failed_list_str.trim().is_empty() {
+        options.failed_list = Some(PathBuf::from(failed_list_str.trim()));
     }
     
     // Ask about rate limiting
-    let rate_limit_str = prompt("Rate limit in bytes/s? (e.g., 2M for 2MB/s, leave empty for unlimited)")?;
+    let rate_limit_str = prompt_optional("Rate limit in bytes/s? (e.g., 2M for 2MB/s, leave empty for unlimited)")?;
     if !rate_limit_str.is_empty() {
         options.max_rate = Some(rate_limit_str);
     }
     
     // Ask about subtitles
-    let subtitles_str = prompt("Download subtitles if available? (yes/no, default: no)")?;
-    options.subtitles = subtitles_str.trim().eq_ignore_ascii_case("yes");
+    options.subtitles = prompt_yes_no("Download subtitles if available?", false)?;
+    if options.subtitles {
+        let langs_str = prompt_with_default("Subtitle languages, comma-separated", &options.sub_langs.join(","))?;
+        options.sub_langs = langs_str.split(',').map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect();
+        options.auto_subs = !prompt_yes_no("Prefer human-written over auto-generated subtitles?", false)?;
+        options.embed_subs = prompt_yes_no("Embed subtitles in the video file?", false)?;
+    }
+    
+    // Ask about embedding
+    options.embed_metadata = prompt_yes_no("Embed title/artist/date metadata? (needs ffmpeg)", false)?;
+    options.embed_thumbnail = prompt_yes_no("Embed the thumbnail as cover art? (needs ffmpeg)", false)?;
     
     // Ask about proxy
-    let proxy_str = prompt("Use proxy? (URL or leave empty for none)")?;
+    let proxy_str = prompt_optional("Use proxy? (URL or leave empty for none)")?;
     if !proxy_str.is_empty() {
         options.proxy = Some(proxy_str);
     }
     
     // Ask about retries
-    let retries_str = prompt("Number of retries on failure (default: 10)")?;
-    if !retries_str.is_empty() {
-        if let Ok(retries) = retries_str.parse::<usize>() {
-            options.retries = retries;
-        }
-    }
+    options.retries = prompt_parse("Number of retries on failure", options.retries)?;
     
     // Show a summary of the download options
     println!("\n{}", "Download Summary:".cyan().bold());
@@ -620,18 +1191,47 @@ async fn handle_video_download() -> Result<(), BoxedError> {
     }
     println!("Parallel downloads: {}", options.concurrent_downloads);
     println!("Download subtitles: {}", if options.subtitles { "Yes" } else { "No" });
+    if options.subtitles {
+        println!("Subtitle languages: {} ({}, {})",
+            options.sub_langs.join(","),
+            if options.auto_subs { "auto-generated" } else { "human-written" },
+            if options.embed_subs { "embedded" } else { "sidecar .srt" });
+    }
     if let Some(proxy) = &options.proxy {
         println!("Using proxy: {}", proxy);
     }
     println!("Retries: {}", options.retries);
     
-    let confirm_str = prompt("\nStart download with these settings? (yes/no, default: yes)")?;
-    if confirm_str.trim().eq_ignore_ascii_case("no") {
+    let defaults = video_download_ops::DownloadOptions::default();
+    let human_subs = options.subtitles && !options.auto_subs;
+    CliEquivalent::new("video-download")
+        .arg(&url)
+        .opt_unless_default("output-dir", output_dir.display().to_string(), ".".to_string())
+        .opt("output-template", options.output_template.as_ref())
+        .opt_unless_default("quality", options.quality.as_str(), defaults.quality.as_str())
+        .opt("format-id", options.format_id.as_ref())
+        .flag("audio-only", options.audio_only && options.quality != video_download_ops::VideoQuality::AudioOnly)
+        .opt_unless_default("concurrent", options.concurrent_downloads, defaults.concurrent_downloads)
+        .opt("playlist-items", options.playlist_items.as_ref())
+        .opt("failed-list", options.failed_list.as_ref().map(|p| p.display()))
+        .opt("rate-limit", options.max_rate.as_ref())
+        .flag("subtitles", options.subtitles)
+        .opt("sub-langs", (options.subtitles && options.sub_langs != defaults.sub_langs).then(|| options.sub_langs.join(",")))
+        .flag("human-subs", human_subs)
+        .flag("embed-subs", options.subtitles && options.embed_subs)
+        .flag("embed-metadata", options.embed_metadata)
+        .flag("embed-thumbnail", options.embed_thumbnail)
+        .opt("proxy", options.proxy.as_ref())
+        .opt_unless_default("retries", options.retries, defaults.retries)
+        .print();
+    
+    if !prompt_yes_no("\nStart download with these settings?", true)? {
         return Ok(());
     }
     
     // Perform the download with full options
-    match video_download_ops::download_video_with_options(&url, &output_dir, &options).await {
+    let (cancel, _ctrl_c) = utils::handle_ctrl_c();
+    match video_download_ops::download_video_with_options(&url, &output_dir, &options, &cancel).await {
         Ok(_) => {
             println!("{}", "Video downloaded successfully.".green());
             Ok(())
@@ -651,8 +1251,7 @@ async fn handle_image_download() -> Result<(), BoxedError> {
     }
     
     // Get number of images to download
-    let count_str = prompt("Number of images to download (default: 10)")?;
-    let count = count_str.parse().unwrap_or(10);
+    let count = prompt_parse("Number of images to download", 10)?;
     
     // Create search options with defaults
     let mut options = image_download_ops::ImageSearchOptions::default();
@@ -663,7 +1262,7 @@ async fn handle_image_download() -> Result<(), BoxedError> {
     println!("\n{}", "Filtering Options:".cyan());
     
     // Minimum dimensions
-    let min_dimensions_str = prompt("Minimum dimensions (format: WIDTHxHEIGHT, default: 800x600)")?;
+    let min_dimensions_str = prompt_optional("Minimum dimensions (format: WIDTHxHEIGHT, default: 800x600)")?;
     if !min_dimensions_str.is_empty() {
         if let Some((width_str, height_str)) = min_dimensions_str.split_once('x') {
             if let Ok(width) = width_str.parse::<u32>() {
@@ -676,32 +1275,31 @@ async fn handle_image_download() -> Result<(), BoxedError> {
     }
     
     // Safe search
-    let safe_search_str = prompt("Enable safe search? (yes/no, default: yes)")?;
-    options.safe_search = !safe_search_str.trim().eq_ignore_ascii_case("no");
+    options.safe_search = prompt_yes_no("Enable safe search?", true)?;
     
     // Color filter
-    let color_str = prompt("Filter by color? (red, green, blue, yellow, black, white, or leave empty)")?;
+    let color_str = prompt_optional("Filter by color? (red, green, blue, yellow, black, white, or leave empty)")?;
     if !color_str.is_empty() {
         options.color = Some(color_str);
     }
     
+    // License filter (only Openverse reports licenses)
+    let license_str = prompt_with_default("Required license (cc0/by/by-sa/any)", "any")?;
+    options.license = match license_str.to_lowercase().as_str() {
+        "cc0" => image_download_ops::ImageLicense::Cc0,
+        "by" => image_download_ops::ImageLicense::By,
+        "by-sa" => image_download_ops::ImageLicense::BySa,
+        _ => image_download_ops::ImageLicense::Any,
+    };
+    
     // Concurrent downloads
-    let concurrent_str = prompt("Number of concurrent downloads (1-10, default: 5)")?;
-    if !concurrent_str.is_empty() {
-        if let Ok(concurrent) = concurrent_str.parse::<usize>() {
-            if concurrent > 0 && concurrent <= 10 {
-                options.concurrent_downloads = concurrent;
-            }
-        }
-    }
+    let concurrent: usize = prompt_parse(&format!("Number of concurrent downloads (1-{})", utils::max_download_concurrency()), options.concurrent_downloads)?;
+    options.concurrent_downloads = utils::clamp_download_concurrency(concurrent);
     
     // Get output directory
-    let output_dir_str = prompt("Enter output directory (default: ./images)")?;
-    let output_dir = if output_dir_str.is_empty() {
-        PathBuf::from("./images")
-    } else {
-        PathBuf::from(output_dir_str)
-    };
+    let output_dir = PathBuf::from(prompt_with_default("Enter output directory", "./images")?);
+    options.overwrite = prompt_yes_no("Re-download images already in that directory?", false)?;
+    options.html_index = prompt_yes_no("Write an index.html with thumbnails when done?", false)?;
     
     // Show a summary
     println!("\n{}", "Search Summary:".cyan().bold());
@@ -711,15 +1309,32 @@ async fn handle_image_download() -> Result<(), BoxedError> {
     if let Some(color) = &options.color {
         println!("Color filter: {}", color);
     }
+    if options.license != image_download_ops::ImageLicense::Any {
+        println!("License: {} or less restrictive (Openverse only)", value_name(options.license));
+    }
     println!("Min dimensions: {}x{}", 
         options.min_width.unwrap_or(0), 
         options.min_height.unwrap_or(0));
     println!("Output directory: {}", output_dir.display());
     println!("Concurrent downloads: {}", options.concurrent_downloads);
+    println!("Overwrite existing: {}", if options.overwrite { "Yes" } else { "No" });
+    
+    CliEquivalent::new("image-download")
+        .arg(&options.query)
+        .opt_unless_default("count", options.count, 10)
+        .opt("output-dir", (output_dir != Path::new("./images")).then(|| output_dir.display()))
+        .opt("min-width", options.min_width)
+        .opt("min-height", options.min_height)
+        .opt("color", options.color.as_ref())
+        .flag("unsafe-search", !options.safe_search)
+        .opt_unless_default("concurrent", options.concurrent_downloads, utils::default_download_concurrency(5))
+        .flag("overwrite", options.overwrite)
+        .flag("index", options.html_index)
+        .opt_unless_default("license", value_name(options.license), value_name(image_download_ops::ImageLicense::Any))
+        .print();
     
     // Confirm
-    let confirm_str = prompt("\nSearch for images with these settings? (yes/no, default: yes)")?;
-    if confirm_str.trim().eq_ignore_ascii_case("no") {
+    if !prompt_yes_no("\nSearch for images with these settings?", true)? {
         return Ok(());
     }
     
@@ -743,15 +1358,15 @@ async fn handle_image_download() -> Result<(), BoxedError> {
             if images.len() > preview_count {
                 println!("\n{} more images found...", images.len() - preview_count);
             }
+            offer_to_open("\nOpen the first image in your browser?", &images[0].url)?;
             
             // Ask to download
-            let download_str = prompt("\nDownload these images? (yes/no, default: yes)")?;
-            if download_str.trim().eq_ignore_ascii_case("no") {
+            if !prompt_yes_no("\nDownload these images?", true)? {
                 return Ok(());
             }
             
             // Download images
-            match image_download_ops::download_images(&images, &output_dir, options.concurrent_downloads).await {
+            match image_download_ops::download_images(&images, &output_dir, &options).await {
                 Ok(_) => {
                     println!("\n{}", "Images downloaded successfully.".green());
                     Ok(())
@@ -778,12 +1393,22 @@ async fn handle_antivirus() -> Result<(), BoxedError> {
         Err(e) => println!("{} {}", "Couldn't get ClamAV version:".yellow(), e),
     }
     
+    // Use the clamd daemon when it is running; it skips reloading the signatures
+    let mut options = antivirus_ops::ScanOptions { engine: antivirus_ops::choose_engine(None), ..Default::default() };
+    if options.engine == antivirus_ops::ScanEngine::Clamd {
+        println!("{}", "Using the clamd daemon for scanning.".green());
+    }
+    
     // Show scan options
     println!("\n{}", "Select scan type:".cyan());
     println!("  1. Scan single file");
     println!("  2. Scan directory (non-recursive)");
     println!("  3. Scan directory recursively");
     println!("  4. Update virus definitions");
+    println!("  5. List quarantined files");
+    println!("  6. Restore a quarantined file");
+    println!("  7. Purge old quarantined files");
+    println!("  8. Empty the quarantine");
     
     let scan_type = prompt("Enter option")?;
     
@@ -796,7 +1421,8 @@ async fn handle_antivirus() -> Result<(), BoxedError> {
             }
             
             let path = PathBuf::from(file_path);
-            match antivirus_ops::scan_file(&path) {
+            CliEquivalent::new("scan").arg(path.display()).print();
+            match antivirus_ops::scan_file(&path, &options) {
                 Ok(result) => {
                     match result.status {
                         antivirus_ops::ScanStatus::Clean => {
@@ -804,16 +1430,14 @@ async fn handle_antivirus() -> Result<(), BoxedError> {
                         },
                         antivirus_ops::ScanStatus::Infected => {
                             println!("{} {} - {}", "🔴 Infected:".red(), path.display(), 
-                                     result.threat_name.unwrap_or_else(|| "Unknown threat".to_string()));
+                                     result.threat_name.as_deref().unwrap_or("Unknown threat"));
                             
                             // Ask if the user wants to quarantine the file
-                            let quarantine = prompt("Quarantine this file? (yes/no, default: no)")?;
-                            if quarantine.trim().eq_ignore_ascii_case("yes") {
-                                let home_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-                                let quarantine_dir = home_dir.join(".quarantine");
+                            if prompt_yes_no("Quarantine this file?", false)? {
+                                let quarantine_dir = antivirus_ops::default_quarantine_dir();
                                 
-                                match antivirus_ops::quarantine_file(&path, &quarantine_dir) {
-                                    Ok(new_path) => println!("{} {} -> {}", "File quarantined:".green(), path.display(), new_path.display()),
+                                match antivirus_ops::quarantine_file(&path, &quarantine_dir, result.threat_name.as_deref(), false) {
+                                    Ok(record) => println!("{} {} (id {})", "File quarantined:".green(), path.display(), record.id.cyan()),
                                     Err(e) => println!("{} {}", "Failed to quarantine file:".red(), e),
                                 }
                             }
@@ -840,29 +1464,63 @@ async fn handle_antivirus() -> Result<(), BoxedError> {
             };
             
             let recursive = scan_type == "3";
+            let exclude_str = prompt_optional("Globs to skip, comma-separated (e.g., *.iso,*.vdi; leave empty for none)")?;
+            options.exclude = exclude_str.split(',')
+                .map(|pattern| pattern.trim().to_string())
+                .filter(|pattern| !pattern.is_empty())
+                .collect();
+            let max_size_str = prompt_optional("Skip files larger than (e.g., 500M; leave empty for no limit)")?;
+            if !max_size_str.trim().is_empty() {
+                options.max_file_size = Some(file_ops::parse_size(&max_size_str)?);
+            }
+            options.changed_only = prompt_yes_no("Only scan files changed since the last scan of this directory?", false)?;
+            let report_str = prompt_optional("Save a report to (.json or .csv; leave empty to skip)")?;
+            let report_path = (!report_str.trim().is_empty()).then(|| PathBuf::from(report_str.trim()));
+            let mut command = CliEquivalent::new("scan").arg(path.display()).flag("recursive", recursive);
+            for pattern in &options.exclude {
+                command = command.opt("exclude", Some(pattern));
+            }
+            command
+                .opt("max-file-size", (!max_size_str.trim().is_empty()).then(|| max_size_str.trim()))
+                .flag("changed-only", options.changed_only)
+                .opt("output", report_path.as_ref().map(|p| p.display()))
+                .print();
             println!("{} {} ({})", "Scanning directory:".cyan(), path.display(), 
                      if recursive { "recursive" } else { "non-recursive" });
             
-            match antivirus_ops::scan_directory(&path, recursive) {
+            let started_at = chrono::Local::now().to_rfc3339();
+            match antivirus_ops::scan_directory(&path, recursive, &options) {
                 Ok(results) => {
                     // Print scan results
                     let formatted_results = antivirus_ops::format_scan_results(&results);
                     println!("{}", formatted_results);
                     
+                    if let Some(report_path) = &report_path {
+                        let report = antivirus_ops::ScanReport {
+                            target: &path,
+                            started_at,
+                            finished_at: chrono::Local::now().to_rfc3339(),
+                            engine: options.engine,
+                            signatures: antivirus_ops::signature_version(options.engine),
+                            results: &results,
+                        };
+                        if let Err(e) = antivirus_ops::export_scan_report(&report, report_path, ExportFormat::for_path(report_path)) {
+                            eprintln!("{}: {}", "Could not save the report".red(), e);
+                        }
+                    }
+                    
                     // If we found infected files, offer to quarantine them
                     let infected_files: Vec<_> = results.iter()
                         .filter(|r| r.status == antivirus_ops::ScanStatus::Infected)
                         .collect();
                     
                     if !infected_files.is_empty() {
-                        let quarantine = prompt("Quarantine infected files? (yes/no, default: no)")?;
-                        if quarantine.trim().eq_ignore_ascii_case("yes") {
-                            let home_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-                            let quarantine_dir = home_dir.join(".quarantine");
+                        if prompt_yes_no("Quarantine infected files?", false)? {
+                            let quarantine_dir = antivirus_ops::default_quarantine_dir();
                             
                             for result in infected_files {
-                                match antivirus_ops::quarantine_file(&result.path, &quarantine_dir) {
-                                    Ok(new_path) => println!("{} {} -> {}", "File quarantined:".green(), result.path.display(), new_path.display()),
+                                match antivirus_ops::quarantine_file(&result.path, &quarantine_dir, result.threat_name.as_deref(), false) {
+                                    Ok(record) => println!("{} {} (id {})", "File quarantined:".green(), result.path.display(), record.id.cyan()),
                                     Err(e) => println!("{} {}: {}", "Failed to quarantine file".red(), result.path.display(), e),
                                 }
                             }
@@ -884,6 +1542,45 @@ async fn handle_antivirus() -> Result<(), BoxedError> {
                 },
             }
         },
+        "5" => {
+            CliEquivalent::new("quarantine").flag("list", true).print();
+            let records = antivirus_ops::list_quarantine(&antivirus_ops::default_quarantine_dir())?;
+            println!("{}", antivirus_ops::format_quarantine_list(&records));
+        },
+        "6" => {
+            let id = prompt("Enter quarantine id to restore")?;
+            if id.is_empty() {
+                return Err("Quarantine id cannot be empty.".into());
+            }
+            CliEquivalent::new("quarantine").opt("restore", Some(&id)).print();
+            match antivirus_ops::restore_file(&id, &antivirus_ops::default_quarantine_dir(), false) {
+                Ok(path) => println!("{} {}", "File restored:".green(), path.display()),
+                Err(e) => println!("{} {}", "Restore failed:".red(), e),
+            }
+        },
+        "7" | "8" => {
+            let empty = scan_type == "8";
+            let (older_than, age) = if empty {
+                (None, None)
+            } else {
+                let age = prompt_with_default("Delete files quarantined longer ago than", "30d")?;
+                (Some(antivirus_ops::parse_retention(&age)?), Some(age))
+            };
+            let secure = prompt_yes_no("Overwrite files before deleting them?", false)?;
+            if empty && !prompt_yes_no("Permanently delete everything in the quarantine?", false)? {
+                println!("Nothing deleted.");
+                return Ok(());
+            }
+            CliEquivalent::new("quarantine")
+                .flag("purge", !empty)
+                .opt("older-than", age.as_deref())
+                .flag("empty", empty)
+                .flag("secure", secure)
+                .flag("yes", empty)
+                .print();
+            let removed = antivirus_ops::purge_quarantine(&antivirus_ops::default_quarantine_dir(), older_than, secure, false)?;
+            println!("{} {} item(s)", "Deleted".green(), removed.len());
+        },
         _ => {
             return Err("Invalid option.".into());
         }
@@ -899,22 +1596,45 @@ async fn handle_pc_specs() -> Result<(), BoxedError> {
     println!("\n{}", "Select an option:".cyan());
     println!("  1. View PC specifications");
     println!("  2. Save PC specifications to file");
+    println!("  3. Compare two saved JSON snapshots");
     
     let option = prompt("Enter option")?;
     
     match option.as_str() {
         "1" => {
+            CliEquivalent::new("pc-specs").print();
             pc_specs_ops::display_system_info().map_err(|e| anyhow!("{}", e).into())
         },
         "2" => {
-            let file_path = prompt("Enter file path to save PC specs (default: pc_specs.txt)")?;
-            let path = if file_path.is_empty() {
-                PathBuf::from("pc_specs.txt")
-            } else {
-                PathBuf::from(file_path)
+            let format_str = prompt_with_default("Format (text/json/markdown)", "text")?;
+            let format = match format_str.to_lowercase().as_str() {
+                "json" => SpecsFormat::Json,
+                "markdown" | "md" => SpecsFormat::Markdown,
+                _ => SpecsFormat::Text,
             };
+            let default_name = match format {
+                SpecsFormat::Text => "pc_specs.txt",
+                SpecsFormat::Json => "pc_specs.json",
+                SpecsFormat::Markdown => "pc_specs.md",
+            };
+            
+            let path = PathBuf::from(prompt_with_default("Enter file path to save PC specs", default_name)?);
+            CliEquivalent::new("pc-specs")
+                .opt("output", Some(path.display()))
+                .opt("format", (format != SpecsFormat::Text).then(|| value_name(format)))
+                .print();
+            
+            pc_specs_ops::save_system_info_to_file(&path, format).map_err(|e| anyhow!("{}", e).into())
+        },
+        "3" => {
+            let old = PathBuf::from(prompt("Enter path of the older snapshot")?);
+            let new = PathBuf::from(prompt_with_default("Enter path of the newer snapshot", "pc_specs.json")?);
+            CliEquivalent::new("pc-specs")
+                .opt("compare", Some(old.display()))
+                .arg(new.display())
+                .print();
             
-            pc_specs_ops::save_system_info_to_file(&path).map_err(|e| anyhow!("{}", e).into())
+            pc_specs_ops::compare_snapshots(&old, &new).map_err(|e| anyhow!("{}", e).into())
         },
         _ => {
             Err("Invalid option.".into())
@@ -926,25 +1646,27 @@ async fn handle_pc_specs() -> Result<(), BoxedError> {
 async fn handle_audio_transcribe() -> Result<(), String> {
     println!("{}", "===== Audio Transcription =====".magenta().bold());
     
+    // Keep the error text as is, so `b` is still recognised as going back
+    let read_err = |e: io::Error| e.to_string();
+    
     // Get file path
-    println!("Enter the path to the audio or video file:");
-    let file_path = read_line().map_err(|e| format!("Failed to read input: {}", e))?;
+    let file_path = prompt("Enter the path to the audio or video file").map_err(read_err)?;
     if file_path.trim().is_empty() {
         return Err("File path cannot be empty".to_string());
     }
     
     // Model size
-    println!("Select model size (default: base):");
+    println!("Model sizes:");
     println!("1. Tiny (fastest, least accurate)");
     println!("2. Base (default)");
     println!("3. Small");
     println!("4. Medium");
     println!("5. Large (slowest, most accurate)");
-    let model_choice = read_line().map_err(|e| format!("Failed to read input: {}", e))?;
+    let model_choice = prompt_with_default("Select model size (1-5)", "2").map_err(read_err)?;
     
-    let model_size = match model_choice.trim() {
+    let model_size = match model_choice.as_str() {
         "1" => audio_text_ops::ModelSize::Tiny,
-        "2" | "" => audio_text_ops::ModelSize::Base,
+        "2" => audio_text_ops::ModelSize::Base,
         "3" => audio_text_ops::ModelSize::Small,
         "4" => audio_text_ops::ModelSize::Medium,
         "5" => audio_text_ops::ModelSize::Large,
@@ -952,8 +1674,7 @@ async fn handle_audio_transcribe() -> Result<(), String> {
     };
     
     // Output path
-    println!("Enter output file path (leave empty for default):");
-    let output_path_str = read_line().map_err(|e| format!("Failed to read input: {}", e))?;
+    let output_path_str = prompt_optional("Enter output file path (leave empty for default)").map_err(read_err)?;
     let output_path = if output_path_str.trim().is_empty() {
         None
     } else {
@@ -961,17 +1682,30 @@ async fn handle_audio_transcribe() -> Result<(), String> {
     };
     
     // Output formats
-    println!("Generate SRT subtitle file? (Y/n):");
-    let srt_choice = read_line().map_err(|e| format!("Failed to read input: {}", e))?;
-    let srt_output = !srt_choice.trim().to_lowercase().starts_with('n');
-    
-    println!("Generate TXT transcript file? (Y/n):");
-    let txt_choice = read_line().map_err(|e| format!("Failed to read input: {}", e))?;
-    let txt_output = !txt_choice.trim().to_lowercase().starts_with('n');
-    
-    println!("Include timestamps in transcript? (Y/n):");
-    let timestamps_choice = read_line().map_err(|e| format!("Failed to read input: {}", e))?;
-    let include_timestamps = !timestamps_choice.trim().to_lowercase().starts_with('n');
+    let srt_output = prompt_yes_no("Generate SRT subtitle file?", true).map_err(read_err)?;
+    let txt_output = prompt_yes_no("Generate TXT transcript file?", true).map_err(read_err)?;
+    let include_timestamps = prompt_yes_no("Include timestamps in transcript?", true).map_err(read_err)?;
+
+    // Language
+    let language_choice = prompt_optional("Spoken language code (e.g., en, de; leave empty to auto-detect)").map_err(read_err)?;
+    let language = if language_choice.trim().is_empty() {
+        None
+    } else {
+        Some(language_choice.trim().to_lowercase())
+    };
+
+    let translate = prompt_yes_no("Translate to English?", false).map_err(read_err)?;
+    
+    CliEquivalent::new("audio-transcribe")
+        .arg(file_path.trim())
+        .opt_unless_default("model", model_size.as_str(), "base")
+        .opt("output", output_path.as_ref().map(|p| p.display()))
+        .flag("no-srt", !srt_output)
+        .flag("no-txt", !txt_output)
+        .flag("no-timestamps", !include_timestamps)
+        .opt("language", language.as_ref())
+        .flag("translate", translate)
+        .print();
     
     // Create options
     let options = audio_text_ops::TranscriptionOptions {
@@ -980,6 +1714,8 @@ async fn handle_audio_transcribe() -> Result<(), String> {
         save_timestamps: include_timestamps,
         output_srt: srt_output,
         output_txt: txt_output,
+        language,
+        translate,
     };
     
     // Perform transcription
@@ -998,9 +1734,50 @@ async fn handle_audio_transcribe() -> Result<(), String> {
     }
 }
 
-// Helper function to read a line from stdin
-fn read_line() -> io::Result<String> {
-    let mut buffer = String::new();
-    io::stdin().read_line(&mut buffer)?;
-    Ok(buffer.trim().to_string())
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operation_key() {
+        assert_eq!(operation_key("12"), Some("12"));
+        assert_eq!(operation_key("28"), Some("28"));
+        assert_eq!(operation_key("0"), None);
+        assert_eq!(operation_key("29"), None);
+        assert_eq!(operation_key(" 12"), None);
+        assert_eq!(operation_key("scan"), None);
+    }
+
+    #[test]
+    fn test_palette_entries() {
+        let entries = palette_entries(None);
+        assert_eq!(entries, OPERATIONS.to_vec());
+        let entries = palette_entries(Some("21"));
+        assert_eq!(entries.len(), OPERATIONS.len());
+        assert_eq!(entries[0], ("21", "Download File"));
+        assert_eq!(entries[1], ("1", "List files in a folder"));
+        assert_eq!(entries.iter().filter(|(key, _)| *key == "21").count(), 1);
+        assert_eq!(palette_entries(Some("99")), OPERATIONS.to_vec());
+    }
+
+    #[test]
+    fn test_matching_operations() {
+        let entries = palette_entries(None);
+        assert_eq!(matching_operations(&entries, "PORTS"), vec![("12", "Scan Host Ports")]);
+        assert_eq!(matching_operations(&entries, "dns lookup"), vec![("27", "DNS Record Lookup")]);
+        let keys: Vec<&str> = matching_operations(&entries, "download").into_iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["21", "22", "23"]);
+        assert!(matching_operations(&entries, "download dns").is_empty());
+        assert_eq!(matching_operations(&entries, "  ").len(), OPERATIONS.len());
+    }
+
+    #[test]
+    fn test_parse_picks() {
+        assert_eq!(parse_picks("1,3", 3), Ok(vec![0, 2]));
+        assert_eq!(parse_picks(" 3, 1 ,3,, ", 3), Ok(vec![2, 0]));
+        assert_eq!(parse_picks("", 3), Ok(vec![]));
+        assert_eq!(parse_picks("0", 3), Err("No option 0".to_string()));
+        assert_eq!(parse_picks("1,4", 3), Err("No option 4".to_string()));
+        assert_eq!(parse_picks("two", 3), Err("Invalid number: two".to_string()));
+    }
+}
@@ -1,18 +1,46 @@
-use anyhow::Result;
-use serde_json::Value;
+use anyhow::{anyhow, Context, Result};
+use maxminddb::{geoip2, MaxMindDBError};
+use serde_json::{json, Value};
 use colored::*;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use crate::utils;
 
-/// Retrieves information about an IP address, including geolocation, ASN, and abuse contacts
-pub async fn lookup_ip_info(ip: &str, show_abuse: bool, show_asn: bool) -> Result<()> {
+/// Environment variable listing GeoLite2 `.mmdb` files for offline lookups, separated like PATH
+pub const GEOIP_DB_ENV: &str = "GEOIP_DB";
+
+/// The offline databases to use: the `--db` paths if any were given, otherwise those in GEOIP_DB
+pub fn geoip_databases(cli_paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    if !cli_paths.is_empty() {
+        return cli_paths;
+    }
+    std::env::var_os(GEOIP_DB_ENV)
+        .map(|paths| std::env::split_paths(&paths).filter(|p| !p.as_os_str().is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// Retrieves information about an IP address, including geolocation, ASN, and abuse contacts.
+///
+/// With `geoip_dbs` the answer comes from local MaxMind databases (GeoLite2 City or Country,
+/// and ASN) without any network access; abuse contacts are only available online.
+/// Returns the information shown, in ipinfo.io's shape.
+pub async fn lookup_ip_info(ip: &str, show_abuse: bool, show_asn: bool, geoip_dbs: &[PathBuf]) -> Result<Value> {
     println!("Looking up information for IP: {}", ip.cyan());
     
+    if !geoip_dbs.is_empty() {
+        let result = lookup_offline(ip, geoip_dbs)?;
+        println!("{}", "Source: local GeoIP database".dimmed());
+        display_ip_info(&result, show_abuse, show_asn)?;
+        return Ok(result);
+    }
+    
     // Use ipinfo.io API for the lookup
     let url = format!("https://ipinfo.io/{}/json", ip);
-    let client = reqwest::Client::new();
-    let response = client.get(&url)
-        .header("Accept", "application/json")
-        .send()
-        .await?;
+    let request = utils::http_client().get(&url)
+        .header("Accept", "application/json");
+    // ipinfo.io's free tier answers 429 once the daily quota is used up
+    let response = utils::send_with_rate_limit(request, "ipinfo.io", 1).await?;
     
     if !response.status().is_success() {
         return Err(anyhow::anyhow!("API request failed with status: {}", response.status()));
@@ -21,7 +49,68 @@ pub async fn lookup_ip_info(ip: &str, show_abuse: bool, show_asn: bool) -> Resul
     let result: Value = response.json().await?;
     display_ip_info(&result, show_abuse, show_asn)?;
     
-    Ok(())
+    Ok(result)
+}
+
+/// OpenStreetMap link centred on the `loc` ("lat,lon") of a lookup, if it has one
+pub fn map_url(data: &Value) -> Option<String> {
+    let (latitude, longitude) = data.get("loc")?.as_str()?.split_once(',')?;
+    let (latitude, longitude): (f64, f64) = (latitude.trim().parse().ok()?, longitude.trim().parse().ok()?);
+    Some(format!("https://www.openstreetmap.org/?mlat={latitude}&mlon={longitude}#map=10/{latitude}/{longitude}"))
+}
+
+/// Looks the address up in each database and merges the answers into the shape
+/// ipinfo.io returns, so both sources are displayed the same way
+fn lookup_offline(ip: &str, databases: &[PathBuf]) -> Result<Value> {
+    let addr: IpAddr = ip.trim().parse().map_err(|_| anyhow!("Offline lookups need an IP address, got '{}'", ip))?;
+    let mut info = json!({ "ip": addr.to_string() });
+    
+    for path in databases {
+        let reader = maxminddb::Reader::open_readfile(path)
+            .with_context(|| format!("Failed to open GeoIP database {}", path.display()))?;
+        // GeoLite2 ships ASN and City/Country data as separate files
+        let result = if reader.metadata.database_type.contains("ASN") {
+            reader.lookup::<geoip2::Asn>(addr).map(|asn| add_asn(&mut info, &asn))
+        } else {
+            reader.lookup::<geoip2::City>(addr).map(|city| add_city(&mut info, &city))
+        };
+        match result {
+            Ok(()) | Err(MaxMindDBError::AddressNotFoundError(_)) => {}
+            Err(e) => return Err(anyhow!("Lookup in {} failed: {}", path.display(), e)),
+        }
+    }
+    
+    Ok(info)
+}
+
+// English name from a MaxMind `names` map
+fn english<'a>(names: &Option<BTreeMap<&'a str, &'a str>>) -> Option<&'a str> {
+    names.as_ref()?.get("en").copied()
+}
+
+fn add_city(info: &mut Value, city: &geoip2::City) {
+    let mut set = |key: &str, value: Option<String>| {
+        if let Some(value) = value {
+            info[key] = Value::String(value);
+        }
+    };
+    set("city", city.city.as_ref().and_then(|c| english(&c.names)).map(str::to_string));
+    set("region", city.subdivisions.as_ref().and_then(|s| s.first()).and_then(|s| english(&s.names)).map(str::to_string));
+    set("country", city.country.as_ref().and_then(|c| c.iso_code).map(str::to_string));
+    set("postal", city.postal.as_ref().and_then(|p| p.code).map(str::to_string));
+    if let Some(location) = &city.location {
+        if let (Some(latitude), Some(longitude)) = (location.latitude, location.longitude) {
+            set("loc", Some(format!("{:.4},{:.4}", latitude, longitude)));
+        }
+        set("timezone", location.time_zone.map(str::to_string));
+    }
+}
+
+fn add_asn(info: &mut Value, asn: &geoip2::Asn) {
+    let Some(number) = asn.autonomous_system_number else { return };
+    let name = asn.autonomous_system_organization.unwrap_or_default();
+    info["org"] = Value::String(format!("AS{} {}", number, name).trim_end().to_string());
+    info["asn"] = json!({ "asn": format!("AS{}", number), "name": name });
 }
 
 fn display_ip_info(data: &Value, show_abuse: bool, show_asn: bool) -> Result<()> {
@@ -132,7 +221,22 @@ mod tests {
     #[tokio::test]
     #[ignore]
     async fn test_lookup_google_dns() {
-        let result = lookup_ip_info("8.8.8.8", false, false).await;
+        let result = lookup_ip_info("8.8.8.8", false, false, &[]).await;
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_map_url() {
+        assert_eq!(map_url(&json!({ "loc": "37.4056,-122.0775" })).as_deref(),
+            Some("https://www.openstreetmap.org/?mlat=37.4056&mlon=-122.0775#map=10/37.4056/-122.0775"));
+        assert_eq!(map_url(&json!({ "loc": "somewhere" })), None);
+        assert_eq!(map_url(&json!({ "ip": "8.8.8.8" })), None);
+    }
+
+    #[test]
+    fn test_lookup_offline_errors() {
+        let missing = [PathBuf::from("/nonexistent/GeoLite2-City.mmdb")];
+        assert!(lookup_offline("not-an-ip", &missing).unwrap_err().to_string().contains("IP address"));
+        assert!(lookup_offline("8.8.8.8", &missing).unwrap_err().to_string().contains("GeoIP database"));
+    }
 } 
\ No newline at end of file
@@ -5,6 +5,7 @@ pub mod browser_ops;
 pub mod calculator_ops;
 pub mod cli;
 pub mod dns_ops;
+pub mod error;
 pub mod file_download_ops;
 pub mod file_ops;
 pub mod http_ops;
@@ -3,6 +3,7 @@
 //! Top-level CLI dispatcher.
 
 mod cli;
+mod error;
 mod file_ops;
 mod browser_ops;
 mod interactive;
@@ -19,15 +20,20 @@ mod video_download_ops;
 mod image_download_ops;
 mod antivirus_ops;
 mod pc_specs_ops;
-// mod audio_text_ops; // Temporarily disabled
+mod audio_text_ops;
+mod system_ops;
 mod ui;
 
+use anyhow::Context;
 use clap::Parser;
 use colored::*;
-use cli::{Cli, Commands};
+use cli::{BrowserDataCommand, Cli, Commands, DnsCommand, RenameArgs, SyncArgs, SystemCommand};
+use std::io::IsTerminal;
 use std::process::exit;
 use std::path::PathBuf;
+use std::time::Duration;
 use crate::unit_converter_ops::handle_unit_converter_command;
+use crate::error::AppError;
 
 /// Tokio runtime: a multithreaded scheduler is the default; specify the flavour
 /// explicitly to make intent clear.
@@ -35,105 +41,335 @@ use crate::unit_converter_ops::handle_unit_converter_command;
 async fn main() {
     // One central error handler with colourised output.
     if let Err(err) = async_main().await {
-        eprintln!("{} {}", "⛔  Error:".red().bold(), err);
-        exit(1);
+        eprintln!("{} {:#}", "⛔  Error:".red().bold(), err);
+        exit(exit_code_for(&err));
     }
 }
 
+// The first categorized error in the chain decides the exit code
+fn exit_code_for(err: &anyhow::Error) -> i32 {
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<AppError>())
+        .map_or(error::EXIT_FAILURE, AppError::exit_code)
+}
+
 async fn async_main() -> anyhow::Result<()> {
     // Nice back-traces & colourised eyre reports (optional)
     color_eyre::install().ok();
 
     let cli_args = Cli::parse();
 
+    // Ctrl-C: a command that took it over with `utils::handle_ctrl_c` is cancelled and stops
+    // its own work; otherwise (or on a second Ctrl-C) delete half-written files and exit.
+    // Runs on its own task so it also fires while the main thread blocks on a prompt.
+    tokio::spawn(async {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if utils::cancel_current_operation() {
+                eprintln!("\n{}", "Stopping… (press Ctrl-C again to quit now)".yellow());
+                continue;
+            }
+            // A second Ctrl-C during cleanup exits immediately
+            tokio::spawn(async {
+                let _ = tokio::signal::ctrl_c().await;
+                exit(error::EXIT_CANCELLED);
+            });
+            eprintln!("\n{}", "Interrupted, cleaning up…".yellow());
+            utils::remove_partial_files();
+            exit(error::EXIT_CANCELLED);
+        }
+    });
+
+    // Plain output when asked to, per https://no-color.org, or when piped to a file
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    if cli_args.no_color || no_color_env || !std::io::stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
+    utils::init_logging(cli_args.quiet, cli_args.verbose);
+    utils::set_pager_mode(match (cli_args.pager, cli_args.no_pager) {
+        (true, _) => utils::PagerMode::Always,
+        (_, true) => utils::PagerMode::Never,
+        _ => utils::PagerMode::Auto,
+    });
+    let dry_run = cli_args.dry_run;
+
     match cli_args.command {
         // ─────────────────────────────── FILE OPS ───────────────────────────────
-        Some(Commands::List { path })                       => file_ops::list_directory(&path)?,
-        Some(Commands::Backup { source, destination })      => file_ops::backup_directory(&source, &destination)?,
-        Some(Commands::OrganizeScreenshots)                 => file_ops::organize_screenshots().map_err(|e| anyhow::anyhow!("{}", e))?,
-        Some(Commands::AnalyzeDisk { path, top })           => file_ops::analyze_disk(&path, top).map_err(|e| anyhow::anyhow!("{}", e))?,
-        Some(Commands::CleanSystem { dry_run })             => file_ops::clean_system(dry_run).map_err(|e| anyhow::anyhow!("{}", e))?,
-        Some(Commands::Rename(args))                        => file_ops::rename_files(&args).map_err(|e| anyhow::anyhow!("{}", e))?,
-        Some(Commands::FindDuplicates { path, min_size })   => file_ops::find_duplicates(&path, &min_size).map_err(|e| anyhow::anyhow!("{}", e))?,
-        Some(Commands::SyncFolders(args))                   => file_ops::sync_folders(&args).map_err(|e| anyhow::anyhow!("{}", e))?,
+        Some(Commands::List(args))                          => file_ops::list_directory(&args)?,
+        Some(Commands::Backup { source, destination, move_source, incremental, checksum }) => {
+            if dry_run {
+                file_ops::preview_backup(&source, &destination, move_source, incremental, checksum)?
+            } else if incremental {
+                file_ops::incremental_backup(&source, &destination, checksum).map_err(|e| anyhow::anyhow!("{}", e))?
+            } else if move_source {
+                file_ops::move_directory(&source, &destination)?
+            } else {
+                let pb = utils::bytes_progress_bar();
+                let result = file_ops::backup_directory(&source, &destination, Some(utils::progress_bar_callback(&pb)));
+                pb.finish_and_clear();
+                result?
+            }
+        }
+        Some(Commands::OrganizeScreenshots { source, dest, by_date }) => file_ops::organize_screenshots(source.as_deref(), dest.as_deref(), by_date, dry_run).map_err(|e| anyhow::anyhow!("{}", e))?,
+        Some(Commands::PhotoSort { path, folders, keep_names }) => file_ops::sort_photos(&path, !keep_names, folders, dry_run).map_err(|e| anyhow::anyhow!("{}", e))?,
+        Some(Commands::AnalyzeDisk { path, top, by_extension }) => file_ops::analyze_disk(&path, top, by_extension).map_err(|e| anyhow::anyhow!("{}", e))?,
+        Some(Commands::CleanSystem {})                      => file_ops::clean_system(true).map_err(|e| anyhow::anyhow!("{}", e))?, // Always dry-run for now
+        Some(Commands::Rename(args))                        => file_ops::rename_files(&RenameArgs { dry_run, ..args }).map_err(|e| anyhow::anyhow!("{}", e))?,
+        Some(Commands::CleanEmpty { path, delete })         => file_ops::clean_empty(&path, delete && !dry_run).map_err(|e| anyhow::anyhow!("{}", e))?,
+        Some(Commands::Organize { path, by })               => file_ops::organize_directory(&path, by, dry_run).map_err(|e| anyhow::anyhow!("{}", e))?,
+        Some(Commands::Watch { path, by, settle })          => file_ops::watch_directory(&path, by, Duration::from_secs(settle), dry_run).map_err(|e| anyhow::anyhow!("{}", e))?,
+        Some(Commands::Hashsum { path, algo, output, verify }) => match verify {
+            Some(manifest) => file_ops::verify_hash_manifest(&path, &manifest).map_err(|e| anyhow::anyhow!("{}", e))?,
+            None => file_ops::write_hash_manifest(&path, algo, output.as_deref()).map_err(|e| anyhow::anyhow!("{}", e))?,
+        },
+        Some(Commands::FindDuplicates { path, min_size, algo, buffer_size }) => file_ops::find_duplicates(&path, &min_size, algo, &buffer_size).map_err(|e| anyhow::anyhow!("{}", e))?,
+        Some(Commands::SyncFolders(args))                   => file_ops::sync_folders(&SyncArgs { dry_run, ..args }).map_err(|e| anyhow::anyhow!("{}", e))?,
         Some(Commands::SearchFiles { path, query })         => file_ops::search_files(&path, &query).map_err(|e| anyhow::anyhow!("{}", e))?,
 
         // ─────────────────────────────── SYSTEM OPS ─────────────────────────────
-        Some(Commands::CloseBrowsers)                       => browser_ops::close_browsers().map_err(|e| anyhow::anyhow!("{}", e))?,
+        Some(Commands::CloseBrowsers { only, except, force }) => browser_ops::close_browsers(&only, &except, force).map_err(|e| anyhow::anyhow!("{}", e))?,
+        Some(Commands::BrowserData(args)) => match args.command {
+            BrowserDataCommand::Delete { browser, data, force_close } => {
+                browser_ops::delete_browser_data(browser, data, force_close, dry_run).map_err(|e| anyhow::anyhow!("{}", e))?;
+            }
+            BrowserDataCommand::Export { browser, data, raw } => {
+                browser_ops::export_browser_data(browser, data, raw).map_err(|e| anyhow::anyhow!("{}", e))?;
+            }
+        },
+        Some(Commands::Scan(args)) => {
+            if !antivirus_ops::check_clamav_installed() && !antivirus_ops::check_clamd_available() {
+                return Err(AppError::MissingTool { tool: "ClamAV", install_hint: "https://docs.clamav.net/manual/Installing.html" }.into());
+            }
+            // Without either flag the daemon is used whenever it is reachable
+            let daemon = if args.daemon { Some(true) } else if args.no_daemon { Some(false) } else { None };
+            let options = antivirus_ops::ScanOptions {
+                engine: antivirus_ops::choose_engine(daemon),
+                exclude: args.exclude,
+                max_file_size: args.max_file_size,
+                since: args.since,
+                changed_only: args.changed_only,
+            };
+            let started_at = chrono::Local::now().to_rfc3339();
+            let results = if args.path.is_dir() {
+                antivirus_ops::scan_directory(&args.path, args.recursive, &options)?
+            } else {
+                vec![antivirus_ops::scan_file(&args.path, &options)?]
+            };
+            println!("{}", antivirus_ops::format_scan_results(&results));
+            if let Some(path) = &args.output {
+                let report = antivirus_ops::ScanReport {
+                    target: &args.path,
+                    started_at,
+                    finished_at: chrono::Local::now().to_rfc3339(),
+                    engine: options.engine,
+                    signatures: antivirus_ops::signature_version(options.engine),
+                    results: &results,
+                };
+                let format = args.format.unwrap_or_else(|| cli::ExportFormat::for_path(path));
+                antivirus_ops::export_scan_report(&report, path, format)?;
+            }
+            if args.quarantine {
+                let quarantine_dir = antivirus_ops::default_quarantine_dir();
+                for result in results.iter().filter(|r| r.status == antivirus_ops::ScanStatus::Infected) {
+                    let record = antivirus_ops::quarantine_file(&result.path, &quarantine_dir, result.threat_name.as_deref(), dry_run)?;
+                    if !dry_run {
+                        println!("{} {} (id {})", "File quarantined:".green(), result.path.display(), record.id.cyan());
+                    }
+                }
+            }
+        }
+        Some(Commands::Quarantine(args)) => {
+            let quarantine_dir = antivirus_ops::default_quarantine_dir();
+            if let Some(id) = args.restore {
+                let path = antivirus_ops::restore_file(&id, &quarantine_dir, dry_run)?;
+                if !dry_run {
+                    println!("{} {}", "File restored:".green(), path.display());
+                }
+            } else if args.purge || args.empty {
+                let confirmed = !args.empty || args.yes || dry_run
+                    || utils::prompt_yes_no("Permanently delete everything in the quarantine?", false)?;
+                if !confirmed {
+                    println!("Nothing deleted.");
+                } else {
+                    let removed = antivirus_ops::purge_quarantine(&quarantine_dir, args.older_than, args.secure, dry_run)?;
+                    if !dry_run {
+                        println!("{} {} item(s)", "Deleted".green(), removed.len());
+                    }
+                }
+            } else {
+                println!("{}", antivirus_ops::format_quarantine_list(&antivirus_ops::list_quarantine(&quarantine_dir)?));
+            }
+        }
 
         // ─────────────────────────────── NETWORK OPS ────────────────────────────
-        Some(Commands::Bandwidth {})                        => network_ops::discover_network_devices(350).await.map_err(|e| anyhow::anyhow!("{}", e))?,
+        Some(Commands::Bandwidth {}) => {
+            let (cancel, _ctrl_c) = utils::handle_ctrl_c();
+            network_ops::discover_network_devices(&network_ops::DiscoveryOptions::default(), &cancel).await?;
+        }
+        Some(Commands::Discover(args)) => {
+            let options = network_ops::DiscoveryOptions {
+                ports: args.ports,
+                timeout_ms: args.timeout,
+                retry: args.retry,
+                arp: args.arp,
+                family: network_ops::IpFamily::from_flags(args.ipv4, args.ipv6),
+                resolve: !args.no_resolve,
+            };
+            let (cancel, _ctrl_c) = utils::handle_ctrl_c();
+            let devices = network_ops::discover_network_devices(&options, &cancel).await?;
+            if let Some(path) = args.output {
+                let format = args.format.unwrap_or_else(|| cli::ExportFormat::for_path(&path));
+                network_ops::export_devices(&devices, &path, format).context("Failed to export devices")?;
+            }
+        }
+        Some(Commands::Wol(args)) => {
+            network_ops::wake_on_lan(args.mac, args.broadcast, args.port).await?
+        }
         Some(Commands::PortScan(args))                      => {
-            network_ops::scan_ports(&args.host, &args.ports, args.timeout).await.map_err(|e| anyhow::anyhow!("{}", e))?
+            let (cancel, _ctrl_c) = utils::handle_ctrl_c();
+            network_ops::scan_ports(&args.host, &args.selected_ports(), args.timeout, args.numeric_ports, args.family(), &args.pacing(), &cancel).await?
         }
 
         // ─────────────────────────────── HTTP / DNS / NETWORK ─────────────────────
         Some(Commands::HttpRequest(args)) => {
-            let headers = args.headers.into_iter().collect();
-            http_ops::make_request(&args.method, &args.url, args.body.as_deref(), &headers).await.map_err(|e| anyhow::anyhow!("{}", e))?
+            let mut headers: std::collections::HashMap<String, String> = match &args.headers_file {
+                Some(path) => http_ops::read_headers_file(path).map_err(|e| anyhow::anyhow!("{}", e))?.into_iter().collect(),
+                None => Default::default(),
+            };
+            headers.extend(args.headers);
+            let graphql_body = match &args.graphql {
+                Some(query) => Some(http_ops::graphql_body(query, args.variables.as_deref()).map_err(|e| anyhow::anyhow!("{}", e))?),
+                None => None,
+            };
+            if args.json.is_some() || graphql_body.is_some() {
+                headers.insert("Content-Type".to_string(), "application/json".to_string());
+            }
+            // GraphQL queries always go out as POST
+            let method = match (args.head, &graphql_body) {
+                (true, _) => "HEAD".to_string(),
+                (false, Some(_)) => "POST".to_string(),
+                (false, None) => args.method,
+            };
+            let body = match &args.body_file {
+                Some(path) => Some(http_ops::read_body_file(path).map_err(|e| anyhow::anyhow!("{}", e))?),
+                None => graphql_body.or(args.json).or(args.body),
+            };
+            let auth = match (args.user, args.bearer) {
+                (Some(credentials), _) => Some(http_ops::HttpAuth::basic(&credentials)),
+                (None, Some(token)) => Some(http_ops::HttpAuth::Bearer(token)),
+                (None, None) => None,
+            };
+            let options = http_ops::RequestOptions {
+                auth,
+                cookies: args.cookies,
+                cookie_jar: args.cookie_jar,
+                json_path: args.jq,
+                graphql: args.graphql.is_some(),
+                force_body: args.force_body,
+                print_curl: args.print_curl,
+                save_har: args.save_har,
+                no_redact: args.no_redact,
+            };
+            match args.repeat {
+                Some(repeat) => {
+                    let bench = http_ops::BenchmarkOptions { repeat, concurrency: args.concurrency, max_error_rate: args.max_error_rate };
+                    http_ops::benchmark_request(&method, &args.url, body.as_deref(), &headers, &options, &bench).await
+                }
+                None => http_ops::make_request(&method, &args.url, body.as_deref(), &headers, &options).await,
+            }
+            .map_err(|e| anyhow::anyhow!("{}", e))?
+        }
+        Some(Commands::HttpCert(args)) => {
+            http_ops::inspect_certificate(&args.host, args.port).await.map_err(|e| anyhow::anyhow!("{}", e))?
         }
         Some(Commands::DnsCache(args))                      => dns_ops::manage_dns(args.action).await.map_err(|e| anyhow::anyhow!("{}", e))?,
-        Some(Commands::Ping(args))                          => network_ops::ping_host(&args.host, args.count).await.map_err(|e| anyhow::anyhow!("{}", e))?,
+        Some(Commands::Dns(args)) => match args.command {
+            DnsCommand::Lookup { name, record_type, server } => {
+                dns_ops::lookup_records(&name, record_type, server).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+            }
+            DnsCommand::Propagation { name, record_type } => {
+                dns_ops::check_propagation(&name, record_type).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+            }
+        },
+        Some(Commands::Ping(args))                          => network_ops::ping_host(&args.host, args.count).await?,
 
         // ─────────────────────────────── UNIT CONVERTER ─────────────────────────
         Some(Commands::Convert(args)) => {
-            match handle_unit_converter_command(args) {
-                Ok(output) => println!("{}", output),
-                Err(e) => eprintln!("Error during conversion: {}", e),
-            }
+            let output = handle_unit_converter_command(args).await.context("Error during conversion")?;
+            println!("{}", output);
         }
 
         // ─────────────────────────────── WHOIS LOOKUP ───────────────────────────
         Some(Commands::Whois(args)) => {
-            match whois_ops::lookup_domain(&args.domain).await {
-                Ok(result) => println!("{}", result),
-                Err(e) => eprintln!("Error during WHOIS lookup: {}", e),
+            if args.available {
+                whois_ops::check_availability(&args.domains).await.context("Error during availability check")?;
+            } else {
+                for domain in &args.domains {
+                    let result = whois_ops::lookup_domain(domain).await.context("Error during WHOIS lookup")?;
+                    if args.summary {
+                        whois_ops::print_summary(domain, &whois_ops::parse_whois(&result), chrono::Local::now().date_naive());
+                    } else {
+                        println!("{}", result);
+                    }
+                }
             }
         }
 
         // ─────────────────────────────── IP INFO LOOKUP ───────────────────────────
         Some(Commands::IpInfo(args)) => {
-            if let Err(e) = ip_info_ops::lookup_ip_info(&args.ip, args.abuse, args.asn).await {
-                eprintln!("Error during IP lookup: {}", e);
-            }
+            ip_info_ops::lookup_ip_info(&args.ip, args.abuse, args.asn, &ip_info_ops::geoip_databases(args.db)).await.context("Error during IP lookup")?;
         }
         
         // ─────────────────────────────── FILE DOWNLOAD ────────────────────────────
+        Some(Commands::Speedtest(args)) => {
+            let options = file_download_ops::SpeedTestOptions {
+                server: args.server,
+                download_bytes: args.size,
+                upload_bytes: args.upload.then_some(args.upload_size),
+                upload_url: args.upload_url,
+            };
+            file_download_ops::speed_test(&options).await.context("Speed test failed")?;
+        }
         Some(Commands::Download(args)) => {
-            // Extract filename from URL if output is not specified
-            let output_path = match args.output {
-                Some(path) => path,
-                None => {
-                    // Extract filename from URL
-                    let url_parts: Vec<&str> = args.url.split('/').collect();
-                    let filename = url_parts.last()
-                        .filter(|s| !s.is_empty())
-                        .unwrap_or(&"downloaded_file")
-                        .to_string();
-                    
-                    PathBuf::from(filename)
-                }
+            let checksum = match (args.sha256, args.md5) {
+                (Some(expected), _) => Some(file_download_ops::Checksum { algorithm: file_ops::HashAlgorithm::Sha256, expected }),
+                (None, Some(expected)) => Some(file_download_ops::Checksum { algorithm: file_ops::HashAlgorithm::Md5, expected }),
+                (None, None) => None,
             };
-            
-            if let Err(e) = file_download_ops::download_file(
+
+            let options = file_download_ops::FileDownloadOptions {
+                retries: args.retries,
+                resume: args.resume,
+                parallel: args.parallel,
+                checksum,
+                mirrors: args.mirrors,
+                rate_limit: args.rate_limit,
+                max_time: args.max_time.map(Duration::from_secs),
+                stall_timeout: (args.stall_timeout > 0).then(|| Duration::from_secs(args.stall_timeout)),
+                force: args.force,
+            };
+
+            let (cancel, _ctrl_c) = utils::handle_ctrl_c();
+            file_download_ops::download_file(
                 &args.url,
-                &output_path,
-                args.retries,
-                args.resume,
-                args.parallel
-            ).await {
-                eprintln!("Error during file download: {}", e);
-            }
+                args.output.as_deref(),
+                &options,
+                None,
+                &cancel
+            ).await.context("Error during file download")?;
         }
         
         // ─────────────────────────────── VIDEO DOWNLOAD ────────────────────────────
         Some(Commands::VideoDownload(args)) => {
             // Either get info or download the video
-            if args.info_only {
-                match video_download_ops::get_video_info(&args.url).await {
-                    Ok(info) => println!("{}", info),
-                    Err(e) => eprintln!("Error getting video info: {}", e),
-                }
+            if args.list_formats {
+                let formats = video_download_ops::list_formats(&args.url).await.context("Error listing formats")?;
+                video_download_ops::print_formats_table(&formats);
+            } else if args.info_only && args.json {
+                let info = video_download_ops::get_video_info_struct(&args.url).await.context("Error getting video info")?;
+                println!("{}", serde_json::to_string_pretty(&info.to_json()).unwrap_or_default());
+            } else if args.info_only {
+                let info = video_download_ops::get_video_info(&args.url).await.context("Error getting video info")?;
+                println!("{}", info);
             } else {
                 // Parse quality
                 let quality = args.quality.as_deref()
@@ -144,25 +380,39 @@ async fn async_main() -> anyhow::Result<()> {
                 let output_dir = args.output_dir.unwrap_or_else(|| PathBuf::from("."));
                 
                 // Create options with all CLI arguments
-                let options = video_download_ops::DownloadOptions {
+                let mut options = video_download_ops::DownloadOptions {
                     quality,
                     audio_only: args.audio_only,
                     max_rate: args.rate_limit,
-                    concurrent_downloads: args.concurrent,
+                    concurrent_downloads: args.concurrent.unwrap_or_else(|| utils::default_download_concurrency(3)),
                     cookies_file: args.cookies_file,
-                    subtitles: args.subtitles,
+                    subtitles: args.subtitles || args.embed_subs || !args.sub_langs.is_empty(),
                     force_ipv4: args.force_ipv4,
                     proxy: args.proxy,
                     retries: args.retries,
+                    auto_subs: !args.human_subs,
+                    embed_subs: args.embed_subs,
+                    format_id: args.format_id,
+                    playlist_items: args.playlist_items
+                        .or_else(|| video_download_ops::playlist_range(args.playlist_start, args.playlist_end)),
+                    failed_list: args.failed_list,
+                    output_template: args.output_template,
+                    embed_metadata: args.embed_metadata,
+                    embed_thumbnail: args.embed_thumbnail,
+                    auto_update: args.auto_update,
+                    ..Default::default()
                 };
+                if !args.sub_langs.is_empty() {
+                    options.sub_langs = args.sub_langs;
+                }
                 
-                if let Err(e) = video_download_ops::download_video_with_options(
+                let (cancel, _ctrl_c) = utils::handle_ctrl_c();
+                video_download_ops::download_video_with_options(
                     &args.url, 
                     &output_dir,
                     &options,
-                ).await {
-                    eprintln!("Error during video download: {}", e);
-                }
+                    &cancel,
+                ).await.context("Error during video download")?;
             }
         }
 
@@ -170,47 +420,96 @@ async fn async_main() -> anyhow::Result<()> {
         Some(Commands::ImageDownload(args)) => {
             // Setup search options from CLI args
             let mut options = image_download_ops::ImageSearchOptions::default();
-            options.query = args.query;
+            options.query = args.query.unwrap_or_default();
             options.count = args.count;
             options.min_width = args.min_width;
             options.min_height = args.min_height;
             options.color = args.color;
             options.safe_search = !args.unsafe_search;
-            options.concurrent_downloads = args.concurrent;
+            if let Some(concurrent) = args.concurrent {
+                options.concurrent_downloads = concurrent;
+            }
+            options.overwrite = args.overwrite;
+            options.html_index = args.index;
+            options.license = args.license;
             
             // Get output directory
-            let output_dir = args.output_dir.unwrap_or_else(|| PathBuf::from("./images"));
+            let default_dir = match &args.from_manifest {
+                Some(path) => path.parent().filter(|p| !p.as_os_str().is_empty()).map(PathBuf::from).unwrap_or_else(|| PathBuf::from(".")),
+                None => PathBuf::from("./images"),
+            };
+            let output_dir = args.output_dir.unwrap_or(default_dir);
             
-            // Search for images
-            match image_download_ops::search_images(&options).await {
-                Ok(images) => {
-                    if images.is_empty() {
-                        println!("{}", "No images found matching your criteria.".yellow());
-                    } else {
-                        println!("{} {} images found", "Found".green(), images.len());
-                        
-                        // Download the images
-                        if let Err(e) = image_download_ops::download_images(
-                            &images, 
-                            &output_dir, 
-                            options.concurrent_downloads
-                        ).await {
-                            eprintln!("Error during image download: {}", e);
-                        }
-                    }
-                },
-                Err(e) => eprintln!("Error during image search: {}", e),
+            // Search for images, or reuse a saved search
+            let images = match &args.from_manifest {
+                Some(path) => {
+                    let manifest = image_download_ops::ImageManifest::load(path)?;
+                    options.query = manifest.query.clone();
+                    manifest.image_results()
+                }
+                None => image_download_ops::search_images(&options).await.context("Error during image search")?,
+            };
+            if images.is_empty() {
+                println!("{}", "No images found matching your criteria.".yellow());
+            } else {
+                println!("{} {} images found", "Found".green(), images.len());
+                
+                // Download the images
+                image_download_ops::download_images(
+                    &images, 
+                    &output_dir, 
+                    &options
+                ).await.context("Error during image download")?;
             }
         }
 
         // ─────────────────────────────── PC SPECS ────────────────────────────
         Some(Commands::PCSpecs(args)) => {
-            if let Err(e) = pc_specs_ops::handle_pc_specs_command(args) {
-                eprintln!("Error getting PC specs: {}", e);
-            }
+            pc_specs_ops::handle_pc_specs_command(args).context("Error getting PC specs")?;
         }
 
+        // ─────────────────────────────── AUDIO TRANSCRIBE ────────────────────────────
+        Some(Commands::AudioTranscribe(args)) => {
+            let model_size = audio_text_ops::ModelSize::from_string(&args.model)
+                .ok_or_else(|| anyhow::anyhow!("Unknown model size '{}'. Use tiny, base, small, medium or large.", args.model))?;
+
+            let options = audio_text_ops::TranscriptionOptions {
+                model_size,
+                output_file: args.output,
+                save_timestamps: !args.no_timestamps,
+                output_srt: !args.no_srt,
+                output_txt: !args.no_txt,
+                language: args.language,
+                translate: args.translate,
+            };
+
+            audio_text_ops::handle_audio_transcription(&args.input, options).await?;
+        }
+
+        // ─────────────────────────────── SYSTEM MONITOR ────────────────────────────
+        Some(Commands::System(args)) => match args.command {
+            SystemCommand::Watch { interval } => {
+                let interval = Duration::from_secs_f64(interval);
+                system_ops::watch(interval).await.map_err(|e| anyhow::anyhow!("{}", e))?
+            }
+            SystemCommand::Io { interval } => {
+                let interval = Duration::from_secs_f64(interval);
+                system_ops::watch_io(interval).await.map_err(|e| anyhow::anyhow!("{}", e))?
+            }
+            SystemCommand::Top { count, sort } => {
+                system_ops::print_top_processes(count, sort).map_err(|e| anyhow::anyhow!("{}", e))?
+            }
+        },
+
         // ─────────────────────────────── INTERACTIVE MODE / GUI MODE ────────────────────────────
+        Some(Commands::Interactive { answers_file }) => {
+            if let Some(path) = answers_file {
+                let file = std::fs::File::open(&path)
+                    .map_err(|e| anyhow::anyhow!("Cannot open answers file {}: {}", path.display(), e))?;
+                utils::set_prompt_input(Box::new(std::io::BufReader::new(file)));
+            }
+            interactive::start_interactive_mode().await.map_err(|e| anyhow::anyhow!("{}", e))?
+        }
         None => {
             // No arguments provided, run the GTK UI
             println!("No command-line arguments provided. Launching GUI...");
@@ -230,14 +529,3 @@ async fn async_main() -> anyhow::Result<()> {
 
     Ok(())
 }
-
-/* Temporarily disabled
-// Handle audio transcription logic
-pub async fn handle_audio_transcription(
-    input_path: &PathBuf,
-    options: audio_text_ops::TranscriptionOptions,
-) -> Result<(), anyhow::Error> {
-    // Use the module to handle the actual transcription
-    audio_text_ops::transcribe_audio(input_path, options).await
-}
-*/
@@ -1,27 +1,45 @@
 //! src/net_tools.rs
 use colored::*;
+use lazy_static::lazy_static;
+use log::info;
+use regex::Regex;
 use dns_lookup::lookup_addr;
 use futures::{stream::FuturesUnordered, StreamExt};
 use get_if_addrs::{get_if_addrs, IfAddr};
+use indicatif::{ProgressBar, ProgressStyle};
 use ipnetwork::Ipv4Network;
+use rand::seq::SliceRandom;
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
-    error::Error,
-    net::{IpAddr, Ipv4Addr, SocketAddr, ToSocketAddrs},
-    process::Command,
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs},
+    path::Path,
+    process::{Command, Stdio},
     sync::{Arc, Mutex},
-    time::Duration,
+    time::{Duration, Instant},
 };
-use tokio::{net::TcpStream, time};
+use serde::Serialize;
+use tokio::{net::TcpStream, sync::Semaphore, task::JoinHandle, time};
+use tokio_util::sync::CancellationToken;
+use crate::cli::ExportFormat;
+use crate::error::{AppError, Result};
+use crate::utils;
 
 // Device information structure
-#[derive(Clone, Debug, Default)]
-struct DeviceInfo {
-    hostname: String,
-    mac_address: Option<String>,
-    open_ports: Vec<u16>,
-    manufacturer: Option<String>,
-    device_type: Option<String>,
+#[derive(Clone, Debug, Serialize)]
+pub struct DeviceInfo {
+    pub ip: IpAddr,
+    pub hostname: String,
+    pub mac_address: Option<String>,
+    pub open_ports: Vec<u16>,
+    pub manufacturer: Option<String>,
+    pub device_type: Option<String>,
+    #[serde(rename = "response_time_ms", serialize_with = "serialize_millis")]
+    pub response_time: Duration,
+}
+
+fn serialize_millis<S: serde::Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis() as u64)
 }
 
 /// ---------------------------------------------------------------------------
@@ -32,6 +50,19 @@ async fn port_is_open(addr: SocketAddr, timeout: Duration) -> bool {
     time::timeout(timeout, TcpStream::connect(addr)).await.is_ok()
 }
 
+// Like port_is_open, but reports how long the answer took and can try a second
+// time when the first attempt times out
+async fn probe_port(addr: SocketAddr, timeout: Duration, retry: bool) -> Option<Duration> {
+    let attempts = if retry { 2 } else { 1 };
+    for _ in 0..attempts {
+        let started = Instant::now();
+        if port_is_open(addr, timeout).await {
+            return Some(started.elapsed());
+        }
+    }
+    None
+}
+
 /// ---------------------------------------------------------------------------
 /// Bandwidth monitoring
 /// ---------------------------------------------------------------------------
@@ -40,7 +71,7 @@ async fn port_is_open(addr: SocketAddr, timeout: Duration) -> bool {
 /// 
 /// This is a placeholder implementation since proper bandwidth monitoring
 /// requires platform-specific implementations.
-pub async fn get_bandwidth_snapshot() -> Result<(), Box<dyn Error + Send + Sync>> {
+pub async fn get_bandwidth_snapshot() -> Result<()> {
     println!("{}", "Network bandwidth monitoring is temporarily unavailable.".yellow());
     println!("{}", "This feature requires additional system access that isn't currently enabled.".dimmed());
     println!("{}", "Use the port scanning option instead for network operations.".dimmed());
@@ -51,24 +82,136 @@ pub async fn get_bandwidth_snapshot() -> Result<(), Box<dyn Error + Send + Sync>
 /// Device discovery
 /// ---------------------------------------------------------------------------
 
+/// Ports probed on each host when no list is given
+pub const DEFAULT_PROBE_PORTS: &[u16] = &[22, 80, 443, 3389, 8080, 8443];
+
+/// Which address family to scan or resolve to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IpFamily {
+    /// Both; IPv4 first when a name has both kinds of address
+    #[default]
+    Any,
+    V4,
+    V6,
+}
+
+impl IpFamily {
+    /// Family chosen by the `--ipv4` / `--ipv6` flags
+    pub fn from_flags(ipv4: bool, ipv6: bool) -> Self {
+        match (ipv4, ipv6) {
+            (true, _) => IpFamily::V4,
+            (_, true) => IpFamily::V6,
+            _ => IpFamily::Any,
+        }
+    }
+
+    pub fn allows(self, ip: &IpAddr) -> bool {
+        match self {
+            IpFamily::Any => true,
+            IpFamily::V4 => ip.is_ipv4(),
+            IpFamily::V6 => ip.is_ipv6(),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            IpFamily::Any => "IP",
+            IpFamily::V4 => "IPv4",
+            IpFamily::V6 => "IPv6",
+        }
+    }
+}
+
+/// Tuning for `discover_network_devices`
+#[derive(Debug, Clone)]
+pub struct DiscoveryOptions {
+    /// Ports probed on every host; a host is live if any of them answers
+    pub ports: Vec<u16>,
+    /// Per-port connection timeout in milliseconds
+    pub timeout_ms: u64,
+    /// Try each port a second time after a timeout (helps on flaky Wi-Fi)
+    pub retry: bool,
+    /// Find hosts with ARP requests instead of TCP probes; falls back to TCP without raw sockets
+    pub arp: bool,
+    /// IPv4 subnets, IPv6 neighbors or both
+    pub family: IpFamily,
+    /// Look up host names with reverse DNS; off lists every device under its address
+    pub resolve: bool,
+}
+
+impl Default for DiscoveryOptions {
+    fn default() -> Self {
+        Self {
+            ports: DEFAULT_PROBE_PORTS.to_vec(),
+            timeout_ms: 350,
+            retry: false,
+            arp: false,
+            family: IpFamily::Any,
+            resolve: true,
+        }
+    }
+}
+
+/// How long a reverse lookup may take before the device is listed under its address
+const REVERSE_DNS_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Host name for `ip` by reverse DNS: the address itself when lookups are off or time out,
+// "Unknown" when there is no PTR record. A timed-out lookup finishes on its blocking thread
+// but nobody waits for it.
+async fn reverse_lookup(ip: IpAddr, resolve: bool) -> String {
+    if !resolve {
+        return ip.to_string();
+    }
+    match time::timeout(REVERSE_DNS_TIMEOUT, tokio::task::spawn_blocking(move || lookup_addr(&ip))).await {
+        Ok(Ok(Ok(name))) => name,
+        Ok(_) => "Unknown".into(),
+        Err(_) => ip.to_string(),
+    }
+}
+
 /// Scan every directly-connected IPv4 network for live hosts.
 ///
-/// A "live" host is any address that responds on common ports (22, 80, 443, 3389, etc.).
+/// A "live" host is any address that responds on one of the probe ports
+/// (by default 22, 80, 443, 3389, 8080 and 8443).
 /// Enhanced to display detailed device information including MAC addresses,
 /// device types, and manufacturers when possible.
-pub async fn discover_network_devices(timeout_ms: u64) -> Result<(), Box<dyn Error + Send + Sync>> {
+///
+/// IPv6 networks are far too large to sweep, so for those the hosts already in the
+/// neighbor table (after a ping to the all-nodes address) are probed instead.
+///
+/// Returns every device found, sorted by IP within each network, for exporting.
+///
+/// Cancelling `cancel` aborts the outstanding probes and returns an error; hosts already
+/// reported stay on screen but no summary table is printed.
+pub async fn discover_network_devices(options: &DiscoveryOptions, cancel: &CancellationToken) -> Result<Vec<DeviceInfo>> {
     println!("{}", "🔍  Discovering network devices...".cyan().bold());
     println!("{}", "This will scan your local networks for connected devices".dimmed());
 
-    // 1. Build the set of IPv4 networks we should test.
-    let mut nets: BTreeSet<Ipv4Network> = BTreeSet::new();
+    // 1. Build the set of IPv4 networks we should test, with the interface each is on.
+    let mut nets: BTreeMap<Ipv4Network, String> = BTreeMap::new();
+    let mut v6_prefixes: BTreeMap<Ipv6Addr, String> = BTreeMap::new();
     let mut local_ips = Vec::new();
     
     println!("{}", "Detecting network interfaces...".cyan());
-    for iface in get_if_addrs().map_err(|e| -> Box<dyn Error + Send + Sync> { Box::new(e) })? {
+    for iface in get_if_addrs()? {
         if iface.is_loopback() {
             continue;
         }
+        if let IfAddr::V6(v6) = &iface.addr {
+            // Link-local neighbors need a zone id to connect to, so only routable prefixes are scanned
+            if options.family != IpFamily::V4 && !is_ipv6_link_local(&v6.ip) {
+                println!("  {} Interface: {} - IP: {} - Network: {}/64",
+                    "✓".green(),
+                    iface.name.cyan(),
+                    v6.ip.to_string().yellow(),
+                    ipv6_prefix64(&v6.ip)
+                );
+                v6_prefixes.insert(ipv6_prefix64(&v6.ip), iface.name.clone());
+            }
+        }
+        if options.family == IpFamily::V6 {
+            continue;
+        }
         if let IfAddr::V4(v4) = iface.addr {
             // Create network using CIDR prefix instead of netmask
             let prefix_len = netmask_to_prefix(v4.netmask);
@@ -84,17 +227,20 @@ pub async fn discover_network_devices(timeout_ms: u64) -> Result<(), Box<dyn Err
             );
             
             local_ips.push(v4.ip);
-            nets.insert(net);
+            nets.insert(net, iface.name.clone());
         }
     }
-    if nets.is_empty() {
-        return Err("No routable IPv4 interface found".into());
+    if nets.is_empty() && v6_prefixes.is_empty() {
+        return Err(AppError::Network(format!("No routable {} interface found", options.family.label())));
     }
 
     // Print a separator
     println!("{}", "─────────────────────────────────────────────────────────────".dimmed());
     
-    for net in nets {
+    let started = Instant::now();
+    let mut hosts_scanned: u64 = 0;
+    let mut devices = Vec::new();
+    for (net, iface_name) in nets {
         // Skip small networks like /31 and /32
         if net.prefix() >= 31 {
             continue;
@@ -106,11 +252,94 @@ pub async fn discover_network_devices(timeout_ms: u64) -> Result<(), Box<dyn Err
             net.to_string().yellow().bold(),
             (net.size() - 2).to_string().green()
         );
-        scan_subnet(net, timeout_ms).await?;
+        let found = if options.arp {
+            match arp_scan_subnet(net, iface_name, options, cancel).await {
+                Ok(found) => found,
+                Err(_) if cancel.is_cancelled() => return Err(AppError::Cancelled("Device discovery cancelled".into())),
+                Err(e) => {
+                    println!("{} ARP scan unavailable ({}), falling back to TCP probes", "⚠".yellow(), e);
+                    scan_subnet(net, options, cancel).await?
+                }
+            }
+        } else {
+            scan_subnet(net, options, cancel).await?
+        };
+        devices.extend(found);
+        hosts_scanned += u64::from(net.size() - 2);
+    }
+    for (prefix, iface_name) in v6_prefixes {
+        println!(
+            "{} {}  ({})",
+            "📡  Scanning Network:".cyan().bold(),
+            format!("{}/64", prefix).yellow().bold(),
+            "neighbor table".green()
+        );
+        let (found, neighbors) = scan_ipv6_neighbors(prefix, &iface_name, options, cancel).await?;
+        devices.extend(found);
+        hosts_scanned += neighbors as u64;
+    }
+
+    let elapsed = started.elapsed();
+    println!(
+        "{} {} host(s) scanned, {} live, in {:.1}s ({:.0} hosts/s)",
+        "⏱  Summary:".cyan().bold(),
+        hosts_scanned,
+        devices.len().to_string().green(),
+        elapsed.as_secs_f64(),
+        hosts_scanned as f64 / elapsed.as_secs_f64().max(0.001)
+    );
+    Ok(devices)
+}
+
+/// Discovered devices as CSV, one row per device; open ports are separated by spaces
+pub fn devices_to_csv(devices: &[DeviceInfo]) -> String {
+    let mut out = String::from("ip,hostname,mac_address,manufacturer,device_type,open_ports,response_time_ms\n");
+    for device in devices {
+        let ports = device.open_ports.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(" ");
+        let row = [
+            device.ip.to_string(),
+            device.hostname.clone(),
+            device.mac_address.clone().unwrap_or_default(),
+            device.manufacturer.clone().unwrap_or_default(),
+            device.device_type.clone().unwrap_or_default(),
+            ports,
+            device.response_time.as_millis().to_string(),
+        ];
+        out.push_str(&row.iter().map(|field| utils::csv_field(field)).collect::<Vec<_>>().join(","));
+        out.push('\n');
     }
+    out
+}
+
+/// Write discovered devices to `path` as CSV or pretty-printed JSON
+pub fn export_devices(devices: &[DeviceInfo], path: &Path, format: ExportFormat) -> Result<()> {
+    let contents = match format {
+        ExportFormat::Csv => devices_to_csv(devices),
+        ExportFormat::Json => serde_json::to_string_pretty(devices)?,
+    };
+    std::fs::write(path, contents)?;
+    println!("{} {} device(s) to {}", "Exported".green(), devices.len(), path.display());
     Ok(())
 }
 
+// Wait for every spawned probe; on cancellation abort the rest and return false
+async fn join_or_cancel(tasks: &mut FuturesUnordered<JoinHandle<()>>, cancel: &CancellationToken) -> bool {
+    loop {
+        tokio::select! {
+            biased;
+            _ = cancel.cancelled() => {
+                tasks.iter().for_each(JoinHandle::abort);
+                return false;
+            }
+            next = tasks.next() => {
+                if next.is_none() {
+                    return true;
+                }
+            }
+        }
+    }
+}
+
 // Helper function to convert an IPv4 netmask to a prefix length (e.g., 255.255.255.0 -> 24)
 fn netmask_to_prefix(netmask: Ipv4Addr) -> u8 {
     let octets = netmask.octets();
@@ -121,10 +350,13 @@ fn netmask_to_prefix(netmask: Ipv4Addr) -> u8 {
     count as u8
 }
 
-async fn scan_subnet(net: Ipv4Network, timeout_ms: u64) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let timeout = Duration::from_millis(timeout_ms);
-    let ports = [22, 80, 443, 3389, 8080, 8443];
-    let live = Arc::new(Mutex::new(BTreeMap::<Ipv4Addr, DeviceInfo>::new()));
+// Returns the live hosts found
+async fn scan_subnet(net: Ipv4Network, options: &DiscoveryOptions, cancel: &CancellationToken) -> Result<Vec<DeviceInfo>> {
+    let timeout = Duration::from_millis(options.timeout_ms);
+    let ports = Arc::new(options.ports.clone());
+    let retry = options.retry;
+    let resolve = options.resolve;
+    let live = Arc::new(Mutex::new(BTreeMap::<IpAddr, DeviceInfo>::new()));
     let mut tasks = FuturesUnordered::new();
 
     // Get MAC address cache from arp table (for faster device identification)
@@ -134,30 +366,40 @@ async fn scan_subnet(net: Ipv4Network, timeout_ms: u64) -> Result<(), Box<dyn Er
     let start_ip = u32::from(net.network());
     let end_ip = start_ip + net.size() - 2; // Skip network and broadcast addresses
     
-    println!("{}", "Scanning network, please wait...".dimmed());
+    info!("{}", "Scanning network, please wait...".dimmed());
     
     for ip_int in start_ip+1..=end_ip {
         let host = Ipv4Addr::from(ip_int);
         let live = live.clone();
         let mac_cache = mac_cache.clone();
+        let ports = ports.clone();
         
         tasks.push(tokio::spawn(async move {
             let mut detected_ports = Vec::new();
+            let mut response_time: Option<Duration> = None;
             
-            for &p in &ports {
-                if port_is_open(SocketAddr::new(IpAddr::V4(host), p), timeout).await {
+            for &p in ports.iter() {
+                if let Some(rtt) = probe_port(SocketAddr::new(IpAddr::V4(host), p), timeout, retry).await {
                     detected_ports.push(p);
+                    response_time = Some(response_time.map_or(rtt, |best| best.min(rtt)));
                 }
             }
             
             if !detected_ports.is_empty() {
-                let name = lookup_addr(&IpAddr::V4(host)).unwrap_or_else(|_| "Unknown".into());
+                let name = reverse_lookup(IpAddr::V4(host), resolve).await;
                 
-                // Get MAC address from cache if available
-                let mac_address = mac_cache.get(&host).cloned();
+                // The connect above usually just added the host to the ARP table,
+                // so ask for its entry again when the start-of-scan snapshot lacks it
+                let mac_address = match mac_cache.get(&host) {
+                    Some(mac) => Some(mac.clone()),
+                    None => tokio::task::spawn_blocking(move || lookup_arp_entry(host)).await.ok().flatten(),
+                };
                 
-                // Try to guess device type based on open ports and hostname
-                let device_type = guess_device_type(&name, &detected_ports);
+                // What the services say about themselves beats guessing from the hostname and ports
+                let device_type = match fingerprint_device(IpAddr::V4(host), &detected_ports).await {
+                    Some(kind) => Some(kind.to_string()),
+                    None => guess_device_type(&name, &detected_ports),
+                };
                 
                 // Guess manufacturer from MAC address if available
                 let manufacturer = match &mac_address {
@@ -166,15 +408,17 @@ async fn scan_subnet(net: Ipv4Network, timeout_ms: u64) -> Result<(), Box<dyn Er
                 };
                 
                 let device_info = DeviceInfo {
+                    ip: IpAddr::V4(host),
                     hostname: name.clone(),
                     mac_address,
                     open_ports: detected_ports.clone(),
                     manufacturer,
                     device_type,
+                    response_time: response_time.unwrap_or_default(),
                 };
                 
                 let mut map = live.lock().unwrap();
-                if map.insert(host, device_info.clone()).is_none() {
+                if map.insert(IpAddr::V4(host), device_info.clone()).is_none() {
                     println!("  {} {} - {}",
                         "✓".green(),
                         host.to_string().cyan(),
@@ -185,10 +429,18 @@ async fn scan_subnet(net: Ipv4Network, timeout_ms: u64) -> Result<(), Box<dyn Er
         }));
     }
     
-    while tasks.next().await.is_some() {}
+    if !join_or_cancel(&mut tasks, cancel).await {
+        return Err(AppError::Cancelled("Device discovery cancelled".into()));
+    }
 
     // --- summary ------------------------------------------------------------
     let map = live.lock().unwrap();
+    print_device_table(&map);
+    Ok(map.values().cloned().collect())
+}
+
+// Summary table of the devices found on one network
+fn print_device_table(map: &BTreeMap<IpAddr, DeviceInfo>) {
     if map.is_empty() {
         println!("{}", "No live devices found.\n".yellow());
     } else {
@@ -199,12 +451,13 @@ async fn scan_subnet(net: Ipv4Network, timeout_ms: u64) -> Result<(), Box<dyn Er
         );
         
         println!("{}", "╭───────────────────────────────────────────────────────────────────────╮".cyan());
-        println!("{:<4} {:<15} {:<20} {:<15} {:<18} {}", 
+        println!("{:<4} {:<15} {:<20} {:<15} {:<18} {:<10} {}", 
             "│ #".cyan(),
             "│ IP Address".cyan(), 
             "│ Hostname".cyan(), 
             "│ Device Type".cyan(),
             "│ Manufacturer".cyan(),
+            "│ Response".cyan(),
             "│ Ports".cyan()
         );
         println!("{}", "├───────────────────────────────────────────────────────────────────────┤".cyan());
@@ -225,12 +478,13 @@ async fn scan_subnet(net: Ipv4Network, timeout_ms: u64) -> Result<(), Box<dyn Er
                 None => "Unknown".to_string()
             };
             
-            println!("{:<4} {:<15} {:<20} {:<15} {:<18} {} {}",
+            println!("{:<4} {:<15} {:<20} {:<15} {:<18} {:<10} {} {}",
                 format!("│ {}", i+1).cyan(),
                 format!("│ {}", ip).cyan(), 
                 format!("│ {}", truncate(&device.hostname, 18)),
                 format!("│ {}", device.device_type.as_deref().unwrap_or("Unknown")),
                 format!("│ {}", device.manufacturer.as_deref().unwrap_or("Unknown")),
+                format!("│ {} ms", device.response_time.as_millis()),
                 format!("│ {}", ports_str),
                 format!("│ MAC: {}", mac_display).dimmed()
             );
@@ -238,50 +492,473 @@ async fn scan_subnet(net: Ipv4Network, timeout_ms: u64) -> Result<(), Box<dyn Er
         println!("{}", "╰───────────────────────────────────────────────────────────────────────╯".cyan());
         println!();
     }
-    Ok(())
+}
+
+// ARP discovery: every host answering a who-has request is live, whatever ports it has open
+async fn arp_scan_subnet(net: Ipv4Network, iface_name: String, options: &DiscoveryOptions, cancel: &CancellationToken) -> Result<Vec<DeviceInfo>> {
+    // Replies can trail the last request a little, so wait at least a second for them
+    let wait = Duration::from_millis(options.timeout_ms).max(Duration::from_secs(1));
+    let scan = tokio::task::spawn_blocking(move || arp_scan(&iface_name, net, wait));
+    let replies = tokio::select! {
+        result = scan => result??,
+        _ = cancel.cancelled() => return Err(AppError::Cancelled("Device discovery cancelled".into())),
+    };
+
+    // Reverse lookups and fingerprinting can be slow, so run them side by side
+    let resolve = options.resolve;
+    let mut lookups: FuturesUnordered<_> = replies.into_iter().map(|(host, mac, response_time)| {
+        tokio::spawn(async move {
+            let name = reverse_lookup(IpAddr::V4(host), resolve).await;
+            let fingerprint = fingerprint_device(IpAddr::V4(host), &[]).await;
+            (host, mac, response_time, name, fingerprint)
+        })
+    }).collect();
+    let mut map = BTreeMap::new();
+    while let Some(result) = lookups.next().await {
+        let (host, mac, response_time, name, fingerprint) = result?;
+        println!("  {} {} - {}", "✓".green(), host.to_string().cyan(), name.yellow());
+        let mac_address = mac.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":");
+        let device_type = match fingerprint {
+            Some(kind) => Some(kind.to_string()),
+            None => guess_device_type(&name, &[]),
+        };
+        let device_info = DeviceInfo {
+            ip: IpAddr::V4(host),
+            device_type,
+            manufacturer: guess_manufacturer(&mac_address),
+            hostname: name,
+            mac_address: Some(mac_address),
+            open_ports: Vec::new(),
+            response_time,
+        };
+        map.insert(IpAddr::V4(host), device_info);
+    }
+
+    print_device_table(&map);
+    Ok(map.into_values().collect())
+}
+
+// IPv6 discovery: probe the hosts in the neighbor table that sit in `prefix`/64.
+// Returns the devices found and how many neighbors were probed.
+async fn scan_ipv6_neighbors(prefix: Ipv6Addr, iface_name: &str, options: &DiscoveryOptions, cancel: &CancellationToken) -> Result<(Vec<DeviceInfo>, usize)> {
+    tokio::select! {
+        _ = ping_all_nodes(iface_name) => {}
+        _ = cancel.cancelled() => return Err(AppError::Cancelled("Device discovery cancelled".into())),
+    }
+    let neighbors: Vec<(Ipv6Addr, String)> = tokio::task::spawn_blocking(query_neighbor_table).await?
+        .into_iter()
+        .filter(|(ip, _)| ipv6_prefix64(ip) == prefix)
+        .collect();
+    let neighbor_count = neighbors.len();
+    let timeout = Duration::from_millis(options.timeout_ms);
+    let ports = Arc::new(options.ports.clone());
+    let retry = options.retry;
+    let resolve = options.resolve;
+
+    // Neighbors are live already, so every one is reported; the probes only fill in the details
+    let mut probes: FuturesUnordered<_> = neighbors.into_iter().map(|(host, mac)| {
+        let ports = ports.clone();
+        tokio::spawn(async move {
+            let ip = IpAddr::V6(host);
+            let mut open_ports = Vec::new();
+            let mut response_time: Option<Duration> = None;
+            for &p in ports.iter() {
+                if let Some(rtt) = probe_port(SocketAddr::new(ip, p), timeout, retry).await {
+                    open_ports.push(p);
+                    response_time = Some(response_time.map_or(rtt, |best| best.min(rtt)));
+                }
+            }
+            let name = reverse_lookup(ip, resolve).await;
+            let device_type = match fingerprint_device(ip, &open_ports).await {
+                Some(kind) => Some(kind.to_string()),
+                None => guess_device_type(&name, &open_ports),
+            };
+            DeviceInfo {
+                ip,
+                hostname: name,
+                manufacturer: guess_manufacturer(&mac),
+                mac_address: Some(mac),
+                open_ports,
+                device_type,
+                response_time: response_time.unwrap_or_default(),
+            }
+        })
+    }).collect();
+
+    let mut map = BTreeMap::new();
+    loop {
+        let next = tokio::select! {
+            next = probes.next() => next,
+            _ = cancel.cancelled() => {
+                probes.iter().for_each(JoinHandle::abort);
+                return Err(AppError::Cancelled("Device discovery cancelled".into()));
+            }
+        };
+        let Some(result) = next else { break };
+        let device = result?;
+        println!("  {} {} - {}", "✓".green(), device.ip.to_string().cyan(), device.hostname.yellow());
+        map.insert(device.ip, device);
+    }
+
+    print_device_table(&map);
+    Ok((map.into_values().collect(), neighbor_count))
+}
+
+// Broadcast an ARP request for every host in `net` from interface `iface_name` and collect the
+// replies (IP, MAC, round-trip time) that arrive until `wait` after the last request.
+// Needs raw socket access (root or CAP_NET_RAW).
+#[cfg(unix)]
+fn arp_scan(iface_name: &str, net: Ipv4Network, wait: Duration) -> io::Result<Vec<(Ipv4Addr, [u8; 6], Duration)>> {
+    use pnet::datalink::{self, Channel};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let iface = datalink::interfaces().into_iter()
+        .find(|i| i.name == iface_name)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("interface {} not found", iface_name)))?;
+    let src_mac = iface.mac
+        .filter(|mac| !mac.is_zero())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, format!("interface {} has no MAC address", iface_name)))?;
+    let config = datalink::Config { read_timeout: Some(Duration::from_millis(50)), ..Default::default() };
+    let (mut tx, mut rx) = match datalink::channel(&iface, config)? {
+        Channel::Ethernet(tx, rx) => (tx, rx),
+        _ => return Err(io::Error::new(io::ErrorKind::Unsupported, "not an Ethernet interface")),
+    };
+
+    let src_ip = net.ip();
+    let start_ip = u32::from(net.network());
+    let end_ip = start_ip + net.size() - 2; // Skip network and broadcast addresses
+    let sent_at = Mutex::new(HashMap::new());
+    let stop = AtomicBool::new(false);
+
+    std::thread::scope(|scope| {
+        // Read replies on a second thread while requests go out, so round-trip times are real
+        let reader = scope.spawn(|| {
+            let mut replies = BTreeMap::new();
+            while !stop.load(Ordering::Relaxed) {
+                let Ok(frame) = rx.next() else { continue };
+                if let Some((ip, mac)) = parse_arp_reply(frame) {
+                    let received = Instant::now();
+                    if let Some(sent) = sent_at.lock().unwrap().get(&ip) {
+                        replies.entry(ip).or_insert((mac, received.duration_since(*sent)));
+                    }
+                }
+            }
+            replies
+        });
+
+        for ip_int in start_ip + 1..=end_ip {
+            let host = Ipv4Addr::from(ip_int);
+            if host == src_ip {
+                continue;
+            }
+            sent_at.lock().unwrap().insert(host, Instant::now());
+            if let Some(Err(e)) = tx.send_to(&arp_request(src_mac.octets(), src_ip, host), None) {
+                stop.store(true, Ordering::Relaxed);
+                return Err(e);
+            }
+        }
+        std::thread::sleep(wait);
+        stop.store(true, Ordering::Relaxed);
+        let replies = reader.join().map_err(|_| io::Error::new(io::ErrorKind::Other, "ARP reader thread panicked"))?;
+        Ok(replies.into_iter().map(|(ip, (mac, rtt))| (ip, mac, rtt)).collect())
+    })
+}
+
+#[cfg(not(unix))]
+fn arp_scan(_iface_name: &str, _net: Ipv4Network, _wait: Duration) -> io::Result<Vec<(Ipv4Addr, [u8; 6], Duration)>> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "raw sockets are not supported on this platform"))
+}
+
+// Broadcast Ethernet frame asking who has `target`
+#[cfg(unix)]
+fn arp_request(src_mac: [u8; 6], src_ip: Ipv4Addr, target: Ipv4Addr) -> [u8; 42] {
+    use pnet::packet::arp::{ArpHardwareTypes, ArpOperations, MutableArpPacket};
+    use pnet::packet::ethernet::{EtherTypes, MutableEthernetPacket};
+    use pnet::packet::MutablePacket;
+    use pnet::util::MacAddr;
+
+    let mut frame = [0u8; 42];
+    {
+        let mut eth = MutableEthernetPacket::new(&mut frame).unwrap();
+        eth.set_destination(MacAddr::broadcast());
+        eth.set_source(MacAddr::from(src_mac));
+        eth.set_ethertype(EtherTypes::Arp);
+
+        let mut arp = MutableArpPacket::new(eth.payload_mut()).unwrap();
+        arp.set_hardware_type(ArpHardwareTypes::Ethernet);
+        arp.set_protocol_type(EtherTypes::Ipv4);
+        arp.set_hw_addr_len(6);
+        arp.set_proto_addr_len(4);
+        arp.set_operation(ArpOperations::Request);
+        arp.set_sender_hw_addr(MacAddr::from(src_mac));
+        arp.set_sender_proto_addr(src_ip);
+        arp.set_target_hw_addr(MacAddr::zero());
+        arp.set_target_proto_addr(target);
+    }
+    frame
+}
+
+// Sender IP and MAC of an ARP reply frame
+#[cfg(unix)]
+fn parse_arp_reply(frame: &[u8]) -> Option<(Ipv4Addr, [u8; 6])> {
+    use pnet::packet::arp::{ArpOperations, ArpPacket};
+    use pnet::packet::ethernet::{EtherTypes, EthernetPacket};
+    use pnet::packet::Packet;
+
+    let eth = EthernetPacket::new(frame)?;
+    if eth.get_ethertype() != EtherTypes::Arp {
+        return None;
+    }
+    let arp = ArpPacket::new(eth.payload())?;
+    if arp.get_operation() != ArpOperations::Reply {
+        return None;
+    }
+    Some((arp.get_sender_proto_addr(), arp.get_sender_hw_addr().octets()))
 }
 
 // Helper functions for device identification
 
+lazy_static! {
+    // macOS/BSD and Linux `arp -a`: "router (192.168.1.1) at 0:11:22:33:44:55 on en0 ..."
+    static ref ARP_BSD_LINE: Regex =
+        Regex::new(r"\((\d{1,3}(?:\.\d{1,3}){3})\) at ([0-9A-Fa-f]{1,2}(?::[0-9A-Fa-f]{1,2}){5})\b").unwrap();
+    // Windows `arp -a`: "  192.168.1.1           00-11-22-33-44-55     dynamic"
+    static ref ARP_WINDOWS_LINE: Regex =
+        Regex::new(r"^\s*(\d{1,3}(?:\.\d{1,3}){3})\s+([0-9A-Fa-f]{2}(?:-[0-9A-Fa-f]{2}){5})\s").unwrap();
+    // Linux `ip neigh`: "192.168.1.1 dev eth0 lladdr 00:11:22:33:44:55 REACHABLE"
+    static ref IP_NEIGH_LINE: Regex =
+        Regex::new(r"^(\d{1,3}(?:\.\d{1,3}){3})\s.*\blladdr ([0-9A-Fa-f]{2}(?::[0-9A-Fa-f]{2}){5})\b").unwrap();
+}
+
+// Which tool printed an ARP table, and so which line layout it uses
+#[derive(Clone, Copy, Debug)]
+enum ArpLayout {
+    Bsd,
+    Windows,
+    IpNeigh,
+}
+
+// Pad, lower-case and colon-separate a MAC ("0:11:2:33:44:55" -> "00:11:02:33:44:55").
+// Zero, broadcast and multicast addresses aren't devices, so they are dropped.
+fn canonical_mac(raw: &str) -> Option<String> {
+    let octets = raw.split([':', '-'])
+        .map(|part| u8::from_str_radix(part, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    if octets.len() != 6 || octets.iter().all(|&b| b == 0) || octets[0] & 1 == 1 {
+        return None;
+    }
+    Some(octets.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":"))
+}
+
+// IP -> MAC pairs from the output of an ARP table command
+fn parse_arp_output(output: &str, layout: ArpLayout) -> HashMap<Ipv4Addr, String> {
+    let regex: &Regex = match layout {
+        ArpLayout::Bsd => &ARP_BSD_LINE,
+        ArpLayout::Windows => &ARP_WINDOWS_LINE,
+        ArpLayout::IpNeigh => &IP_NEIGH_LINE,
+    };
+    output.lines()
+        .filter_map(|line| regex.captures(line))
+        .filter_map(|caps| Some((caps[1].parse().ok()?, canonical_mac(&caps[2])?)))
+        .collect()
+}
+
+// Run a command and return its stdout, or None if it can't run or fails
+fn command_stdout(program: &str, args: &[String]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// Read the OS ARP table, either whole or just the entry for `target`
+fn query_arp_table(target: Option<Ipv4Addr>) -> HashMap<Ipv4Addr, String> {
+    let target: Vec<String> = target.map(|ip| ip.to_string()).into_iter().collect();
+    if cfg!(target_os = "windows") {
+        let args = [vec!["-a".to_string()], target].concat();
+        return command_stdout("arp", &args).map_or_else(HashMap::new, |out| parse_arp_output(&out, ArpLayout::Windows));
+    }
+    if cfg!(target_os = "linux") {
+        // net-tools' arp prints the BSD layout with -a; newer systems may only have iproute2
+        let args = [vec!["-a".to_string(), "-n".to_string()], target.clone()].concat();
+        if let Some(out) = command_stdout("arp", &args) {
+            return parse_arp_output(&out, ArpLayout::Bsd);
+        }
+        let args = [vec!["neigh".to_string(), "show".to_string()], target].concat();
+        return command_stdout("ip", &args).map_or_else(HashMap::new, |out| parse_arp_output(&out, ArpLayout::IpNeigh));
+    }
+    // macOS/BSD: `arp -a` lists everything, `arp -n <ip>` a single host
+    let args = if target.is_empty() { vec!["-a".to_string(), "-n".to_string()] } else { [vec!["-n".to_string()], target].concat() };
+    command_stdout("arp", &args).map_or_else(HashMap::new, |out| parse_arp_output(&out, ArpLayout::Bsd))
+}
+
 // Get MAC addresses from ARP cache
 fn get_arp_cache() -> HashMap<Ipv4Addr, String> {
-    let mut result = HashMap::new();
-    
-    // Try to run arp command to get MAC addresses
-    let output = match Command::new("arp").arg("-a").output() {
-        Ok(output) => output,
-        Err(_) => return result,
+    query_arp_table(None)
+}
+
+// MAC address of one host from the ARP table, if it has an entry
+fn lookup_arp_entry(host: Ipv4Addr) -> Option<String> {
+    query_arp_table(Some(host)).remove(&host)
+}
+
+// The /64 network an IPv6 address belongs to
+fn ipv6_prefix64(ip: &Ipv6Addr) -> Ipv6Addr {
+    Ipv6Addr::from(u128::from(*ip) & (u128::MAX << 64))
+}
+
+// fe80::/10
+fn is_ipv6_link_local(ip: &Ipv6Addr) -> bool {
+    ip.segments()[0] & 0xffc0 == 0xfe80
+}
+
+// IP -> MAC pairs from an IPv6 neighbor table. Linux `ip -6 neigh`, macOS `ndp -an` and
+// Windows `netsh interface ipv6 show neighbors` all start a line with the address
+// (possibly with a %zone) and have the MAC further along; entries without one are skipped.
+fn parse_neighbor_table(output: &str) -> HashMap<Ipv6Addr, String> {
+    output.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let ip = fields.next()?.split('%').next()?.parse().ok()?;
+            Some((ip, fields.find_map(canonical_mac)?))
+        })
+        .collect()
+}
+
+// Read the OS IPv6 neighbor table, the IPv6 counterpart of the ARP cache
+fn query_neighbor_table() -> HashMap<Ipv6Addr, String> {
+    let (program, args): (&str, &[&str]) = if cfg!(target_os = "windows") {
+        ("netsh", &["interface", "ipv6", "show", "neighbors"])
+    } else if cfg!(target_os = "linux") {
+        ("ip", &["-6", "neigh", "show"])
+    } else {
+        ("ndp", &["-an"])
     };
-    
-    if !output.status.success() {
-        return result;
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    command_stdout(program, &args).map_or_else(HashMap::new, |out| parse_neighbor_table(&out))
+}
+
+// Ping the all-nodes multicast address on `iface_name` so the hosts on the link answer
+// and land in the neighbor table. Windows' ping wants an interface index rather than a
+// name here, so it relies on the table as it is.
+async fn ping_all_nodes(iface_name: &str) {
+    if cfg!(target_os = "windows") {
+        return;
     }
-    
-    let arp_output = match String::from_utf8(output.stdout) {
-        Ok(s) => s,
-        Err(_) => return result,
+    let target = format!("ff02::1%{}", iface_name);
+    let mut cmd = if cfg!(target_os = "linux") {
+        let mut cmd = tokio::process::Command::new("ping");
+        cmd.args(["-6", "-c", "2"]);
+        cmd
+    } else {
+        let mut cmd = tokio::process::Command::new("ping6");
+        cmd.args(["-c", "2"]);
+        cmd
     };
-    
-    // Parse the ARP table output (format differs by OS)
-    for line in arp_output.lines() {
-        // Skip header lines
-        if line.contains("Address") || line.trim().is_empty() {
-            continue;
-        }
-        
-        // Extract IP and MAC address using common patterns
-        if let Some(ip_str) = line.split_whitespace().find(|s| s.contains('.')) {
-            if let Ok(ip) = ip_str.parse::<Ipv4Addr>() {
-                // Extract MAC address (format may vary by OS)
-                if let Some(mac) = line.split_whitespace()
-                    .find(|s| s.contains(':') || s.contains('-')) {
-                    result.insert(ip, mac.to_string());
-                }
-            }
+    cmd.arg(&target).stdout(Stdio::null()).stderr(Stdio::null()).kill_on_drop(true);
+    let _ = time::timeout(Duration::from_secs(3), cmd.status()).await;
+}
+
+// How long to wait for a service to introduce itself
+const BANNER_TIMEOUT: Duration = Duration::from_millis(1500);
+// Plain-HTTP ports where a HEAD request gets a Server header back
+const HTTP_BANNER_PORTS: &[u16] = &[80, 8000, 8008, 8080, 8081];
+
+// Connect to `addr` and return the first thing the service says. HTTP ports only
+// answer a request, so they get a HEAD; SSH, FTP, SMTP and Telnet speak first.
+async fn grab_banner(addr: SocketAddr) -> Option<String> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut stream = time::timeout(BANNER_TIMEOUT, TcpStream::connect(addr)).await.ok()?.ok()?;
+    if HTTP_BANNER_PORTS.contains(&addr.port()) {
+        let request = format!("HEAD / HTTP/1.0\r\nHost: {}\r\n\r\n", addr.ip());
+        stream.write_all(request.as_bytes()).await.ok()?;
+    }
+    let mut buf = [0u8; 1024];
+    let n = time::timeout(BANNER_TIMEOUT, stream.read(&mut buf)).await.ok()?.ok()?;
+    (n > 0).then(|| String::from_utf8_lossy(&buf[..n]).into_owned())
+}
+
+// SSDP M-SEARCH naming the multicast group of the host's address family in HOST,
+// as UPnP requires even when the search is sent straight to one host
+fn ssdp_search_request(host: IpAddr) -> String {
+    let group = match host {
+        IpAddr::V4(_) => "239.255.255.250:1900",
+        IpAddr::V6(_) => "[FF02::C]:1900",
+    };
+    format!("M-SEARCH * HTTP/1.1\r\nHOST: {}\r\nMAN: \"ssdp:discover\"\r\nMX: 1\r\nST: ssdp:all\r\n\r\n", group)
+}
+
+// Ask a host's UPnP stack (SSDP, UDP 1900) to describe itself; returns the reply headers
+async fn ssdp_probe(host: IpAddr) -> Option<String> {
+    let local: SocketAddr = match host {
+        IpAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+        IpAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+    };
+    let socket = tokio::net::UdpSocket::bind(local).await.ok()?;
+    socket.send_to(ssdp_search_request(host).as_bytes(), (host, 1900)).await.ok()?;
+    let mut buf = [0u8; 2048];
+    let (n, _) = time::timeout(BANNER_TIMEOUT, socket.recv_from(&mut buf)).await.ok()?.ok()?;
+    Some(String::from_utf8_lossy(&buf[..n]).into_owned())
+}
+
+// Map a service banner to a device type. `ssdp` marks replies to an SSDP probe.
+fn classify_banner(banner: &str, ssdp: bool) -> Option<&'static str> {
+    let lower = banner.to_lowercase();
+    let server = lower.lines()
+        .find_map(|line| line.strip_prefix("server:"))
+        .map(str::trim)
+        .unwrap_or("");
+
+    let known: &[(&str, &str)] = &[
+        ("routeros", "Router"),
+        ("mikrotik", "Router"),
+        ("rosssh", "Router"),
+        ("openwrt", "Router"),
+        ("ddwrt", "Router"),
+        ("cups", "Printer"),
+        ("hp http server", "Printer"),
+        ("epson", "Printer"),
+        ("brother", "Printer"),
+        ("hikvision", "Camera"),
+        ("dnvrs", "Camera"),
+        ("sonos", "Speaker"),
+        ("roku", "Media Player"),
+        ("synology", "NAS"),
+        ("qnap", "NAS"),
+        ("microsoft-iis", "Windows Server"),
+    ];
+    if let Some((_, kind)) = known.iter().find(|(needle, _)| lower.contains(needle)) {
+        return Some(kind);
+    }
+
+    if lower.starts_with("ssh-") {
+        // Dropbear is the SSH server of choice on routers and other embedded Linux boxes
+        return Some(if lower.contains("dropbear") { "Embedded Device" } else { "SSH Host" });
+    }
+    if ssdp {
+        return Some("Media/IoT Device");
+    }
+    if ["lighttpd", "micro_httpd", "mini_httpd", "goahead", "boa/", "uhttpd"].iter().any(|s| server.contains(s)) {
+        return Some("Embedded Device");
+    }
+    if !server.is_empty() {
+        return Some("Web Server");
+    }
+    if lower.starts_with("220") {
+        return Some(if lower.contains("smtp") { "Mail Server" } else { "FTP Server" });
+    }
+    None
+}
+
+// Fingerprint a host from its services: banners on the open ports first, then UPnP
+async fn fingerprint_device(host: IpAddr, open_ports: &[u16]) -> Option<&'static str> {
+    for &port in open_ports {
+        if let Some(kind) = grab_banner(SocketAddr::new(host, port)).await
+            .and_then(|banner| classify_banner(&banner, false))
+        {
+            return Some(kind);
         }
     }
-    
-    result
+    ssdp_probe(host).await.and_then(|reply| classify_banner(&reply, true))
 }
 
 // Guess device type based on hostname and open ports
@@ -319,18 +996,37 @@ fn guess_device_type(hostname: &str, open_ports: &[u16]) -> Option<String> {
     None
 }
 
+// Strip ':' / '-' separators and upper-case a MAC address ("aa-bb-cc..." -> "AABBCC...")
+fn normalize_mac(mac: &str) -> String {
+    mac.replace([':', '-'], "").to_uppercase()
+}
+
+/// Parses a MAC address in colon or dash form (e.g. 00:11:22:33:44:55) into its six bytes
+pub fn parse_mac(mac: &str) -> Result<[u8; 6], String> {
+    let clean_mac = normalize_mac(mac.trim());
+    if clean_mac.len() != 12 || !clean_mac.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid MAC address: {}", mac));
+    }
+    let mut bytes = [0u8; 6];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&clean_mac[i * 2..i * 2 + 2], 16)
+            .map_err(|_| format!("Invalid MAC address: {}", mac))?;
+    }
+    Ok(bytes)
+}
+
 // Guess manufacturer from MAC address
 fn guess_manufacturer(mac: &str) -> Option<String> {
     // Extract OUI (first 6 characters of MAC address without separators)
-    let clean_mac = mac.replace(':', "").replace('-', "");
+    let clean_mac = normalize_mac(mac);
     if clean_mac.len() < 6 {
         return None;
     }
     
-    let oui = clean_mac[0..6].to_uppercase();
+    let oui = &clean_mac[0..6];
     
     // Very simple OUI to manufacturer mapping for common vendors
-    match oui.as_str() {
+    match oui {
         "001122" | "003342" | "0050B6" => Some("Apple".to_string()),
         "FCFBFB" | "8C8ABE" => Some("Google".to_string()),
         "000DE8" | "00127F" => Some("Cisco".to_string()),
@@ -355,14 +1051,89 @@ fn truncate(s: &str, max_len: usize) -> String {
     }
 }
 
+/// ---------------------------------------------------------------------------
+/// Wake-on-LAN
+/// ---------------------------------------------------------------------------
+
+// 6 × 0xFF followed by the target MAC repeated 16 times
+fn magic_packet(mac: [u8; 6]) -> Vec<u8> {
+    let mut packet = vec![0xFF; 6];
+    for _ in 0..16 {
+        packet.extend_from_slice(&mac);
+    }
+    packet
+}
+
+/// Sends a Wake-on-LAN magic packet for `mac` to the given broadcast address.
+pub async fn wake_on_lan(mac: [u8; 6], broadcast: Ipv4Addr, port: u16) -> Result<()> {
+    let socket = tokio::net::UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    socket.set_broadcast(true)?;
+    socket.send_to(&magic_packet(mac), (broadcast, port)).await?;
+
+    let mac_str = mac.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(":");
+    println!(
+        "{} {} via {}:{}",
+        "✓  Magic packet sent to".green(),
+        mac_str.yellow(),
+        broadcast.to_string().cyan(),
+        port
+    );
+    Ok(())
+}
+
 /// ---------------------------------------------------------------------------
 /// Ping tool
 /// ---------------------------------------------------------------------------
 
+/// Result of `ping_host_result`
+#[derive(Debug, Clone, Serialize)]
+pub struct PingResult {
+    pub target: String,
+    pub ip: String,
+    pub sent: u32,
+    pub received: u32,
+    pub loss_percent: f64,
+    pub min_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+    pub avg_ms: Option<f64>,
+    pub raw_output: String, // The system ping command's own output
+}
+
 /// Pings a host to check if it's online and measures response time
-pub async fn ping_host(target: &str, count: u32) -> Result<(), Box<dyn Error + Send + Sync>> {
+pub async fn ping_host(target: &str, count: u32) -> Result<()> {
     println!("{}", format!("🔔 Pinging {} {} times...", target, count).cyan().bold());
-    
+
+    let result = ping_host_result(target, count).await?;
+    println!("{}", format!("Resolved to IP: {}", result.ip).dimmed());
+
+    // Print raw output first
+    println!("{}", "-".repeat(50).dimmed());
+    println!("{}", result.raw_output);
+    println!("{}", "-".repeat(50).dimmed());
+
+    // Calculate statistics
+    println!("{}", "Ping Statistics Summary:".blue().bold());
+    println!("Target: {}", target.yellow());
+    println!("Packets: Sent = {}, Received = {}, Lost = {} ({}% loss)",
+        result.sent,
+        result.received,
+        result.sent.saturating_sub(result.received),
+        result.loss_percent.round()
+    );
+
+    if let (Some(min_ms), Some(max_ms), Some(avg_ms)) = (result.min_ms, result.max_ms, result.avg_ms) {
+        println!("Round-trip times: Min = {:.2}ms, Max = {:.2}ms, Average = {:.2}ms", 
+            min_ms, 
+            max_ms,
+            avg_ms
+        );
+    }
+
+    Ok(())
+}
+
+/// Pings a host with the system ping command and returns the parsed statistics
+pub async fn ping_host_result(target: &str, count: u32) -> Result<PingResult> {
     // Clone target string for use in the async block
     let target_owned = target.to_string();
     
@@ -372,26 +1143,22 @@ pub async fn ping_host(target: &str, count: u32) -> Result<(), Box<dyn Error + S
         Err(_) => {
             // Try to resolve hostname to IP
             let target_clone = target_owned.clone();
-            match tokio::task::spawn_blocking(move || -> Result<String, Box<dyn Error + Send + Sync>> {
+            tokio::task::spawn_blocking(move || -> Result<String> {
                 let ips = dns_lookup::lookup_host(&target_clone)?
                     .into_iter()
                     .filter(|ip| ip.is_ipv4())
                     .collect::<Vec<_>>();
                 
                 if ips.is_empty() {
-                    return Err(format!("Could not resolve hostname: {}", target_clone).into());
+                    return Err(AppError::Network(format!("Could not resolve hostname: {}", target_clone)));
                 }
                 Ok(ips[0].to_string())
-            }).await?? {
-                ip => ip,
-            }
+            }).await??
         }
     };
     
-    println!("{}", format!("Resolved to IP: {}", ip_addr).dimmed());
-    
     let mut success_count = 0;
-    let mut _failure_count = 0; // Using underscore prefix to indicate intentionally unused
+    let mut timed_count = 0;
     let mut total_ms: f64 = 0.0;
     let mut min_ms: f64 = f64::MAX;
     let mut max_ms: f64 = 0.0;
@@ -412,15 +1179,10 @@ pub async fn ping_host(target: &str, count: u32) -> Result<(), Box<dyn Error + S
         .args(&args)
         .output() {
         Ok(output) => output,
-        Err(e) => return Err(format!("Failed to execute ping command: {}", e).into()),
+        Err(e) => return Err(AppError::Other(format!("Failed to execute ping command: {}", e))),
     };
     
-    let output_str = String::from_utf8_lossy(&output.stdout);
-    
-    // Print raw output first
-    println!("{}", "-".repeat(50).dimmed());
-    println!("{}", output_str);
-    println!("{}", "-".repeat(50).dimmed());
+    let output_str = String::from_utf8_lossy(&output.stdout).to_string();
     
     // Parse output to extract times (varies by OS, this is a simplified version)
     for line in output_str.lines() {
@@ -430,100 +1192,669 @@ pub async fn ping_host(target: &str, count: u32) -> Result<(), Box<dyn Error + S
             success_count += 1;
             
             // Extract time in ms - this parsing is simplified and may need adjustment
-            if let Some(time_pos) = line.find("time=") {
-                if let Some(end_pos) = line[time_pos+5..].find(" ") {
-                    if let Ok(time_ms) = line[time_pos+5..time_pos+5+end_pos].trim_end_matches("ms").parse::<f64>() {
-                        total_ms += time_ms;
-                        min_ms = min_ms.min(time_ms);
-                        max_ms = max_ms.max(time_ms);
-                    }
-                }
-            } else if let Some(time_pos) = line.find("time:") {
+            if let Some(time_pos) = line.find("time=").or_else(|| line.find("time:")) {
                 if let Some(end_pos) = line[time_pos+5..].find(" ") {
                     if let Ok(time_ms) = line[time_pos+5..time_pos+5+end_pos].trim_end_matches("ms").parse::<f64>() {
                         total_ms += time_ms;
                         min_ms = min_ms.min(time_ms);
                         max_ms = max_ms.max(time_ms);
+                        timed_count += 1;
                     }
                 }
             }
-        } else if line.contains("Request timed out") || line.contains("Destination Host Unreachable") {
-            _failure_count += 1;
         }
     }
     
-    // Calculate statistics
-    println!("{}", "Ping Statistics Summary:".blue().bold());
-    println!("Target: {}", target.yellow());
-    println!("Packets: Sent = {}, Received = {}, Lost = {} ({}% loss)",
-        count,
-        success_count,
-        count - success_count,
-        ((count - success_count) as f64 / count as f64 * 100.0).round()
-    );
-    
-    if success_count > 0 {
-        let avg_ms = total_ms / success_count as f64;
-        println!("Round-trip times: Min = {:.2}ms, Max = {:.2}ms, Average = {:.2}ms", 
-            min_ms, 
-            max_ms,
-            avg_ms
-        );
-    }
-    
-    Ok(())
+    let has_times = timed_count > 0;
+    Ok(PingResult {
+        target: target_owned,
+        ip: ip_addr,
+        sent: count,
+        received: success_count,
+        loss_percent: if count > 0 { count.saturating_sub(success_count) as f64 / count as f64 * 100.0 } else { 0.0 },
+        min_ms: has_times.then_some(min_ms),
+        max_ms: has_times.then_some(max_ms),
+        avg_ms: has_times.then(|| total_ms / timed_count as f64),
+        raw_output: output_str,
+    })
 }
 
 /// ---------------------------------------------------------------------------
 /// Port scanner
 /// ---------------------------------------------------------------------------
 
-/// Scan `ports` on `target` (hostname or IPv4) within `timeout_ms` per port.
-pub async fn scan_ports(target: &str, ports: &[u16], timeout_ms: u64) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let timeout = Duration::from_millis(timeout_ms);
+/// Well-known TCP service names (IANA service-name registry), sorted by port
+const WELL_KNOWN_PORTS: &[(u16, &str)] = &[
+    (20, "ftp-data"),
+    (21, "ftp"),
+    (22, "ssh"),
+    (23, "telnet"),
+    (25, "smtp"),
+    (53, "domain"),
+    (67, "bootps"),
+    (68, "bootpc"),
+    (69, "tftp"),
+    (80, "http"),
+    (88, "kerberos"),
+    (110, "pop3"),
+    (111, "sunrpc"),
+    (119, "nntp"),
+    (123, "ntp"),
+    (135, "msrpc"),
+    (137, "netbios-ns"),
+    (138, "netbios-dgm"),
+    (139, "netbios-ssn"),
+    (143, "imap"),
+    (161, "snmp"),
+    (162, "snmptrap"),
+    (179, "bgp"),
+    (389, "ldap"),
+    (443, "https"),
+    (445, "microsoft-ds"),
+    (465, "submissions"),
+    (514, "syslog"),
+    (515, "printer"),
+    (548, "afp"),
+    (554, "rtsp"),
+    (587, "submission"),
+    (631, "ipp"),
+    (636, "ldaps"),
+    (873, "rsync"),
+    (993, "imaps"),
+    (995, "pop3s"),
+    (1080, "socks"),
+    (1194, "openvpn"),
+    (1433, "ms-sql-s"),
+    (1521, "oracle"),
+    (1723, "pptp"),
+    (1883, "mqtt"),
+    (2049, "nfs"),
+    (2375, "docker"),
+    (2376, "docker-s"),
+    (3000, "hbci"),
+    (3306, "mysql"),
+    (3389, "ms-wbt-server"),
+    (5000, "upnp"),
+    (5060, "sip"),
+    (5353, "mdns"),
+    (5432, "postgresql"),
+    (5672, "amqp"),
+    (5900, "vnc"),
+    (6379, "redis"),
+    (6443, "kubernetes"),
+    (8000, "http-alt"),
+    (8080, "http-alt"),
+    (8443, "https-alt"),
+    (8883, "secure-mqtt"),
+    (9000, "cslistener"),
+    (9090, "websm"),
+    (9100, "jetdirect"),
+    (9200, "wap-wsp"),
+    (11211, "memcache"),
+    (25565, "minecraft"),
+    (27017, "mongodb"),
+];
 
-    // 1. Resolve once
-    let ip = format!("{}:0", target)
+/// The 100 most frequently open TCP ports (after nmap's `--top-ports 100`), sorted
+const TOP_100_PORTS: &[u16] = &[
+    7, 9, 13, 21, 22, 23, 25, 26, 37, 53, 79, 80, 81, 88, 106, 110, 111, 113, 119, 135,
+    139, 143, 144, 179, 199, 389, 427, 443, 444, 445, 465, 513, 514, 515, 543, 544, 548, 554,
+    587, 631, 646, 873, 990, 993, 995, 1025, 1026, 1027, 1028, 1029, 1110, 1433, 1720, 1723,
+    1755, 1900, 2000, 2001, 2049, 2121, 2717, 3000, 3128, 3306, 3389, 3986, 4899, 5000, 5009,
+    5051, 5060, 5101, 5190, 5357, 5432, 5631, 5666, 5800, 5900, 6000, 6001, 6646, 7070, 8000,
+    8008, 8009, 8080, 8081, 8443, 8888, 9100, 9999, 10000, 32768, 49152, 49153, 49154, 49155,
+    49156, 49157,
+];
+
+/// The 1000 most frequently open TCP ports (nmap's default `--top-ports 1000` set), sorted
+const TOP_1000_PORTS: &[u16] = &[
+    1, 3, 4, 6, 7, 9, 13, 17, 19, 20, 21, 22, 23, 24, 25, 26, 30, 32, 33, 37, 42, 43, 49, 53, 70,
+    79, 80, 81, 82, 83, 84, 85, 88, 89, 90, 99, 100, 106, 109, 110, 111, 113, 119, 125, 135, 139,
+    143, 144, 146, 161, 163, 179, 199, 211, 212, 222, 254, 255, 256, 259, 264, 280, 301, 306, 311,
+    340, 366, 389, 406, 407, 416, 417, 425, 427, 443, 444, 445, 458, 464, 465, 481, 497, 500, 512,
+    513, 514, 515, 524, 541, 543, 544, 545, 548, 554, 555, 563, 587, 593, 616, 617, 625, 631, 636,
+    646, 648, 666, 667, 668, 683, 687, 691, 700, 705, 711, 714, 720, 722, 726, 749, 765, 777, 783,
+    787, 800, 801, 808, 843, 873, 880, 888, 898, 900, 901, 902, 903, 911, 912, 981, 987, 990, 992,
+    993, 995, 999, 1000, 1001, 1002, 1007, 1009, 1010, 1011, 1021, 1022, 1023, 1024, 1025, 1026,
+    1027, 1028, 1029, 1030, 1031, 1032, 1033, 1034, 1035, 1036, 1037, 1038, 1039, 1040, 1041, 1042,
+    1043, 1044, 1045, 1046, 1047, 1048, 1049, 1050, 1051, 1052, 1053, 1054, 1055, 1056, 1057, 1058,
+    1059, 1060, 1061, 1062, 1063, 1064, 1065, 1066, 1067, 1068, 1069, 1070, 1071, 1072, 1073, 1074,
+    1075, 1076, 1077, 1078, 1079, 1080, 1081, 1082, 1083, 1084, 1085, 1086, 1087, 1088, 1089, 1090,
+    1091, 1092, 1093, 1094, 1095, 1096, 1097, 1098, 1099, 1100, 1102, 1104, 1105, 1106, 1107, 1108,
+    1110, 1111, 1112, 1113, 1114, 1117, 1119, 1121, 1122, 1123, 1124, 1126, 1130, 1131, 1132, 1137,
+    1138, 1141, 1145, 1147, 1148, 1149, 1151, 1152, 1154, 1163, 1164, 1165, 1166, 1169, 1174, 1175,
+    1183, 1185, 1186, 1187, 1192, 1198, 1199, 1201, 1213, 1216, 1217, 1218, 1233, 1234, 1236, 1244,
+    1247, 1248, 1259, 1271, 1272, 1277, 1287, 1296, 1300, 1301, 1309, 1310, 1311, 1322, 1328, 1334,
+    1352, 1417, 1433, 1434, 1443, 1455, 1461, 1494, 1500, 1501, 1503, 1521, 1524, 1533, 1556, 1580,
+    1583, 1594, 1600, 1641, 1658, 1666, 1687, 1688, 1700, 1717, 1718, 1719, 1720, 1721, 1723, 1755,
+    1761, 1782, 1783, 1801, 1805, 1812, 1839, 1840, 1862, 1863, 1864, 1875, 1900, 1914, 1935, 1947,
+    1971, 1972, 1974, 1984, 1998, 1999, 2000, 2001, 2002, 2003, 2004, 2005, 2006, 2007, 2008, 2009,
+    2010, 2013, 2020, 2021, 2022, 2030, 2033, 2034, 2035, 2038, 2040, 2041, 2042, 2043, 2045, 2046,
+    2047, 2048, 2049, 2065, 2068, 2099, 2100, 2103, 2105, 2106, 2107, 2111, 2119, 2121, 2126, 2135,
+    2144, 2160, 2161, 2170, 2179, 2190, 2191, 2196, 2200, 2222, 2251, 2260, 2288, 2301, 2323, 2366,
+    2381, 2382, 2383, 2393, 2394, 2399, 2401, 2492, 2500, 2522, 2525, 2557, 2601, 2602, 2604, 2605,
+    2607, 2608, 2638, 2701, 2702, 2710, 2717, 2718, 2725, 2800, 2809, 2811, 2869, 2875, 2909, 2910,
+    2920, 2967, 2968, 2998, 3000, 3001, 3003, 3005, 3006, 3007, 3011, 3013, 3017, 3030, 3031, 3052,
+    3071, 3077, 3128, 3168, 3211, 3221, 3260, 3261, 3268, 3269, 3283, 3300, 3301, 3306, 3322, 3323,
+    3324, 3325, 3333, 3351, 3367, 3369, 3370, 3371, 3372, 3389, 3390, 3404, 3476, 3493, 3517, 3527,
+    3546, 3551, 3580, 3659, 3689, 3690, 3703, 3737, 3766, 3784, 3800, 3801, 3809, 3814, 3826, 3827,
+    3828, 3851, 3869, 3871, 3878, 3880, 3889, 3905, 3914, 3918, 3920, 3945, 3971, 3986, 3995, 3998,
+    4000, 4001, 4002, 4003, 4004, 4005, 4006, 4045, 4111, 4125, 4126, 4129, 4224, 4242, 4279, 4321,
+    4343, 4443, 4444, 4445, 4446, 4449, 4550, 4567, 4662, 4848, 4899, 4900, 4998, 5000, 5001, 5002,
+    5003, 5004, 5009, 5030, 5033, 5050, 5051, 5054, 5060, 5061, 5080, 5087, 5100, 5101, 5102, 5120,
+    5190, 5200, 5214, 5221, 5222, 5225, 5226, 5269, 5280, 5298, 5357, 5405, 5414, 5431, 5432, 5440,
+    5500, 5510, 5544, 5550, 5555, 5560, 5566, 5631, 5633, 5666, 5678, 5679, 5718, 5730, 5800, 5801,
+    5802, 5810, 5811, 5815, 5822, 5825, 5850, 5859, 5862, 5877, 5900, 5901, 5902, 5903, 5904, 5906,
+    5907, 5910, 5911, 5915, 5922, 5925, 5950, 5952, 5959, 5960, 5961, 5962, 5963, 5987, 5988, 5989,
+    5998, 5999, 6000, 6001, 6002, 6003, 6004, 6005, 6006, 6007, 6009, 6025, 6059, 6100, 6101, 6106,
+    6112, 6123, 6129, 6156, 6346, 6389, 6502, 6510, 6543, 6547, 6565, 6566, 6567, 6580, 6646, 6666,
+    6667, 6668, 6669, 6689, 6692, 6699, 6779, 6788, 6789, 6792, 6839, 6881, 6901, 6969, 7000, 7001,
+    7002, 7004, 7007, 7019, 7025, 7070, 7100, 7103, 7106, 7200, 7201, 7402, 7435, 7443, 7496, 7512,
+    7625, 7627, 7676, 7741, 7777, 7778, 7800, 7911, 7920, 7921, 7937, 7938, 7999, 8000, 8001, 8002,
+    8007, 8008, 8009, 8010, 8011, 8021, 8022, 8031, 8042, 8045, 8080, 8081, 8082, 8083, 8084, 8085,
+    8086, 8087, 8088, 8089, 8090, 8093, 8099, 8100, 8180, 8181, 8192, 8193, 8194, 8200, 8222, 8254,
+    8290, 8291, 8292, 8300, 8333, 8383, 8400, 8402, 8443, 8500, 8600, 8649, 8651, 8652, 8654, 8701,
+    8800, 8873, 8888, 8899, 8994, 9000, 9001, 9002, 9003, 9009, 9010, 9011, 9040, 9050, 9071, 9080,
+    9081, 9090, 9091, 9099, 9100, 9101, 9102, 9103, 9110, 9111, 9200, 9207, 9220, 9290, 9415, 9418,
+    9485, 9500, 9502, 9503, 9535, 9575, 9593, 9594, 9595, 9618, 9666, 9876, 9877, 9878, 9898, 9900,
+    9917, 9929, 9943, 9944, 9968, 9998, 9999, 10000, 10001, 10002, 10003, 10004, 10009, 10010,
+    10012, 10024, 10025, 10082, 10180, 10215, 10243, 10566, 10616, 10617, 10621, 10626, 10628,
+    10629, 10778, 11110, 11111, 11967, 12000, 12174, 12265, 12345, 13456, 13722, 13782, 13783,
+    14000, 14238, 14441, 14442, 15000, 15002, 15003, 15004, 15660, 15742, 16000, 16001, 16012,
+    16016, 16018, 16080, 16113, 16992, 16993, 17877, 17988, 18040, 18101, 18988, 19101, 19283,
+    19315, 19350, 19780, 19801, 19842, 20000, 20005, 20031, 20221, 20222, 20828, 21571, 22939,
+    23502, 24444, 24800, 25734, 25735, 26214, 27000, 27352, 27353, 27355, 27356, 27715, 28201,
+    30000, 30718, 30951, 31038, 31337, 32768, 32769, 32770, 32771, 32772, 32773, 32774, 32775,
+    32776, 32777, 32778, 32779, 32780, 32781, 32782, 32783, 32784, 32785, 33354, 33899, 34571,
+    34572, 34573, 35500, 38292, 40193, 40911, 41511, 42510, 44176, 44442, 44443, 44501, 45100,
+    48080, 49152, 49153, 49154, 49155, 49156, 49157, 49158, 49159, 49160, 49161, 49163, 49165,
+    49167, 49175, 49176, 49400, 49999, 50000, 50001, 50002, 50003, 50006, 50300, 50389, 50500,
+    50636, 50800, 51103, 51493, 52673, 52822, 52848, 52869, 54045, 54328, 55055, 55056, 55555,
+    55600, 56737, 56738, 57294, 57797, 58080, 60020, 60443, 61532, 61900, 62078, 63331, 64623,
+    64680, 65000, 65129, 65389,
+];
+
+/// Named port sets for the scanner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortPreset {
+    Top100,
+    Top1000,
+    Common,
+    WellKnown,
+    All,
+}
+
+impl PortPreset {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "top-100" | "top100" => Some(Self::Top100),
+            "top-1000" | "top1000" => Some(Self::Top1000),
+            "common" => Some(Self::Common),
+            "well-known" | "wellknown" => Some(Self::WellKnown),
+            "all" => Some(Self::All),
+            _ => None,
+        }
+    }
+
+    /// Sorted, de-duplicated ports in this preset
+    pub fn ports(self) -> Vec<u16> {
+        match self {
+            Self::Top100 => TOP_100_PORTS.to_vec(),
+            Self::Top1000 => TOP_1000_PORTS.to_vec(),
+            // Every port below 1024 plus the high ports from the top-100 list
+            // and the service-name table
+            Self::Common => {
+                let mut ports: BTreeSet<u16> = (1..1024).collect();
+                ports.extend(TOP_100_PORTS.iter().copied());
+                ports.extend(WELL_KNOWN_PORTS.iter().map(|&(p, _)| p));
+                ports.into_iter().collect()
+            }
+            Self::WellKnown => (1..1024).collect(),
+            Self::All => (1..=u16::MAX).collect(),
+        }
+    }
+}
+
+/// Well-known service name for a TCP port, e.g. 443 → "https".
+pub fn port_service_name(port: u16) -> Option<&'static str> {
+    WELL_KNOWN_PORTS
+        .binary_search_by_key(&port, |&(p, _)| p)
+        .ok()
+        .map(|i| WELL_KNOWN_PORTS[i].1)
+}
+
+// "443 (https)", or just "443" when the port has no well-known name
+fn format_port(port: u16, numeric: bool) -> String {
+    match port_service_name(port) {
+        Some(name) if !numeric => format!("{} ({})", port, name),
+        _ => port.to_string(),
+    }
+}
+
+/// An open port and its well-known service name, if any
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenPort {
+    pub port: u16,
+    pub service: Option<&'static str>,
+}
+
+/// Result of `scan_ports_result`
+#[derive(Debug, Clone, Serialize)]
+pub struct PortScanResult {
+    pub target: String,
+    pub ip: IpAddr,
+    pub timeout_ms: u64,
+    pub open_ports: Vec<OpenPort>, // Sorted by port number
+}
+
+// First address of the wanted family; with `Any` IPv4 wins when the host has both (A and AAAA)
+fn pick_address(addrs: &[IpAddr], family: IpFamily) -> Option<IpAddr> {
+    match family {
+        IpFamily::Any => addrs.iter().find(|ip| ip.is_ipv4()).or_else(|| addrs.first()).copied(),
+        _ => addrs.iter().find(|ip| family.allows(ip)).copied(),
+    }
+}
+
+// Resolve a host name or address literal ("192.0.2.1", "2001:db8::1", "[2001:db8::1]")
+fn resolve_host(target: &str, family: IpFamily) -> Result<IpAddr> {
+    if let Ok(ip) = target.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        if !family.allows(&ip) {
+            return Err(AppError::InvalidInput(format!("{} is not an {} address", ip, family.label())));
+        }
+        return Ok(ip);
+    }
+    let addrs: Vec<IpAddr> = (target, 0)
         .to_socket_addrs()
-        .map_err(|e| -> Box<dyn Error + Send + Sync> { Box::new(e) })?
-        .find(|a| a.is_ipv4())
+        .map_err(|e| AppError::Network(e.to_string()))?
         .map(|a| a.ip())
-        .ok_or_else(|| -> Box<dyn Error + Send + Sync> { "Failed to resolve host".into() })?;
+        .collect();
+    pick_address(&addrs, family)
+        .ok_or_else(|| AppError::Network(format!("Failed to resolve {} to an {} address", target, family.label())))
+}
 
-    println!(
-        "{} {} ({}) – timeout {} ms",
-        "🚀  Port scan on".cyan(),
-        target.yellow(),
-        ip.to_string().cyan(),
-        timeout_ms
-    );
+/// Default cap on port probes in flight at once
+pub const DEFAULT_PORT_SCAN_CONCURRENCY: usize = 500;
+
+/// How fast `scan_ports` probes: both the in-flight cap and the per-second rate apply
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanPacing {
+    /// Probes in flight at once
+    pub concurrency: usize,
+    /// Probes started per second; `None` starts them as fast as the concurrency cap allows
+    pub rate: Option<u32>,
+    /// Probe the ports in random order instead of ascending
+    pub randomize: bool,
+}
+
+impl Default for ScanPacing {
+    fn default() -> Self {
+        ScanPacing { concurrency: DEFAULT_PORT_SCAN_CONCURRENCY, rate: None, randomize: false }
+    }
+}
+
+// Token bucket for `--rate`: refills at `rate` tokens a second and holds up to a tenth of a
+// second's worth, so high rates aren't held back by timer granularity
+struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u32, now: Instant) -> Self {
+        let rate = f64::from(rate.max(1));
+        let capacity = (rate / 10.0).max(1.0);
+        TokenBucket { rate, capacity, tokens: capacity, last: now }
+    }
+
+    // Take a token at `now`, or return how long until the next one is due
+    fn try_take(&mut self, now: Instant) -> Option<Duration> {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
 
-    // 2. Concurrent scan
+    async fn take(&mut self) {
+        while let Some(wait) = self.try_take(Instant::now()) {
+            time::sleep(wait).await;
+        }
+    }
+}
+
+// Probe the ports within the limits of `pacing` and return the open ones, sorted; None when cancelled
+async fn scan_open_ports(ip: IpAddr, ports: &[u16], timeout: Duration, pacing: &ScanPacing, cancel: &CancellationToken, pb: &ProgressBar) -> Option<Vec<u16>> {
     let open = Arc::new(Mutex::new(Vec::<u16>::new()));
     let mut tasks = FuturesUnordered::new();
+    let slots = Arc::new(Semaphore::new(pacing.concurrency.max(1)));
+    let mut bucket = pacing.rate.map(|rate| TokenBucket::new(rate, Instant::now()));
+    let mut order = ports.to_vec();
+    if pacing.randomize {
+        order.shuffle(&mut rand::thread_rng());
+    }
 
-    for &port in ports {
+    pb.set_length(ports.len() as u64);
+    pb.set_message("0 open");
+    for port in order {
+        // Wait for a free slot, then for a token, so neither limit can be exceeded
+        let launch = async {
+            let permit = slots.clone().acquire_owned().await.ok();
+            if let Some(bucket) = bucket.as_mut() {
+                bucket.take().await;
+            }
+            permit
+        };
+        let permit = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => {
+                tasks.iter().for_each(JoinHandle::abort);
+                pb.finish_and_clear();
+                return None;
+            }
+            permit = launch => permit,
+        };
         let open = open.clone();
+        let pb = pb.clone();
         tasks.push(tokio::spawn(async move {
+            let _permit = permit;
             if port_is_open(SocketAddr::new(ip, port), timeout).await {
-                open.lock().unwrap().push(port);
+                let mut open = open.lock().unwrap();
+                open.push(port);
+                pb.set_message(format!("{} open", open.len()));
             }
+            pb.inc(1);
         }));
     }
-    while tasks.next().await.is_some() {}
+    let finished = join_or_cancel(&mut tasks, cancel).await;
+    pb.finish_and_clear();
+    if !finished {
+        return None;
+    }
 
-    // 3. Report
-    let mut open = open.lock().unwrap();
+    let mut open = open.lock().unwrap().clone();
     open.sort_unstable();
+    Some(open)
+}
 
+/// Scan `ports` on `target` and return the open ones instead of printing them.
+/// `family` picks between the host's A and AAAA records.
+/// Cancelling `cancel` aborts the outstanding probes and returns an error.
+pub async fn scan_ports_result(target: &str, ports: &[u16], timeout_ms: u64, family: IpFamily, pacing: &ScanPacing, cancel: &CancellationToken) -> Result<PortScanResult> {
+    let ip = resolve_host(target, family)?;
+    let open = scan_open_ports(ip, ports, Duration::from_millis(timeout_ms), pacing, cancel, &ProgressBar::hidden()).await
+        .ok_or_else(|| AppError::Cancelled("Port scan cancelled".into()))?;
+    Ok(PortScanResult {
+        target: target.to_string(),
+        ip,
+        timeout_ms,
+        open_ports: open.into_iter().map(|port| OpenPort { port, service: port_service_name(port) }).collect(),
+    })
+}
+
+/// Scan `ports` on `target` and print the open ones.
+/// `family` picks between the host's A and AAAA records; `pacing` limits how fast probes go out.
+/// Cancelling `cancel` aborts the outstanding probes and returns an error without a report.
+pub async fn scan_ports(target: &str, ports: &[u16], timeout_ms: u64, numeric_ports: bool, family: IpFamily, pacing: &ScanPacing, cancel: &CancellationToken) -> Result<()> {
+    // 1. Resolve once
+    let ip = resolve_host(target, family)?;
+
+    println!(
+        "{} {} ({}) – timeout {} ms",
+        "🚀  Port scan on".cyan(),
+        target.yellow(),
+        ip.to_string().cyan(),
+        timeout_ms
+    );
+    if let Some(rate) = pacing.rate {
+        println!("{}", format!("Pacing at {} probe(s)/s, at most {} in flight{}", rate, pacing.concurrency,
+            if pacing.randomize { ", random order" } else { "" }).dimmed());
+    }
+
+    // 2. Concurrent scan, with a bar of probes finished and ports found so far
+    let pb = if utils::progress_bars_enabled() { ProgressBar::new(0) } else { ProgressBar::hidden() };
+    pb.set_style(ProgressStyle::default_bar()
+        .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ports, {msg} ({eta})")
+        .unwrap()
+        .progress_chars("#>-"));
+    let open = scan_open_ports(ip, ports, Duration::from_millis(timeout_ms), pacing, cancel, &pb).await
+        .ok_or_else(|| AppError::Cancelled("Port scan cancelled".into()))?;
+
+    // 3. Report
     if open.is_empty() {
         println!("{}", "No open ports detected.".yellow());
     } else {
         println!(
             "{} {}",
             "✓  Open port(s):".green(),
-            open.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(", ").yellow()
+            open.iter().map(|&p| format_port(p, numeric_ports)).collect::<Vec<_>>().join(", ").yellow()
         );
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_port_service_name() {
+        assert_eq!(port_service_name(22), Some("ssh"));
+        assert_eq!(port_service_name(443), Some("https"));
+        assert_eq!(port_service_name(3306), Some("mysql"));
+        assert_eq!(port_service_name(1), None);
+        assert!(WELL_KNOWN_PORTS.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn test_port_presets() {
+        assert_eq!(TOP_100_PORTS.len(), 100);
+        assert_eq!(PortPreset::from_name("top-100"), Some(PortPreset::Top100));
+        assert_eq!(PortPreset::from_name("80"), None);
+        assert_eq!(PortPreset::WellKnown.ports().len(), 1023);
+        assert_eq!(PortPreset::All.ports().len(), 65535);
+        assert_eq!(PortPreset::from_name("top-1000"), Some(PortPreset::Top1000));
+        let top_1000 = PortPreset::Top1000.ports();
+        assert_eq!(top_1000.len(), 1000);
+        assert!(top_1000.windows(2).all(|w| w[0] < w[1]));
+        assert!(TOP_100_PORTS.iter().all(|p| top_1000.contains(p)));
+        let common = PortPreset::Common.ports();
+        assert!(common.contains(&3306) && common.contains(&22) && common.contains(&27017));
+        assert!(common.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_ssdp_search_request() {
+        let v4 = ssdp_search_request(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1)));
+        assert!(v4.starts_with("M-SEARCH * HTTP/1.1\r\nHOST: 239.255.255.250:1900\r\n"));
+        let v6 = ssdp_search_request(IpAddr::V6(Ipv6Addr::LOCALHOST));
+        assert!(v6.contains("\r\nHOST: [FF02::C]:1900\r\n"));
+        assert!(v6.ends_with("ST: ssdp:all\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_parse_mac() {
+        let expected = [0x00, 0x11, 0x22, 0xAA, 0xBB, 0xCC];
+        assert_eq!(parse_mac("00:11:22:aa:bb:cc"), Ok(expected));
+        assert_eq!(parse_mac("00-11-22-AA-BB-CC"), Ok(expected));
+        assert!(parse_mac("00:11:22:aa:bb").is_err());
+        assert!(parse_mac("zz:11:22:aa:bb:cc").is_err());
+    }
+
+    #[test]
+    fn test_magic_packet() {
+        let mac = [1, 2, 3, 4, 5, 6];
+        let packet = magic_packet(mac);
+        assert_eq!(packet.len(), 102);
+        assert_eq!(&packet[..6], &[0xFF; 6]);
+        assert!(packet[6..].chunks(6).all(|chunk| chunk == mac));
+    }
+
+    #[test]
+    fn test_format_port() {
+        assert_eq!(format_port(443, false), "443 (https)");
+        assert_eq!(format_port(443, true), "443");
+        assert_eq!(format_port(1, false), "1");
+    }
+
+    #[test]
+    fn test_classify_banner() {
+        assert_eq!(classify_banner("SSH-2.0-OpenSSH_9.6p1 Ubuntu-3ubuntu13\r\n", false), Some("SSH Host"));
+        assert_eq!(classify_banner("SSH-2.0-dropbear_2022.83\r\n", false), Some("Embedded Device"));
+        assert_eq!(classify_banner("SSH-2.0-ROSSSH\r\n", false), Some("Router"));
+        assert_eq!(classify_banner("HTTP/1.0 200 OK\r\nServer: RouterOS\r\n\r\n", false), Some("Router"));
+        assert_eq!(classify_banner("HTTP/1.1 200 OK\r\nServer: lighttpd/1.4.59\r\n\r\n", false), Some("Embedded Device"));
+        assert_eq!(classify_banner("HTTP/1.1 301 Moved\r\nServer: nginx\r\n\r\n", false), Some("Web Server"));
+        assert_eq!(classify_banner("220 mail.example.com ESMTP Postfix\r\n", false), Some("Mail Server"));
+        assert_eq!(classify_banner("HTTP/1.1 200 OK\r\nSERVER: Linux/5.4 UPnP/1.0 MiniUPnPd/2.2\r\n\r\n", true), Some("Media/IoT Device"));
+        assert_eq!(classify_banner("garbage", false), None);
+    }
+
+    #[test]
+    fn test_devices_to_csv() {
+        let device = DeviceInfo {
+            ip: IpAddr::V4(Ipv4Addr::new(192, 168, 1, 20)),
+            hostname: "printer, upstairs".into(),
+            mac_address: Some("00:11:22:33:44:55".into()),
+            open_ports: vec![80, 631],
+            manufacturer: None,
+            device_type: Some("Printer".into()),
+            response_time: Duration::from_millis(12),
+        };
+        let csv = devices_to_csv(&[device.clone()]);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("ip,hostname,mac_address,manufacturer,device_type,open_ports,response_time_ms"));
+        assert_eq!(lines.next(), Some("192.168.1.20,\"printer, upstairs\",00:11:22:33:44:55,,Printer,80 631,12"));
+
+        let json = serde_json::to_value(&device).unwrap();
+        assert_eq!(json["ip"], "192.168.1.20");
+        assert_eq!(json["response_time_ms"], 12);
+        assert_eq!(json["open_ports"], serde_json::json!([80, 631]));
+    }
+
+    #[test]
+    fn test_parse_arp_output() {
+        let macos = "? (192.168.1.1) at 0:11:2:33:44:55 on en0 ifscope [ethernet]\n\
+                     ? (192.168.1.7) at (incomplete) on en0 ifscope [ethernet]\n\
+                     ? (224.0.0.251) at 1:0:5e:0:0:fb on en0 ifscope permanent [ethernet]";
+        let table = parse_arp_output(macos, ArpLayout::Bsd);
+        assert_eq!(table.len(), 1);
+        assert_eq!(table[&Ipv4Addr::new(192, 168, 1, 1)], "00:11:02:33:44:55");
+
+        let linux = "router.lan (192.168.1.1) at aa:bb:cc:dd:ee:ff [ether] on eth0\n\
+                     ? (192.168.1.9) at <incomplete> on eth0";
+        let table = parse_arp_output(linux, ArpLayout::Bsd);
+        assert_eq!(table[&Ipv4Addr::new(192, 168, 1, 1)], "aa:bb:cc:dd:ee:ff");
+        assert_eq!(table.len(), 1);
+
+        let windows = "Interface: 192.168.1.10 --- 0x5\r\n  Internet Address      Physical Address      Type\r\n\
+                       \x20 192.168.1.1           00-11-22-33-44-55     dynamic\r\n\
+                       \x20 192.168.1.255         ff-ff-ff-ff-ff-ff     static";
+        let table = parse_arp_output(windows, ArpLayout::Windows);
+        assert_eq!(table.len(), 1);
+        assert_eq!(table[&Ipv4Addr::new(192, 168, 1, 1)], "00:11:22:33:44:55");
+
+        let neigh = "192.168.1.1 dev eth0 lladdr 00:11:22:33:44:55 REACHABLE\n192.168.1.8 dev eth0 FAILED";
+        let table = parse_arp_output(neigh, ArpLayout::IpNeigh);
+        assert_eq!(table.len(), 1);
+        assert_eq!(table[&Ipv4Addr::new(192, 168, 1, 1)], "00:11:22:33:44:55");
+    }
+
+    #[test]
+    fn test_parse_neighbor_table() {
+        let linux = "2001:db8::1 dev eth0 lladdr 00:11:22:33:44:55 router REACHABLE\n\
+                     2001:db8::9 dev eth0 FAILED\n\
+                     fe80::1 dev eth0 lladdr 00:11:22:33:44:55 router STALE";
+        let table = parse_neighbor_table(linux);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table[&"2001:db8::1".parse::<Ipv6Addr>().unwrap()], "00:11:22:33:44:55");
+
+        let macos = "Neighbor                        Linklayer Address  Netif Expire    St Flgs Prbs\n\
+                     2001:db8::7                     0:11:2:33:44:55    en0 23h59m58s S R\n\
+                     fe80::1%en0                     (incomplete)       en0 expired   N";
+        let table = parse_neighbor_table(macos);
+        assert_eq!(table.len(), 1);
+        assert_eq!(table[&"2001:db8::7".parse::<Ipv6Addr>().unwrap()], "00:11:02:33:44:55");
+
+        let windows = "Internet Address                              Physical Address   Type\r\n\
+                       2001:db8::1                                   00-11-22-33-44-55  Reachable (Router)\r\n\
+                       ff02::1                                       33-33-00-00-00-01  Permanent";
+        let table = parse_neighbor_table(windows);
+        assert_eq!(table.len(), 1);
+        assert!(table.contains_key(&"2001:db8::1".parse::<Ipv6Addr>().unwrap()));
+    }
+
+    #[test]
+    fn test_ipv6_prefix64() {
+        let ip: Ipv6Addr = "2001:db8:1:2:aaaa:bbbb:cccc:dddd".parse().unwrap();
+        assert_eq!(ipv6_prefix64(&ip), "2001:db8:1:2::".parse::<Ipv6Addr>().unwrap());
+        assert!(is_ipv6_link_local(&"fe80::1".parse().unwrap()));
+        assert!(!is_ipv6_link_local(&ip));
+    }
+
+    #[test]
+    fn test_resolve_host_families() {
+        let v4 = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+        let v6: IpAddr = "2001:db8::1".parse().unwrap();
+        assert_eq!(pick_address(&[v6, v4], IpFamily::Any), Some(v4));
+        assert_eq!(pick_address(&[v6], IpFamily::Any), Some(v6));
+        assert_eq!(pick_address(&[v4, v6], IpFamily::V6), Some(v6));
+        assert_eq!(pick_address(&[v6], IpFamily::V4), None);
+
+        assert_eq!(resolve_host("[2001:db8::1]", IpFamily::Any).unwrap(), v6);
+        assert_eq!(resolve_host("192.0.2.1", IpFamily::V4).unwrap(), v4);
+        assert!(resolve_host("192.0.2.1", IpFamily::V6).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_arp_frames() {
+        let mac = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55];
+        let mut frame = arp_request(mac, Ipv4Addr::new(192, 168, 1, 10), Ipv4Addr::new(192, 168, 1, 20));
+        assert_eq!(&frame[0..6], &[0xff; 6]);
+        assert_eq!(&frame[12..14], &[0x08, 0x06]);
+        // A request is not a reply
+        assert_eq!(parse_arp_reply(&frame), None);
+
+        frame[21] = 2; // ARP operation: reply
+        assert_eq!(parse_arp_reply(&frame), Some((Ipv4Addr::new(192, 168, 1, 10), mac)));
+    }
+
+    #[tokio::test]
+    async fn test_scan_open_ports_cancelled() {
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let pacing = ScanPacing::default();
+        assert!(scan_open_ports(ip, &[1, 2, 3], Duration::from_secs(5), &pacing, &cancel, &ProgressBar::hidden()).await.is_none());
+        assert!(scan_open_ports(ip, &[], Duration::from_secs(5), &pacing, &CancellationToken::new(), &ProgressBar::hidden()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reverse_lookup_disabled() {
+        let ip = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 7));
+        assert_eq!(reverse_lookup(ip, false).await, "192.0.2.7");
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let start = Instant::now();
+        // 20/s holds two tokens, then refills one every 50 ms
+        let mut bucket = TokenBucket::new(20, start);
+        assert_eq!(bucket.try_take(start), None);
+        assert_eq!(bucket.try_take(start), None);
+        let wait = bucket.try_take(start).unwrap();
+        assert!(wait > Duration::from_millis(49) && wait <= Duration::from_millis(50));
+        assert_eq!(bucket.try_take(start + Duration::from_millis(50)), None);
+        // A long pause doesn't bank more than the capacity
+        let later = start + Duration::from_secs(10);
+        assert_eq!(bucket.try_take(later), None);
+        assert_eq!(bucket.try_take(later), None);
+        assert!(bucket.try_take(later).is_some());
+    }
+}
@@ -1,31 +1,53 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::*;
-use sysinfo::{System, Disks, Networks};
+use sysinfo::{System, Components, Disks, Networks};
 use std::path::Path;
 use std::fs::File;
 use std::io::Write;
+use std::process::Command;
 use std::time::{Duration, UNIX_EPOCH};
 use std::fmt;
+use std::collections::BTreeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::cli::SpecsFormat;
 
 /// Structure to hold the full system information
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SystemInfo {
     hostname: String,
     os_name: String,
     os_version: String,
     kernel_version: String,
+    /// Missing from snapshots saved before detection existed
+    #[serde(default)]
+    environment: EnvironmentInfo,
     total_memory: u64,
     used_memory: u64,
     total_swap: u64,
     used_swap: u64,
+    #[serde(rename = "uptime_secs", serialize_with = "serialize_secs", deserialize_with = "deserialize_secs")]
     uptime: Duration,
+    #[serde(rename = "boot_time_unix", serialize_with = "serialize_secs", deserialize_with = "deserialize_secs")]
     boot_time: Duration,
     processors: Vec<ProcessorInfo>,
     disks: Vec<DiskInfo>,
     networks: Vec<NetworkInfo>,
+    temperatures: Vec<TemperatureInfo>,
+    fans: Vec<FanInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    batteries: Vec<BatteryInfo>,
+    /// `None` when smartctl is missing or couldn't read any drive
+    smart: Option<Vec<SmartInfo>>,
 }
 
-#[derive(Debug)]
+/// The hypervisor and container we run under, as far as they can be detected
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+struct EnvironmentInfo {
+    hypervisor: Option<String>,
+    container: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 struct ProcessorInfo {
     name: String,
     brand: String, 
@@ -34,7 +56,7 @@ struct ProcessorInfo {
     cores: usize,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 struct DiskInfo {
     name: String,
     mount_point: String,
@@ -44,7 +66,7 @@ struct DiskInfo {
     is_removable: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 struct NetworkInfo {
     name: String,
     sent_bytes: u64,
@@ -53,12 +75,60 @@ struct NetworkInfo {
     packets_received: u64,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct TemperatureInfo {
+    label: String,
+    current_celsius: f32,
+    max_celsius: f32,
+    critical_celsius: Option<f32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FanInfo {
+    label: String,
+    rpm: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BatteryInfo {
+    charge_percent: f32,
+    state: String,
+    design_capacity_wh: f32,
+    full_capacity_wh: f32,
+    health_percent: f32,
+    time_to_empty_secs: Option<u64>,
+    time_to_full_secs: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct SmartInfo {
+    device: String,
+    model: String,
+    healthy: Option<bool>,
+    power_on_hours: Option<u64>,
+    temperature_celsius: Option<i64>,
+}
+
+impl fmt::Display for EnvironmentInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.container, &self.hypervisor) {
+            (Some(container), Some(hypervisor)) => write!(f, "{} container on {}", container, hypervisor),
+            (Some(container), None) => write!(f, "{} container", container),
+            (None, Some(hypervisor)) => write!(f, "{} virtual machine", hypervisor),
+            (None, None) => write!(f, "Bare metal / unknown"),
+        }
+    }
+}
+
+const SMART_UNAVAILABLE: &str = "SMART unavailable (install smartmontools / run elevated)";
+
 impl fmt::Display for SystemInfo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "{}", "=== SYSTEM INFORMATION ===".cyan().bold())?;
         writeln!(f, "{}: {}", "Hostname".green(), self.hostname)?;
         writeln!(f, "{}: {} {}", "OS".green(), self.os_name, self.os_version)?;
         writeln!(f, "{}: {}", "Kernel".green(), self.kernel_version)?;
+        writeln!(f, "{}: {}", "Environment".green(), self.environment)?;
         writeln!(f, "{}: {} days, {} hours, {} minutes", 
             "Uptime".green(),
             self.uptime.as_secs() / 86400,
@@ -112,10 +182,197 @@ impl fmt::Display for SystemInfo {
             writeln!(f, "  {}: {}", "Packets Received".yellow(), net.packets_received)?;
         }
         
+        writeln!(f, "\n{}", "=== TEMPERATURES ===".cyan().bold())?;
+        if self.temperatures.is_empty() {
+            writeln!(f, "{}", "No readable temperature sensors (common in containers and some Windows setups)".italic())?;
+        }
+        for temp in &self.temperatures {
+            let current = format!("{:.1}°C", temp.current_celsius);
+            // Highlight sensors close to their critical threshold
+            let current = match temp.critical_celsius {
+                Some(critical) if temp.current_celsius >= critical - 10.0 => current.red().to_string(),
+                _ => current,
+            };
+            write!(f, "{}: {} (max {:.1}°C", temp.label.green(), current, temp.max_celsius)?;
+            if let Some(critical) = temp.critical_celsius {
+                write!(f, ", critical {:.1}°C", critical)?;
+            }
+            writeln!(f, ")")?;
+        }
+        
+        if !self.fans.is_empty() {
+            writeln!(f, "\n{}", "=== FANS ===".cyan().bold())?;
+            for fan in &self.fans {
+                writeln!(f, "{}: {} RPM", fan.label.green(), fan.rpm)?;
+            }
+        }
+        
+        writeln!(f, "\n{}", "=== DISK HEALTH (SMART) ===".cyan().bold())?;
+        match &self.smart {
+            None => writeln!(f, "{}", SMART_UNAVAILABLE.italic())?,
+            Some(drives) => {
+                for drive in drives {
+                    let health = match drive.healthy {
+                        Some(true) => "OK".green(),
+                        Some(false) => "FAILING".red().bold(),
+                        None => "Unknown".yellow(),
+                    };
+                    writeln!(f, "{}: {} ({})", "Drive".green(), drive.device, drive.model)?;
+                    writeln!(f, "  {}: {}", "Health".yellow(), health)?;
+                    if let Some(hours) = drive.power_on_hours {
+                        writeln!(f, "  {}: {} h", "Power-On Hours".yellow(), hours)?;
+                    }
+                    if let Some(temp) = drive.temperature_celsius {
+                        writeln!(f, "  {}: {}°C", "Temperature".yellow(), temp)?;
+                    }
+                }
+            }
+        }
+        
+        // Desktops have no battery, so the section is left out entirely
+        if !self.batteries.is_empty() {
+            writeln!(f, "\n{}", "=== BATTERY ===".cyan().bold())?;
+            for (i, battery) in self.batteries.iter().enumerate() {
+                if self.batteries.len() > 1 {
+                    writeln!(f, "{} {}", "Battery".green(), i + 1)?;
+                }
+                writeln!(f, "  {}: {:.0}% ({})", "Charge".yellow(), battery.charge_percent, battery.state)?;
+                writeln!(f, "  {}: {:.1}% ({:.1} Wh of {:.1} Wh design)", "Health".yellow(),
+                    battery.health_percent, battery.full_capacity_wh, battery.design_capacity_wh)?;
+                if let Some(secs) = battery.time_to_empty_secs {
+                    writeln!(f, "  {}: {}h {:02}m", "Time to Empty".yellow(), secs / 3600, (secs % 3600) / 60)?;
+                }
+                if let Some(secs) = battery.time_to_full_secs {
+                    writeln!(f, "  {}: {}h {:02}m", "Time to Full".yellow(), secs / 3600, (secs % 3600) / 60)?;
+                }
+            }
+        }
+        
         Ok(())
     }
 }
 
+/// Serialize a duration as whole seconds
+fn serialize_secs<S: Serializer>(duration: &Duration, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_secs())
+}
+
+fn deserialize_secs<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Duration, D::Error> {
+    u64::deserialize(deserializer).map(Duration::from_secs)
+}
+
+impl SystemInfo {
+    /// Pretty-printed JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+    
+    /// The console layout without ANSI color codes
+    pub fn to_plain_text(&self) -> String {
+        crate::utils::strip_ansi(&self.to_string())
+    }
+    
+    /// Markdown report with one table per section
+    pub fn to_markdown(&self) -> String {
+        let gb = |bytes: u64| bytes as f64 / 1_073_741_824.0;
+        let mut md = String::new();
+        
+        md.push_str("# System Information\n\n");
+        md.push_str("| Property | Value |\n|---|---|\n");
+        md.push_str(&format!("| Hostname | {} |\n", self.hostname));
+        md.push_str(&format!("| OS | {} {} |\n", self.os_name, self.os_version));
+        md.push_str(&format!("| Kernel | {} |\n", self.kernel_version));
+        md.push_str(&format!("| Environment | {} |\n", self.environment));
+        md.push_str(&format!("| Uptime | {} days, {} hours, {} minutes |\n",
+            self.uptime.as_secs() / 86400,
+            (self.uptime.as_secs() % 86400) / 3600,
+            (self.uptime.as_secs() % 3600) / 60));
+        
+        md.push_str("\n## Memory\n\n| | Used | Total |\n|---|---|---|\n");
+        md.push_str(&format!("| Memory | {:.2} GB | {:.2} GB |\n", gb(self.used_memory), gb(self.total_memory)));
+        md.push_str(&format!("| Swap | {:.2} GB | {:.2} GB |\n", gb(self.used_swap), gb(self.total_swap)));
+        
+        md.push_str("\n## Processors\n\n| # | Name | Brand | Frequency | Vendor | Cores |\n|---|---|---|---|---|---|\n");
+        for (i, proc) in self.processors.iter().enumerate() {
+            md.push_str(&format!("| {} | {} | {} | {} MHz | {} | {} |\n",
+                i + 1, proc.name, proc.brand, proc.frequency, proc.vendor_id, proc.cores));
+        }
+        
+        md.push_str("\n## Disks\n\n| Disk | Mount Point | File System | Used | Total | Type |\n|---|---|---|---|---|---|\n");
+        for disk in &self.disks {
+            md.push_str(&format!("| {} | {} | {} | {:.2} GB | {:.2} GB | {} |\n",
+                disk.name, disk.mount_point, disk.file_system,
+                gb(disk.total_space.saturating_sub(disk.available_space)), gb(disk.total_space),
+                if disk.is_removable { "Removable" } else { "Fixed" }));
+        }
+        
+        md.push_str("\n## Network Interfaces\n\n| Interface | Sent | Received | Packets Sent | Packets Received |\n|---|---|---|---|---|\n");
+        for net in &self.networks {
+            md.push_str(&format!("| {} | {} MB | {} MB | {} | {} |\n",
+                net.name, net.sent_bytes / 1_048_576, net.received_bytes / 1_048_576,
+                net.packets_sent, net.packets_received));
+        }
+        
+        md.push_str("\n## Temperatures\n\n");
+        if self.temperatures.is_empty() {
+            md.push_str("No readable temperature sensors.\n");
+        } else {
+            md.push_str("| Sensor | Current | Max | Critical |\n|---|---|---|---|\n");
+            for temp in &self.temperatures {
+                md.push_str(&format!("| {} | {:.1}°C | {:.1}°C | {} |\n",
+                    temp.label, temp.current_celsius, temp.max_celsius,
+                    temp.critical_celsius.map(|c| format!("{:.1}°C", c)).unwrap_or_else(|| "-".to_string())));
+            }
+        }
+        
+        if !self.fans.is_empty() {
+            md.push_str("\n## Fans\n\n| Fan | Speed |\n|---|---|\n");
+            for fan in &self.fans {
+                md.push_str(&format!("| {} | {} RPM |\n", fan.label, fan.rpm));
+            }
+        }
+        
+        md.push_str("\n## Disk Health (SMART)\n\n");
+        match &self.smart {
+            None => md.push_str(&format!("{}.\n", SMART_UNAVAILABLE)),
+            Some(drives) => {
+                md.push_str("| Drive | Model | Health | Power-On Hours | Temperature |\n|---|---|---|---|---|\n");
+                for drive in drives {
+                    md.push_str(&format!("| {} | {} | {} | {} | {} |\n",
+                        drive.device, drive.model,
+                        match drive.healthy { Some(true) => "OK", Some(false) => "FAILING", None => "Unknown" },
+                        drive.power_on_hours.map(|h| h.to_string()).unwrap_or_else(|| "-".to_string()),
+                        drive.temperature_celsius.map(|t| format!("{}°C", t)).unwrap_or_else(|| "-".to_string())));
+                }
+            }
+        }
+        
+        if !self.batteries.is_empty() {
+            let time = |secs: Option<u64>| secs
+                .map(|s| format!("{}h {:02}m", s / 3600, (s % 3600) / 60))
+                .unwrap_or_else(|| "-".to_string());
+            md.push_str("\n## Battery\n\n| Charge | State | Health | Full / Design | Time to Empty | Time to Full |\n|---|---|---|---|---|---|\n");
+            for battery in &self.batteries {
+                md.push_str(&format!("| {:.0}% | {} | {:.1}% | {:.1} / {:.1} Wh | {} | {} |\n",
+                    battery.charge_percent, battery.state, battery.health_percent,
+                    battery.full_capacity_wh, battery.design_capacity_wh,
+                    time(battery.time_to_empty_secs), time(battery.time_to_full_secs)));
+            }
+        }
+        
+        md
+    }
+    
+    /// Render in the requested export format (text is uncolored)
+    pub fn render(&self, format: SpecsFormat) -> Result<String> {
+        match format {
+            SpecsFormat::Text => Ok(self.to_plain_text()),
+            SpecsFormat::Json => self.to_json(),
+            SpecsFormat::Markdown => Ok(self.to_markdown()),
+        }
+    }
+}
+
 /// Gather all system information
 pub fn get_system_info() -> Result<SystemInfo> {
     // Create a new System instance
@@ -129,6 +386,10 @@ pub fn get_system_info() -> Result<SystemInfo> {
     let os_name = System::name().unwrap_or_else(|| "Unknown".into());
     let os_version = System::os_version().unwrap_or_else(|| "Unknown".into());
     let kernel_version = System::kernel_version().unwrap_or_else(|| "Unknown".into());
+    let environment = EnvironmentInfo {
+        hypervisor: detect_hypervisor(),
+        container: detect_container(),
+    };
     
     // Memory info
     let total_memory = system.total_memory();
@@ -185,11 +446,28 @@ pub fn get_system_info() -> Result<SystemInfo> {
         }
     }).collect();
     
+    // Temperature sensors (empty when the platform exposes none)
+    let components = Components::new_with_refreshed_list();
+    let temperatures = components.iter()
+        .filter(|c| c.temperature().is_finite() && c.temperature() > 0.0)
+        .map(|c| TemperatureInfo {
+            label: c.label().to_string(),
+            current_celsius: c.temperature(),
+            max_celsius: c.max(),
+            critical_celsius: c.critical(),
+        })
+        .collect();
+    
+    let fans = read_fan_speeds();
+    let batteries = read_batteries();
+    let smart = read_smart_status();
+    
     Ok(SystemInfo {
         hostname,
         os_name,
         os_version,
         kernel_version,
+        environment,
         total_memory,
         used_memory,
         total_swap,
@@ -199,9 +477,298 @@ pub fn get_system_info() -> Result<SystemInfo> {
         processors,
         disks,
         networks,
+        temperatures,
+        fans,
+        batteries,
+        smart,
+    })
+}
+
+/// Name the hypervisor behind a DMI vendor/product string or a CPUID vendor signature
+fn hypervisor_from_signature(signature: &str) -> Option<&'static str> {
+    const SIGNATURES: &[(&str, &str)] = &[
+        ("vmware", "VMware"),
+        ("virtualbox", "VirtualBox"),
+        ("vboxvbox", "VirtualBox"),
+        ("innotek", "VirtualBox"),
+        ("kvmkvmkvm", "KVM"),
+        ("kvm", "KVM"),
+        ("qemu", "QEMU"),
+        ("tcgtcgtcg", "QEMU"),
+        ("microsoft hv", "Hyper-V"),
+        ("virtual machine", "Hyper-V"),
+        ("xen", "Xen"),
+        ("parallels", "Parallels"),
+        ("prl hyperv", "Parallels"),
+        ("bhyve", "bhyve"),
+        ("amazon ec2", "Amazon EC2"),
+        ("google compute engine", "Google Compute Engine"),
+    ];
+    let signature = signature.to_lowercase();
+    SIGNATURES.iter()
+        .find(|(needle, _)| signature.contains(needle))
+        .map(|(_, name)| *name)
+}
+
+/// What the firmware says about the machine: its DMI vendor/product strings and, where the OS
+/// reports it (Windows), whether a hypervisor is present at all
+#[derive(Debug, Default)]
+struct FirmwareIdentity {
+    identity: Option<String>,
+    hypervisor_present: Option<bool>,
+}
+
+/// Decide on the hypervisor from the firmware identity, with the hypervisor's own vendor
+/// signature (CPUID, or /sys/hypervisor on Linux) as a fallback for clouds whose DMI strings
+/// name the provider rather than the hypervisor. A "Microsoft Hv" signature on a machine
+/// whose firmware names a real OEM is ignored: Windows hosts with virtualization-based
+/// security and Hyper-V root partitions run under Hyper-V without being guests.
+fn hypervisor_from(firmware: &FirmwareIdentity, vendor: Option<&str>) -> Option<String> {
+    if firmware.hypervisor_present == Some(false) {
+        return None;
+    }
+    let identity = firmware.identity.as_deref().filter(|identity| !identity.trim().is_empty());
+    if let Some(name) = identity.and_then(hypervisor_from_signature) {
+        return Some(name.to_string());
+    }
+    let vendor = vendor?;
+    if identity.is_some() && vendor.eq_ignore_ascii_case("Microsoft Hv") {
+        return None;
+    }
+    Some(hypervisor_from_signature(vendor)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("Unknown hypervisor ({})", vendor)))
+}
+
+fn detect_hypervisor() -> Option<String> {
+    let firmware = read_firmware_identity();
+    let vendor = cpuid_hypervisor_vendor().or_else(sysfs_hypervisor_type);
+    hypervisor_from(&firmware, vendor.as_deref())
+}
+
+/// The hypervisor type the kernel reports (e.g. "xen"), for guests where CPUID doesn't help
+#[cfg(target_os = "linux")]
+fn sysfs_hypervisor_type() -> Option<String> {
+    std::fs::read_to_string("/sys/hypervisor/type").ok()
+        .map(|kind| kind.trim().to_string())
+        .filter(|kind| !kind.is_empty())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn sysfs_hypervisor_type() -> Option<String> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn read_firmware_identity() -> FirmwareIdentity {
+    let read = |name: &str| std::fs::read_to_string(Path::new("/sys/class/dmi/id").join(name)).ok();
+    let identity = [read("sys_vendor"), read("product_name"), read("bios_vendor")]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+    FirmwareIdentity {
+        identity: (!identity.trim().is_empty()).then_some(identity),
+        hypervisor_present: None,
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn read_firmware_identity() -> FirmwareIdentity {
+    let script = "$cs = Get-CimInstance Win32_ComputerSystem; \"$($cs.Manufacturer) $($cs.Model)\"; $cs.HypervisorPresent";
+    let Ok(output) = Command::new("powershell").args(["-NoProfile", "-Command", script]).output() else {
+        return FirmwareIdentity::default();
+    };
+    if !output.status.success() {
+        return FirmwareIdentity::default();
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines().map(str::trim);
+    FirmwareIdentity {
+        identity: lines.next().filter(|identity| !identity.is_empty()).map(str::to_string),
+        hypervisor_present: lines.next().and_then(|present| match present {
+            "True" => Some(true),
+            "False" => Some(false),
+            _ => None,
+        }),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn read_firmware_identity() -> FirmwareIdentity {
+    FirmwareIdentity::default()
+}
+
+/// The hypervisor vendor signature (leaf 0x40000000), read only when CPUID's "running under
+/// a hypervisor" bit is set
+#[cfg(target_arch = "x86_64")]
+#[allow(unused_unsafe)]
+fn cpuid_hypervisor_vendor() -> Option<String> {
+    use std::arch::x86_64::__cpuid;
+    // SAFETY: CPUID is available on every x86_64 CPU
+    let features = unsafe { __cpuid(1) };
+    if features.ecx & (1 << 31) == 0 {
+        return None;
+    }
+    let leaf = unsafe { __cpuid(0x4000_0000) };
+    let vendor: Vec<u8> = [leaf.ebx, leaf.ecx, leaf.edx].iter().flat_map(|r| r.to_le_bytes()).collect();
+    Some(String::from_utf8_lossy(&vendor).trim_matches(char::from(0)).trim().to_string())
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn cpuid_hypervisor_vendor() -> Option<String> {
+    None
+}
+
+/// Name the container runtime from the contents of /proc/1/cgroup
+fn container_from_cgroup(cgroup: &str) -> Option<&'static str> {
+    const RUNTIMES: &[(&str, &str)] = &[
+        ("kubepods", "Kubernetes"),
+        ("libpod", "Podman"),
+        ("docker", "Docker"),
+        ("containerd", "containerd"),
+        ("lxc", "LXC"),
+    ];
+    RUNTIMES.iter()
+        .find(|(needle, _)| cgroup.contains(needle))
+        .map(|(_, name)| *name)
+}
+
+/// Container runtime from its marker files, the `container` variable systemd-style runtimes set, or cgroups
+#[cfg(target_os = "linux")]
+fn detect_container() -> Option<String> {
+    if Path::new("/.dockerenv").exists() {
+        return Some("Docker".to_string());
+    }
+    if Path::new("/run/.containerenv").exists() {
+        return Some("Podman".to_string());
+    }
+    if let Some(name) = std::env::var("container").ok().filter(|name| !name.is_empty()) {
+        return Some(name);
+    }
+    std::fs::read_to_string("/proc/1/cgroup")
+        .ok()
+        .and_then(|cgroup| container_from_cgroup(&cgroup))
+        .map(str::to_string)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn detect_container() -> Option<String> {
+    None
+}
+
+/// Query every drive `smartctl --scan` finds; `None` if smartctl is missing or no drive could be read
+fn read_smart_status() -> Option<Vec<SmartInfo>> {
+    let scan = Command::new("smartctl").args(["--scan", "--json"]).output().ok()?;
+    let scan: serde_json::Value = serde_json::from_slice(&scan.stdout).ok()?;
+    
+    let drives: Vec<SmartInfo> = scan["devices"].as_array()?
+        .iter()
+        .filter_map(|device| {
+            let name = device["name"].as_str()?;
+            let mut cmd = Command::new("smartctl");
+            cmd.args(["-a", "--json"]);
+            if let Some(kind) = device["type"].as_str() {
+                cmd.args(["-d", kind]);
+            }
+            // smartctl's exit code is a bit mask that is non-zero for many healthy drives,
+            // so the JSON is parsed regardless
+            let output = cmd.arg(name).output().ok()?;
+            parse_smartctl_json(name, &String::from_utf8_lossy(&output.stdout))
+        })
+        .collect();
+    
+    if drives.is_empty() { None } else { Some(drives) }
+}
+
+/// Parse `smartctl -a --json` output; `None` when it holds no SMART data (e.g. permission denied)
+fn parse_smartctl_json(device: &str, json: &str) -> Option<SmartInfo> {
+    let value: serde_json::Value = serde_json::from_str(json).ok()?;
+    let healthy = value["smart_status"]["passed"].as_bool();
+    let power_on_hours = value["power_on_time"]["hours"].as_u64();
+    let temperature_celsius = value["temperature"]["current"].as_i64();
+    if healthy.is_none() && power_on_hours.is_none() && temperature_celsius.is_none() {
+        return None;
+    }
+    
+    Some(SmartInfo {
+        device: device.to_string(),
+        model: value["model_name"].as_str().unwrap_or("Unknown").to_string(),
+        healthy,
+        power_on_hours,
+        temperature_celsius,
     })
 }
 
+/// Read laptop battery status; an empty list means no battery (or no way to query it)
+fn read_batteries() -> Vec<BatteryInfo> {
+    use battery::units::energy::watt_hour;
+    use battery::units::ratio::percent;
+    use battery::units::time::second;
+    
+    let Ok(manager) = battery::Manager::new() else {
+        return Vec::new();
+    };
+    let Ok(batteries) = manager.batteries() else {
+        return Vec::new();
+    };
+    
+    batteries.flatten()
+        .map(|b| BatteryInfo {
+            charge_percent: b.state_of_charge().get::<percent>(),
+            state: b.state().to_string(),
+            design_capacity_wh: b.energy_full_design().get::<watt_hour>(),
+            full_capacity_wh: b.energy_full().get::<watt_hour>(),
+            health_percent: b.state_of_health().get::<percent>(),
+            time_to_empty_secs: b.time_to_empty().map(|t| t.get::<second>() as u64),
+            time_to_full_secs: b.time_to_full().map(|t| t.get::<second>() as u64),
+        })
+        .collect()
+}
+
+/// Read fan speeds from the Linux hwmon interface; other platforms don't expose them to sysinfo
+#[cfg(target_os = "linux")]
+fn read_fan_speeds() -> Vec<FanInfo> {
+    let mut fans = Vec::new();
+    let Ok(hwmons) = std::fs::read_dir("/sys/class/hwmon") else {
+        return fans;
+    };
+    
+    for hwmon in hwmons.flatten() {
+        let dir = hwmon.path();
+        let chip = std::fs::read_to_string(dir.join("name"))
+            .map(|n| n.trim().to_string())
+            .unwrap_or_else(|_| hwmon.file_name().to_string_lossy().to_string());
+        
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        let mut inputs: Vec<String> = entries.flatten()
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with("fan") && name.ends_with("_input"))
+            .collect();
+        inputs.sort();
+        
+        for input in inputs {
+            let Some(rpm) = std::fs::read_to_string(dir.join(&input))
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok()) else { continue };
+            
+            let index = input.trim_end_matches("_input");
+            let label = std::fs::read_to_string(dir.join(format!("{}_label", index)))
+                .map(|l| l.trim().to_string())
+                .unwrap_or_else(|_| index.to_string());
+            
+            fans.push(FanInfo { label: format!("{} {}", chip, label), rpm });
+        }
+    }
+    
+    fans
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_fan_speeds() -> Vec<FanInfo> {
+    Vec::new()
+}
+
 /// Display all system information on the console
 pub fn display_system_info() -> Result<()> {
     let system_info = get_system_info()?;
@@ -209,15 +776,15 @@ pub fn display_system_info() -> Result<()> {
     Ok(())
 }
 
-/// Save system information to a file
-pub fn save_system_info_to_file(path: &Path) -> Result<()> {
+/// Save system information to a file in the given format
+pub fn save_system_info_to_file(path: &Path, format: SpecsFormat) -> Result<()> {
     let system_info = get_system_info()?;
     
     // Create or truncate the file
     let mut file = File::create(path)?;
     
-    // Write system info as formatted text
-    write!(file, "{}", system_info)?;
+    // Write system info without terminal color codes
+    write!(file, "{}", crate::utils::strip_ansi(&system_info.render(format)?))?;
     
     println!("{} {}", "System information saved to:".green(), path.display());
     Ok(())
@@ -247,16 +814,262 @@ fn format_size(size: u64) -> String {
 /// This version is suitable for GUI display where color codes are not needed.
 pub fn get_system_info_string() -> Result<String> {
     let system_info = get_system_info()?;
-    // Use the Display implementation to format the string
-    Ok(format!("{}", system_info))
+    Ok(system_info.to_plain_text())
+}
+
+/// Load a snapshot saved with `--format json`
+pub fn load_snapshot(path: &Path) -> Result<SystemInfo> {
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&json)
+        .with_context(|| format!("{} is not a JSON spec snapshot (save one with --format json)", path.display()))
+}
+
+/// Signed size difference, e.g. "+1.50 GB" or "-512.00 MB"
+fn format_size_delta(old: u64, new: u64) -> String {
+    if new >= old {
+        format!("+{}", format_size(new - old))
+    } else {
+        format!("-{}", format_size(old - new))
+    }
+}
+
+/// Describe what changed between two snapshots, one line per change
+pub fn compare_system_info(old: &SystemInfo, new: &SystemInfo) -> Vec<String> {
+    let mut changes = Vec::new();
+    let mut changed = |label: &str, old: &str, new: &str| {
+        if old != new {
+            changes.push(format!("{}: {} -> {}", label, old, new));
+        }
+    };
+    
+    changed("Hostname", &old.hostname, &new.hostname);
+    changed("OS", &format!("{} {}", old.os_name, old.os_version), &format!("{} {}", new.os_name, new.os_version));
+    changed("Kernel", &old.kernel_version, &new.kernel_version);
+    changed("Environment", &old.environment.to_string(), &new.environment.to_string());
+    changed("Total memory", &format_size(old.total_memory), &format_size(new.total_memory));
+    changed("Total swap", &format_size(old.total_swap), &format_size(new.total_swap));
+    let cpus = |info: &SystemInfo| info.processors.first()
+        .map(|proc| format!("{} ({} cores)", proc.brand, proc.cores))
+        .unwrap_or_else(|| "none".to_string());
+    changed("CPU", &cpus(old), &cpus(new));
+    if old.used_memory != new.used_memory {
+        changes.push(format!("Memory in use: {} -> {} ({})", format_size(old.used_memory),
+            format_size(new.used_memory), format_size_delta(old.used_memory, new.used_memory)));
+    }
+    
+    // Disks are matched by mount point, interfaces and SMART drives by name
+    let old_disks: BTreeMap<_, _> = old.disks.iter().map(|disk| (&disk.mount_point, disk)).collect();
+    let new_disks: BTreeMap<_, _> = new.disks.iter().map(|disk| (&disk.mount_point, disk)).collect();
+    for (mount, disk) in &old_disks {
+        if !new_disks.contains_key(mount) {
+            changes.push(format!("Disk removed: {} at {} ({})", disk.name, mount, format_size(disk.total_space)));
+        }
+    }
+    for (mount, disk) in &new_disks {
+        match old_disks.get(mount) {
+            None => changes.push(format!("Disk added: {} at {} ({}, {})", disk.name, mount, format_size(disk.total_space), disk.file_system)),
+            Some(before) => {
+                if before.total_space != disk.total_space {
+                    changes.push(format!("Disk {} size: {} -> {}", mount, format_size(before.total_space), format_size(disk.total_space)));
+                }
+                if before.file_system != disk.file_system {
+                    changes.push(format!("Disk {} file system: {} -> {}", mount, before.file_system, disk.file_system));
+                }
+                let (used_before, used_now) = (before.total_space.saturating_sub(before.available_space), disk.total_space.saturating_sub(disk.available_space));
+                if used_before != used_now {
+                    changes.push(format!("Disk {} used: {} ({})", mount, format_size(used_now), format_size_delta(used_before, used_now)));
+                }
+            }
+        }
+    }
+    
+    let old_nets: BTreeMap<_, _> = old.networks.iter().map(|net| (&net.name, net)).collect();
+    for net in &new.networks {
+        match old_nets.get(&net.name) {
+            None => changes.push(format!("Interface added: {}", net.name)),
+            // Counters start over at boot, so a drop means a restart rather than negative traffic
+            Some(before) if net.sent_bytes < before.sent_bytes || net.received_bytes < before.received_bytes => {
+                changes.push(format!("Interface {}: counters reset (rebooted in between?)", net.name));
+            }
+            Some(before) if net.sent_bytes != before.sent_bytes || net.received_bytes != before.received_bytes => {
+                changes.push(format!("Interface {}: {} sent, {} received, {} packets sent, {} packets received",
+                    net.name,
+                    format_size_delta(before.sent_bytes, net.sent_bytes),
+                    format_size_delta(before.received_bytes, net.received_bytes),
+                    net.packets_sent.saturating_sub(before.packets_sent),
+                    net.packets_received.saturating_sub(before.packets_received)));
+            }
+            Some(_) => {}
+        }
+    }
+    for net in &old.networks {
+        if !new.networks.iter().any(|n| n.name == net.name) {
+            changes.push(format!("Interface removed: {}", net.name));
+        }
+    }
+    
+    let health = |drive: &SmartInfo| match drive.healthy {
+        Some(true) => "OK",
+        Some(false) => "FAILING",
+        None => "Unknown",
+    };
+    let old_drives = old.smart.as_deref().unwrap_or_default();
+    let new_drives = new.smart.as_deref().unwrap_or_default();
+    for drive in new_drives {
+        match old_drives.iter().find(|d| d.device == drive.device) {
+            None => changes.push(format!("Drive added: {} ({})", drive.device, drive.model)),
+            Some(before) if before.model != drive.model => {
+                changes.push(format!("Drive {} replaced: {} -> {}", drive.device, before.model, drive.model));
+            }
+            Some(before) if before.healthy != drive.healthy => {
+                changes.push(format!("Drive {} health: {} -> {}", drive.device, health(before), health(drive)));
+            }
+            Some(_) => {}
+        }
+    }
+    for drive in old_drives {
+        if !new_drives.iter().any(|d| d.device == drive.device) {
+            changes.push(format!("Drive removed: {} ({})", drive.device, drive.model));
+        }
+    }
+    
+    for (i, (before, battery)) in old.batteries.iter().zip(&new.batteries).enumerate() {
+        if (before.health_percent - battery.health_percent).abs() >= 0.1 {
+            changes.push(format!("Battery {} health: {:.1}% -> {:.1}%", i + 1, before.health_percent, battery.health_percent));
+        }
+    }
+    if old.batteries.len() != new.batteries.len() {
+        changes.push(format!("Batteries: {} -> {}", old.batteries.len(), new.batteries.len()));
+    }
+    
+    changes
+}
+
+/// Print the differences between two saved snapshots
+pub fn compare_snapshots(old_path: &Path, new_path: &Path) -> Result<()> {
+    let changes = compare_system_info(&load_snapshot(old_path)?, &load_snapshot(new_path)?);
+    println!("{} {} -> {}", "Comparing".cyan().bold(), old_path.display(), new_path.display());
+    if changes.is_empty() {
+        println!("{}", "No differences found.".green());
+    }
+    for change in &changes {
+        println!("  {}", change);
+    }
+    Ok(())
 }
 
 pub fn handle_pc_specs_command(args: crate::cli::PCSpecsArgs) -> anyhow::Result<()> {
-    if let Some(output_path) = args.output {
+    if let [old, new] = args.compare.as_slice() {
+        compare_snapshots(old, new)
+    } else if let Some(output_path) = args.output {
         // Save to file
-        save_system_info_to_file(&output_path)
-    } else {
-        // Display to console
+        save_system_info_to_file(&output_path, args.format)
+    } else if args.format == SpecsFormat::Text {
+        // Display to console with colors
         display_system_info()
+    } else {
+        println!("{}", get_system_info()?.render(args.format)?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_smartctl_json() {
+        let json = r#"{
+            "model_name": "Samsung SSD 970 EVO Plus 1TB",
+            "smart_status": { "passed": true },
+            "power_on_time": { "hours": 8123 },
+            "temperature": { "current": 38 }
+        }"#;
+        assert_eq!(parse_smartctl_json("/dev/nvme0", json), Some(SmartInfo {
+            device: "/dev/nvme0".to_string(),
+            model: "Samsung SSD 970 EVO Plus 1TB".to_string(),
+            healthy: Some(true),
+            power_on_hours: Some(8123),
+            temperature_celsius: Some(38),
+        }));
+        
+        let denied = r#"{ "smartctl": { "messages": [{ "string": "Smartctl open device: /dev/sda failed: Permission denied", "severity": "error" }] } }"#;
+        assert_eq!(parse_smartctl_json("/dev/sda", denied), None);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_detect_environment() {
+        assert_eq!(hypervisor_from_signature("QEMU Standard PC (Q35 + ICH9, 2009)"), Some("QEMU"));
+        assert_eq!(hypervisor_from_signature("innotek GmbH VirtualBox"), Some("VirtualBox"));
+        assert_eq!(hypervisor_from_signature("Microsoft Corporation Virtual Machine"), Some("Hyper-V"));
+        assert_eq!(hypervisor_from_signature("KVMKVMKVM"), Some("KVM"));
+        assert_eq!(hypervisor_from_signature("Dell Inc. OptiPlex 7090"), None);
+        
+        let firmware = |identity: Option<&str>, present: Option<bool>| FirmwareIdentity {
+            identity: identity.map(str::to_string),
+            hypervisor_present: present,
+        };
+        // A real OEM machine with the hypervisor bit set (VBS, Hyper-V root) is bare metal
+        assert_eq!(hypervisor_from(&firmware(Some("Dell Inc. OptiPlex 7090"), Some(true)), Some("Microsoft Hv")), None);
+        assert_eq!(hypervisor_from(&firmware(Some("Dell Inc. OptiPlex 7090"), None), Some("Microsoft Hv")), None);
+        assert_eq!(hypervisor_from(&firmware(Some("Dell Inc. OptiPlex 7090"), None), None), None);
+        // Cloud DMI strings name the provider; the signature names the hypervisor
+        assert_eq!(hypervisor_from(&firmware(Some("DigitalOcean Droplet"), None), Some("KVMKVMKVM")).as_deref(), Some("KVM"));
+        assert_eq!(hypervisor_from(&firmware(Some("Hetzner vServer"), None), Some("xen")).as_deref(), Some("Xen"));
+        assert_eq!(hypervisor_from(&firmware(Some("Microsoft Corporation Virtual Machine"), Some(true)), None).as_deref(), Some("Hyper-V"));
+        assert_eq!(hypervisor_from(&firmware(None, None), Some("KVMKVMKVM")).as_deref(), Some("KVM"));
+        assert_eq!(hypervisor_from(&firmware(None, None), Some("ACRNACRNACRN")).as_deref(), Some("Unknown hypervisor (ACRNACRNACRN)"));
+        assert_eq!(hypervisor_from(&firmware(None, Some(false)), Some("Microsoft Hv")), None);
+        
+        assert_eq!(container_from_cgroup("0::/system.slice/docker-3f1e.scope\n"), Some("Docker"));
+        assert_eq!(container_from_cgroup("0::/kubepods/besteffort/pod12/cri-containerd-9a\n"), Some("Kubernetes"));
+        assert_eq!(container_from_cgroup("0::/init.scope\n"), None);
+        
+        let environment = |hypervisor: Option<&str>, container: Option<&str>| EnvironmentInfo {
+            hypervisor: hypervisor.map(str::to_string),
+            container: container.map(str::to_string),
+        }.to_string();
+        assert_eq!(environment(Some("KVM"), Some("Docker")), "Docker container on KVM");
+        assert_eq!(environment(Some("VMware"), None), "VMware virtual machine");
+        assert_eq!(environment(None, None), "Bare metal / unknown");
+    }
+
+    #[test]
+    fn test_compare_system_info() {
+        let snapshot = |os_version: &str, disks: serde_json::Value, sent: u64| -> SystemInfo {
+            serde_json::from_value(serde_json::json!({
+                "hostname": "desk", "os_name": "Ubuntu", "os_version": os_version, "kernel_version": "6.8.0",
+                "total_memory": 17_179_869_184u64, "used_memory": 4_294_967_296u64, "total_swap": 0, "used_swap": 0,
+                "uptime_secs": 3600, "boot_time_unix": 1_700_000_000,
+                "processors": [], "disks": disks, "temperatures": [], "fans": [], "smart": null,
+                "networks": [{ "name": "eth0", "sent_bytes": sent, "received_bytes": 0, "packets_sent": sent / 1024, "packets_received": 0 }],
+            })).unwrap()
+        };
+        let root = serde_json::json!({ "name": "sda1", "mount_point": "/", "file_system": "ext4",
+            "total_space": 107_374_182_400u64, "available_space": 53_687_091_200u64, "is_removable": false });
+        let data = serde_json::json!({ "name": "sdb1", "mount_point": "/data", "file_system": "xfs",
+            "total_space": 1_099_511_627_776u64, "available_space": 1_099_511_627_776u64, "is_removable": false });
+        
+        let old = snapshot("22.04", serde_json::json!([root]), 1_048_576);
+        let new = snapshot("24.04", serde_json::json!([root, data]), 3_145_728);
+        assert_eq!(compare_system_info(&old, &new), vec![
+            "OS: Ubuntu 22.04 -> Ubuntu 24.04",
+            "Disk added: sdb1 at /data (1.00 TB, xfs)",
+            "Interface eth0: +2.00 MB sent, +0 bytes received, 2048 packets sent, 0 packets received",
+        ]);
+        assert!(compare_system_info(&new, &new).is_empty());
+        
+        let rebooted = snapshot("24.04", serde_json::json!([]), 0);
+        assert_eq!(compare_system_info(&new, &rebooted), vec![
+            "Disk removed: sda1 at / (100.00 GB)",
+            "Disk removed: sdb1 at /data (1.00 TB)",
+            "Interface eth0: counters reset (rebooted in between?)",
+        ]);
+        
+        // The JSON export reads back into the same snapshot
+        let reloaded: SystemInfo = serde_json::from_str(&new.to_json().unwrap()).unwrap();
+        assert_eq!(reloaded.uptime, Duration::from_secs(3600));
+        assert!(compare_system_info(&new, &reloaded).is_empty());
+    }
+}
@@ -1,5 +1,10 @@
-use sysinfo::{System, CpuRefreshKind, MemoryRefreshKind, ProcessRefreshKind, RefreshKind};
+use sysinfo::{System, CpuRefreshKind, MemoryRefreshKind, Networks, ProcessRefreshKind, RefreshKind, UpdateKind, Users, MINIMUM_CPU_UPDATE_INTERVAL};
 use std::time::Duration;
+use std::io::{self, Write};
+use colored::*;
+use crossterm::{cursor::MoveTo, execute, terminal::{Clear, ClearType}};
+use crate::cli::ProcessSort;
+use crate::utils;
 
 /// Get the current CPU usage as a percentage
 pub fn get_cpu_usage() -> Result<f64, String> {
@@ -77,4 +82,252 @@ pub fn get_cpu_temperature() -> Result<f64, String> {
     // This would require additional setup
     // For now, we return an error
     Err("CPU temperature information not available".to_string())
-} 
\ No newline at end of file
+}
+
+/// Get the current usage of each logical core as a percentage
+pub fn get_per_core_usage() -> Result<Vec<f32>, String> {
+    let mut sys = System::new_with_specifics(
+        RefreshKind::new().with_cpu(CpuRefreshKind::new().with_cpu_usage())
+    );
+    
+    // sysinfo computes usage from the difference between two samples
+    std::thread::sleep(MINIMUM_CPU_UPDATE_INTERVAL);
+    sys.refresh_cpu_usage();
+    
+    Ok(sys.cpus().iter().map(|cpu| cpu.cpu_usage()).collect())
+}
+
+/// Continuously redraw CPU, memory and per-core load until Ctrl+C is pressed
+pub async fn watch(interval: Duration) -> Result<(), String> {
+    // Sampling faster than sysinfo's minimum interval yields meaningless CPU numbers
+    let interval = interval.max(MINIMUM_CPU_UPDATE_INTERVAL);
+    
+    let mut sys = System::new_with_specifics(
+        RefreshKind::new()
+            .with_cpu(CpuRefreshKind::new().with_cpu_usage())
+            .with_memory(MemoryRefreshKind::new().with_ram().with_swap())
+    );
+    
+    // Ctrl+C ends the view normally instead of exiting the program
+    let (quit, _ctrl_c) = utils::handle_ctrl_c();
+    
+    // Take the first sample so the first frame already has real percentages
+    tokio::time::sleep(MINIMUM_CPU_UPDATE_INTERVAL).await;
+    
+    loop {
+        sys.refresh_cpu_usage();
+        sys.refresh_memory();
+        
+        draw_watch_frame(&sys, interval).map_err(|e| format!("Failed to draw: {}", e))?;
+        
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = quit.cancelled() => {
+                println!();
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Render one frame of the watch view
+fn draw_watch_frame(sys: &System, interval: Duration) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    execute!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
+    
+    writeln!(stdout, "{} (every {:.1}s, Ctrl+C to quit)", "System Watch".magenta().bold(), interval.as_secs_f64())?;
+    writeln!(stdout)?;
+    
+    let cpu = sys.global_cpu_info().cpu_usage();
+    writeln!(stdout, "{:<6} {} {:>5.1}%", "CPU".green(), usage_bar(cpu, 40), cpu)?;
+    
+    let total = sys.total_memory();
+    let used = sys.used_memory();
+    let mem_percent = if total > 0 { used as f32 / total as f32 * 100.0 } else { 0.0 };
+    writeln!(stdout, "{:<6} {} {:>5.1}% ({:.1} / {:.1} GB)", "Mem".green(), usage_bar(mem_percent, 40), mem_percent,
+        used as f64 / 1_073_741_824.0, total as f64 / 1_073_741_824.0)?;
+    
+    let swap_total = sys.total_swap();
+    if swap_total > 0 {
+        let swap_percent = sys.used_swap() as f32 / swap_total as f32 * 100.0;
+        writeln!(stdout, "{:<6} {} {:>5.1}%", "Swap".green(), usage_bar(swap_percent, 40), swap_percent)?;
+    }
+    
+    writeln!(stdout, "\n{}", "Per-core load".cyan().bold())?;
+    for (i, core) in sys.cpus().iter().enumerate() {
+        let usage = core.cpu_usage();
+        writeln!(stdout, "{:<6} {} {:>5.1}%", format!("#{}", i), usage_bar(usage, 30), usage)?;
+    }
+    
+    stdout.flush()
+}
+
+/// Draw a usage bar coloured by load level
+fn usage_bar(percent: f32, width: usize) -> String {
+    let filled = ((percent.clamp(0.0, 100.0) / 100.0) * width as f32).round() as usize;
+    let bar = format!("{}{}", "|".repeat(filled), " ".repeat(width - filled));
+    let colored_bar = if percent >= 85.0 {
+        bar.red()
+    } else if percent >= 60.0 {
+        bar.yellow()
+    } else {
+        bar.green()
+    };
+    format!("[{}]", colored_bar)
+}
+
+/// Continuously redraw disk and network throughput until Ctrl+C is pressed
+pub async fn watch_io(interval: Duration) -> Result<(), String> {
+    let interval = interval.max(MINIMUM_CPU_UPDATE_INTERVAL);
+    
+    // sysinfo 0.30 has no system-wide disk counters, so disk IO is summed over processes
+    let mut sys = System::new_with_specifics(
+        RefreshKind::new().with_processes(ProcessRefreshKind::new().with_disk_usage())
+    );
+    let mut networks = Networks::new_with_refreshed_list();
+    let (quit, _ctrl_c) = utils::handle_ctrl_c();
+    
+    // Both counters report the delta since the previous refresh, so the first
+    // refresh only sets the baseline; render from the second one on
+    let mut last_sample = std::time::Instant::now();
+    tokio::time::sleep(interval).await;
+    
+    loop {
+        sys.refresh_processes_specifics(ProcessRefreshKind::new().with_disk_usage());
+        networks.refresh();
+        let elapsed = last_sample.elapsed().as_secs_f64().max(0.001);
+        last_sample = std::time::Instant::now();
+        
+        draw_io_frame(&sys, &networks, elapsed, interval).map_err(|e| format!("Failed to draw: {}", e))?;
+        
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = quit.cancelled() => {
+                println!();
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Render one frame of the IO view; `elapsed` is the sampling window in seconds
+fn draw_io_frame(sys: &System, networks: &Networks, elapsed: f64, interval: Duration) -> io::Result<()> {
+    const MB: f64 = 1_048_576.0;
+    let mut stdout = io::stdout();
+    execute!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
+    
+    writeln!(stdout, "{} (every {:.1}s, Ctrl+C to quit)", "System IO".magenta().bold(), interval.as_secs_f64())?;
+    writeln!(stdout)?;
+    
+    let (read, written) = sys.processes().values()
+        .map(|p| p.disk_usage())
+        .fold((0u64, 0u64), |(r, w), usage| (r + usage.read_bytes, w + usage.written_bytes));
+    writeln!(stdout, "{}", "Disk".cyan().bold())?;
+    writeln!(stdout, "  {:<8} {:>10.2} MB/s", "Read".green(), read as f64 / MB / elapsed)?;
+    writeln!(stdout, "  {:<8} {:>10.2} MB/s", "Write".green(), written as f64 / MB / elapsed)?;
+    
+    // The processes doing the most IO in this window
+    let mut busiest: Vec<_> = sys.processes().iter()
+        .map(|(pid, p)| (pid.as_u32(), p.name(), p.disk_usage()))
+        .filter(|(_, _, usage)| usage.read_bytes + usage.written_bytes > 0)
+        .collect();
+    busiest.sort_by_key(|(_, _, usage)| std::cmp::Reverse(usage.read_bytes + usage.written_bytes));
+    if !busiest.is_empty() {
+        writeln!(stdout, "\n  {:>8}  {:<28} {:>12} {:>12}", "PID".bold(), "NAME".bold(), "READ".bold(), "WRITE".bold())?;
+        for (pid, name, usage) in busiest.iter().take(5) {
+            let name: String = name.chars().take(28).collect();
+            writeln!(stdout, "  {:>8}  {:<28} {:>7.2} MB/s {:>7.2} MB/s", pid.to_string().cyan(), name,
+                usage.read_bytes as f64 / MB / elapsed, usage.written_bytes as f64 / MB / elapsed)?;
+        }
+    }
+    
+    writeln!(stdout, "\n{}", "Network".cyan().bold())?;
+    let mut interfaces: Vec<_> = networks.iter().collect();
+    interfaces.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, data) in interfaces {
+        // Network speeds are conventionally quoted in megabits
+        let down = data.received() as f64 * 8.0 / 1_000_000.0 / elapsed;
+        let up = data.transmitted() as f64 * 8.0 / 1_000_000.0 / elapsed;
+        writeln!(stdout, "  {:<16} {} {:>8.2} Mb/s   {} {:>8.2} Mb/s", name.green(), "↓".cyan(), down, "↑".cyan(), up)?;
+    }
+    
+    stdout.flush()
+}
+
+/// A running process as shown by `system top`
+#[derive(Debug, Clone)]
+pub struct ProcessEntry {
+    pub pid: u32,
+    pub name: String,
+    pub user: String,
+    pub cpu_usage: f32, // Percent of one core, so it can exceed 100 on multi-core machines
+    pub memory: u64,    // Resident memory in bytes
+}
+
+/// Get the `count` processes using the most CPU or memory
+pub fn get_top_processes(count: usize, sort: ProcessSort) -> Result<Vec<ProcessEntry>, String> {
+    let mut sys = System::new_with_specifics(
+        RefreshKind::new().with_processes(ProcessRefreshKind::new().with_cpu().with_memory().with_user(UpdateKind::OnlyIfNotSet))
+    );
+    
+    // Per-process CPU usage is also a difference between two samples
+    std::thread::sleep(MINIMUM_CPU_UPDATE_INTERVAL);
+    sys.refresh_processes_specifics(ProcessRefreshKind::new().with_cpu().with_memory().with_user(UpdateKind::OnlyIfNotSet));
+    
+    let users = Users::new_with_refreshed_list();
+    
+    let mut entries: Vec<ProcessEntry> = sys.processes().iter()
+        .map(|(pid, process)| ProcessEntry {
+            pid: pid.as_u32(),
+            name: process.name().to_string(),
+            user: process.user_id()
+                .and_then(|uid| users.get_user_by_id(uid))
+                .map(|user| user.name().to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            cpu_usage: process.cpu_usage(),
+            memory: process.memory(),
+        })
+        .collect();
+    
+    match sort {
+        ProcessSort::Cpu => entries.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage).then(b.memory.cmp(&a.memory))),
+        ProcessSort::Mem => entries.sort_by(|a, b| b.memory.cmp(&a.memory).then(b.cpu_usage.total_cmp(&a.cpu_usage))),
+    }
+    entries.truncate(count);
+    
+    Ok(entries)
+}
+
+/// Print the top processes as a table
+pub fn print_top_processes(count: usize, sort: ProcessSort) -> Result<(), String> {
+    let entries = get_top_processes(count, sort)?;
+    
+    let title = match sort {
+        ProcessSort::Cpu => "Top processes by CPU",
+        ProcessSort::Mem => "Top processes by memory",
+    };
+    println!("{}", title.magenta().bold());
+    println!("{:>8}  {:<28} {:<14} {:>7} {:>10}",
+        "PID".bold(), "NAME".bold(), "USER".bold(), "CPU%".bold(), "MEM".bold());
+    
+    for entry in entries {
+        let name: String = entry.name.chars().take(28).collect();
+        let user: String = entry.user.chars().take(14).collect();
+        println!("{:>8}  {:<28} {:<14} {:>7.1} {:>10}",
+            entry.pid.to_string().cyan(), name, user, entry.cpu_usage, format_memory(entry.memory));
+    }
+    
+    Ok(())
+}
+
+/// Format a byte count with a binary unit
+fn format_memory(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
@@ -166,7 +166,7 @@ fn create_backup_dir_tab() -> Box {
                 if let (Some(source_path), Some(dest_path)) = (source.path(), dest.path()) {
                     // NOTE: This runs synchronously and will block the UI for large backups.
                     // Consider glib::spawn_blocking for long operations.
-                    match file_ops::backup_directory(&source_path, &dest_path) {
+                    match file_ops::backup_directory(&source_path, &dest_path, None) {
                         Ok(_) => {
                              status_label.set_markup(&format!(
                                 "<span color='green'><b>Success:</b> Backup completed to '{}'</span>",
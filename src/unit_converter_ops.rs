@@ -1,10 +1,25 @@
 use anyhow::Result;
 use clap::{Args, Subcommand};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::utils;
+
+/// Free exchange-rate feed (no API key); rates are relative to USD
+const EXCHANGE_RATES_URL: &str = "https://open.er-api.com/v6/latest/USD";
+/// How long cached exchange rates are used before fetching fresh ones
+const EXCHANGE_RATES_TTL: Duration = Duration::from_secs(6 * 60 * 60);
 
 #[derive(Args, Debug, Clone)]
 pub struct UnitConverterArgs {
+    /// List supported categories and units (optionally for one category) instead of converting
+    #[arg(long, value_name = "CATEGORY", num_args = 0..=1, default_missing_value = "")]
+    pub list: Option<String>,
     #[command(subcommand)]
-    pub command: UnitConverterCommands,
+    pub command: Option<UnitConverterCommands>,
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -18,39 +33,295 @@ pub enum UnitConverterCommands {
         /// Unit to convert to (e.g., km, m, mi, ft)
         to_unit: String,
     },
-    // Add other categories like Mass, Temperature, Currency later
+    /// Convert temperatures
+    Temperature {
+        /// Value to convert (may be negative)
+        #[arg(allow_hyphen_values = true)]
+        value: f64,
+        /// Unit to convert from (c, f, k)
+        from_unit: String,
+        /// Unit to convert to (c, f, k)
+        to_unit: String,
+    },
+    /// Convert data transfer rates
+    DataRate {
+        /// Value to convert
+        value: f64,
+        /// Unit to convert from (bps, kbps, mbps, gbps)
+        from_unit: String,
+        /// Unit to convert to (bps, kbps, mbps, gbps)
+        to_unit: String,
+    },
+    /// Convert data sizes (decimal KB/MB/GB or binary KiB/MiB/GiB)
+    DataSize {
+        /// Value to convert
+        value: f64,
+        /// Unit to convert from (e.g., b, kb, mb, kib, mib)
+        from_unit: String,
+        /// Unit to convert to (e.g., b, kb, mb, kib, mib)
+        to_unit: String,
+    },
+    /// Convert between currencies using live exchange rates (e.g., 100 usd eur)
+    Currency {
+        /// Amount to convert
+        value: f64,
+        /// ISO 4217 currency code to convert from (e.g., usd)
+        from_unit: String,
+        /// ISO 4217 currency code to convert to (e.g., eur)
+        to_unit: String,
+    },
+    // Add other categories like Mass later
 }
 
-pub fn handle_unit_converter_command(args: UnitConverterArgs) -> Result<String> {
-    match args.command {
+pub async fn handle_unit_converter_command(args: UnitConverterArgs) -> Result<String> {
+    if let Some(category) = args.list {
+        return list_units(Some(category.as_str()).filter(|c| !c.is_empty()));
+    }
+
+    let command = args.command
+        .ok_or_else(|| anyhow::anyhow!("Specify a category to convert (see `convert --list`)"))?;
+    match command {
         UnitConverterCommands::Length { value, from_unit, to_unit } => {
             convert_length(value, &from_unit, &to_unit)
         }
+        UnitConverterCommands::Temperature { value, from_unit, to_unit } => {
+            convert_temperature(value, &from_unit, &to_unit)
+        }
+        UnitConverterCommands::DataRate { value, from_unit, to_unit } => {
+            convert_data_rate(value, &from_unit, &to_unit)
+        }
+        UnitConverterCommands::DataSize { value, from_unit, to_unit } => {
+            convert_data_size(value, &from_unit, &to_unit)
+        }
+        UnitConverterCommands::Currency { value, from_unit, to_unit } => {
+            let rates = load_exchange_rates().await?;
+            convert_currency(value, &from_unit, &to_unit, &rates)
+        }
     }
 }
 
+/// One unit a category understands: its symbol, the other names it accepts and its
+/// size in the category's base unit. Temperature is affine, so its factors are unused.
+struct Unit {
+    symbol: &'static str,
+    aliases: &'static [&'static str],
+    factor: f64,
+}
+
+/// Base unit: meter
+const LENGTH_UNITS: &[Unit] = &[
+    Unit { symbol: "m", aliases: &["meter", "meters"], factor: 1.0 },
+    Unit { symbol: "km", aliases: &["kilometer", "kilometers"], factor: 1000.0 },
+    Unit { symbol: "ft", aliases: &["foot", "feet"], factor: 0.3048 },
+    Unit { symbol: "mi", aliases: &["mile", "miles"], factor: 1609.344 },
+];
+
+const TEMPERATURE_UNITS: &[Unit] = &[
+    Unit { symbol: "c", aliases: &["celsius"], factor: 1.0 },
+    Unit { symbol: "f", aliases: &["fahrenheit"], factor: 1.0 },
+    Unit { symbol: "k", aliases: &["kelvin"], factor: 1.0 },
+];
+
+/// Base unit: bit per second
+const DATA_RATE_UNITS: &[Unit] = &[
+    Unit { symbol: "bps", aliases: &["bit/s"], factor: 1.0 },
+    Unit { symbol: "kbps", aliases: &["kbit/s"], factor: 1e3 },
+    Unit { symbol: "mbps", aliases: &["mbit/s"], factor: 1e6 },
+    Unit { symbol: "gbps", aliases: &["gbit/s"], factor: 1e9 },
+];
+
+/// Base unit: byte. KB/MB/... are decimal, KiB/MiB/... binary
+const DATA_SIZE_UNITS: &[Unit] = &[
+    Unit { symbol: "b", aliases: &["byte", "bytes"], factor: 1.0 },
+    Unit { symbol: "kb", aliases: &[], factor: 1e3 },
+    Unit { symbol: "mb", aliases: &[], factor: 1e6 },
+    Unit { symbol: "gb", aliases: &[], factor: 1e9 },
+    Unit { symbol: "tb", aliases: &[], factor: 1e12 },
+    Unit { symbol: "kib", aliases: &[], factor: 1024.0 },
+    Unit { symbol: "mib", aliases: &[], factor: 1024.0 * 1024.0 },
+    Unit { symbol: "gib", aliases: &[], factor: 1024.0 * 1024.0 * 1024.0 },
+    Unit { symbol: "tib", aliases: &[], factor: 1024.0 * 1024.0 * 1024.0 * 1024.0 },
+];
+
+/// Categories with fixed unit tables, in the order `convert --list` prints them
+const CATEGORIES: &[(&str, &[Unit])] = &[
+    ("length", LENGTH_UNITS),
+    ("temperature", TEMPERATURE_UNITS),
+    ("data-rate", DATA_RATE_UNITS),
+    ("data-size", DATA_SIZE_UNITS),
+];
+
+/// Look a unit up by symbol or alias, ignoring case
+fn find_unit(units: &'static [Unit], name: &str) -> Option<&'static Unit> {
+    let name = name.to_lowercase();
+    units.iter().find(|unit| unit.symbol == name || unit.aliases.contains(&name.as_str()))
+}
+
+/// Convert between two units of a multiplicative category
+fn convert_by_factor(units: &'static [Unit], category: &str, value: f64, from_unit: &str, to_unit: &str) -> Result<String> {
+    let from = find_unit(units, from_unit)
+        .ok_or_else(|| anyhow::anyhow!("Unsupported {} unit: {}", category, from_unit))?;
+    let to = find_unit(units, to_unit)
+        .ok_or_else(|| anyhow::anyhow!("Unsupported {} unit: {}", category, to_unit))?;
+
+    Ok(format!("{} {} = {:.4} {}", value, from_unit, value * from.factor / to.factor, to_unit))
+}
+
 fn convert_length(value: f64, from_unit: &str, to_unit: &str) -> Result<String> {
-    const KM_TO_MILES: f64 = 0.621371;
-    const METERS_TO_FEET: f64 = 3.28084;
-
-    let result = match (from_unit.to_lowercase().as_str(), to_unit.to_lowercase().as_str()) {
-        ("km", "mi") | ("kilometers", "miles") => value * KM_TO_MILES,
-        ("mi", "km") | ("miles", "kilometers") => value / KM_TO_MILES,
-        ("m", "ft") | ("meters", "feet") => value * METERS_TO_FEET,
-        ("ft", "m") | ("feet", "meters") => value / METERS_TO_FEET,
-        ("km", "m") | ("kilometers", "meters") => value * 1000.0,
-        ("m", "km") | ("meters", "kilometers") => value / 1000.0,
-        ("mi", "ft") | ("miles", "feet") => value * 5280.0,
-        ("ft", "mi") | ("feet", "miles") => value / 5280.0,
-         // Add more conversions as needed: m <-> mi, km <-> ft etc. via intermediate conversions
-        (f, t) if f == t => value, // Same unit
-        _ => return Err(anyhow::anyhow!("Unsupported length conversion: {} to {}", from_unit, to_unit)),
+    convert_by_factor(LENGTH_UNITS, "length", value, from_unit, to_unit)
+}
+
+/// Temperature scales are offset from each other, so values go through Celsius
+/// with the full affine formula instead of a single multiplier.
+fn convert_temperature(value: f64, from_unit: &str, to_unit: &str) -> Result<String> {
+    let from = find_unit(TEMPERATURE_UNITS, from_unit)
+        .ok_or_else(|| anyhow::anyhow!("Unsupported temperature unit: {}", from_unit))?;
+    let to = find_unit(TEMPERATURE_UNITS, to_unit)
+        .ok_or_else(|| anyhow::anyhow!("Unsupported temperature unit: {}", to_unit))?;
+
+    let celsius = match from.symbol {
+        "f" => (value - 32.0) * 5.0 / 9.0,
+        "k" => value - 273.15,
+        _ => value,
+    };
+
+    if celsius < -273.15 - 1e-9 {
+        return Err(anyhow::anyhow!("{} {} is below absolute zero", value, from_unit));
+    }
+
+    let result = match to.symbol {
+        "f" => celsius * 9.0 / 5.0 + 32.0,
+        "k" => celsius + 273.15,
+        _ => celsius,
     };
 
     Ok(format!("{} {} = {:.4} {}", value, from_unit, result, to_unit))
 }
 
-// Add functions for other conversions (mass, temp, currency) here
+fn convert_data_rate(value: f64, from_unit: &str, to_unit: &str) -> Result<String> {
+    convert_by_factor(DATA_RATE_UNITS, "data rate", value, from_unit, to_unit)
+}
+
+fn convert_data_size(value: f64, from_unit: &str, to_unit: &str) -> Result<String> {
+    convert_by_factor(DATA_SIZE_UNITS, "data size", value, from_unit, to_unit)
+}
+
+/// Describe the supported categories and their units, or just one category
+fn list_units(category: Option<&str>) -> Result<String> {
+    let category = category.map(|c| c.to_lowercase().replace('_', "-"));
+    let mut output = String::new();
+
+    for (name, units) in CATEGORIES {
+        if category.as_deref().is_some_and(|c| c != *name) {
+            continue;
+        }
+        output.push_str(&format!("{}\n", name.green().bold()));
+        for unit in units.iter() {
+            if unit.aliases.is_empty() {
+                output.push_str(&format!("  {}\n", unit.symbol));
+            } else {
+                output.push_str(&format!("  {} ({})\n", unit.symbol, unit.aliases.join(", ")));
+            }
+        }
+    }
+
+    if matches!(category.as_deref(), None | Some("currency")) {
+        output.push_str(&format!("{}\n", "currency".green().bold()));
+        output.push_str("  any ISO 4217 code (e.g., usd, eur, jpy) with live exchange rates\n");
+    }
+
+    if output.is_empty() {
+        let known: Vec<&str> = CATEGORIES.iter().map(|(name, _)| *name).chain(["currency"]).collect();
+        return Err(anyhow::anyhow!("Unknown category '{}' (expected {})", category.unwrap_or_default(), known.join(", ")));
+    }
+    Ok(output.trim_end().to_string())
+}
+
+/// Exchange rates as cached on disk
+#[derive(Debug, Serialize, Deserialize)]
+struct ExchangeRateCache {
+    fetched_at: u64, // Unix seconds
+    rates: HashMap<String, f64>, // Units of each currency per USD
+}
+
+fn exchange_rate_cache_file() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("terminal-pc-matrix").join("exchange_rates.json"))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+async fn fetch_exchange_rates() -> Result<HashMap<String, f64>> {
+    let response = utils::http_client()
+        .get(EXCHANGE_RATES_URL)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("Exchange rate request failed with status: {}", response.status()));
+    }
+
+    let body: serde_json::Value = response.json().await?;
+    if body.get("result").and_then(|v| v.as_str()) != Some("success") {
+        return Err(anyhow::anyhow!("Exchange rate service returned an error"));
+    }
+    let rates = body.get("rates")
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("Exchange rate response has no rates"))?;
+    Ok(serde_json::from_value(rates)?)
+}
+
+/// Use cached rates while they are fresh, otherwise fetch new ones; when the fetch
+/// fails (e.g. offline) fall back to whatever is cached, however old.
+async fn load_exchange_rates() -> Result<HashMap<String, f64>> {
+    let cache_file = exchange_rate_cache_file();
+    let cached: Option<ExchangeRateCache> = cache_file.as_ref()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok());
+
+    let fresh = cached.as_ref()
+        .filter(|cache| now_secs().saturating_sub(cache.fetched_at) < EXCHANGE_RATES_TTL.as_secs());
+    if let Some(cache) = fresh {
+        return Ok(cache.rates.clone());
+    }
+
+    match fetch_exchange_rates().await {
+        Ok(rates) => {
+            if let Some(path) = &cache_file {
+                let cache = ExchangeRateCache { fetched_at: now_secs(), rates };
+                if let Some(parent) = path.parent() {
+                    let _ = fs::create_dir_all(parent);
+                }
+                let _ = fs::write(path, serde_json::to_string(&cache)?);
+                return Ok(cache.rates);
+            }
+            Ok(rates)
+        }
+        Err(e) => match cached {
+            Some(cache) => {
+                eprintln!("{} {} (using cached rates)", "Could not fetch exchange rates:".yellow(), e);
+                Ok(cache.rates)
+            }
+            None => Err(anyhow::anyhow!("Could not fetch exchange rates and none are cached: {}", e)),
+        },
+    }
+}
+
+fn convert_currency(value: f64, from_unit: &str, to_unit: &str, rates: &HashMap<String, f64>) -> Result<String> {
+    let rate = |code: &str| {
+        rates.get(&code.to_uppercase())
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("Unknown currency code: {}", code))
+    };
+    let from = rate(from_unit)?;
+    let to = rate(to_unit)?;
+
+    Ok(format!("{} {} = {:.2} {}", value, from_unit.to_uppercase(), value / from * to, to_unit.to_uppercase()))
+}
+
+// Add functions for other conversions (mass) here
 
 #[cfg(test)]
 mod tests {
@@ -71,4 +342,67 @@ mod tests {
     fn test_invalid_length_conversion() {
         assert!(convert_length(1.0, "km", "kg").is_err());
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_temperature_conversion() {
+        // 0°C = 32°F = 273.15K, in every direction
+        assert!(convert_temperature(0.0, "c", "f").unwrap().contains("32.0000"));
+        assert!(convert_temperature(0.0, "c", "k").unwrap().contains("273.1500"));
+        assert!(convert_temperature(32.0, "f", "c").unwrap().contains("= 0.0000"));
+        assert!(convert_temperature(32.0, "f", "k").unwrap().contains("273.1500"));
+        assert!(convert_temperature(273.15, "k", "c").unwrap().contains("= 0.0000"));
+        assert!(convert_temperature(273.15, "k", "f").unwrap().contains("32.0000"));
+        assert!(convert_temperature(100.0, "celsius", "fahrenheit").unwrap().contains("212.0000"));
+        assert!(convert_temperature(-40.0, "f", "c").unwrap().contains("-40.0000"));
+    }
+
+    #[test]
+    fn test_invalid_temperature_conversion() {
+        assert!(convert_temperature(1.0, "c", "km").is_err());
+        assert!(convert_temperature(-1.0, "k", "c").is_err());
+    }
+
+    #[test]
+    fn test_data_rate_conversion() {
+        assert!(convert_data_rate(1.0, "Gbps", "Mbps").unwrap().contains("1000.0000"));
+        assert!(convert_data_rate(1500.0, "kbps", "Mbps").unwrap().contains("1.5000"));
+        assert!(convert_data_rate(1.0, "mbps", "kb").is_err());
+    }
+
+    #[test]
+    fn test_data_size_conversion() {
+        assert!(convert_data_size(1.0, "MiB", "KiB").unwrap().contains("1024.0000"));
+        assert!(convert_data_size(1.0, "MB", "KB").unwrap().contains("1000.0000"));
+        assert!(convert_data_size(1.0, "GiB", "GB").unwrap().contains("1.0737"));
+        assert!(convert_data_size(1.0, "MB", "mbps").is_err());
+    }
+
+    #[test]
+    fn test_currency_conversion() {
+        let rates: HashMap<String, f64> = [("USD", 1.0), ("EUR", 0.9), ("JPY", 150.0)]
+            .into_iter()
+            .map(|(code, rate)| (code.to_string(), rate))
+            .collect();
+        assert!(convert_currency(100.0, "usd", "eur", &rates).unwrap().contains("= 90.00 EUR"));
+        assert!(convert_currency(90.0, "EUR", "JPY", &rates).unwrap().contains("= 15000.00 JPY"));
+        assert!(convert_currency(1.0, "usd", "xyz", &rates).unwrap_err().to_string().contains("Unknown currency code"));
+    }
+
+    #[test]
+    fn test_list_units() {
+        let all = list_units(None).unwrap();
+        for (name, units) in CATEGORIES {
+            assert!(all.contains(name));
+            for unit in units.iter() {
+                assert!(find_unit(units, unit.symbol).is_some());
+                assert!(unit.aliases.iter().all(|alias| find_unit(units, alias).is_some()));
+            }
+        }
+        assert!(all.contains("currency"));
+
+        let temperature = list_units(Some("temperature")).unwrap();
+        assert!(temperature.contains("k (kelvin)"));
+        assert!(!temperature.contains("km"));
+        assert!(list_units(Some("mass")).is_err());
+    }
+}
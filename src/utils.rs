@@ -1,13 +1,656 @@
 use colored::Colorize;
-use std::io::{self, Write};
+use indicatif::{ProgressBar, ProgressStyle};
+use log::LevelFilter;
+use serde::Serialize;
+use std::fmt::Display;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use crate::error::AppError;
 
-// Helper function to prompt user for input
+// Partially written files to delete if the program is interrupted
+static PARTIAL_FILES: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+// Token of the operation that currently handles Ctrl-C itself, if any
+static CTRL_C_TARGET: Mutex<Option<CancellationToken>> = Mutex::new(None);
+
+// Where prompt() reads answers from: stdin unless a script was installed
+static PROMPT_INPUT: Mutex<Option<Box<dyn BufRead + Send>>> = Mutex::new(None);
+
+// Whether maybe_page() may hand long output to a pager
+static PAGER_MODE: Mutex<PagerMode> = Mutex::new(PagerMode::Auto);
+
+/// When long listings go through a pager (only ever when stdout is a terminal)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PagerMode {
+    /// Page output taller than the terminal
+    Auto,
+    /// Always page
+    Always,
+    /// Never page
+    Never,
+}
+
+// Set up the logger behind status chatter: quiet hides it, each -v adds detail.
+// RUST_LOG still overrides the level when set.
+pub fn init_logging(quiet: bool, verbose: u8) {
+    let level = match (quiet, verbose) {
+        (true, _) => LevelFilter::Warn,
+        (false, 0) => LevelFilter::Info,
+        (false, 1) => LevelFilter::Debug,
+        (false, _) => LevelFilter::Trace,
+    };
+    env_logger::Builder::new()
+        .filter_level(level)
+        .parse_default_env()
+        .format(|buf, record| writeln!(buf, "{}", record.args()))
+        .init();
+}
+
+// Feed prompt() from a reader (one answer per line) instead of stdin, e.g. an
+// --answers-file or scripted answers in tests
+pub fn set_prompt_input(input: Box<dyn BufRead + Send>) {
+    *PROMPT_INPUT.lock().unwrap_or_else(|e| e.into_inner()) = Some(input);
+}
+
+// Returned (wrapped in an io::Error) by prompt() when the user types `b` to leave a sub-menu
+#[derive(Debug)]
+pub struct GoBack;
+
+impl std::fmt::Display for GoBack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "back to main menu")
+    }
+}
+
+impl std::error::Error for GoBack {}
+
+// Whether an error (possibly re-wrapped by a handler) is the user asking to go back
+pub fn is_go_back(err: &(dyn std::error::Error + 'static)) -> bool {
+    let wrapped = err.downcast_ref::<io::Error>()
+        .and_then(|e| e.get_ref())
+        .is_some_and(|inner| inner.is::<GoBack>());
+    wrapped || err.to_string() == GoBack.to_string()
+}
+
+// Whether an error (possibly re-wrapped by a handler) means input ran out (Ctrl-D)
+pub fn is_end_of_input(err: &(dyn std::error::Error + 'static)) -> bool {
+    err.downcast_ref::<io::Error>()
+        .is_some_and(|e| e.kind() == io::ErrorKind::UnexpectedEof)
+}
+
+// Prompt for the main menu choice; unlike sub-menu prompts, `b` is an ordinary answer here
+pub fn prompt_menu(message: &str) -> io::Result<String> {
+    read_answer(message)
+}
+
+// Helper function to prompt user for input inside a sub-menu.
+// Typing `b` or just pressing Enter returns a GoBack error so the handler unwinds to the main menu.
 pub fn prompt(message: &str) -> io::Result<String> {
+    let answer = prompt_optional(message)?;
+    if answer.is_empty() {
+        return Err(io::Error::other(GoBack));
+    }
+    Ok(answer)
+}
+
+// Sub-menu prompt for an answer that may be left empty ("leave empty for none");
+// only `b` goes back to the main menu
+pub fn prompt_optional(message: &str) -> io::Result<String> {
+    let answer = read_answer(message)?;
+    if answer.eq_ignore_ascii_case("b") {
+        return Err(io::Error::other(GoBack));
+    }
+    Ok(answer)
+}
+
+// Sub-menu prompt showing "(default: ...)"; an empty answer returns the default
+pub fn prompt_with_default(message: &str, default: &str) -> io::Result<String> {
+    let answer = prompt_optional(&format!("{} (default: {})", message, default))?;
+    Ok(if answer.is_empty() { default.to_string() } else { answer })
+}
+
+// Yes/no question; takes y/yes/n/no in any case, an empty answer is the default,
+// and anything else asks again
+pub fn prompt_yes_no(message: &str, default: bool) -> io::Result<bool> {
+    let hint = if default { "yes/no, default: yes" } else { "yes/no, default: no" };
+    loop {
+        match prompt_optional(&format!("{} ({})", message, hint))?.to_ascii_lowercase().as_str() {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => eprintln!("{}", "Please answer yes or no.".yellow()),
+        }
+    }
+}
+
+// Prompt for a value parsed with FromStr; an empty answer is the default and
+// an answer that doesn't parse asks again
+pub fn prompt_parse<T>(message: &str, default: T) -> io::Result<T>
+where
+    T: FromStr + Display,
+    T::Err: Display,
+{
+    loop {
+        let answer = prompt_optional(&format!("{} (default: {})", message, default))?;
+        if answer.is_empty() {
+            return Ok(default);
+        }
+        match answer.parse() {
+            Ok(value) => return Ok(value),
+            Err(e) => eprintln!("{} {}", "Invalid value:".yellow(), e),
+        }
+    }
+}
+
+fn read_answer(message: &str) -> io::Result<String> {
     print!("{}: ", message.cyan());
     io::stdout().flush()?; // Ensure the prompt message is displayed before input
     let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
+    let mut scripted = PROMPT_INPUT.lock().unwrap_or_else(|e| e.into_inner());
+    let read = match scripted.as_mut() {
+        Some(reader) => {
+            let read = reader.read_line(&mut input)?;
+            // Echo scripted answers so the session transcript stays readable
+            println!("{}", input.trim_end());
+            read
+        }
+        None => io::stdin().read_line(&mut input)?,
+    };
+    if read == 0 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "no more input"));
+    }
     Ok(input.trim().to_string())
 }
 
-// Add other utility functions here later (e.g., parsing human sizes) 
\ No newline at end of file
+// Whether prompts read from a person at a terminal, rather than from a script or a pipe,
+// so full-screen widgets like the command palette can be used
+pub fn prompt_is_terminal() -> bool {
+    use std::io::IsTerminal;
+    PROMPT_INPUT.lock().unwrap_or_else(|e| e.into_inner()).is_none() && io::stdin().is_terminal()
+}
+
+// Whether to draw progress bars: not under --quiet (which lowers the log level below info),
+// and only when both stdout and stderr are terminals, so piped or redirected output stays clean
+pub fn progress_bars_enabled() -> bool {
+    use std::io::IsTerminal;
+    log::log_enabled!(log::Level::Info) && io::stdout().is_terminal() && io::stderr().is_terminal()
+}
+
+pub fn set_pager_mode(mode: PagerMode) {
+    *PAGER_MODE.lock().unwrap_or_else(|e| e.into_inner()) = mode;
+}
+
+// Whether output of `lines` lines should be paged on a terminal `height` rows tall
+fn needs_pager(lines: usize, mode: PagerMode, is_terminal: bool, height: usize) -> bool {
+    match mode {
+        _ if !is_terminal => false,
+        PagerMode::Never => false,
+        PagerMode::Always => true,
+        // Keep a row free for the shell prompt that follows
+        PagerMode::Auto => lines >= height,
+    }
+}
+
+// Print a finished listing, through $PAGER (default `less`) when it wouldn't fit on screen,
+// much like git does. Falls back to printing directly if the pager can't be started.
+pub fn maybe_page(output: &str) {
+    use std::io::IsTerminal;
+
+    let mode = *PAGER_MODE.lock().unwrap_or_else(|e| e.into_inner());
+    let height = crossterm::terminal::size().map(|(_, rows)| rows as usize).unwrap_or(24);
+    if needs_pager(output.lines().count(), mode, io::stdout().is_terminal(), height) {
+        match run_pager(output) {
+            Ok(()) => return,
+            Err(e) => log::debug!("Pager failed, printing directly: {}", e),
+        }
+    }
+    // Redirected output goes to a file or another program, so leave the colors out
+    if io::stdout().is_terminal() {
+        println!("{}", output);
+    } else {
+        println!("{}", strip_ansi(output));
+    }
+}
+
+fn run_pager(output: &str) -> io::Result<()> {
+    let default = if cfg!(windows) { "more" } else { "less" };
+    let command = std::env::var("PAGER").ok().filter(|p| !p.trim().is_empty()).unwrap_or_else(|| default.to_string());
+    let mut parts = command.split_whitespace();
+    let program = parts.next().unwrap_or(default);
+    let mut pager = std::process::Command::new(program);
+    pager.args(parts).stdin(std::process::Stdio::piped());
+    // Same defaults as git: quit if it fits, keep colors, don't clear the screen
+    if std::env::var_os("LESS").is_none() {
+        pager.env("LESS", "FRX");
+    }
+    let mut child = pager.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Quitting the pager early closes the pipe; that's not an error
+        match stdin.write_all(output.as_bytes()).and_then(|_| stdin.write_all(b"\n")) {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e),
+            _ => {}
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
+
+// Remember a file that is still being written so Ctrl-C can delete it
+pub fn track_partial_file(path: &Path) {
+    PARTIAL_FILES.lock().unwrap_or_else(|e| e.into_inner()).push(path.to_path_buf());
+}
+
+// Forget a file once it is complete (or already removed)
+pub fn untrack_partial_file(path: &Path) {
+    PARTIAL_FILES.lock().unwrap_or_else(|e| e.into_inner()).retain(|tracked| tracked != path);
+}
+
+// Delete every tracked partial file; called from the Ctrl-C handler
+pub fn remove_partial_files() {
+    let files = std::mem::take(&mut *PARTIAL_FILES.lock().unwrap_or_else(|e| e.into_inner()));
+    for path in files {
+        match std::fs::remove_file(&path) {
+            Ok(()) => eprintln!("{} {}", "Removed partial file".dimmed(), path.display()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => eprintln!("{} {}: {}", "Failed to remove".yellow(), path.display(), e),
+        }
+    }
+}
+
+// Returned by `handle_ctrl_c`; Ctrl-C exits the program again once it is dropped
+pub struct CtrlCGuard(());
+
+impl Drop for CtrlCGuard {
+    fn drop(&mut self) {
+        *CTRL_C_TARGET.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+// Take over Ctrl-C for a long operation: while the guard lives the first Ctrl-C cancels the
+// returned token, so the operation can stop its work and clean up after itself; a second one
+// still exits
+pub fn handle_ctrl_c() -> (CancellationToken, CtrlCGuard) {
+    let token = CancellationToken::new();
+    *CTRL_C_TARGET.lock().unwrap_or_else(|e| e.into_inner()) = Some(token.clone());
+    (token, CtrlCGuard(()))
+}
+
+// Cancel the operation handling Ctrl-C; false when there is none or it is already cancelled,
+// in which case the caller cleans up and exits
+pub fn cancel_current_operation() -> bool {
+    match &*CTRL_C_TARGET.lock().unwrap_or_else(|e| e.into_inner()) {
+        Some(token) if !token.is_cancelled() => {
+            token.cancel();
+            true
+        }
+        _ => false,
+    }
+}
+
+// Escape text for use in HTML content and attribute values
+pub fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+// Remove ANSI escape sequences (colors from `colored`, cursor moves, OSC titles) so text
+// rendered for the terminal can be written to a file or a pipe. Used for the PC specs text
+// report; JSON and CSV exports are built without colors in the first place.
+pub fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters, then one final byte in @..=~
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC: runs until BEL or ESC \
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            // Two-character escapes such as ESC 7
+            _ => {}
+        }
+    }
+    out
+}
+
+// How far a long operation (download, backup, ...) has got; `total` is 0 when unknown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Progress {
+    pub done: u64,
+    pub total: u64,
+}
+
+// Receives Progress updates so a frontend can draw its own bar instead of the terminal one
+pub type ProgressCallback = Arc<dyn Fn(Progress) + Send + Sync>;
+
+// Terminal bar for byte counts, in the same style as the download bars
+pub fn bytes_progress_bar() -> ProgressBar {
+    let pb = ProgressBar::new(0);
+    pb.set_style(ProgressStyle::default_bar()
+        .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
+        .unwrap()
+        .progress_chars("#>-"));
+    pb
+}
+
+// Callback that mirrors progress onto a terminal ProgressBar
+pub fn progress_bar_callback(pb: &ProgressBar) -> ProgressCallback {
+    let pb = pb.clone();
+    Arc::new(move |progress: Progress| {
+        pb.set_length(progress.total);
+        pb.set_position(progress.done);
+    })
+}
+
+// Parallel downloads mostly wait on the network, so a couple per core is fine, but past
+// a few dozen they only exhaust file handles and get the client rate-limited
+const DOWNLOADS_PER_CORE: usize = 2;
+const MAX_DOWNLOAD_CONCURRENCY: usize = 16;
+
+// Most parallel image or playlist downloads that will run: two per CPU core, at most 16
+pub fn max_download_concurrency() -> usize {
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    download_concurrency_limit(cores)
+}
+
+fn download_concurrency_limit(cores: usize) -> usize {
+    (cores * DOWNLOADS_PER_CORE).clamp(1, MAX_DOWNLOAD_CONCURRENCY)
+}
+
+// Default number of parallel downloads: `preferred`, or fewer on a machine with few cores
+pub fn default_download_concurrency(preferred: usize) -> usize {
+    preferred.min(max_download_concurrency())
+}
+
+// Clamp a requested number of parallel downloads to 1..=max_download_concurrency(),
+// saying so when the request was out of range
+pub fn clamp_download_concurrency(requested: usize) -> usize {
+    let max = max_download_concurrency();
+    let effective = requested.clamp(1, max);
+    if effective != requested {
+        println!("{} {} parallel downloads requested; using {} (limit on this machine: 1-{})",
+            "Note:".yellow(), requested, effective, max);
+    }
+    effective
+}
+
+// Quote a value for a POSIX shell when it holds anything beyond plain word characters
+pub fn shell_quote(value: &str) -> String {
+    let plain = !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=,@%+".contains(c));
+    if plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+// Quote a CSV field when it contains a separator, quote or line break
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// User agent of every request that doesn't set its own
+const HTTP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+// Only connecting is bounded for everyone; whole-request limits are set per request,
+// since a file download may rightly take hours
+const HTTP_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Client settings every request shares: our user agent and the connect timeout.
+// Proxies come from HTTP_PROXY / HTTPS_PROXY / NO_PROXY.
+pub fn http_client_builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .user_agent(HTTP_USER_AGENT)
+        .connect_timeout(HTTP_CONNECT_TIMEOUT)
+}
+
+// The app's one HTTP client, built on first use so every module shares its connection pool
+// and TLS sessions. Cloning it is cheap and keeps sharing the pool; only http-request builds
+// its own from http_client_builder(), for its per-run cookie jar.
+pub fn http_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        http_client_builder().build().unwrap_or_else(|e| {
+            log::warn!("Failed to configure the HTTP client ({}); using reqwest defaults", e);
+            reqwest::Client::new()
+        })
+    })
+}
+
+// Only web addresses go to the system opener. A file: URL would be handed to whatever the
+// file type is associated with, so a downloaded .exe or .desktop file would run; anything
+// that isn't a URL could be read as one of the opener's options.
+fn browser_url(url: &str) -> io::Result<reqwest::Url> {
+    let parsed = reqwest::Url::parse(url.trim())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid URL '{}': {}", url, e)))?;
+    match parsed.scheme() {
+        "http" | "https" => Ok(parsed),
+        scheme => Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Won't open a {}: URL in the browser", scheme))),
+    }
+}
+
+// Open `url` in the default browser: `open` on macOS, the URL protocol handler on Windows,
+// `xdg-open` elsewhere
+pub fn open_in_browser(url: &str) -> io::Result<()> {
+    let url = browser_url(url)?;
+
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = std::process::Command::new("open");
+        command.arg(url.as_str());
+        command
+    };
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        // Not `cmd /C start`: cmd re-parses the URL, so `&` or `%VAR%` in it would be interpreted
+        let mut command = std::process::Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler").arg(url.as_str());
+        command
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = {
+        let mut command = std::process::Command::new("xdg-open");
+        command.arg(url.as_str());
+        command
+    };
+
+    let status = command
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("The browser opener exited with {}", status)));
+    }
+    Ok(())
+}
+
+// How long a 429 (or 503) answer asks us to wait: Retry-After as seconds or as an HTTP date
+pub fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some((date.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().unwrap_or_default())
+}
+
+// Longest Retry-After we sit out before giving up with a "rate limited" error
+pub const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+// Wait used when a 429 comes without a Retry-After header
+const DEFAULT_RATE_LIMIT_WAIT: Duration = Duration::from_secs(5);
+
+// Host name used to say who is rate limiting us
+pub fn url_host(url: &str) -> String {
+    reqwest::Url::parse(url).ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_else(|| url.to_string())
+}
+
+// Send `request`, waiting and retrying up to `retries` times while `service` answers
+// 429 Too Many Requests. Gives up with AppError::RateLimited when the budget runs out
+// or the server asks for a longer wait than MAX_RATE_LIMIT_WAIT.
+pub async fn send_with_rate_limit(request: reqwest::RequestBuilder, service: &str, retries: u32) -> Result<reqwest::Response, AppError> {
+    let mut attempt = 0;
+    loop {
+        let this_try = request.try_clone()
+            .ok_or_else(|| AppError::InvalidInput("Request body can't be resent".into()))?;
+        let response = this_try.send().await?;
+        if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Ok(response);
+        }
+        let retry_after = retry_after(response.headers());
+        let wait = retry_after.unwrap_or(DEFAULT_RATE_LIMIT_WAIT);
+        if attempt >= retries || wait > MAX_RATE_LIMIT_WAIT {
+            return Err(AppError::RateLimited { service: service.to_string(), retry_after });
+        }
+        attempt += 1;
+        println!("{} {} is rate limiting requests, retry {}/{} in {}s", "⚠".yellow(), service, attempt, retries, wait.as_secs());
+        tokio::time::sleep(wait).await;
+    }
+}
+
+// Add other utility functions here later (e.g., parsing human sizes)
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi("\x1b[1;32mCPU\x1b[0m: 8 cores"), "CPU: 8 cores");
+        assert_eq!(strip_ansi("\x1b]0;title\x07plain \x1b]8;;http://x\x1b\\link"), "plain link");
+        assert_eq!(strip_ansi("no escapes, ünïcode"), "no escapes, ünïcode");
+    }
+
+    #[test]
+    fn test_handle_ctrl_c() {
+        assert!(!cancel_current_operation());
+        let (token, guard) = handle_ctrl_c();
+        assert!(cancel_current_operation());
+        assert!(token.is_cancelled());
+        // A second Ctrl-C is left to the caller, which exits
+        assert!(!cancel_current_operation());
+        drop(guard);
+        assert!(!cancel_current_operation());
+    }
+
+    #[test]
+    fn test_browser_url() {
+        assert_eq!(browser_url(" https://example.com/a?b=1&c=2 ").unwrap().as_str(), "https://example.com/a?b=1&c=2");
+        assert!(browser_url("file:///tmp/index.html").is_err());
+        assert!(browser_url("file:///tmp/setup.exe").is_err());
+        assert!(browser_url("javascript:alert(1)").is_err());
+        assert!(browser_url("--help").is_err());
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(reqwest::header::RETRY_AFTER, "120".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+        headers.insert(reqwest::header::RETRY_AFTER, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+        headers.insert(reqwest::header::RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[test]
+    fn test_download_concurrency_limit() {
+        assert_eq!(download_concurrency_limit(1), 2);
+        assert_eq!(download_concurrency_limit(4), 8);
+        assert_eq!(download_concurrency_limit(64), MAX_DOWNLOAD_CONCURRENCY);
+        assert_eq!(clamp_download_concurrency(0), 1);
+        assert_eq!(clamp_download_concurrency(500), max_download_concurrency());
+    }
+
+    #[test]
+    fn test_prompt_reads_scripted_answers() {
+        set_prompt_input(Box::new(io::Cursor::new("first\n  second  \n\n\n")));
+        assert_eq!(prompt("One").unwrap(), "first");
+        assert_eq!(prompt_optional("Two").unwrap(), "second");
+        assert_eq!(prompt_optional("Three").unwrap(), "");
+        assert!(is_go_back(&prompt("Four").unwrap_err()));
+        assert_eq!(prompt("Five").unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+        // Same test: the scripted input is global, so parallel tests would race on it
+        set_prompt_input(Box::new(io::Cursor::new("b\nB\n")));
+        let err = prompt_optional("Path").unwrap_err();
+        assert!(is_go_back(&err));
+        assert!(!is_end_of_input(&err));
+        assert_eq!(prompt_menu("Choice").unwrap(), "B");
+
+        let boxed: Box<dyn std::error::Error + Send + Sync> = prompt("Path").unwrap_err().into();
+        assert!(is_end_of_input(boxed.as_ref()));
+
+        set_prompt_input(Box::new(io::Cursor::new("\nout\n\nY\nmaybe\nNO\n\nten\n12\n")));
+        assert_eq!(prompt_with_default("Dir", ".").unwrap(), ".");
+        assert_eq!(prompt_with_default("Dir", ".").unwrap(), "out");
+        assert!(prompt_yes_no("Sure?", true).unwrap());
+        assert!(prompt_yes_no("Sure?", false).unwrap());
+        assert!(!prompt_yes_no("Sure?", true).unwrap()); // "maybe" is asked again
+        assert_eq!(prompt_parse("Count", 10u32).unwrap(), 10);
+        assert_eq!(prompt_parse("Count", 10u32).unwrap(), 12); // "ten" is asked again
+    }
+
+    #[test]
+    fn test_needs_pager() {
+        assert!(!needs_pager(10, PagerMode::Auto, true, 24));
+        assert!(needs_pager(24, PagerMode::Auto, true, 24));
+        assert!(needs_pager(1, PagerMode::Always, true, 24));
+        assert!(!needs_pager(500, PagerMode::Never, true, 24));
+        assert!(!needs_pager(500, PagerMode::Always, false, 24));
+    }
+
+    #[test]
+    fn test_progress_bar_callback() {
+        let pb = ProgressBar::hidden();
+        let callback = progress_bar_callback(&pb);
+        callback(Progress { done: 40, total: 100 });
+        assert_eq!(pb.position(), 40);
+        assert_eq!(pb.length(), Some(100));
+    }
+
+    #[test]
+    fn test_remove_partial_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let partial = dir.path().join("video.mp4.part0");
+        let finished = dir.path().join("video.mp4.part1");
+        std::fs::write(&partial, b"half").unwrap();
+        std::fs::write(&finished, b"done").unwrap();
+
+        track_partial_file(&partial);
+        track_partial_file(&finished);
+        untrack_partial_file(&finished);
+        remove_partial_files();
+
+        assert!(!partial.exists());
+        assert!(finished.exists());
+    }
+}
@@ -1,17 +1,20 @@
-use anyhow::Result;
 use colored::*;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use std::path::Path;
-use std::process::{Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
 use tokio::task;
 use std::io::{BufRead, BufReader};
 use regex::Regex;
 use std::fs;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use tokio::sync::Semaphore;
-use std::collections::HashMap;
-use std::time::Duration;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, SystemTime};
 use lazy_static::lazy_static;
+use tokio_util::sync::CancellationToken;
+use crate::audio_text_ops::check_ffmpeg_installed;
+use crate::error::{AppError, Result};
+use crate::utils;
 
 lazy_static! {
     static ref PROGRESS_REGEX: Regex = Regex::new(r"\[download\]\s+(\d+\.\d+)%").unwrap();
@@ -29,6 +32,20 @@ const DEFAULT_ARGS: &[&str] = &[
     "--concurrent-fragments", "5", // Download multiple fragments concurrently
 ];
 
+// Lines in yt-dlp's error output that mean an extractor no longer matches the site,
+// which a newer yt-dlp usually fixes
+const EXTRACTOR_ERROR_PATTERNS: &[&str] = &[
+    "unable to extract",
+    "signature extraction failed",
+    "nsig extraction failed",
+    "latest version using",
+    "please report this issue",
+    "http error 403: forbidden",
+];
+
+/// yt-dlp output template used when none is given, relative to the output directory
+pub const DEFAULT_OUTPUT_TEMPLATE: &str = "%(title)s.%(ext)s";
+
 /// Video quality options
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum VideoQuality {
@@ -52,6 +69,18 @@ impl VideoQuality {
         }
     }
     
+    /// The --quality value that selects this quality
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VideoQuality::Best => "best",
+            VideoQuality::HD1080 => "1080p",
+            VideoQuality::HD720 => "720p",
+            VideoQuality::SD480 => "480p",
+            VideoQuality::Lowest => "lowest",
+            VideoQuality::AudioOnly => "audio-only",
+        }
+    }
+    
     pub fn from_string(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "best" => Some(VideoQuality::Best),
@@ -75,12 +104,22 @@ pub struct DownloadOptions {
     pub quality: VideoQuality,
     pub audio_only: bool,
     pub max_rate: Option<String>,    // Bandwidth limit (e.g., "1M")
-    pub concurrent_downloads: usize, // Number of parallel playlist items
+    pub concurrent_downloads: usize, // Parallel playlist items, clamped to 1..=utils::max_download_concurrency()
     pub cookies_file: Option<String>, // Optional cookies file for auth
     pub subtitles: bool,             // Download subtitles
+    pub sub_langs: Vec<String>,      // Subtitle language patterns (e.g., "en.*", "de")
+    pub auto_subs: bool,             // Use auto-generated subtitles instead of human-written ones
+    pub embed_subs: bool,            // Embed subtitles in the container instead of sidecar files
     pub force_ipv4: bool,            // Force IPv4 (sometimes faster)
     pub proxy: Option<String>,       // Optional proxy URL
     pub retries: usize,              // Number of retries
+    pub format_id: Option<String>,   // Explicit yt-dlp format id, overrides quality
+    pub playlist_items: Option<String>, // yt-dlp playlist item spec (e.g., "1-5,8,10-")
+    pub failed_list: Option<PathBuf>,   // Write URLs of failed playlist videos here
+    pub output_template: Option<String>, // yt-dlp -o template relative to the output dir, defaults to DEFAULT_OUTPUT_TEMPLATE
+    pub embed_metadata: bool,        // Write title/artist/date into the file (needs ffmpeg)
+    pub embed_thumbnail: bool,       // Embed the thumbnail as cover art (needs ffmpeg)
+    pub auto_update: bool,           // Run `yt-dlp -U` when a download fails with an extractor error
 }
 
 impl Default for DownloadOptions {
@@ -89,26 +128,83 @@ impl Default for DownloadOptions {
             quality: VideoQuality::Best,
             audio_only: false,
             max_rate: None,
-            concurrent_downloads: 3,
+            concurrent_downloads: utils::default_download_concurrency(3),
             cookies_file: None,
             subtitles: false,
+            sub_langs: vec!["en.*".to_string()],
+            auto_subs: true,
+            embed_subs: false,
             force_ipv4: true,
             proxy: None,
             retries: 10,
+            format_id: None,
+            playlist_items: None,
+            failed_list: None,
+            output_template: None,
+            embed_metadata: false,
+            embed_thumbnail: false,
+            auto_update: false,
         }
     }
 }
 
 /// Check if yt-dlp is installed on the system
 pub async fn check_ytdlp_installed() -> bool {
-    match Command::new("yt-dlp")
-        .arg("--version")
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status() {
-            Ok(_) => true,
-            Err(_) => false,
-        }
+    get_ytdlp_version().is_some()
+}
+
+/// The installed yt-dlp version (e.g. "2024.08.06"), or `None` if it can't be run.
+/// Looked up once per process, so playlist items don't each spawn `yt-dlp --version`.
+pub fn get_ytdlp_version() -> Option<String> {
+    static VERSION: OnceLock<Option<String>> = OnceLock::new();
+    VERSION
+        .get_or_init(|| {
+            let output = Command::new("yt-dlp").arg("--version").stderr(Stdio::null()).output().ok()?;
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        })
+        .clone()
+}
+
+/// Run `yt-dlp -U` and return its last line of output
+pub fn update_ytdlp() -> Result<String> {
+    let output = Command::new("yt-dlp").arg("-U").output()?;
+    let text = format!("{}\n{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    let last_line = text.lines().map(str::trim).filter(|line| !line.is_empty()).next_back().unwrap_or_default().to_string();
+    if !output.status.success() {
+        return Err(AppError::Other(format!("yt-dlp -U failed: {}", last_line)));
+    }
+    Ok(last_line)
+}
+
+/// Whether yt-dlp's error output points at an outdated extractor
+fn looks_like_extractor_error(error_lines: &[String]) -> bool {
+    error_lines.iter().any(|line| {
+        let line = line.to_lowercase();
+        EXTRACTOR_ERROR_PATTERNS.iter().any(|pattern| line.contains(pattern))
+    })
+}
+
+/// After a failed download, suggest updating yt-dlp (or update it with `auto_update`)
+/// when the errors look like the site changed under it
+fn suggest_update(error_lines: &[String], auto_update: bool) {
+    if !looks_like_extractor_error(error_lines) {
+        return;
+    }
+    let version = get_ytdlp_version().unwrap_or_else(|| "unknown".to_string());
+    println!(
+        "{} yt-dlp {} could not handle this site, which usually means it is out of date.",
+        "Hint:".yellow().bold(),
+        version
+    );
+    if !auto_update {
+        println!("      Update it with {} or rerun with --auto-update.", "yt-dlp -U".cyan());
+        return;
+    }
+    println!("{}", "Updating yt-dlp...".cyan());
+    match update_ytdlp() {
+        Ok(message) => println!("{} {} Run the download again.", "yt-dlp:".green(), message),
+        Err(e) => eprintln!("{} {}", "Update failed:".red(), e),
+    }
 }
 
 /// Download a video from a URL with specified options
@@ -124,38 +220,63 @@ pub async fn download_video(
         ..Default::default()
     };
     
-    download_video_with_options(url, output_dir, &options).await
+    download_video_with_options(url, output_dir, &options, &CancellationToken::new()).await
 }
 
 /// Download a video with detailed options
+///
+/// Cancelling `cancel` kills the running yt-dlp process(es), deletes the `.part`/`.ytdl`
+/// files they left in `output_dir` and returns an error; videos that already finished stay.
 pub async fn download_video_with_options(
     url: &str,
     output_dir: &Path,
     options: &DownloadOptions,
+    cancel: &CancellationToken,
 ) -> Result<()> {
     // Check if yt-dlp is installed
     if !check_ytdlp_installed().await {
-        return Err(anyhow::anyhow!("yt-dlp is not installed. Please install it first: https://github.com/yt-dlp/yt-dlp#installation"));
+        return Err(AppError::MissingTool { tool: "yt-dlp", install_hint: "https://github.com/yt-dlp/yt-dlp#installation" });
     }
     
     println!("{} {}", "Downloading video from:".cyan().bold(), url);
     println!("{} {}", "Output directory:".cyan().bold(), output_dir.display());
-    println!("{} {:?}", "Selected quality:".cyan().bold(), options.quality);
+    if let Some(version) = get_ytdlp_version() {
+        println!("{} {}", "yt-dlp version:".dimmed(), version.dimmed());
+    }
+    if let Some(template) = &options.output_template {
+        validate_output_template(template)?;
+        println!("{} {}", "Output template:".cyan().bold(), template);
+    }
+    match &options.format_id {
+        Some(format_id) => println!("{} {}", "Selected format id:".cyan().bold(), format_id),
+        None => println!("{} {:?}", "Selected quality:".cyan().bold(), options.quality),
+    }
+    
+    // Embedding runs through ffmpeg after the download; without it yt-dlp would fail at the very end
+    let without_embedding;
+    let options = if (options.embed_metadata || options.embed_thumbnail) && !check_ffmpeg_installed().await {
+        println!("{}", "Warning: ffmpeg is not installed, so metadata and thumbnails won't be embedded.".yellow());
+        without_embedding = DownloadOptions { embed_metadata: false, embed_thumbnail: false, ..options.clone() };
+        &without_embedding
+    } else {
+        options
+    };
     
     // Create output directory if it doesn't exist
     fs::create_dir_all(output_dir)?;
     
     // Check if URL is a playlist
     if is_playlist(url).await? {
-        return download_playlist(url, output_dir, options).await;
+        return download_playlist(url, output_dir, options, cancel).await;
+    }
+    
+    // Fail fast on a format id the video doesn't offer
+    if let Some(format_id) = &options.format_id {
+        validate_format_id(url, format_id).await?;
     }
     
     // For single video
-    let format = if options.audio_only || options.quality == VideoQuality::AudioOnly {
-        "bestaudio/best"
-    } else {
-        options.quality.to_ytdlp_arg()
-    };
+    let format = select_format(options);
     
     // Setup file extension
     let _ext = if options.audio_only || options.quality == VideoQuality::AudioOnly {
@@ -164,9 +285,6 @@ pub async fn download_video_with_options(
         "mp4"
     };
     
-    // Setup output template
-    let output_template = output_dir.join("%(title)s.%(ext)s");
-    
     // Create progress bar
     let pb = ProgressBar::new(100);
     pb.set_style(ProgressStyle::default_bar()
@@ -178,7 +296,7 @@ pub async fn download_video_with_options(
     let mut cmd = Command::new("yt-dlp");
     cmd.arg(url)
         .arg("-f").arg(format)
-        .arg("-o").arg(output_template.to_string_lossy().to_string())
+        .arg("-o").arg(output_template(output_dir, options))
         .args(DEFAULT_ARGS)
         .arg("--retries").arg(options.retries.to_string());
     
@@ -194,9 +312,8 @@ pub async fn download_video_with_options(
     }
     
     // Add subtitles if requested
-    if options.subtitles {
-        cmd.arg("--write-auto-subs").arg("--sub-langs").arg("en.*");
-    }
+    add_subtitle_args(&mut cmd, options);
+    add_embed_args(&mut cmd, options);
     
     // Force IPv4 if requested (can be faster)
     if options.force_ipv4 {
@@ -214,6 +331,7 @@ pub async fn download_video_with_options(
     }
     
     // Execute command with capture progress
+    let started = SystemTime::now();
     let mut process = cmd
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
@@ -226,6 +344,7 @@ pub async fn download_video_with_options(
     // Track progress in a separate task
     let pb_clone = pb.clone();
     let progress_task = task::spawn_blocking(move || {
+        let mut tail = VecDeque::with_capacity(STDERR_TAIL_LINES);
         for line in reader.lines() {
             if let Ok(line) = line {
                 if let Some(caps) = PROGRESS_REGEX.captures(&line) {
@@ -234,6 +353,11 @@ pub async fn download_video_with_options(
                             pb_clone.set_position((percent * 100.0) as u64);
                         }
                     }
+                } else if !line.trim().is_empty() {
+                    if tail.len() == STDERR_TAIL_LINES {
+                        tail.pop_front();
+                    }
+                    tail.push_back(line.clone());
                 }
                 // Print the line if it contains important info (filter out progress lines)
                 if !line.contains("[download]") || line.contains("Destination") || line.contains("error") {
@@ -241,18 +365,26 @@ pub async fn download_video_with_options(
                 }
             }
         }
+        tail
     });
     
     // Wait for the command to complete
-    let status = process.wait()?;
+    let status = wait_or_kill(&mut process, cancel).await?;
     
     // Wait for progress tracking to complete
-    let _ = progress_task.await;
+    let error_tail: Vec<String> = progress_task.await.map(|tail| tail.into_iter().collect()).unwrap_or_default();
+    
+    let Some(status) = status else {
+        pb.abandon_with_message("Download cancelled".yellow().to_string());
+        remove_ytdlp_partials(output_dir, started);
+        return Err(AppError::Cancelled("Video download cancelled".into()));
+    };
     
     // Check if command was successful
     if !status.success() {
         pb.finish_with_message("Download failed".red().to_string());
-        return Err(anyhow::anyhow!("Failed to download video: yt-dlp exited with status {}", status));
+        suggest_update(&error_tail, options.auto_update);
+        return Err(AppError::Other(format!("Failed to download video: yt-dlp exited with status {}", status)));
     }
     
     pb.finish_with_message("Download complete".green().to_string());
@@ -261,6 +393,84 @@ pub async fn download_video_with_options(
     Ok(())
 }
 
+/// Wait for yt-dlp to exit; returns `None` if `cancel` fired first and the process was killed
+async fn wait_or_kill(process: &mut Child, cancel: &CancellationToken) -> std::io::Result<Option<ExitStatus>> {
+    loop {
+        if let Some(status) = process.try_wait()? {
+            return Ok(Some(status));
+        }
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                process.kill()?;
+                process.wait()?;
+                return Ok(None);
+            }
+            _ = tokio::time::sleep(Duration::from_millis(200)) => {}
+        }
+    }
+}
+
+/// Delete the `.part`/`.ytdl` files yt-dlp wrote to in `output_dir` since `since`.
+/// Leftovers from earlier runs that this run never touched stay for yt-dlp to resume.
+fn remove_ytdlp_partials(output_dir: &Path, since: SystemTime) {
+    let Ok(entries) = fs::read_dir(output_dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_partial = path.extension().is_some_and(|ext| ext == "part" || ext == "ytdl");
+        let is_recent = entry.metadata().and_then(|m| m.modified()).is_ok_and(|modified| modified >= since);
+        if is_partial && is_recent {
+            match fs::remove_file(&path) {
+                Ok(()) => println!("{} {}", "Removed partial file".dimmed(), path.display()),
+                Err(e) => eprintln!("{} {}: {}", "Failed to remove".yellow(), path.display(), e),
+            }
+        }
+    }
+}
+
+/// Resolve the yt-dlp `-f` argument; an explicit format id wins over the quality mapping
+fn select_format(options: &DownloadOptions) -> String {
+    if let Some(format_id) = &options.format_id {
+        format_id.clone()
+    } else if options.audio_only || options.quality == VideoQuality::AudioOnly {
+        "bestaudio/best".to_string()
+    } else {
+        options.quality.to_ytdlp_arg().to_string()
+    }
+}
+
+/// Add the yt-dlp subtitle arguments described by the options
+fn add_subtitle_args(cmd: &mut Command, options: &DownloadOptions) {
+    if !options.subtitles {
+        return;
+    }
+    
+    cmd.arg(if options.auto_subs { "--write-auto-subs" } else { "--write-subs" });
+    
+    let langs = if options.sub_langs.is_empty() {
+        "en.*".to_string()
+    } else {
+        options.sub_langs.join(",")
+    };
+    cmd.arg("--sub-langs").arg(langs);
+    
+    if options.embed_subs {
+        cmd.arg("--embed-subs");
+    } else {
+        // Sidecar files are converted to SRT so any player can pick them up
+        cmd.arg("--convert-subs").arg("srt");
+    }
+}
+
+fn add_embed_args(cmd: &mut Command, options: &DownloadOptions) {
+    if options.embed_metadata {
+        cmd.arg("--embed-metadata");
+    }
+    if options.embed_thumbnail {
+        // Not every container accepts WebP cover art, so convert it first
+        cmd.arg("--embed-thumbnail").arg("--convert-thumbnails").arg("jpg");
+    }
+}
+
 /// Check if a URL is a playlist
 async fn is_playlist(url: &str) -> Result<bool> {
     let output = Command::new("yt-dlp")
@@ -280,22 +490,53 @@ async fn is_playlist(url: &str) -> Result<bool> {
     Ok(count > 1)
 }
 
+/// Number of yt-dlp stderr lines kept for the failure report
+const STDERR_TAIL_LINES: usize = 5;
+
+/// A video listed in a playlist
+#[derive(Debug, Clone)]
+pub struct PlaylistEntry {
+    pub id: String,
+    pub title: String,
+}
+
+/// Outcome of downloading one playlist video
+#[derive(Debug, Clone)]
+pub struct PlaylistItemResult {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub success: bool,
+    pub error_tail: Vec<String>, // Last lines yt-dlp wrote to stderr
+}
+
 /// Download a playlist with parallel processing
 async fn download_playlist(
     url: &str,
     output_dir: &Path,
     options: &DownloadOptions,
+    cancel: &CancellationToken,
 ) -> Result<()> {
     println!("{}", "Playlist detected. Getting video list...".cyan());
     
-    // First, get the list of videos in the playlist
-    let video_ids = get_playlist_video_ids(url).await?;
-    let total_videos = video_ids.len();
+    // First, get the list of videos in the playlist (restricted to the selected items)
+    let entries = get_playlist_entries(url, options.playlist_items.as_deref()).await?;
+    let total_videos = entries.len();
     
-    println!("{} {} videos", "Found".green(), total_videos);
+    match &options.playlist_items {
+        Some(items) => println!("{} {} videos (items {})", "Selected".green(), total_videos, items),
+        None => println!("{} {} videos", "Found".green(), total_videos),
+    }
     
     if total_videos == 0 {
-        return Err(anyhow::anyhow!("No videos found in playlist"));
+        return Err(AppError::NotFound("No videos found in playlist".into()));
+    }
+    
+    // Fail fast on a format id the videos don't offer, checking the first one rather than
+    // listing formats for every item
+    if let Some(format_id) = &options.format_id {
+        let first_url = format!("https://www.youtube.com/watch?v={}", entries[0].id);
+        validate_format_id(&first_url, format_id).await?;
     }
     
     // Set up a multi-progress display
@@ -306,15 +547,17 @@ async fn download_playlist(
         .unwrap()
         .progress_chars("#>-"));
     
-    // Set up a semaphore to limit concurrent downloads
-    let max_concurrent = std::cmp::min(options.concurrent_downloads, total_videos);
+    // Set up a semaphore to limit concurrent downloads to the selected item count
+    // and to what this machine can sensibly run (utils::max_download_concurrency())
+    let max_concurrent = utils::clamp_download_concurrency(options.concurrent_downloads).min(total_videos);
     let semaphore = Arc::new(Semaphore::new(max_concurrent));
     
     println!("{} {} parallel downloads", "Using".cyan(), max_concurrent);
+    let started = SystemTime::now();
     
     // Generate the full playlist URL for each video
-    let tasks = video_ids.into_iter().enumerate().map(|(i, id)| {
-        let video_url = format!("https://www.youtube.com/watch?v={}", id);
+    let tasks = entries.into_iter().enumerate().map(|(i, entry)| {
+        let video_url = format!("https://www.youtube.com/watch?v={}", entry.id);
         let output_dir = output_dir.to_path_buf();
         let options = options.clone();
         let sem_clone = semaphore.clone();
@@ -325,23 +568,35 @@ async fn download_playlist(
             .progress_chars("#>-"));
         
         let main_pb_clone = main_pb.clone();
+        let cancel = cancel.clone();
         
         async move {
-            // Acquire permit from semaphore
-            let _permit = sem_clone.acquire().await.unwrap();
+            let mut result = PlaylistItemResult {
+                id: entry.id,
+                title: entry.title,
+                url: video_url.clone(),
+                success: false,
+                error_tail: Vec::new(),
+            };
             
-            // Download individual video
-            let format = if options.audio_only || options.quality == VideoQuality::AudioOnly {
-                "bestaudio/best"
-            } else {
-                options.quality.to_ytdlp_arg()
+            // Acquire permit from semaphore; videos still queued on cancel are never started
+            let _permit = tokio::select! {
+                permit = sem_clone.acquire() => permit.unwrap(),
+                _ = cancel.cancelled() => {
+                    pb.finish_and_clear();
+                    result.error_tail.push("Cancelled".to_string());
+                    return result;
+                }
             };
             
+            // Download individual video
+            let format = select_format(&options);
+            
             // Prepare command for individual video
             let mut cmd = Command::new("yt-dlp");
             cmd.arg(&video_url)
                 .arg("-f").arg(format)
-                .arg("-o").arg(output_dir.join("%(title)s.%(ext)s").to_string_lossy().to_string())
+                .arg("-o").arg(output_template(&output_dir, &options))
                 .args(DEFAULT_ARGS)
                 .arg("--retries").arg(options.retries.to_string());
             
@@ -354,42 +609,74 @@ async fn download_playlist(
                 cmd.arg("--limit-rate").arg(rate);
             }
             
+            // Add subtitles if requested
+            add_subtitle_args(&mut cmd, &options);
+            add_embed_args(&mut cmd, &options);
+            
             // Force IPv4 if requested (can be faster)
             if options.force_ipv4 {
                 cmd.arg("--force-ipv4");
             }
             
-            let mut process = cmd
+            let mut process = match cmd
                 .stdout(Stdio::null())
                 .stderr(Stdio::piped())
                 .spawn()
-                .unwrap();
+            {
+                Ok(process) => process,
+                Err(e) => {
+                    pb.finish_and_clear();
+                    main_pb_clone.inc(1);
+                    result.error_tail.push(format!("Failed to start yt-dlp: {}", e));
+                    return result;
+                }
+            };
             
-            if let Some(stderr) = process.stderr.take() {
+            // Follow progress and keep the last few stderr lines for the failure report
+            let reader_handle = process.stderr.take().map(|stderr| {
                 let reader = BufReader::new(stderr);
                 let pb_clone = pb.clone();
                 
                 task::spawn_blocking(move || {
-                    for line in reader.lines() {
-                        if let Ok(line) = line {
-                            if let Some(caps) = PROGRESS_REGEX.captures(&line) {
-                                if let Some(percent_match) = caps.get(1) {
-                                    if let Ok(percent) = percent_match.as_str().parse::<f64>() {
-                                        pb_clone.set_position((percent * 100.0) as u64);
-                                    }
+                    let mut tail = VecDeque::with_capacity(STDERR_TAIL_LINES);
+                    for line in reader.lines().map_while(|line| line.ok()) {
+                        if let Some(caps) = PROGRESS_REGEX.captures(&line) {
+                            if let Some(percent_match) = caps.get(1) {
+                                if let Ok(percent) = percent_match.as_str().parse::<f64>() {
+                                    pb_clone.set_position((percent * 100.0) as u64);
                                 }
                             }
+                            continue;
+                        }
+                        
+                        if !line.trim().is_empty() {
+                            if tail.len() == STDERR_TAIL_LINES {
+                                tail.pop_front();
+                            }
+                            tail.push_back(line);
                         }
                     }
-                });
-            }
+                    tail
+                })
+            });
             
-            let status = process.wait().unwrap();
+            let status = wait_or_kill(&mut process, &cancel).await;
+            if let Some(handle) = reader_handle {
+                if let Ok(tail) = handle.await {
+                    result.error_tail = tail.into_iter().collect();
+                }
+            }
             pb.finish_and_clear();
             
             main_pb_clone.inc(1);
             
-            status.success()
+            match status {
+                Ok(Some(status)) => result.success = status.success(),
+                Ok(None) => result.error_tail.push("Cancelled".to_string()),
+                Err(e) => result.error_tail.push(format!("Failed to wait for yt-dlp: {}", e)),
+            }
+            
+            result
         }
     });
     
@@ -399,169 +686,400 @@ async fn download_playlist(
     });
     
     // Collect and process all download tasks
-    let results: Vec<bool> = futures::future::join_all(tasks).await;
+    let results: Vec<PlaylistItemResult> = futures::future::join_all(tasks).await;
     
     // Wait for the progress display thread to finish
     let _ = mp_handle.await;
     
     // Count successful downloads
-    let successes = results.iter().filter(|&&success| success).count();
+    let successes = results.iter().filter(|r| r.success).count();
+    
+    if cancel.is_cancelled() {
+        main_pb.abandon_with_message(format!("Cancelled after {}/{} videos", successes, total_videos).yellow().to_string());
+        remove_ytdlp_partials(output_dir, started);
+        return Err(AppError::Cancelled("Playlist download cancelled".into()));
+    }
     
     main_pb.finish_with_message(format!("{}/{} videos downloaded", successes, total_videos).green().to_string());
     
     if successes == total_videos {
         println!("{} {} {} {}", "Successfully downloaded".green().bold(), successes, "videos to", output_dir.display());
-        Ok(())
-    } else {
-        Err(anyhow::anyhow!("Failed to download {} videos", total_videos - successes))
+        return Ok(());
+    }
+    
+    let failures: Vec<&PlaylistItemResult> = results.iter().filter(|r| !r.success).collect();
+    print_playlist_failures(&failures);
+    // One hint (or update) for the whole playlist, not one per failed video
+    let error_lines: Vec<String> = failures.iter().flat_map(|r| r.error_tail.iter().cloned()).collect();
+    suggest_update(&error_lines, options.auto_update);
+    
+    if let Some(path) = &options.failed_list {
+        let urls: String = failures.iter().map(|r| format!("{}\n", r.url)).collect();
+        match fs::write(path, urls) {
+            Ok(()) => println!("{} {}", "Failed video URLs written to".yellow(), path.display()),
+            Err(e) => eprintln!("{} {}: {}", "Could not write".red(), path.display(), e),
+        }
     }
+    
+    Err(AppError::Other(format!("Failed to download {} of {} videos", failures.len(), total_videos)))
 }
 
-/// Get a list of video IDs from a playlist URL
-async fn get_playlist_video_ids(url: &str) -> Result<Vec<String>> {
-    let output = Command::new("yt-dlp")
-        .arg("--flat-playlist")
+/// Print a table of failed playlist videos with the tail of yt-dlp's error output
+fn print_playlist_failures(failures: &[&PlaylistItemResult]) {
+    println!("\n{}", "Failed Downloads".red().bold());
+    println!("----------------");
+    println!("{:<14} {}", "ID".bold(), "TITLE".bold());
+    
+    for failure in failures {
+        let title: String = failure.title.chars().take(60).collect();
+        println!("{:<14} {}", failure.id.yellow(), title);
+        
+        if failure.error_tail.is_empty() {
+            println!("    {}", "(no error output)".dimmed());
+        }
+        for line in &failure.error_tail {
+            println!("    {}", line.dimmed());
+        }
+    }
+}
+
+/// Make sure an output template names the file extension, so yt-dlp doesn't write
+/// extension-less files
+pub fn validate_output_template(template: &str) -> Result<()> {
+    if !template.contains("%(ext)s") {
+        return Err(AppError::InvalidInput(format!(
+            "Output template '{}' must contain %(ext)s, e.g. %(uploader)s/%(title)s.%(ext)s",
+            template
+        )));
+    }
+    Ok(())
+}
+
+/// The `-o` argument for yt-dlp: the chosen template inside the output directory
+fn output_template(output_dir: &Path, options: &DownloadOptions) -> String {
+    let template = options.output_template.as_deref().unwrap_or(DEFAULT_OUTPUT_TEMPLATE);
+    output_dir.join(template).to_string_lossy().to_string()
+}
+
+/// Build a yt-dlp `--playlist-items` spec from start/end indices (1-based, inclusive)
+pub fn playlist_range(start: Option<u64>, end: Option<u64>) -> Option<String> {
+    match (start, end) {
+        (None, None) => None,
+        (Some(start), None) => Some(format!("{}-", start)),
+        (None, Some(end)) => Some(format!("1-{}", end)),
+        (Some(start), Some(end)) => Some(format!("{}-{}", start, end)),
+    }
+}
+
+/// Get the videos of a playlist URL, optionally limited to a `--playlist-items` spec
+async fn get_playlist_entries(url: &str, playlist_items: Option<&str>) -> Result<Vec<PlaylistEntry>> {
+    let mut cmd = Command::new("yt-dlp");
+    cmd.arg("--flat-playlist")
         .arg("--print-to-file")
-        .arg("%(id)s")
-        .arg("-") // Print to stdout
-        .arg(url)
-        .output()?;
+        .arg("%(id)s\t%(title)s")
+        .arg("-"); // Print to stdout
+    
+    if let Some(items) = playlist_items {
+        cmd.arg("--playlist-items").arg(items);
+    }
+    
+    let output = cmd.arg(url).output()?;
     
     if !output.status.success() {
-        return Err(anyhow::anyhow!("Failed to get playlist information"));
+        return Err(AppError::Other("Failed to get playlist information".into()));
     }
     
     let stdout = String::from_utf8_lossy(&output.stdout);
-    let ids: Vec<String> = stdout.lines()
-        .map(|line| line.trim().to_string())
+    let entries: Vec<PlaylistEntry> = stdout.lines()
+        .map(|line| line.trim())
         .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (id, title) = line.split_once('\t').unwrap_or((line, ""));
+            PlaylistEntry { id: id.to_string(), title: title.to_string() }
+        })
         .collect();
     
-    Ok(ids)
+    Ok(entries)
 }
 
-/// Get information about a video URL
-pub async fn get_video_info(url: &str) -> Result<String> {
-    // Check if yt-dlp is installed
-    if !check_ytdlp_installed().await {
-        return Err(anyhow::anyhow!("yt-dlp is not installed. Please install it first: https://github.com/yt-dlp/yt-dlp#installation"));
+/// Metadata about a video as reported by `yt-dlp --dump-json`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VideoInfo {
+    pub title: Option<String>,
+    pub uploader: Option<String>,
+    pub duration: Option<f64>,          // Seconds
+    pub view_count: Option<u64>,
+    pub upload_date: Option<String>,    // YYYY-MM-DD when yt-dlp gives YYYYMMDD
+    pub is_playlist: bool,
+    pub playlist_count: Option<u64>,
+    pub description: Option<String>,
+    pub formats: Vec<(String, usize)>,  // Format note and how many formats share it
+    pub estimated_size: Option<u64>,    // Largest known format size in bytes
+}
+
+impl VideoInfo {
+    /// Build from the JSON document printed by `yt-dlp --dump-json`
+    pub fn from_json(parsed: &serde_json::Value) -> Self {
+        let get_str = |key: &str| parsed.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+        
+        // Format YYYYMMDD as YYYY-MM-DD
+        let upload_date = get_str("upload_date").map(|date| {
+            if date.len() == 8 && date.chars().all(|c| c.is_ascii_digit()) {
+                format!("{}-{}-{}", &date[0..4], &date[4..6], &date[6..8])
+            } else {
+                date
+            }
+        });
+        
+        let mut estimated_size = None;
+        let mut formats = Vec::new();
+        if let Some(list) = parsed.get("formats").and_then(|v| v.as_array()) {
+            // Use the largest format size as an estimate
+            estimated_size = list.iter()
+                .filter_map(|f| f.get("filesize").and_then(|v| v.as_u64()))
+                .filter(|&size| size > 0)
+                .max();
+            
+            let mut format_counts: HashMap<String, usize> = HashMap::new();
+            for format in list {
+                if let Some(note) = format.get("format_note").and_then(|v| v.as_str()) {
+                    if !note.is_empty() {
+                        *format_counts.entry(note.to_string()).or_insert(0) += 1;
+                    }
+                }
+            }
+            formats = format_counts.into_iter().collect();
+            formats.sort();
+        }
+        
+        VideoInfo {
+            title: get_str("title"),
+            uploader: get_str("uploader"),
+            duration: parsed.get("duration").and_then(|v| v.as_f64()),
+            view_count: parsed.get("view_count").and_then(|v| v.as_u64()),
+            upload_date,
+            is_playlist: parsed.get("playlist").map_or(false, |v| !v.is_null()),
+            playlist_count: parsed.get("playlist_count").and_then(|v| v.as_u64()),
+            description: get_str("description"),
+            formats,
+            estimated_size,
+        }
     }
     
-    println!("{} {}", "Getting video information for:".cyan().bold(), url);
-    
-    // Use a timeout for potentially slow queries
-    let output = tokio::time::timeout(
-        Duration::from_secs(15), 
-        tokio::process::Command::new("yt-dlp")
-            .arg("--dump-json")
-            .arg("--no-playlist") // Only get info for the main video, not the playlist
-            .arg(url)
-            .output()
-    ).await??;
-    
-    if !output.status.success() {
-        let error = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow::anyhow!("Failed to get video info: {}", error));
+    /// Machine-readable representation for `--json` output
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "title": self.title,
+            "uploader": self.uploader,
+            "duration": self.duration,
+            "view_count": self.view_count,
+            "upload_date": self.upload_date,
+            "is_playlist": self.is_playlist,
+            "playlist_count": self.playlist_count,
+            "description": self.description,
+            "formats": self.formats.iter()
+                .map(|(note, count)| serde_json::json!({ "note": note, "count": count }))
+                .collect::<Vec<_>>(),
+            "estimated_size": self.estimated_size,
+        })
     }
-    
-    let json = String::from_utf8_lossy(&output.stdout);
-    
-    // Format a simplified info output (not the raw JSON)
-    let mut info = String::new();
-    
-    // Try to parse the JSON to extract useful fields
-    if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&json) {
-        if let Some(title) = parsed.get("title").and_then(|v| v.as_str()) {
-            info.push_str(&format!("{}: {}\n", "Title".green(), title));
+}
+
+impl std::fmt::Display for VideoInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(title) = &self.title {
+            writeln!(f, "{}: {}", "Title".green(), title)?;
         }
         
-        if let Some(uploader) = parsed.get("uploader").and_then(|v| v.as_str()) {
-            info.push_str(&format!("{}: {}\n", "Uploader".green(), uploader));
+        if let Some(uploader) = &self.uploader {
+            writeln!(f, "{}: {}", "Uploader".green(), uploader)?;
         }
         
-        if let Some(duration) = parsed.get("duration").and_then(|v| v.as_f64()) {
+        if let Some(duration) = self.duration {
             let mins = (duration / 60.0).floor();
             let secs = duration % 60.0;
-            info.push_str(&format!("{}: {:.0}:{:02.0}\n", "Duration".green(), mins, secs));
+            writeln!(f, "{}: {:.0}:{:02.0}", "Duration".green(), mins, secs)?;
         }
         
-        if let Some(view_count) = parsed.get("view_count").and_then(|v| v.as_u64()) {
-            info.push_str(&format!("{}: {}\n", "View Count".green(), view_count));
+        if let Some(view_count) = self.view_count {
+            writeln!(f, "{}: {}", "View Count".green(), view_count)?;
         }
         
-        if let Some(upload_date) = parsed.get("upload_date").and_then(|v| v.as_str()) {
-            // Format YYYYMMDD as YYYY-MM-DD
-            if upload_date.len() == 8 {
-                let year = &upload_date[0..4];
-                let month = &upload_date[4..6];
-                let day = &upload_date[6..8];
-                info.push_str(&format!("{}: {}-{}-{}\n", "Upload Date".green(), year, month, day));
-            } else {
-                info.push_str(&format!("{}: {}\n", "Upload Date".green(), upload_date));
-            }
+        if let Some(upload_date) = &self.upload_date {
+            writeln!(f, "{}: {}", "Upload Date".green(), upload_date)?;
         }
         
         // Indicate if it's a playlist
-        if parsed.get("playlist").is_some() {
-            info.push_str(&format!("{}: {}\n", "Type".green(), "Playlist"));
-            
-            if let Some(playlist_count) = parsed.get("playlist_count").and_then(|v| v.as_u64()) {
-                info.push_str(&format!("{}: {}\n", "Items".green(), playlist_count));
+        if self.is_playlist {
+            writeln!(f, "{}: {}", "Type".green(), "Playlist")?;
+            if let Some(count) = self.playlist_count {
+                writeln!(f, "{}: {}", "Items".green(), count)?;
             }
         } else {
-            info.push_str(&format!("{}: {}\n", "Type".green(), "Single Video"));
+            writeln!(f, "{}: {}", "Type".green(), "Single Video")?;
         }
         
-        if let Some(description) = parsed.get("description").and_then(|v| v.as_str()) {
+        if let Some(description) = &self.description {
             // Truncate description if too long
-            let desc = if description.len() > 200 {
-                format!("{}...", &description[0..200])
+            let desc = if description.chars().count() > 200 {
+                format!("{}...", description.chars().take(200).collect::<String>())
             } else {
-                description.to_string()
+                description.clone()
             };
-            info.push_str(&format!("{}: {}\n", "Description".green(), desc));
+            writeln!(f, "{}: {}", "Description".green(), desc)?;
         }
         
-        // Add download size estimate
-        if let Some(formats) = parsed.get("formats").and_then(|v| v.as_array()) {
-            // Get largest format size as an estimate
-            let mut max_filesize = 0;
-            for format in formats {
-                if let Some(filesize) = format.get("filesize").and_then(|v| v.as_u64()) {
-                    if filesize > max_filesize {
-                        max_filesize = filesize;
-                    }
-                }
-            }
-            
-            if max_filesize > 0 {
-                info.push_str(&format!("{}: {}\n", "Estimated Size".green(), format_bytes(max_filesize)));
-            }
-            
-            // Format summary
-            let mut format_counts = HashMap::new();
-            for format in formats {
-                if let Some(format_note) = format.get("format_note").and_then(|v| v.as_str()) {
-                    format_counts.entry(format_note.to_string()).or_insert(0);
-                    *format_counts.get_mut(format_note).unwrap() += 1;
-                }
-            }
-            
-            let format_summary: Vec<String> = format_counts.iter()
-                .filter(|(k, _)| !k.is_empty())
-                .map(|(k, v)| format!("{} ({})", k, v))
+        if let Some(size) = self.estimated_size {
+            writeln!(f, "{}: {}", "Estimated Size".green(), format_bytes(size))?;
+        }
+        
+        if !self.formats.is_empty() {
+            let summary: Vec<String> = self.formats.iter()
+                .map(|(note, count)| format!("{} ({})", note, count))
                 .collect();
-            
-            if !format_summary.is_empty() {
-                info.push_str(&format!("{}: {}\n", "Formats".green(), format_summary.join(", ")));
-            }
+            writeln!(f, "{}: {}", "Formats".green(), summary.join(", "))?;
         }
-    } else {
-        // If parsing fails, return a simpler message
-        info = format!("Video information retrieved. Use download option to proceed.\n");
+        
+        Ok(())
+    }
+}
+
+/// Run `yt-dlp --dump-json` for a single video and parse the result
+async fn dump_video_json(url: &str) -> Result<serde_json::Value> {
+    // Check if yt-dlp is installed
+    if !check_ytdlp_installed().await {
+        return Err(AppError::MissingTool { tool: "yt-dlp", install_hint: "https://github.com/yt-dlp/yt-dlp#installation" });
     }
     
-    Ok(info)
+    // Use a timeout for potentially slow queries
+    let output = tokio::time::timeout(
+        Duration::from_secs(15), 
+        tokio::process::Command::new("yt-dlp")
+            .arg("--dump-json")
+            .arg("--no-playlist") // Only get info for the main video, not the playlist
+            .arg(url)
+            .output()
+    ).await??;
+    
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::Other(format!("Failed to get video info: {}", error)));
+    }
+    
+    serde_json::from_slice(&output.stdout)
+        .map_err(|e| AppError::InvalidInput(format!("Failed to parse yt-dlp output: {}", e)))
+}
+
+/// Get structured information about a video URL
+pub async fn get_video_info_struct(url: &str) -> Result<VideoInfo> {
+    Ok(VideoInfo::from_json(&dump_video_json(url).await?))
+}
+
+/// A single downloadable format, as listed by `yt-dlp -F`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VideoFormat {
+    pub id: String,
+    pub ext: String,
+    pub resolution: String,
+    pub fps: Option<f64>,
+    pub filesize: Option<u64>,  // Exact size, or yt-dlp's approximation
+    pub vcodec: String,
+    pub acodec: String,
+    pub note: String,
+}
+
+impl VideoFormat {
+    /// Build from one entry of the `formats` array in yt-dlp's JSON
+    pub fn from_json(format: &serde_json::Value) -> Option<Self> {
+        let get_str = |key: &str| format.get(key).and_then(|v| v.as_str()).unwrap_or("").to_string();
+        
+        let id = get_str("format_id");
+        if id.is_empty() {
+            return None;
+        }
+        
+        Some(VideoFormat {
+            id,
+            ext: get_str("ext"),
+            resolution: get_str("resolution"),
+            fps: format.get("fps").and_then(|v| v.as_f64()),
+            filesize: format.get("filesize").and_then(|v| v.as_u64())
+                .or_else(|| format.get("filesize_approx").and_then(|v| v.as_u64())),
+            vcodec: get_str("vcodec"),
+            acodec: get_str("acodec"),
+            note: get_str("format_note"),
+        })
+    }
+}
+
+/// List the formats available for a video
+pub async fn list_formats(url: &str) -> Result<Vec<VideoFormat>> {
+    let parsed = dump_video_json(url).await?;
+    
+    Ok(parsed.get("formats")
+        .and_then(|v| v.as_array())
+        .map(|list| list.iter().filter_map(VideoFormat::from_json).collect())
+        .unwrap_or_default())
+}
+
+/// Print formats as a table
+pub fn print_formats_table(formats: &[VideoFormat]) {
+    if formats.is_empty() {
+        println!("{}", "No formats reported for this video.".yellow());
+        return;
+    }
+    
+    println!("{:<12} {:<6} {:<12} {:>5} {:>12} {:<14} {:<14} {}",
+        "ID".bold(), "EXT".bold(), "RESOLUTION".bold(), "FPS".bold(),
+        "SIZE".bold(), "VCODEC".bold(), "ACODEC".bold(), "NOTE".bold());
+    
+    for format in formats {
+        let fps = format.fps.map(|f| format!("{:.0}", f)).unwrap_or_default();
+        let size = format.filesize.map(format_bytes).unwrap_or_else(|| "-".to_string());
+        println!("{:<12} {:<6} {:<12} {:>5} {:>12} {:<14} {:<14} {}",
+            format.id.cyan(), format.ext, format.resolution, fps, size,
+            format.vcodec, format.acodec, format.note);
+    }
+}
+
+/// Make sure every plain format id in a selector exists for the video.
+/// Selector keywords (`best`, `bestaudio`, ...) and filters (`[height<=720]`) are left to yt-dlp.
+async fn validate_format_id(url: &str, format_id: &str) -> Result<()> {
+    let available: Vec<String> = list_formats(url).await?.into_iter().map(|f| f.id).collect();
+    
+    let unknown = unknown_format_ids(format_id, &available);
+    if !unknown.is_empty() {
+        return Err(AppError::InvalidInput(format!(
+            "Format id(s) not available for this video: {}. Use --list-formats to see available formats.",
+            unknown.join(", ")
+        )));
+    }
+    
+    Ok(())
+}
+
+/// Return the plain ids in a format selector that are missing from `available`
+fn unknown_format_ids(selector: &str, available: &[String]) -> Vec<String> {
+    const KEYWORDS: &[&str] = &[
+        "best", "worst", "bestvideo", "worstvideo", "bestaudio", "worstaudio",
+        "b", "w", "bv", "wv", "ba", "wa", "bv*", "wv*", "ba*", "wa*", "all", "mergeall",
+    ];
+    
+    selector
+        .split(|c| c == '+' || c == '/' || c == ',')
+        .map(|token| token.trim())
+        .filter(|token| !token.is_empty() && !token.contains('[') && !token.contains('('))
+        .filter(|token| !KEYWORDS.contains(token))
+        .filter(|token| !available.iter().any(|id| id == token))
+        .map(|token| token.to_string())
+        .collect()
+}
+
+/// Get information about a video URL as a formatted string
+pub async fn get_video_info(url: &str) -> Result<String> {
+    println!("{} {}", "Getting video information for:".cyan().bold(), url);
+    
+    Ok(get_video_info_struct(url).await?.to_string())
 }
 
 /// Format bytes to human-readable size string
@@ -576,4 +1094,87 @@ fn format_bytes(bytes: u64) -> String {
     }
     
     format!("{:.2} {}", size, UNITS[unit_index])
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_video_info_from_json() {
+        let json = serde_json::json!({
+            "title": "Example",
+            "uploader": "Someone",
+            "duration": 125.0,
+            "view_count": 42,
+            "upload_date": "20240131",
+            "formats": [
+                { "format_note": "720p", "filesize": 1000 },
+                { "format_note": "720p", "filesize": 3000 },
+                { "format_note": "1080p" },
+                { "format_note": "" }
+            ]
+        });
+        let info = VideoInfo::from_json(&json);
+        assert_eq!(info.title.as_deref(), Some("Example"));
+        assert_eq!(info.upload_date.as_deref(), Some("2024-01-31"));
+        assert_eq!(info.view_count, Some(42));
+        assert!(!info.is_playlist);
+        assert_eq!(info.estimated_size, Some(3000));
+        assert_eq!(info.formats, vec![("1080p".to_string(), 1), ("720p".to_string(), 2)]);
+        assert_eq!(info.to_json()["formats"][1]["count"], 2);
+    }
+
+    #[test]
+    fn test_looks_like_extractor_error() {
+        let lines = |text: &str| text.lines().map(str::to_string).collect::<Vec<_>>();
+        assert!(looks_like_extractor_error(&lines("ERROR: [youtube] abc: Unable to extract uploader id")));
+        assert!(looks_like_extractor_error(&lines("WARNING: nsig extraction failed: You may experience throttling")));
+        assert!(looks_like_extractor_error(&lines("ERROR: unable to download video data: HTTP Error 403: Forbidden")));
+        assert!(!looks_like_extractor_error(&lines("ERROR: [youtube] abc: Video unavailable. This video is private")));
+        assert!(!looks_like_extractor_error(&[]));
+    }
+
+    #[test]
+    fn test_add_embed_args() {
+        let args = |options: &DownloadOptions| {
+            let mut cmd = Command::new("yt-dlp");
+            add_embed_args(&mut cmd, options);
+            cmd.get_args().map(|a| a.to_string_lossy().to_string()).collect::<Vec<_>>()
+        };
+        let mut options = DownloadOptions::default();
+        assert!(args(&options).is_empty());
+        options.embed_metadata = true;
+        assert_eq!(args(&options), ["--embed-metadata"]);
+        options.embed_thumbnail = true;
+        assert_eq!(args(&options), ["--embed-metadata", "--embed-thumbnail", "--convert-thumbnails", "jpg"]);
+    }
+
+    #[test]
+    fn test_output_template() {
+        assert!(validate_output_template("%(uploader)s/%(title)s.%(ext)s").is_ok());
+        assert!(validate_output_template("%(upload_date)s - %(title)s.%(ext)s").is_ok());
+        assert!(validate_output_template("%(title)s").is_err());
+        let mut options = DownloadOptions::default();
+        assert_eq!(output_template(Path::new("out"), &options), Path::new("out").join("%(title)s.%(ext)s").to_string_lossy());
+        options.output_template = Some("%(uploader)s/%(title)s.%(ext)s".to_string());
+        assert_eq!(output_template(Path::new("out"), &options), Path::new("out").join("%(uploader)s/%(title)s.%(ext)s").to_string_lossy());
+    }
+
+    #[test]
+    fn test_playlist_range() {
+        assert_eq!(playlist_range(None, None), None);
+        assert_eq!(playlist_range(Some(3), None).as_deref(), Some("3-"));
+        assert_eq!(playlist_range(None, Some(7)).as_deref(), Some("1-7"));
+        assert_eq!(playlist_range(Some(2), Some(4)).as_deref(), Some("2-4"));
+    }
+
+    #[test]
+    fn test_unknown_format_ids() {
+        let available = vec!["137".to_string(), "140".to_string(), "hls-720p".to_string()];
+        assert!(unknown_format_ids("137+140", &available).is_empty());
+        assert!(unknown_format_ids("hls-720p/best", &available).is_empty());
+        assert!(unknown_format_ids("bestvideo[height<=720]+bestaudio", &available).is_empty());
+        assert_eq!(unknown_format_ids("22+140", &available), vec!["22".to_string()]);
+    }
+}
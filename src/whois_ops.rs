@@ -1,21 +1,234 @@
 use anyhow::Result;
-use std::io::{Read, Write};
+use chrono::NaiveDate;
+use colored::*;
+use futures::{stream::FuturesUnordered, StreamExt};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
 use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tokio::sync::Semaphore;
+use crate::error::AppError;
+
+// How many WHOIS queries `check_availability` runs at once
+const MAX_CONCURRENT_QUERIES: usize = 5;
+// Timeout for each read from a WHOIS server
+const WHOIS_READ_TIMEOUT: Duration = Duration::from_secs(10);
+// Extra attempts when a server rate-limits us or drops the connection early
+const WHOIS_RETRIES: u32 = 2;
+// Expiry dates closer than this are highlighted in the summary
+const EXPIRY_WARNING_DAYS: i64 = 30;
+
+lazy_static! {
+    // TLD → WHOIS server answers from IANA, so a bulk check asks only once per TLD
+    static ref WHOIS_SERVER_CACHE: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+}
 
 // Performs a WHOIS lookup for the given domain.
 pub async fn lookup_domain(domain: &str) -> Result<String> {
     println!("Looking up WHOIS for: {}", domain);
 
-    // Extract TLD for server selection
-    let tld = extract_tld(domain);
-    let server = get_whois_server(&tld);
-    
-    // Connect to the WHOIS server directly
-    match query_whois_server(server, domain) {
-        Ok(result) => Ok(result),
-        Err(e) => Err(anyhow::anyhow!("WHOIS lookup failed: {}", e)),
+    let domain = domain.to_string();
+    tokio::task::spawn_blocking(move || {
+        // Extract TLD for server selection
+        let tld = extract_tld(&domain);
+        let server = resolve_whois_server(&tld);
+
+        // Connect to the WHOIS server directly
+        query_whois_server(&server, &domain)
+    })
+    .await?
+    .map_err(|e| anyhow::anyhow!("WHOIS lookup failed: {}", e))
+}
+
+/// The fields most lookups are after, pulled out of a raw WHOIS answer
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WhoisSummary {
+    pub registrar: Option<String>,
+    pub created: Option<NaiveDate>,
+    pub expires: Option<NaiveDate>,
+    pub name_servers: Vec<String>,
+}
+
+// Labels registries use for each summary field, matched case-insensitively; the first line found wins
+const REGISTRAR_KEYS: &[&str] = &["registrar", "sponsoring registrar", "registrar name", "registrar organization"];
+const CREATED_KEYS: &[&str] = &["creation date", "created", "created on", "registered on", "registration time", "domain record activated"];
+const EXPIRES_KEYS: &[&str] = &[
+    "registry expiry date", "registrar registration expiration date", "expiry date", "expiration date",
+    "expires on", "expires", "expire date", "paid-till", "expiration time", "renewal date",
+];
+const NAME_SERVER_KEYS: &[&str] = &["name server", "name servers", "nameserver", "nameservers", "nserver"];
+
+/// Pull registrar, creation/expiry dates and name servers out of a WHOIS answer.
+/// Understands `Key: value` lines as well as the `Key:` + indented lines layout used by .uk.
+pub fn parse_whois(response: &str) -> WhoisSummary {
+    let mut summary = WhoisSummary::default();
+    let mut current_key: Option<String> = None;
+
+    for line in response.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('%') || trimmed.starts_with('#') || trimmed.starts_with(">>>") {
+            current_key = None;
+            continue;
+        }
+
+        // An indented line continues the block opened by a bare "Key:" line, unless it has a key we want
+        let (key, value) = match trimmed.split_once(':').map(|(key, value)| (key.trim().to_lowercase(), value.trim())) {
+            Some((key, value)) if current_key.is_none() || !line.starts_with(char::is_whitespace) || is_summary_key(&key) => {
+                current_key = value.is_empty().then(|| key.clone());
+                (key, value)
+            }
+            _ => match &current_key {
+                Some(key) => (key.clone(), trimmed),
+                None => continue,
+            },
+        };
+        if value.is_empty() {
+            continue;
+        }
+
+        let key = key.as_str();
+        if REGISTRAR_KEYS.contains(&key) {
+            summary.registrar = summary.registrar.or_else(|| Some(value.to_string()));
+        } else if CREATED_KEYS.contains(&key) {
+            summary.created = summary.created.or_else(|| parse_whois_date(value));
+        } else if EXPIRES_KEYS.contains(&key) {
+            summary.expires = summary.expires.or_else(|| parse_whois_date(value));
+        } else if NAME_SERVER_KEYS.contains(&key) {
+            // Some registries append the server's addresses after its name
+            if let Some(server) = value.split_whitespace().next() {
+                let server = server.trim_end_matches('.').to_lowercase();
+                if !summary.name_servers.contains(&server) {
+                    summary.name_servers.push(server);
+                }
+            }
+        }
     }
+    summary
+}
+
+fn is_summary_key(key: &str) -> bool {
+    [REGISTRAR_KEYS, CREATED_KEYS, EXPIRES_KEYS, NAME_SERVER_KEYS].iter().any(|keys| keys.contains(&key))
+}
+
+// Dates appear as 2028-09-14T04:00:00Z, 2028-09-14 04:00:00, 2028.09.14, 2028/09/14 or 14-Sep-2028
+fn parse_whois_date(value: &str) -> Option<NaiveDate> {
+    let token = value.split_whitespace().next()?;
+    let date = token.split('T').next().unwrap_or(token);
+    ["%Y-%m-%d", "%Y.%m.%d", "%Y/%m/%d", "%d-%b-%Y", "%d.%m.%Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(date, format).ok())
+}
+
+/// Print the summary of one domain's WHOIS answer, flagging an expiry less than 30 days away
+pub fn print_summary(domain: &str, summary: &WhoisSummary, today: NaiveDate) {
+    let unknown = || "unknown".dimmed().to_string();
+    println!("{}", domain.bold());
+    println!("  {:<13} {}", "Registrar:".cyan(), summary.registrar.clone().unwrap_or_else(unknown));
+    println!("  {:<13} {}", "Created:".cyan(), summary.created.map(|d| d.to_string()).unwrap_or_else(unknown));
+
+    let expires = match summary.expires {
+        Some(date) => {
+            let days = (date - today).num_days();
+            let text = match days {
+                d if d < 0 => format!("{} (expired {} days ago)", date, -d),
+                1 => format!("{} (in 1 day)", date),
+                d => format!("{} (in {} days)", date, d),
+            };
+            if days < EXPIRY_WARNING_DAYS { text.red().bold().to_string() } else { text.green().to_string() }
+        }
+        None => unknown(),
+    };
+    println!("  {:<13} {}", "Expires:".cyan(), expires);
+
+    if summary.name_servers.is_empty() {
+        println!("  {:<13} {}", "Name servers:".cyan(), unknown());
+    } else {
+        println!("  {:<13} {}", "Name servers:".cyan(), summary.name_servers.join(", "));
+    }
+}
+
+/// Whether a domain is taken, as far as its registry's WHOIS answer tells
+#[derive(Debug, Clone, PartialEq)]
+pub enum DomainStatus {
+    Registered,
+    Available,
+    Unknown(String), // The query failed; holds the error
+}
+
+/// Checks a list of domains concurrently and prints a registered/available table.
+pub async fn check_availability(domains: &[String]) -> Result<Vec<(String, DomainStatus)>> {
+    println!("Checking availability of {} domain(s)...", domains.len());
+
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_QUERIES));
+    let mut tasks = FuturesUnordered::new();
+    for (i, domain) in domains.iter().enumerate() {
+        let semaphore = semaphore.clone();
+        let domain = domain.trim().to_lowercase();
+        tasks.push(tokio::spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let query_domain = domain.clone();
+            let status = tokio::task::spawn_blocking(move || {
+                let tld = extract_tld(&query_domain);
+                let server = resolve_whois_server(&tld);
+                query_whois_server(&server, &query_domain).map(|response| {
+                    if is_not_found_response(&tld, &response) {
+                        DomainStatus::Available
+                    } else {
+                        DomainStatus::Registered
+                    }
+                })
+            })
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result)
+            .unwrap_or_else(|e| DomainStatus::Unknown(e.to_string()));
+            (i, domain, status)
+        }));
+    }
+
+    let mut results = Vec::new();
+    while let Some(result) = tasks.next().await {
+        results.push(result?);
+    }
+    results.sort_by_key(|(i, _, _)| *i);
+    let results: Vec<(String, DomainStatus)> = results.into_iter().map(|(_, domain, status)| (domain, status)).collect();
+
+    let width = results.iter().map(|(domain, _)| domain.len()).max().unwrap_or(6).max(6);
+    println!("{:<width$}  {}", "Domain".bold(), "Status".bold(), width = width);
+    for (domain, status) in &results {
+        let status = match status {
+            DomainStatus::Registered => "registered".yellow(),
+            DomainStatus::Available => "available".green().bold(),
+            DomainStatus::Unknown(e) => format!("unknown ({})", e).red(),
+        };
+        println!("{:<width$}  {}", domain, status, width = width);
+    }
+    Ok(results)
+}
+
+// Registry-specific "no such domain" sentinels, matched case-insensitively
+fn not_found_patterns(tld: &str) -> &'static [&'static str] {
+    match tld {
+        "com" | "net" => &["no match for"],
+        "org" | "io" | "ai" | "dev" => &["domain not found"],
+        "co" => &["no data found", "domain not found"],
+        "uk" => &["no match for"],
+        "ru" => &["no entries found"],
+        "jp" => &["no match!!"],
+        "cn" => &["no matching record"],
+        "fr" => &["%% not found"],
+        "nl" => &["is free"],
+        "de" => &["status: free"],
+        "au" => &["not found"],
+        _ => &["no match", "not found", "no entries found", "no data found", "status: free", "is free"],
+    }
+}
+
+fn is_not_found_response(tld: &str, response: &str) -> bool {
+    let response = response.to_lowercase();
+    not_found_patterns(tld).iter().any(|pattern| response.contains(pattern))
 }
 
 // Extract the TLD from a domain name
@@ -28,9 +241,35 @@ fn extract_tld(domain: &str) -> String {
     }
 }
 
-// Get the appropriate WHOIS server for a TLD
-fn get_whois_server(tld: &str) -> &str {
-    match tld {
+// The WHOIS server for a TLD: the built-in table, then IANA's referral (cached)
+fn resolve_whois_server(tld: &str) -> String {
+    if let Some(server) = get_whois_server(tld) {
+        return server.to_string();
+    }
+    if let Some(server) = WHOIS_SERVER_CACHE.lock().unwrap_or_else(|e| e.into_inner()).get(tld) {
+        return server.clone();
+    }
+    let server = query_whois_server(IANA_WHOIS_SERVER, tld)
+        .ok()
+        .and_then(|response| parse_iana_referral(&response))
+        .unwrap_or_else(|| IANA_WHOIS_SERVER.to_string());
+    WHOIS_SERVER_CACHE.lock().unwrap_or_else(|e| e.into_inner()).insert(tld.to_string(), server.clone());
+    server
+}
+
+const IANA_WHOIS_SERVER: &str = "whois.iana.org";
+
+// Pulls the "whois: whois.nic.xyz" line out of an IANA TLD record
+fn parse_iana_referral(response: &str) -> Option<String> {
+    response.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim().eq_ignore_ascii_case("whois") && !value.trim().is_empty()).then(|| value.trim().to_string())
+    })
+}
+
+// Get the appropriate WHOIS server for a TLD from the built-in table
+fn get_whois_server(tld: &str) -> Option<&'static str> {
+    let server = match tld {
         "com" => "whois.verisign-grs.com",
         "net" => "whois.verisign-grs.com",
         "org" => "whois.pir.org",
@@ -46,35 +285,176 @@ fn get_whois_server(tld: &str) -> &str {
         "nl" => "whois.domain-registry.nl",
         "de" => "whois.denic.de",
         "au" => "whois.auda.org.au",
-        _ => "whois.iana.org",  // Default WHOIS server
-    }
+        _ => return None,
+    };
+    Some(server)
 }
 
-// Query a WHOIS server directly via TCP
+// Banners servers send instead of a record when queried too fast, matched case-insensitively
+const RATE_LIMIT_PATTERNS: &[&str] = &[
+    "query rate exceeded",
+    "queries exceeded",
+    "too many queries",
+    "too many requests",
+    "exceeded the maximum allowable number",
+    "limit exceeded",
+];
+
+fn is_rate_limited_response(response: &str) -> bool {
+    let response = response.to_lowercase();
+    RATE_LIMIT_PATTERNS.iter().any(|pattern| response.contains(pattern))
+}
+
+// Query a WHOIS server, backing off and retrying when it rate-limits us or cuts the answer short.
+// A reply that is still truncated after the last attempt is returned with a warning.
 fn query_whois_server(server: &str, domain: &str) -> Result<String> {
+    let mut attempt = 0;
+    loop {
+        let outcome = query_whois_once(server, domain);
+        let rate_limited = matches!(&outcome, Ok((response, _)) if is_rate_limited_response(response));
+        let complete = matches!(&outcome, Ok((response, None)) if !response.is_empty());
+
+        if (complete && !rate_limited) || attempt >= WHOIS_RETRIES {
+            if rate_limited {
+                return Err(AppError::RateLimited { service: server.to_string(), retry_after: None }.into());
+            }
+            let (response, read_error) = outcome?;
+            match read_error {
+                Some(e) => eprintln!("{}: WHOIS answer from {} may be incomplete ({})", "Warning".yellow(), server, e),
+                None if response.is_empty() => eprintln!("{}: {} closed the connection without answering", "Warning".yellow(), server),
+                None => {}
+            }
+            return Ok(response);
+        }
+
+        attempt += 1;
+        let wait = Duration::from_secs(2u64.pow(attempt));
+        let reason = match &outcome {
+            _ if rate_limited => "rate limited".to_string(),
+            Ok((_, Some(e))) => format!("connection dropped: {}", e),
+            Ok(_) => "empty answer".to_string(),
+            Err(e) => e.to_string(),
+        };
+        eprintln!("{}: {} ({}), retry {}/{} in {}s", "Warning".yellow(), server, reason, attempt, WHOIS_RETRIES, wait.as_secs());
+        std::thread::sleep(wait);
+    }
+}
+
+// One WHOIS query over TCP. A read that fails after some data still returns that data,
+// together with the error that cut it short.
+fn query_whois_once(server: &str, domain: &str) -> Result<(String, Option<io::Error>)> {
     // Connect to server on port 43 (standard WHOIS port)
     let address = format!("{}:43", server);
     let mut stream = TcpStream::connect(&address)?;
     
     // Set reasonable timeout
-    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    stream.set_read_timeout(Some(WHOIS_READ_TIMEOUT))?;
     stream.set_write_timeout(Some(Duration::from_secs(5)))?;
     
     // Send the query (domain name followed by \r\n)
     let query = format!("{}\r\n", domain);
     stream.write_all(query.as_bytes())?;
     
-    // Read the response
-    let mut response = String::new();
-    stream.read_to_string(&mut response)?;
-    
-    Ok(response)
+    let (bytes, read_error) = read_until_eof(&mut stream);
+    match read_error {
+        Some(e) if bytes.is_empty() => Err(e.into()),
+        read_error => Ok((String::from_utf8_lossy(&bytes).into_owned(), read_error)),
+    }
+}
+
+// Read until the server closes the connection, keeping whatever arrived before an error
+fn read_until_eof(reader: &mut impl Read) -> (Vec<u8>, Option<io::Error>) {
+    let mut bytes = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return (bytes, None),
+            Ok(n) => bytes.extend_from_slice(&buf[..n]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return (bytes, Some(e)),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_not_found_detection() {
+        assert!(is_not_found_response("com", "No match for \"EXAMPLE-FREE.COM\"."));
+        assert!(!is_not_found_response("com", "Domain Name: GOOGLE.COM\nRegistrar: MarkMonitor Inc."));
+        assert!(is_not_found_response("de", "Domain: example-free.de\nStatus: free"));
+        assert!(is_not_found_response("xyz", "The queried object does not exist: DOMAIN NOT FOUND"));
+    }
+
+    #[test]
+    fn test_parse_iana_referral() {
+        let response = "domain:       XYZ\n\norganisation: XYZ.COM LLC\nwhois:        whois.nic.xyz\n";
+        assert_eq!(parse_iana_referral(response), Some("whois.nic.xyz".to_string()));
+        assert_eq!(parse_iana_referral("domain: EXAMPLE\nwhois:\n"), None);
+    }
+
+    #[test]
+    fn test_rate_limit_detection() {
+        assert!(is_rate_limited_response("%% Query rate exceeded, please try again later"));
+        assert!(is_rate_limited_response("WHOIS LIMIT EXCEEDED - SEE WWW.PIR.ORG/WHOIS FOR DETAILS"));
+        assert!(!is_rate_limited_response("Domain Name: GOOGLE.COM\nRegistrar: MarkMonitor Inc."));
+    }
+
+    // Yields its data, then fails the way a timed-out or reset socket does
+    struct DroppingReader(Vec<u8>);
+
+    impl Read for DroppingReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset"));
+            }
+            let n = self.0.len().min(buf.len());
+            self.0.drain(..n).zip(buf.iter_mut()).for_each(|(b, slot)| *slot = b);
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_read_until_eof_keeps_partial_data() {
+        let (bytes, error) = read_until_eof(&mut DroppingReader(b"Domain Name: EXAMPLE.COM\n".to_vec()));
+        assert_eq!(bytes, b"Domain Name: EXAMPLE.COM\n");
+        assert_eq!(error.map(|e| e.kind()), Some(io::ErrorKind::ConnectionReset));
+
+        let (bytes, error) = read_until_eof(&mut &b"complete answer"[..]);
+        assert_eq!(bytes, b"complete answer");
+        assert!(error.is_none());
+    }
+
+    #[test]
+    fn test_parse_whois() {
+        let verisign = "   Domain Name: EXAMPLE.COM\n   Registrar: RESERVED-Internet Assigned Numbers Authority\n   \
+            Updated Date: 2024-08-14T07:01:34Z\n   Creation Date: 1995-08-14T04:00:00Z\n   \
+            Registry Expiry Date: 2025-08-13T04:00:00Z\n   Name Server: A.IANA-SERVERS.NET\n   \
+            Name Server: B.IANA-SERVERS.NET\n>>> Last update of whois database: 2024-10-01T00:00:00Z <<<\n";
+        let summary = parse_whois(verisign);
+        assert_eq!(summary.registrar.as_deref(), Some("RESERVED-Internet Assigned Numbers Authority"));
+        assert_eq!(summary.created, NaiveDate::from_ymd_opt(1995, 8, 14));
+        assert_eq!(summary.expires, NaiveDate::from_ymd_opt(2025, 8, 13));
+        assert_eq!(summary.name_servers, vec!["a.iana-servers.net", "b.iana-servers.net"]);
+
+        let nominet = "    Domain name:\n        example.co.uk\n\n    Registrar:\n        Markmonitor Inc. t/a MarkMonitor Inc. [Tag = MARKMONITOR]\n        \
+            URL: http://www.markmonitor.com\n\n    Relevant dates:\n        Registered on: 24-Apr-1999\n        \
+            Expiry date:  24-Apr-2026\n\n    Name servers:\n        ns1.example.net\n        ns2.example.net   192.0.2.1\n";
+        let summary = parse_whois(nominet);
+        assert_eq!(summary.registrar.as_deref(), Some("Markmonitor Inc. t/a MarkMonitor Inc. [Tag = MARKMONITOR]"));
+        assert_eq!(summary.created, NaiveDate::from_ymd_opt(1999, 4, 24));
+        assert_eq!(summary.expires, NaiveDate::from_ymd_opt(2026, 4, 24));
+        assert_eq!(summary.name_servers, vec!["ns1.example.net", "ns2.example.net"]);
+
+        let tcinet = "domain:        EXAMPLE.RU\nnserver:       ns1.example.ru.\ncreated:       2004-05-07T20:00:00Z\npaid-till:     2025-05-31T21:00:00Z\n";
+        let summary = parse_whois(tcinet);
+        assert_eq!(summary.registrar, None);
+        assert_eq!(summary.expires, NaiveDate::from_ymd_opt(2025, 5, 31));
+        assert_eq!(summary.name_servers, vec!["ns1.example.ru"]);
+    }
+
     // Note: These tests require network access and may be brittle
     // depending on domain availability and WHOIS server responses.
     // They are marked `ignore` by default.